@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE transactions DROP COLUMN IF EXISTS currency;
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_amount_positive;
+ALTER TABLE transactions RENAME COLUMN amount_minor_units TO amount_in_rs;
+ALTER TABLE transactions
+    ALTER COLUMN amount_in_rs TYPE DOUBLE PRECISION USING amount_in_rs / 100.0,
+    ALTER COLUMN amount_in_rs SET DEFAULT 0.0;
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_balance_non_negative;
+ALTER TABLE users RENAME COLUMN balance_minor_units TO balance_in_rs;
+ALTER TABLE users
+    ALTER COLUMN balance_in_rs DROP DEFAULT,
+    ALTER COLUMN balance_in_rs TYPE DOUBLE PRECISION USING balance_in_rs / 100.0,
+    ALTER COLUMN balance_in_rs SET DEFAULT 0.0;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+ALTER TABLE users
+    ALTER COLUMN balance_in_rs DROP DEFAULT,
+    ALTER COLUMN balance_in_rs TYPE BIGINT USING ROUND(balance_in_rs * 100)::BIGINT,
+    ALTER COLUMN balance_in_rs SET DEFAULT 0;
+ALTER TABLE users RENAME COLUMN balance_in_rs TO balance_minor_units;
+ALTER TABLE users ADD CONSTRAINT users_balance_non_negative CHECK (balance_minor_units >= 0);
+
+ALTER TABLE transactions
+    ALTER COLUMN amount_in_rs DROP DEFAULT,
+    ALTER COLUMN amount_in_rs TYPE BIGINT USING ROUND(amount_in_rs * 100)::BIGINT;
+ALTER TABLE transactions RENAME COLUMN amount_in_rs TO amount_minor_units;
+ALTER TABLE transactions ADD CONSTRAINT transactions_amount_positive CHECK (amount_minor_units > 0);
+ALTER TABLE transactions ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'INR';
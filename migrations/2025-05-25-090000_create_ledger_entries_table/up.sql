@@ -0,0 +1,43 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS ledger_entries (
+    id BIGSERIAL PRIMARY KEY,
+    entry_id VARCHAR(64) NOT NULL UNIQUE,
+    journal_id VARCHAR(64) NOT NULL,
+    account_id VARCHAR(64) NOT NULL,
+    direction VARCHAR(8) NOT NULL CHECK (direction IN ('DEBIT', 'CREDIT')),
+    amount_minor_units BIGINT NOT NULL CHECK (amount_minor_units > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'INR',
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS ledger_entries_account_id_created_at_idx ON ledger_entries (account_id, created_at);
+CREATE INDEX IF NOT EXISTS ledger_entries_journal_id_idx ON ledger_entries (journal_id);
+
+-- Backfill the journal so existing balances can be rebuilt from it: every transfer becomes a
+-- debit of the sender and a credit of the recipient, and whatever remains of a user's balance
+-- is booked as an opening balance against the adjustments system account.
+INSERT INTO ledger_entries (entry_id, journal_id, account_id, direction, amount_minor_units, currency, created_at)
+SELECT 'le_' || md5(transaction_id || ':DEBIT'), transaction_id, sender_id, 'DEBIT', amount_minor_units, currency, created_at
+FROM transactions
+UNION ALL
+SELECT 'le_' || md5(transaction_id || ':CREDIT'), transaction_id, recipient_id, 'CREDIT', amount_minor_units, currency, created_at
+FROM transactions;
+
+WITH opening AS (
+    SELECT u.user_id, u.created_at,
+        u.balance_minor_units - COALESCE((
+            SELECT SUM(CASE WHEN l.direction = 'CREDIT' THEN l.amount_minor_units ELSE -l.amount_minor_units END)
+            FROM ledger_entries l
+            WHERE l.account_id = u.user_id
+        ), 0) AS delta
+    FROM users u
+)
+INSERT INTO ledger_entries (entry_id, journal_id, account_id, direction, amount_minor_units, currency, created_at)
+SELECT 'le_' || md5('opening:' || user_id || ':' || side.account), 'opening_' || user_id,
+    CASE WHEN side.account = 'user' THEN user_id ELSE 'sys_adjustments' END,
+    CASE WHEN (side.account = 'user') = (delta > 0) THEN 'CREDIT' ELSE 'DEBIT' END,
+    ABS(delta), 'INR', created_at
+FROM opening
+CROSS JOIN (VALUES ('user'), ('system')) AS side (account)
+WHERE delta <> 0;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE transactions DROP COLUMN IF EXISTS failure_reason;
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+ALTER TABLE transactions ALTER COLUMN status SET DEFAULT 'pending';
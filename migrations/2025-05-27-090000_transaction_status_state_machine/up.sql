@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+-- Every existing transfer has already moved money, so it is completed.
+UPDATE transactions SET status = 'COMPLETED', updated_at = now() WHERE status IN ('pending', 'PENDING');
+
+ALTER TABLE transactions ALTER COLUMN status SET DEFAULT 'PENDING';
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('PENDING', 'COMPLETED', 'FAILED', 'REVERSED'));
+ALTER TABLE transactions ADD COLUMN failure_reason TEXT;
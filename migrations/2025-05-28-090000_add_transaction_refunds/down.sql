@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS transactions_parent_transaction_id_idx;
+
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+UPDATE transactions SET status = 'COMPLETED' WHERE status IN ('REFUNDED', 'PARTIALLY_REFUNDED');
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('PENDING', 'COMPLETED', 'FAILED', 'REVERSED'));
+
+ALTER TABLE transactions DROP COLUMN IF EXISTS parent_transaction_id;
+ALTER TABLE transactions DROP COLUMN IF EXISTS transaction_type;
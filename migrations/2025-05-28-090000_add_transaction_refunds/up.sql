@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+ALTER TABLE transactions ADD COLUMN transaction_type VARCHAR(32) NOT NULL DEFAULT 'TRANSFER';
+ALTER TABLE transactions ADD COLUMN parent_transaction_id VARCHAR(64) REFERENCES transactions(transaction_id);
+
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('PENDING', 'COMPLETED', 'FAILED', 'REVERSED', 'REFUNDED', 'PARTIALLY_REFUNDED'));
+
+CREATE INDEX IF NOT EXISTS transactions_parent_transaction_id_idx ON transactions (parent_transaction_id);
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS transactions_active_holds_idx;
+
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+UPDATE transactions SET status = 'FAILED' WHERE status IN ('AUTHORIZED', 'VOIDED', 'EXPIRED');
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('PENDING', 'COMPLETED', 'FAILED', 'REVERSED', 'REFUNDED', 'PARTIALLY_REFUNDED'));
+
+ALTER TABLE transactions DROP COLUMN IF EXISTS expires_at;
+ALTER TABLE transactions DROP COLUMN IF EXISTS authorized_amount_minor_units;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+ALTER TABLE transactions ADD COLUMN authorized_amount_minor_units BIGINT
+    CONSTRAINT transactions_authorized_amount_non_negative CHECK (authorized_amount_minor_units >= 0);
+ALTER TABLE transactions ADD COLUMN expires_at TIMESTAMP;
+
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('PENDING', 'AUTHORIZED', 'COMPLETED', 'FAILED', 'VOIDED', 'EXPIRED',
+                      'REVERSED', 'REFUNDED', 'PARTIALLY_REFUNDED'));
+
+-- Active holds are summed whenever a sender's available balance is checked.
+CREATE INDEX IF NOT EXISTS transactions_active_holds_idx
+    ON transactions (sender_id, expires_at) WHERE status = 'AUTHORIZED';
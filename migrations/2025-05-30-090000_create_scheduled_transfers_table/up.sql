@@ -0,0 +1,26 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS scheduled_transfers (
+    id SERIAL PRIMARY KEY,
+    scheduled_transfer_id VARCHAR(64) NOT NULL UNIQUE,
+    -- ID the transfer gets when it is executed, so a retried execution cannot move money twice.
+    transaction_id VARCHAR(64) NOT NULL UNIQUE,
+    sender_id VARCHAR(64) NOT NULL,
+    recipient_id VARCHAR(64) NOT NULL,
+    amount_minor_units BIGINT NOT NULL CONSTRAINT scheduled_transfers_amount_positive CHECK (amount_minor_units > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'INR',
+    description TEXT,
+    execute_at TIMESTAMP NOT NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'SCHEDULED'
+        CONSTRAINT scheduled_transfers_status_check CHECK (status IN ('SCHEDULED', 'EXECUTED', 'FAILED', 'CANCELLED')),
+    failure_reason TEXT,
+    executed_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (sender_id) REFERENCES users(user_id),
+    FOREIGN KEY (recipient_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS scheduled_transfers_due_idx
+    ON scheduled_transfers (execute_at) WHERE status = 'SCHEDULED';
+CREATE INDEX IF NOT EXISTS scheduled_transfers_sender_id_idx ON scheduled_transfers (sender_id);
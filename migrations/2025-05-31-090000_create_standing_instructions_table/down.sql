@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS transactions_standing_instruction_id_idx;
+ALTER TABLE transactions DROP COLUMN IF EXISTS standing_instruction_id;
+
+DROP TABLE IF EXISTS standing_instructions;
@@ -0,0 +1,40 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS standing_instructions (
+    id SERIAL PRIMARY KEY,
+    standing_instruction_id VARCHAR(64) NOT NULL UNIQUE,
+    sender_id VARCHAR(64) NOT NULL,
+    recipient_id VARCHAR(64) NOT NULL,
+    amount_minor_units BIGINT NOT NULL CONSTRAINT standing_instructions_amount_positive CHECK (amount_minor_units > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'INR',
+    description TEXT,
+    frequency VARCHAR(32) NOT NULL
+        CONSTRAINT standing_instructions_frequency_check CHECK (frequency IN ('DAILY', 'WEEKLY', 'MONTHLY', 'CRON')),
+    day_of_month SMALLINT
+        CONSTRAINT standing_instructions_day_of_month_check CHECK (day_of_month BETWEEN 1 AND 31),
+    cron_expression VARCHAR(255),
+    start_at TIMESTAMP NOT NULL,
+    end_at TIMESTAMP,
+    max_occurrences INTEGER CONSTRAINT standing_instructions_max_occurrences_positive CHECK (max_occurrences > 0),
+    -- Runs attempted so far, including failed ones. Also numbers the transactions of the runs.
+    occurrences_count INTEGER NOT NULL DEFAULT 0,
+    next_run_at TIMESTAMP,
+    last_run_at TIMESTAMP,
+    last_failure_reason TEXT,
+    status VARCHAR(32) NOT NULL DEFAULT 'ACTIVE'
+        CONSTRAINT standing_instructions_status_check CHECK (status IN ('ACTIVE', 'PAUSED', 'COMPLETED')),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (sender_id) REFERENCES users(user_id),
+    FOREIGN KEY (recipient_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS standing_instructions_due_idx
+    ON standing_instructions (next_run_at) WHERE status = 'ACTIVE';
+CREATE INDEX IF NOT EXISTS standing_instructions_sender_id_idx ON standing_instructions (sender_id);
+
+ALTER TABLE transactions
+    ADD COLUMN standing_instruction_id VARCHAR(64) REFERENCES standing_instructions(standing_instruction_id);
+
+CREATE INDEX IF NOT EXISTS transactions_standing_instruction_id_idx
+    ON transactions (standing_instruction_id) WHERE standing_instruction_id IS NOT NULL;
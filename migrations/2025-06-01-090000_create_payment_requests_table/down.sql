@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS transactions_payment_request_id_idx;
+ALTER TABLE transactions DROP COLUMN IF EXISTS payment_request_id;
+
+DROP TABLE IF EXISTS payment_requests;
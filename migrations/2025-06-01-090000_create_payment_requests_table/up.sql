@@ -0,0 +1,33 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS payment_requests (
+    id SERIAL PRIMARY KEY,
+    payment_request_id VARCHAR(64) NOT NULL UNIQUE,
+    -- User asking for the money, who receives it when the request is paid.
+    requester_id VARCHAR(64) NOT NULL,
+    -- User asked to pay.
+    payer_id VARCHAR(64) NOT NULL,
+    amount_minor_units BIGINT NOT NULL CONSTRAINT payment_requests_amount_positive CHECK (amount_minor_units > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'INR',
+    note TEXT,
+    status VARCHAR(32) NOT NULL DEFAULT 'PENDING'
+        CONSTRAINT payment_requests_status_check CHECK (status IN ('PENDING', 'PAID', 'DECLINED', 'EXPIRED', 'CANCELLED')),
+    transaction_id VARCHAR(64) REFERENCES transactions(transaction_id),
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (requester_id) REFERENCES users(user_id),
+    FOREIGN KEY (payer_id) REFERENCES users(user_id),
+    CONSTRAINT payment_requests_distinct_parties CHECK (requester_id <> payer_id)
+);
+
+CREATE INDEX IF NOT EXISTS payment_requests_requester_id_idx ON payment_requests (requester_id, status);
+CREATE INDEX IF NOT EXISTS payment_requests_payer_id_idx ON payment_requests (payer_id, status);
+CREATE INDEX IF NOT EXISTS payment_requests_expiry_idx
+    ON payment_requests (expires_at) WHERE status = 'PENDING';
+
+ALTER TABLE transactions
+    ADD COLUMN payment_request_id VARCHAR(64) REFERENCES payment_requests(payment_request_id);
+
+CREATE INDEX IF NOT EXISTS transactions_payment_request_id_idx
+    ON transactions (payment_request_id) WHERE payment_request_id IS NOT NULL;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS transactions_batch_id_idx;
+ALTER TABLE transactions DROP COLUMN IF EXISTS batch_id;
+
+DROP TABLE IF EXISTS transfer_batches;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+
+CREATE TABLE IF NOT EXISTS transfer_batches (
+    id SERIAL PRIMARY KEY,
+    batch_id VARCHAR(64) NOT NULL UNIQUE,
+    sender_id VARCHAR(64) NOT NULL,
+    -- Sum of the amounts of every leg of the batch.
+    total_minor_units BIGINT NOT NULL CONSTRAINT transfer_batches_total_positive CHECK (total_minor_units > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'INR',
+    leg_count INTEGER NOT NULL CONSTRAINT transfer_batches_leg_count_positive CHECK (leg_count > 0),
+    description TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (sender_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS transfer_batches_sender_id_idx ON transfer_batches (sender_id, created_at);
+
+ALTER TABLE transactions
+    ADD COLUMN batch_id VARCHAR(64) REFERENCES transfer_batches(batch_id);
+
+CREATE INDEX IF NOT EXISTS transactions_batch_id_idx
+    ON transactions (batch_id) WHERE batch_id IS NOT NULL;
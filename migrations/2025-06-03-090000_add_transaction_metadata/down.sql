@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX IF EXISTS transactions_metadata_idx;
+
+ALTER TABLE standing_instructions DROP COLUMN IF EXISTS metadata;
+ALTER TABLE scheduled_transfers DROP COLUMN IF EXISTS metadata;
+ALTER TABLE transactions DROP COLUMN IF EXISTS metadata;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- Client supplied key-value pairs, kept on the transfers that create transactions so every
+-- transaction they execute carries them.
+ALTER TABLE transactions ADD COLUMN metadata JSONB;
+ALTER TABLE scheduled_transfers ADD COLUMN metadata JSONB;
+ALTER TABLE standing_instructions ADD COLUMN metadata JSONB;
+
+CREATE INDEX IF NOT EXISTS transactions_metadata_idx
+    ON transactions USING GIN (metadata jsonb_path_ops);
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+-- Platform fee charged to the sender, included in amount_minor_units. The recipient is
+-- credited the amount less the fee and the fee goes to the platform revenue account.
+ALTER TABLE transactions
+    ADD COLUMN fee_minor_units BIGINT NOT NULL DEFAULT 0
+    CONSTRAINT transactions_fee_check CHECK (
+        fee_minor_units >= 0 AND (fee_minor_units = 0 OR fee_minor_units < amount_minor_units)
+    );
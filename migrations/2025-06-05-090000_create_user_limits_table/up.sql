@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- Per-user overrides of the configured transfer limits. A NULL limit falls back to the
+-- configured default.
+CREATE TABLE IF NOT EXISTS user_limits (
+    id SERIAL PRIMARY KEY,
+    user_id VARCHAR(64) NOT NULL UNIQUE,
+    max_transfer_minor_units BIGINT CONSTRAINT user_limits_max_transfer_positive CHECK (max_transfer_minor_units > 0),
+    daily_outgoing_minor_units BIGINT CONSTRAINT user_limits_daily_outgoing_positive CHECK (daily_outgoing_minor_units > 0),
+    monthly_outgoing_minor_units BIGINT CONSTRAINT user_limits_monthly_outgoing_positive CHECK (monthly_outgoing_minor_units > 0),
+    transfers_per_hour INTEGER CONSTRAINT user_limits_transfers_per_hour_positive CHECK (transfers_per_hour > 0),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
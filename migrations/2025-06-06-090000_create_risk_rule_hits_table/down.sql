@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE IF EXISTS risk_rule_hits;
+
+DROP INDEX IF EXISTS transactions_under_review_idx;
+
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+UPDATE transactions SET status = 'FAILED' WHERE status = 'UNDER_REVIEW';
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('PENDING', 'AUTHORIZED', 'COMPLETED', 'FAILED', 'VOIDED', 'EXPIRED',
+                      'REVERSED', 'REFUNDED', 'PARTIALLY_REFUNDED'));
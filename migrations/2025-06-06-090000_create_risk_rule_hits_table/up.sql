@@ -0,0 +1,25 @@
+-- Your SQL goes here
+
+-- Transfers flagged by the risk rules wait in UNDER_REVIEW, holding their amount on the
+-- sender's account, until an admin approves or rejects them.
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_status_check
+    CHECK (status IN ('PENDING', 'AUTHORIZED', 'UNDER_REVIEW', 'COMPLETED', 'FAILED', 'VOIDED',
+                      'EXPIRED', 'REVERSED', 'REFUNDED', 'PARTIALLY_REFUNDED'));
+
+CREATE INDEX IF NOT EXISTS transactions_under_review_idx
+    ON transactions (sender_id) WHERE status = 'UNDER_REVIEW';
+
+-- The risk rules that flagged a transfer held for review or blocked.
+CREATE TABLE IF NOT EXISTS risk_rule_hits (
+    id SERIAL PRIMARY KEY,
+    transaction_id VARCHAR(64) NOT NULL,
+    rule VARCHAR(64) NOT NULL,
+    decision VARCHAR(16) NOT NULL CONSTRAINT risk_rule_hits_decision_check CHECK (decision IN ('REVIEW', 'BLOCK')),
+    score INTEGER NOT NULL CONSTRAINT risk_rule_hits_score_non_negative CHECK (score >= 0),
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (transaction_id) REFERENCES transactions(transaction_id)
+);
+
+CREATE INDEX IF NOT EXISTS risk_rule_hits_transaction_id_idx ON risk_rule_hits (transaction_id);
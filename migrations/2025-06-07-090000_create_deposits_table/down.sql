@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE IF EXISTS deposits;
+
+-- Keep the money deposited so far, booked as balance adjustments.
+UPDATE ledger_entries SET account_id = 'sys_adjustments' WHERE account_id = 'sys_funding';
+DELETE FROM transactions WHERE transaction_type = 'DEPOSIT';
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_transaction_type_check;
+
+DELETE FROM users WHERE user_id = 'sys_funding';
@@ -0,0 +1,33 @@
+-- Your SQL goes here
+
+-- System account that deposits are booked from. It exists as a user so that deposit
+-- transactions can name it as their sender; it cannot log in and its cached balance is not
+-- maintained, since its ledger balance goes negative as money enters the platform.
+INSERT INTO users (user_id, email, name, password)
+VALUES ('sys_funding', 'funding@system.invalid', 'System funding account', '!')
+ON CONFLICT (user_id) DO NOTHING;
+
+ALTER TABLE transactions ADD CONSTRAINT transactions_transaction_type_check
+    CHECK (transaction_type IN ('TRANSFER', 'REFUND', 'DEPOSIT'));
+
+-- Money a user adds to their balance from an external funding source. The balance is credited
+-- through a DEPOSIT transaction once the funding source confirms the charge.
+CREATE TABLE IF NOT EXISTS deposits (
+    id SERIAL PRIMARY KEY,
+    deposit_id VARCHAR(64) NOT NULL UNIQUE,
+    user_id VARCHAR(64) NOT NULL,
+    amount_minor_units BIGINT NOT NULL CONSTRAINT deposits_amount_positive CHECK (amount_minor_units > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'INR',
+    status VARCHAR(32) NOT NULL DEFAULT 'PENDING'
+        CONSTRAINT deposits_status_check CHECK (status IN ('PENDING', 'COMPLETED', 'FAILED')),
+    funding_source VARCHAR(32) NOT NULL,
+    -- Reference of the charge at the funding source, once it is confirmed.
+    source_reference VARCHAR(255),
+    failure_reason TEXT,
+    transaction_id VARCHAR(64) REFERENCES transactions(transaction_id),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS deposits_user_id_created_at_idx ON deposits (user_id, created_at);
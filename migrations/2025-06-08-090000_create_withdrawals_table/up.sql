@@ -0,0 +1,48 @@
+-- Your SQL goes here
+
+-- System account that withdrawals are paid out through. It exists as a user so that withdrawal
+-- transactions can name it as their recipient; it cannot log in and its cached balance is not
+-- maintained, since its ledger balance only grows as money leaves the platform.
+INSERT INTO users (user_id, email, name, password)
+VALUES ('sys_payouts', 'payouts@system.invalid', 'System payouts account', '!')
+ON CONFLICT (user_id) DO NOTHING;
+
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_transaction_type_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_transaction_type_check
+    CHECK (transaction_type IN ('TRANSFER', 'REFUND', 'DEPOSIT', 'WITHDRAWAL'));
+
+-- External bank accounts a user can withdraw their balance to.
+CREATE TABLE IF NOT EXISTS bank_accounts (
+    id SERIAL PRIMARY KEY,
+    bank_account_id VARCHAR(64) NOT NULL UNIQUE,
+    user_id VARCHAR(64) NOT NULL,
+    account_holder_name VARCHAR(255) NOT NULL,
+    account_number VARCHAR(18) NOT NULL,
+    ifsc VARCHAR(11) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(user_id),
+    UNIQUE (user_id, account_number, ifsc)
+);
+
+-- Money a user moves out to one of their bank accounts. The balance is debited through a
+-- WITHDRAWAL transaction when the withdrawal is created, and returned if the payout fails.
+CREATE TABLE IF NOT EXISTS withdrawals (
+    id SERIAL PRIMARY KEY,
+    withdrawal_id VARCHAR(64) NOT NULL UNIQUE,
+    user_id VARCHAR(64) NOT NULL,
+    bank_account_id VARCHAR(64) NOT NULL REFERENCES bank_accounts(bank_account_id),
+    amount_minor_units BIGINT NOT NULL CONSTRAINT withdrawals_amount_positive CHECK (amount_minor_units > 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'INR',
+    status VARCHAR(32) NOT NULL DEFAULT 'PENDING'
+        CONSTRAINT withdrawals_status_check CHECK (status IN ('PENDING', 'COMPLETED', 'FAILED')),
+    connector VARCHAR(32) NOT NULL,
+    -- Reference of the payout at the connector, once it is paid out.
+    connector_reference VARCHAR(255),
+    failure_reason TEXT,
+    transaction_id VARCHAR(64) NOT NULL REFERENCES transactions(transaction_id),
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS withdrawals_user_id_created_at_idx ON withdrawals (user_id, created_at);
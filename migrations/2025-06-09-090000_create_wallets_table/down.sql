@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE users ADD COLUMN balance_minor_units BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE users ADD CONSTRAINT users_balance_non_negative CHECK (balance_minor_units >= 0);
+
+-- Only the INR balances fit back on `users`; balances in other currencies are dropped.
+UPDATE users SET balance_minor_units = wallets.balance_minor_units
+FROM wallets
+WHERE wallets.user_id = users.user_id AND wallets.currency = 'INR';
+
+DROP TABLE IF EXISTS wallets;
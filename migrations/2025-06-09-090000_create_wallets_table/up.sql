@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Each user holds one balance per currency they use, instead of a single balance on `users`.
+CREATE TABLE IF NOT EXISTS wallets (
+    id SERIAL PRIMARY KEY,
+    user_id VARCHAR(64) NOT NULL REFERENCES users(user_id),
+    currency VARCHAR(3) NOT NULL,
+    balance_minor_units BIGINT NOT NULL DEFAULT 0 CHECK (balance_minor_units >= 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, currency)
+);
+
+-- System accounts are tracked by the ledger alone and hold no wallet.
+INSERT INTO wallets (user_id, currency, balance_minor_units, created_at, updated_at)
+SELECT user_id, 'INR', balance_minor_units, created_at, last_modified_at
+FROM users
+WHERE user_id NOT LIKE 'sys\_%';
+
+ALTER TABLE users DROP COLUMN balance_minor_units;
@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE IF EXISTS fx_quotes;
+DROP TABLE IF EXISTS fx_rates;
+
+-- Keep the money converted so far, booked as balance adjustments.
+UPDATE ledger_entries SET account_id = 'sys_adjustments' WHERE account_id = 'sys_fx';
+DELETE FROM transactions WHERE transaction_type = 'CONVERSION';
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_transaction_type_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_transaction_type_check
+    CHECK (transaction_type IN ('TRANSFER', 'REFUND', 'DEPOSIT', 'WITHDRAWAL'));
+
+DELETE FROM users WHERE user_id = 'sys_fx';
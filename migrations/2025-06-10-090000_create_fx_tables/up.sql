@@ -0,0 +1,49 @@
+-- Your SQL goes here
+
+-- System account that currency conversions go through. It exists as a user so that conversion
+-- transactions can name it; it holds no wallet, and its ledger balance in each currency shows
+-- the platform's position from conversions.
+INSERT INTO users (user_id, email, name, password)
+VALUES ('sys_fx', 'fx@system.invalid', 'System FX account', '!')
+ON CONFLICT (user_id) DO NOTHING;
+
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_transaction_type_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_transaction_type_check
+    CHECK (transaction_type IN ('TRANSFER', 'REFUND', 'DEPOSIT', 'WITHDRAWAL', 'CONVERSION'));
+
+-- Mid-market exchange rates set by operators: one major unit of the base currency buys `rate`
+-- major units of the quote currency, scaled by 10^8.
+CREATE TABLE IF NOT EXISTS fx_rates (
+    id SERIAL PRIMARY KEY,
+    base_currency VARCHAR(3) NOT NULL,
+    quote_currency VARCHAR(3) NOT NULL,
+    rate_scaled BIGINT NOT NULL CHECK (rate_scaled > 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (base_currency, quote_currency),
+    CHECK (base_currency <> quote_currency)
+);
+
+-- Conversions between the wallets of a user at a rate locked until `expires_at`.
+CREATE TABLE IF NOT EXISTS fx_quotes (
+    id SERIAL PRIMARY KEY,
+    quote_id VARCHAR(64) NOT NULL UNIQUE,
+    user_id VARCHAR(64) NOT NULL REFERENCES users(user_id),
+    source_currency VARCHAR(3) NOT NULL,
+    target_currency VARCHAR(3) NOT NULL,
+    source_amount_minor_units BIGINT NOT NULL CHECK (source_amount_minor_units > 0),
+    target_amount_minor_units BIGINT NOT NULL CHECK (target_amount_minor_units > 0),
+    spread_minor_units BIGINT NOT NULL CHECK (spread_minor_units >= 0),
+    mid_rate_scaled BIGINT NOT NULL CHECK (mid_rate_scaled > 0),
+    spread_basis_points INTEGER NOT NULL CHECK (spread_basis_points >= 0),
+    rate_updated_at TIMESTAMP NOT NULL,
+    status VARCHAR(32) NOT NULL CHECK (status IN ('OPEN', 'EXECUTED')),
+    expires_at TIMESTAMP NOT NULL,
+    debit_transaction_id VARCHAR(64) REFERENCES transactions(transaction_id),
+    credit_transaction_id VARCHAR(64) REFERENCES transactions(transaction_id),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK (source_currency <> target_currency)
+);
+
+CREATE INDEX IF NOT EXISTS fx_quotes_user_id_idx ON fx_quotes (user_id, created_at);
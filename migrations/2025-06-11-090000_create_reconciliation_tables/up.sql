@@ -0,0 +1,32 @@
+-- Your SQL goes here
+
+-- One pass of the reconciliation job over every wallet, with how much it checked and found.
+CREATE TABLE IF NOT EXISTS reconciliation_runs (
+    id SERIAL PRIMARY KEY,
+    run_id VARCHAR(64) NOT NULL UNIQUE,
+    wallets_checked INTEGER NOT NULL CHECK (wallets_checked >= 0),
+    findings_count INTEGER NOT NULL CHECK (findings_count >= 0),
+    started_at TIMESTAMP NOT NULL DEFAULT now(),
+    completed_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS reconciliation_runs_started_at_idx ON reconciliation_runs (started_at);
+
+-- A discrepancy found by a run: a wallet whose cached balance differs from its transaction
+-- history or its ledger postings, or a currency in which money was created or destroyed. The
+-- account is only absent for findings about a whole currency.
+CREATE TABLE IF NOT EXISTS reconciliation_findings (
+    id SERIAL PRIMARY KEY,
+    finding_id VARCHAR(64) NOT NULL UNIQUE,
+    run_id VARCHAR(64) NOT NULL REFERENCES reconciliation_runs(run_id),
+    kind VARCHAR(32) NOT NULL
+        CONSTRAINT reconciliation_findings_kind_check
+        CHECK (kind IN ('BALANCE_MISMATCH', 'LEDGER_MISMATCH', 'MONEY_NOT_CONSERVED')),
+    account_id VARCHAR(64),
+    currency VARCHAR(3) NOT NULL,
+    expected_minor_units BIGINT NOT NULL,
+    actual_minor_units BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS reconciliation_findings_run_id_idx ON reconciliation_findings (run_id);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+-- The closing balance of a wallet at the end of a UTC day, materialized by the snapshot job
+-- once the day is over so balance history does not replay long stretches of transactions.
+CREATE TABLE IF NOT EXISTS balance_snapshots (
+    id SERIAL PRIMARY KEY,
+    account_id VARCHAR(64) NOT NULL REFERENCES users(user_id),
+    currency VARCHAR(3) NOT NULL,
+    snapshot_date DATE NOT NULL,
+    closing_balance_minor_units BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    UNIQUE (account_id, currency, snapshot_date)
+);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE IF EXISTS disputes;
+
+-- Keep the money returned by won disputes, booked as refunds.
+UPDATE transactions SET transaction_type = 'REFUND' WHERE transaction_type = 'REVERSAL';
+UPDATE transactions SET status = 'REFUNDED' WHERE status = 'REVERSED';
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_transaction_type_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_transaction_type_check
+    CHECK (transaction_type IN ('TRANSFER', 'REFUND', 'DEPOSIT', 'WITHDRAWAL', 'CONVERSION'));
@@ -0,0 +1,40 @@
+-- Your SQL goes here
+
+-- Money a won dispute returns from the recipient of a transfer to its sender is booked as a
+-- REVERSAL transaction linked to the transfer, which moves to REVERSED.
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_transaction_type_check;
+ALTER TABLE transactions ADD CONSTRAINT transactions_transaction_type_check
+    CHECK (transaction_type IN ('TRANSFER', 'REFUND', 'DEPOSIT', 'WITHDRAWAL', 'CONVERSION',
+                                'REVERSAL'));
+
+-- A dispute raised by the sender or the recipient of a completed transfer. While it is OPEN or
+-- UNDER_REVIEW, the disputed amount is frozen in the recipient's balance; an admin resolves it
+-- as WON, reversing the transfer, or LOST, releasing the funds. A transfer is disputed at most
+-- once.
+CREATE TABLE IF NOT EXISTS disputes (
+    id SERIAL PRIMARY KEY,
+    dispute_id VARCHAR(64) NOT NULL UNIQUE,
+    transaction_id VARCHAR(64) NOT NULL UNIQUE REFERENCES transactions(transaction_id),
+    opened_by VARCHAR(64) NOT NULL REFERENCES users(user_id),
+    sender_id VARCHAR(64) NOT NULL REFERENCES users(user_id),
+    recipient_id VARCHAR(64) NOT NULL REFERENCES users(user_id),
+    amount_minor_units BIGINT NOT NULL CONSTRAINT disputes_amount_positive CHECK (amount_minor_units > 0),
+    currency VARCHAR(3) NOT NULL,
+    reason VARCHAR(32) NOT NULL
+        CONSTRAINT disputes_reason_check
+        CHECK (reason IN ('UNAUTHORIZED', 'DUPLICATE', 'INCORRECT_AMOUNT', 'SENT_IN_ERROR',
+                          'NOT_RECEIVED', 'OTHER')),
+    evidence TEXT NOT NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'OPEN'
+        CONSTRAINT disputes_status_check CHECK (status IN ('OPEN', 'UNDER_REVIEW', 'WON', 'LOST')),
+    resolution_note TEXT,
+    -- The REVERSAL transaction that returned the money, only for WON disputes.
+    reversal_transaction_id VARCHAR(64) REFERENCES transactions(transaction_id),
+    resolved_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS disputes_opened_by_created_at_idx ON disputes (opened_by, created_at);
+CREATE INDEX IF NOT EXISTS disputes_frozen_idx
+    ON disputes (recipient_id, currency) WHERE status IN ('OPEN', 'UNDER_REVIEW');
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Lets the recovery job find the withdrawals whose payout has been left without an outcome.
+CREATE INDEX IF NOT EXISTS withdrawals_pending_idx
+    ON withdrawals (created_at) WHERE status = 'PENDING';
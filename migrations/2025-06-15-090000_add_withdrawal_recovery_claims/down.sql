@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE withdrawals DROP COLUMN IF EXISTS recovery_claimed_until;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Until when a recovery worker holds a pending withdrawal, so that only one instance at a time
+-- checks its payout with the connector or dispatches it again.
+ALTER TABLE withdrawals ADD COLUMN IF NOT EXISTS recovery_claimed_until TIMESTAMP;
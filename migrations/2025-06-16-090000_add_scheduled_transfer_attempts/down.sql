@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE scheduled_transfers DROP COLUMN IF EXISTS retry_at;
+ALTER TABLE scheduled_transfers DROP COLUMN IF EXISTS attempts;
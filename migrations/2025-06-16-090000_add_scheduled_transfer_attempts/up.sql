@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+-- How often executing a scheduled transfer hit a database error, and when the next attempt is
+-- due, so that a transfer that keeps failing backs off instead of holding up the queue.
+ALTER TABLE scheduled_transfers ADD COLUMN IF NOT EXISTS attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scheduled_transfers ADD COLUMN IF NOT EXISTS retry_at TIMESTAMP;
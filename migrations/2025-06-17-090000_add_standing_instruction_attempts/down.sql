@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE standing_instructions DROP CONSTRAINT IF EXISTS standing_instructions_status_check;
+UPDATE standing_instructions SET status = 'PAUSED' WHERE status = 'FAILED';
+ALTER TABLE standing_instructions ADD CONSTRAINT standing_instructions_status_check
+    CHECK (status IN ('ACTIVE', 'PAUSED', 'COMPLETED'));
+
+ALTER TABLE standing_instructions DROP COLUMN IF EXISTS retry_at;
+ALTER TABLE standing_instructions DROP COLUMN IF EXISTS attempts;
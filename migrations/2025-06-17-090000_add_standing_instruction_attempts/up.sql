@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- How often the due run of a standing instruction hit a database error, and when it is retried,
+-- so that an instruction that keeps failing backs off instead of holding up the others.
+ALTER TABLE standing_instructions ADD COLUMN IF NOT EXISTS attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE standing_instructions ADD COLUMN IF NOT EXISTS retry_at TIMESTAMP;
+
+-- Instructions whose recurrence can no longer be read stop running as FAILED.
+ALTER TABLE standing_instructions DROP CONSTRAINT IF EXISTS standing_instructions_status_check;
+ALTER TABLE standing_instructions ADD CONSTRAINT standing_instructions_status_check
+    CHECK (status IN ('ACTIVE', 'PAUSED', 'COMPLETED', 'FAILED'));
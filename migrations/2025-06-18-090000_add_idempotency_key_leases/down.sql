@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE idempotency_keys DROP COLUMN IF EXISTS locked_until;
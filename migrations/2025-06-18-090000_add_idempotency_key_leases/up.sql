@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- Until when the request that claimed a key holds it. A key still without a response after its
+-- lease lapsed was left behind by a request that died, and a retry may claim it again.
+ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS locked_until TIMESTAMP;
+
+-- Keys claimed before leases existed and never answered are taken to have lapsed.
+UPDATE idempotency_keys SET locked_until = created_at WHERE response_status_code IS NULL;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+UPDATE ledger_entries SET created_at = users.created_at
+FROM users
+WHERE ledger_entries.journal_id = 'opening_' || users.user_id;
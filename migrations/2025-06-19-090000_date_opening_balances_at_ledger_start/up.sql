@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+-- The ledger backfill dated every opening balance at the sign-up of its user, but balances used
+-- to be set directly and when that happened was never recorded, so the journal invented history.
+-- Date the opening balances when they are known to exist instead: now, in UTC like every other
+-- timestamp, which for a deployment running this with the ledger migration is when the ledger
+-- was introduced. Balances cannot be rebuilt for earlier instants, and the ledger endpoint
+-- rejects them.
+UPDATE ledger_entries SET created_at = now() AT TIME ZONE 'UTC' WHERE journal_id LIKE 'opening\_%';
@@ -2,12 +2,17 @@ use std::sync::Arc;
 
 use axum::{error_handling::HandleErrorLayer, response::IntoResponse};
 use error_stack::ResultExt;
-use tower_http::{trace as tower_trace, cors::CorsLayer};
+use tower_http::{cors::CorsLayer, trace as tower_trace};
 
 use crate::{
     configs::Config,
-    error, logger, routes,
+    error,
+    funding::{self, FundingSource},
+    logger,
+    payout::{self, PayoutConnector},
+    routes,
     storage::{self, caching::Caching},
+    workers,
 };
 
 const BUFFER_LIMIT: usize = 1024;
@@ -23,18 +28,62 @@ type Storage = Caching<storage::Storage>;
 pub struct AppState {
     pub db: Storage,
     pub config: Config,
+    pub funding_source: Box<dyn FundingSource>,
+    pub payout_connector: Box<dyn PayoutConnector>,
 }
 
 impl AppState {
     /// Creates a new AppState instance.
     pub async fn new(config: Config) -> error_stack::Result<Self, error::ConfigurationError> {
+        config.pagination.validate().change_context(
+            error::ConfigurationError::InvalidConfigurationValueError("pagination".into()),
+        )?;
+        config.fees.validate().change_context(
+            error::ConfigurationError::InvalidConfigurationValueError("fees".into()),
+        )?;
+        config.transfer_limits.validate().change_context(
+            error::ConfigurationError::InvalidConfigurationValueError("transfer_limits".into()),
+        )?;
+        config.risk.validate().change_context(
+            error::ConfigurationError::InvalidConfigurationValueError("risk".into()),
+        )?;
+        config.deposit.validate().change_context(
+            error::ConfigurationError::InvalidConfigurationValueError("deposit".into()),
+        )?;
+        config.withdrawal.validate().change_context(
+            error::ConfigurationError::InvalidConfigurationValueError("withdrawal".into()),
+        )?;
+        config.fx.validate().change_context(
+            error::ConfigurationError::InvalidConfigurationValueError("fx".into()),
+        )?;
+
         #[allow(clippy::map_identity)]
-        let db = storage::Storage::new(&config.database)
-            .await
-            .map(Caching::implement_cache(&config.cache))
-            .change_context(error::ConfigurationError::DatabaseError)?;
+        let db = storage::Storage::new(
+            &config.database,
+            &config.fees,
+            &config.transfer_limits,
+            &config.risk,
+        )
+        .await
+        .map(Caching::implement_cache(&config.cache))
+        .change_context(error::ConfigurationError::DatabaseError)?;
+
+        let funding_source = funding::from_config(&config.deposit);
+        let payout_connector = payout::from_config(&config.withdrawal);
+
+        Ok(Self {
+            db,
+            config,
+            funding_source,
+            payout_connector,
+        })
+    }
 
-        Ok(Self { db, config })
+    /// Returns the payout connector recorded as `name` on a withdrawal, if it is still
+    /// configured.
+    pub fn payout_connector_named(&self, name: &str) -> Option<&dyn PayoutConnector> {
+        let connector = self.payout_connector.as_ref();
+        (connector.name() == name).then_some(connector)
     }
 }
 
@@ -65,6 +114,31 @@ where
             "/transaction",
             routes::transaction::serve(app_state.clone()),
         )
+        .nest(
+            "/scheduled-transfer",
+            routes::scheduled_transfer::serve(app_state.clone()),
+        )
+        .nest(
+            "/standing-instruction",
+            routes::standing_instruction::serve(app_state.clone()),
+        )
+        .nest(
+            "/payment-request",
+            routes::payment_request::serve(app_state.clone()),
+        )
+        .nest(
+            "/batch-transfer",
+            routes::batch_transfer::serve(app_state.clone()),
+        )
+        .nest("/deposit", routes::deposit::serve(app_state.clone()))
+        .nest(
+            "/bank-account",
+            routes::bank_account::serve(app_state.clone()),
+        )
+        .nest("/withdrawal", routes::withdrawal::serve(app_state.clone()))
+        .nest("/fx", routes::fx::serve(app_state.clone()))
+        .nest("/dispute", routes::dispute::serve(app_state.clone()))
+        .nest("/admin", routes::admin::serve(app_state.clone()))
         .layer(ratelimit_middleware)
         .layer(
             tower_trace::TraceLayer::new_for_http()
@@ -95,6 +169,14 @@ where
         app_state.config.limit
     );
 
+    workers::spawn_authorization_expiry(app_state.clone());
+    workers::spawn_payment_request_expiry(app_state.clone());
+    workers::spawn_scheduled_transfer_executor(app_state.clone());
+    workers::spawn_standing_instruction_executor(app_state.clone());
+    workers::spawn_reconciliation(app_state.clone());
+    workers::spawn_balance_snapshots(app_state.clone());
+    workers::spawn_withdrawal_recovery(app_state.clone());
+
     let tcp_listener = tokio::net::TcpListener::bind(socket_addr).await?;
 
     axum::serve(tcp_listener, router.into_make_service()).await?;
@@ -0,0 +1,85 @@
+//! Balance history of a wallet over a range of days.
+//!
+//! A range of UTC days is split into [`BalanceBucket`]s of a [`BalanceInterval`], each showing
+//! the balance of the wallet at the end of its last day. Balances are walked back from the
+//! current balance of the wallet through what moved in or out of it each day since, so the
+//! history ends exactly at the balance users see. The snapshot job stores the closing balance
+//! of every wallet once a day is over, which spares long ranges from walking back that far.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+/// Represents how many days each point of a balance history covers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BalanceInterval {
+    #[default]
+    Day,
+    /// Weeks run from Monday to Sunday.
+    Week,
+    Month,
+}
+
+/// Represents the days a point of a balance history covers, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceBucket {
+    pub start: Date,
+    pub end: Date,
+}
+
+/// Splits the days from `from` to `to`, both included, into buckets of `interval`. The first
+/// and last buckets are cut short at `from` and `to`.
+pub fn buckets(
+    from: Date,
+    to: Date,
+    interval: BalanceInterval,
+) -> impl Iterator<Item = BalanceBucket> {
+    let bucket = move |start: Date| {
+        let end = match interval {
+            BalanceInterval::Day => start,
+            BalanceInterval::Week => {
+                let days_left = 6 - i64::from(start.weekday().number_days_from_monday());
+                start.saturating_add(time::Duration::days(days_left))
+            }
+            BalanceInterval::Month => start
+                .replace_day(start.month().length(start.year()))
+                .unwrap_or(start),
+        };
+        BalanceBucket {
+            start,
+            end: end.min(to),
+        }
+    };
+
+    let first = (from <= to).then(|| bucket(from));
+    std::iter::successors(first, move |previous| {
+        let start = previous.end.next_day()?;
+        (start <= to).then(|| bucket(start))
+    })
+}
+
+/// Returns the balance at the end of each of `days`, walking back from `current_balance`
+/// through `daily_flows`: what the transactions of each day added to the wallet, less what
+/// they took from it. Returns `None` if a balance overflows.
+pub fn closing_balances(
+    current_balance: i64,
+    daily_flows: &BTreeMap<Date, i64>,
+    days: impl IntoIterator<Item = Date>,
+) -> Option<BTreeMap<Date, i64>> {
+    let mut days = days.into_iter().collect::<Vec<_>>();
+    days.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut balance = current_balance;
+    let mut flows = daily_flows.iter().rev().peekable();
+    let mut closing = BTreeMap::new();
+    for day in days {
+        while let Some((_, flow)) = flows.next_if(|(flow_day, _)| **flow_day > day) {
+            balance = balance.checked_sub(*flow)?;
+        }
+        closing.insert(day, balance);
+    }
+
+    Some(closing)
+}
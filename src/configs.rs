@@ -1,6 +1,12 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
-use crate::logger::LogConfig;
+use crate::{
+    error::{FeeError, ValidationError},
+    logger::LogConfig,
+    payout::MockPayoutOutcome,
+    risk::{FanOutRule, NewAccountRule, NewCounterpartyRule, RoundTripRule, RuleAction},
+    types::{FeeSchedule, TransferLimits, fee::BASIS_POINTS_PER_WHOLE},
+};
 
 /// Represents the application configuration.
 #[derive(Clone, serde::Deserialize, Debug)]
@@ -15,8 +21,37 @@ pub struct Config {
     pub cache: Cache,
     /// Rate limiting configuration.
     pub limit: Limit,
+    /// List pagination configuration.
+    pub pagination: Pagination,
     /// Secrets configuration.
     pub secrets: Secrets,
+    /// Idempotency configuration.
+    pub idempotency: Idempotency,
+    /// Authorization hold configuration.
+    pub authorization: Authorization,
+    /// Scheduled transfer and standing instruction worker configuration.
+    pub scheduler: Scheduler,
+    /// Payment request configuration.
+    pub payment_request: PaymentRequest,
+    /// Batch transfer configuration.
+    pub batch_transfer: BatchTransfer,
+    /// Transfer fee configuration.
+    pub fees: Fees,
+    /// Default spending and velocity limits of every user, unless overridden for the user.
+    pub transfer_limits: TransferLimits,
+    /// Risk rules evaluated before each transfer.
+    #[serde(default)]
+    pub risk: Risk,
+    /// Deposit configuration.
+    pub deposit: Deposit,
+    /// Withdrawal configuration.
+    pub withdrawal: Withdrawal,
+    /// Currency conversion configuration.
+    pub fx: Fx,
+    /// Balance reconciliation configuration.
+    pub reconciliation: Reconciliation,
+    /// Balance history configuration.
+    pub balance_history: BalanceHistory,
 }
 
 /// Represents the server configuration.
@@ -65,11 +100,288 @@ pub struct Limit {
     pub buffer_size: Option<usize>,
 }
 
+/// Represents the list pagination configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Pagination {
+    /// Number of items on a page when the request does not ask for a page size.
+    pub default_page_size: u64,
+    /// Largest page size a request may ask for.
+    pub max_page_size: u64,
+}
+
+impl Pagination {
+    /// Validates that the default page size is within the maximum and pages are not empty.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.default_page_size == 0 || self.default_page_size > self.max_page_size {
+            return Err(ValidationError::InvalidValue {
+                message: "The default page size must be between 1 and the maximum page size".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the idempotency configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Idempotency {
+    /// Time (in seconds) after which an idempotency key expires and can be reused.
+    pub key_ttl: u64,
+    /// Time (in seconds) a request holds the key it claimed. A retry may claim a key whose
+    /// request has not stored a response by then, so it has to outlast the slowest request.
+    pub lock_ttl: u64,
+}
+
+/// Represents the authorization hold configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Authorization {
+    /// Time (in seconds) a hold lasts when the request does not ask for an expiry.
+    pub hold_ttl: u64,
+    /// Longest time (in seconds) a request may ask a hold to last.
+    pub max_hold_ttl: u64,
+    /// Interval (in seconds) between sweeps that mark lapsed authorizations as EXPIRED.
+    pub expiry_sweep_interval: u64,
+}
+
+/// Represents the scheduled transfer and standing instruction worker configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Scheduler {
+    /// Interval (in seconds) between polls for due scheduled transfers and standing instructions.
+    pub poll_interval: u64,
+    /// Maximum number of scheduled transfers or standing instruction runs executed per poll.
+    pub batch_size: u32,
+    /// What to do with standing instruction runs that are overdue by more than
+    /// `missed_run_grace`, e.g. after the workers were down.
+    pub catch_up_policy: CatchUpPolicy,
+    /// How late (in seconds) a standing instruction run may start before it counts as missed.
+    pub missed_run_grace: u64,
+    /// Time (in seconds) before a scheduled transfer or standing instruction run that hit a
+    /// database error is retried, doubled after every further attempt.
+    pub retry_delay: u64,
+    /// Number of attempts after which a scheduled transfer or standing instruction run that
+    /// keeps hitting database errors fails.
+    pub max_attempts: u32,
+}
+
+/// Represents how standing instruction runs missed while the workers were down are handled.
+#[derive(Clone, Copy, serde::Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CatchUpPolicy {
+    /// Skip missed runs and continue with the next one that is due.
+    Skip,
+    /// Execute every missed run, in order.
+    Execute,
+}
+
+/// Represents the payment request configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct PaymentRequest {
+    /// Time (in seconds) a payment request stays open when the request does not ask for an expiry.
+    pub default_ttl: u64,
+    /// Longest time (in seconds) a payment request may ask to stay open.
+    pub max_ttl: u64,
+    /// Interval (in seconds) between sweeps that mark lapsed payment requests as EXPIRED.
+    pub expiry_sweep_interval: u64,
+}
+
+/// Represents the batch transfer configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct BatchTransfer {
+    /// Maximum number of legs a single batch transfer may contain.
+    pub max_legs: usize,
+}
+
+/// Represents the transfer fee configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Fees {
+    /// Fee schedule applied to senders without an override.
+    pub default: FeeSchedule,
+    /// Fee schedules of specific senders, keyed by user ID.
+    #[serde(default)]
+    pub overrides: HashMap<String, FeeSchedule>,
+}
+
+impl Fees {
+    /// Returns the fee schedule that applies to transfers sent by `user_id`.
+    pub fn schedule_for(&self, user_id: &str) -> &FeeSchedule {
+        self.overrides.get(user_id).unwrap_or(&self.default)
+    }
+
+    /// Validates the default schedule and every override.
+    pub fn validate(&self) -> Result<(), FeeError> {
+        self.overrides
+            .values()
+            .chain([&self.default])
+            .try_for_each(FeeSchedule::validate)
+    }
+}
+
+/// Represents the risk rules evaluated before each transfer. Rules left out are disabled.
+#[derive(Clone, serde::Deserialize, Debug, Default)]
+pub struct Risk {
+    /// Flags large transfers from recently created accounts.
+    pub new_account: Option<NewAccountRule>,
+    /// Flags large first transfers to a recipient.
+    pub new_counterparty: Option<NewCounterpartyRule>,
+    /// Flags senders paying many different recipients within a short window.
+    pub fan_out: Option<FanOutRule>,
+    /// Flags money sent back to an account that recently paid the sender.
+    pub round_trip: Option<RoundTripRule>,
+}
+
+impl Risk {
+    /// Validates the enabled rules.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        [
+            self.new_account.as_ref().map(|rule| rule.action),
+            self.new_counterparty.as_ref().map(|rule| rule.action),
+            self.fan_out.as_ref().map(|rule| rule.action),
+            self.round_trip.as_ref().map(|rule| rule.action),
+        ]
+        .iter()
+        .flatten()
+        .try_for_each(RuleAction::validate)?;
+
+        if self
+            .fan_out
+            .as_ref()
+            .is_some_and(|rule| rule.max_recipients == 0)
+        {
+            return Err(ValidationError::InvalidValue {
+                message: "The fan-out rule must allow at least one recipient".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the deposit configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Deposit {
+    /// The funding source deposits are charged to.
+    pub funding_source: FundingSourceKind,
+    /// Largest amount of a single deposit, in minor units.
+    pub max_amount_minor_units: i64,
+}
+
+impl Deposit {
+    /// Validates that deposits of some amount are allowed.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.max_amount_minor_units <= 0 {
+            return Err(ValidationError::InvalidValue {
+                message: "The largest deposit amount must be positive".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the funding sources deposits can be charged to.
+#[derive(Clone, Copy, serde::Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FundingSourceKind {
+    /// Accepts every payment token except the test tokens of `MockFundingSource`.
+    Mock,
+}
+
+/// Represents the withdrawal configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Withdrawal {
+    /// The connector payouts are dispatched through.
+    pub connector: PayoutConnectorKind,
+    /// Time (in seconds) to wait for the connector before the withdrawal is left PENDING for
+    /// the recovery worker.
+    pub timeout: u64,
+    /// Largest amount of a single withdrawal, in minor units.
+    pub max_amount_minor_units: i64,
+    /// Interval (in seconds) between passes of the job that checks withdrawals left PENDING for
+    /// longer than `timeout` with their connector, or 0 to disable it.
+    pub recovery_interval: u64,
+}
+
+impl Withdrawal {
+    /// Validates that withdrawals of some amount are allowed and the connector gets time to
+    /// respond.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.max_amount_minor_units <= 0 {
+            return Err(ValidationError::InvalidValue {
+                message: "The largest withdrawal amount must be positive".into(),
+            });
+        }
+
+        if self.timeout == 0 {
+            return Err(ValidationError::InvalidValue {
+                message: "The payout timeout must be positive".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the connectors payouts can be dispatched through.
+#[derive(Clone, Copy, serde::Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PayoutConnectorKind {
+    /// Pays nothing out; every payout has the configured outcome.
+    Mock { outcome: MockPayoutOutcome },
+}
+
+/// Represents the currency conversion configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Fx {
+    /// Share of each converted amount the platform keeps as revenue, in basis points.
+    pub spread_basis_points: u32,
+    /// Time (in seconds) a quote locks its rate for.
+    pub quote_ttl: u64,
+    /// Age (in seconds) after which a rate is too old to be quoted.
+    pub max_rate_age: u64,
+}
+
+impl Fx {
+    /// Validates that conversions leave the customer something and quotes can be executed.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.spread_basis_points >= BASIS_POINTS_PER_WHOLE {
+            return Err(ValidationError::InvalidValue {
+                message: "The FX spread must be less than 10000 basis points".into(),
+            });
+        }
+
+        if self.quote_ttl == 0 || self.max_rate_age == 0 {
+            return Err(ValidationError::InvalidValue {
+                message: "The FX quote and rate lifetimes must be positive".into(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the balance reconciliation configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct Reconciliation {
+    /// Interval (in seconds) between scheduled reconciliation runs, or 0 to only run it from
+    /// the command line.
+    pub interval: u64,
+}
+
+/// Represents the balance history configuration.
+#[derive(Clone, serde::Deserialize, Debug)]
+pub struct BalanceHistory {
+    /// Interval (in seconds) between passes of the job that snapshots the closing balance of
+    /// every wallet for the days that are over, or 0 to disable it.
+    pub snapshot_interval: u64,
+}
+
 /// Represents the secrets configuration.
 #[derive(Debug, serde::Deserialize, Clone)]
 pub struct Secrets {
     /// KMS encrypted JWT secret.
     pub jwt_secret: String,
+    /// KMS encrypted API key for admin endpoints.
+    pub admin_api_key: String,
 }
 
 /// Get the origin directory of the project
@@ -96,7 +408,13 @@ impl Config {
 
         let config = Self::builder(&env)?
             .add_source(config::File::from(config_path).required(false))
-            .add_source(config::Environment::with_prefix("DODOPAYMENTS").separator("__"))
+            // Parse numbers and booleans in environment overrides, since tagged sections such as
+            // fee schedules are deserialized without a schema to coerce strings into them.
+            .add_source(
+                config::Environment::with_prefix("DODOPAYMENTS")
+                    .separator("__")
+                    .try_parsing(true),
+            )
             .build()?;
 
         serde_path_to_error::deserialize(config).map_err(|error| {
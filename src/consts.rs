@@ -1,2 +1,51 @@
 /// JWT token expiration time in seconds (2 days).
 pub const JWT_TOKEN_TIME_IN_SECS: u64 = 60 * 60 * 24 * 2; // 2 days
+
+/// System ledger account that balances manual balance adjustments and opening balances.
+pub const SYSTEM_ADJUSTMENT_ACCOUNT: &str = "sys_adjustments";
+
+/// System ledger account that collects the platform fees charged on transfers.
+pub const PLATFORM_REVENUE_ACCOUNT: &str = "sys_revenue";
+
+/// System account that deposits from external funding sources are booked from.
+pub const SYSTEM_FUNDING_ACCOUNT: &str = "sys_funding";
+
+/// System account that withdrawals to external bank accounts are paid out through.
+pub const SYSTEM_PAYOUTS_ACCOUNT: &str = "sys_payouts";
+
+/// System account that currency conversions are booked through.
+pub const SYSTEM_FX_ACCOUNT: &str = "sys_fx";
+
+/// Key of the Postgres advisory lock held while the scheduled reconciliation runs, so only one
+/// instance reconciles at a time.
+pub const RECONCILIATION_LOCK_ID: i64 = 0x7265_636f_6e63;
+
+/// Header carrying the API key for admin endpoints.
+pub const ADMIN_API_KEY_HEADER: &str = "x-admin-api-key";
+
+/// Header carrying the client supplied idempotency key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header set on responses replayed from a stored idempotency key.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Maximum length of a transaction description, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 500;
+
+/// Maximum length of the evidence of a dispute and of the note resolving it, in characters.
+pub const MAX_DISPUTE_TEXT_LENGTH: usize = 5000;
+
+/// Maximum number of points a balance history covers.
+pub const MAX_BALANCE_HISTORY_POINTS: usize = 1000;
+
+/// Number of days a balance history covers when the request does not set `from`.
+pub const DEFAULT_BALANCE_HISTORY_DAYS: i64 = 30;
+
+/// Number of balance snapshots stored per insert by the snapshot job.
+pub const BALANCE_SNAPSHOT_INSERT_BATCH_SIZE: usize = 1000;
+
+/// Number of transactions fetched from the database cursor at a time by an export.
+pub const EXPORT_BATCH_SIZE: usize = 500;
+
+/// Number of fetched batches an export buffers ahead of the client.
+pub const EXPORT_BUFFERED_BATCHES: usize = 2;
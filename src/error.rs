@@ -1,4 +1,7 @@
-use crate::error::container::ContainerError;
+use crate::{
+    error::container::{ContainerError, ErrorTransform},
+    types::{Currency, limits::LimitKind},
+};
 
 pub mod container;
 
@@ -91,6 +94,81 @@ pub enum ApiError {
 
     #[error("Failed to parse headers: {0}")]
     HeadersError(&'static str),
+
+    #[error("Insufficient balance to complete the transaction")]
+    InsufficientBalance,
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(&'static str),
+
+    #[error("Idempotency key was already used for a different request")]
+    IdempotencyKeyReused,
+
+    #[error("A request with the same idempotency key is still being processed")]
+    IdempotentRequestInProgress,
+
+    #[error("Transaction cannot move to the requested status")]
+    InvalidStatusTransition,
+
+    #[error("Refund exceeds the refundable amount of the transaction")]
+    RefundExceedsAmount,
+
+    #[error("Capture exceeds the authorized amount of the transaction")]
+    CaptureExceedsAuthorization,
+
+    #[error("Authorization has expired and its hold was released")]
+    AuthorizationExpired,
+
+    #[error("Payment request has expired")]
+    PaymentRequestExpired,
+
+    #[error("Payment request is being paid by a transfer under review")]
+    PaymentRequestUnderReview,
+
+    #[error("The request source is not allowed to perform this action: {0}")]
+    Forbidden(&'static str),
+
+    #[error("The fee of the transfer leaves nothing of the amount for the receiver")]
+    FeeExceedsAmount,
+
+    #[error("Transfer exceeds the {0} limit of the sender")]
+    LimitExceeded(LimitKind),
+
+    #[error("Transfer was blocked by risk checks")]
+    TransferBlocked,
+
+    #[error("The funding source declined the deposit")]
+    DepositDeclined,
+
+    #[error("The funding source is unavailable, try again later")]
+    FundingSourceUnavailable,
+
+    #[error("The bank account is already linked")]
+    BankAccountAlreadyLinked,
+
+    #[error("The user already holds a {0} wallet")]
+    WalletAlreadyExists(Currency),
+
+    #[error("The account does not hold a {0} wallet")]
+    CurrencyNotHeld(Currency),
+
+    #[error("No current exchange rate for the currency pair")]
+    FxRateUnavailable,
+
+    #[error("FX quote has expired")]
+    FxQuoteExpired,
+
+    #[error("The transaction was already disputed")]
+    DisputeAlreadyRaised,
+
+    #[error("The transaction has an open dispute")]
+    TransactionDisputed,
+
+    #[error("The receiver's available balance cannot cover the disputed amount")]
+    DisputeNotCovered,
+
+    #[error("The ledger cannot rebuild the balance from before the opening balance of the account")]
+    LedgerHistoryIncomplete,
 }
 
 /// Error code constants.
@@ -106,40 +184,35 @@ mod error_codes {
 
     /// Validation error: Represents an error occurring during data validation or integrity checks.
     pub const TE_03: &str = "TE_03";
+
+    /// Business rule error: Indicates a well-formed request that violates a business rule.
+    pub const TE_04: &str = "TE_04";
+
+    /// Idempotency error: Indicates a conflict with an earlier request using the same idempotency key.
+    pub const TE_05: &str = "TE_05";
+
+    /// Limit error: Indicates a transfer that exceeds a spending or velocity limit of its sender.
+    pub const TE_06: &str = "TE_06";
 }
 
-impl axum::response::IntoResponse for ApiError {
-    fn into_response(self) -> axum::response::Response {
+impl ApiError {
+    /// Returns the status code and body of the response for this error.
+    pub fn to_response_parts(self) -> (hyper::StatusCode, ApiErrorResponse) {
         match self {
             data @ Self::IncorrectPassword | data @ Self::UnAuthenticated => (
                 hyper::StatusCode::UNAUTHORIZED,
-                axum::Json(ApiErrorResponse::new(
-                    error_codes::TE_00,
-                    format!("{}", data),
-                    None,
-                )),
-            )
-                .into_response(),
+                ApiErrorResponse::new(error_codes::TE_00, format!("{}", data), None),
+            ),
 
             data @ Self::EncodingError => (
                 hyper::StatusCode::INTERNAL_SERVER_ERROR,
-                axum::Json(ApiErrorResponse::new(
-                    error_codes::TE_00,
-                    format!("{}", data),
-                    None,
-                )),
-            )
-                .into_response(),
+                ApiErrorResponse::new(error_codes::TE_00, format!("{}", data), None),
+            ),
 
             data @ Self::UnknownError(_) => (
                 hyper::StatusCode::INTERNAL_SERVER_ERROR,
-                axum::Json(ApiErrorResponse::new(
-                    error_codes::TE_00,
-                    format!("{}", data),
-                    None,
-                )),
-            )
-                .into_response(),
+                ApiErrorResponse::new(error_codes::TE_00, format!("{}", data), None),
+            ),
 
             data @ Self::DatabaseInsertFailed(_)
             | data @ Self::DatabaseError
@@ -147,44 +220,111 @@ impl axum::response::IntoResponse for ApiError {
             | data @ Self::DatabaseUpdationFailed(_)
             | data @ Self::RetrieveDataFailed(_) => (
                 hyper::StatusCode::INTERNAL_SERVER_ERROR,
-                axum::Json(ApiErrorResponse::new(
-                    error_codes::TE_01,
-                    format!("{}", data),
-                    None,
-                )),
-            )
-                .into_response(),
+                ApiErrorResponse::new(error_codes::TE_01, format!("{}", data), None),
+            ),
             data @ Self::DecodingError
             | data @ Self::ValidationError
-            | data @ Self::HeadersError(_) => (
+            | data @ Self::HeadersError(_)
+            | data @ Self::InvalidAmount(_) => (
                 hyper::StatusCode::BAD_REQUEST,
-                axum::Json(ApiErrorResponse::new(
-                    error_codes::TE_03,
-                    format!("{}", data),
-                    None,
-                )),
-            )
-                .into_response(),
+                ApiErrorResponse::new(error_codes::TE_03, format!("{}", data), None),
+            ),
             data @ Self::NotFoundError(_) => (
                 hyper::StatusCode::NOT_FOUND,
-                axum::Json(ApiErrorResponse::new(
-                    error_codes::TE_02,
-                    format!("{}", data),
-                    None,
-                )),
-            )
-                .into_response(),
+                ApiErrorResponse::new(error_codes::TE_02, format!("{}", data), None),
+            ),
             data @ Self::TransactionDatabaseError => (
                 hyper::StatusCode::INTERNAL_SERVER_ERROR,
-                axum::Json(ApiErrorResponse::new(
-                    error_codes::TE_01,
+                ApiErrorResponse::new(error_codes::TE_01, format!("{}", data), None),
+            ),
+            data @ Self::InsufficientBalance => (
+                hyper::StatusCode::UNPROCESSABLE_ENTITY,
+                ApiErrorResponse::new(error_codes::TE_04, format!("{}", data), None),
+            ),
+            data @ Self::IdempotencyKeyReused => (
+                hyper::StatusCode::UNPROCESSABLE_ENTITY,
+                ApiErrorResponse::new(error_codes::TE_05, format!("{}", data), None),
+            ),
+            data @ Self::IdempotentRequestInProgress => (
+                hyper::StatusCode::CONFLICT,
+                ApiErrorResponse::new(error_codes::TE_05, format!("{}", data), None),
+            ),
+            data @ Self::InvalidStatusTransition
+            | data @ Self::AuthorizationExpired
+            | data @ Self::PaymentRequestExpired
+            | data @ Self::PaymentRequestUnderReview
+            | data @ Self::FxQuoteExpired
+            | data @ Self::BankAccountAlreadyLinked
+            | data @ Self::WalletAlreadyExists(_)
+            | data @ Self::DisputeAlreadyRaised
+            | data @ Self::TransactionDisputed => (
+                hyper::StatusCode::CONFLICT,
+                ApiErrorResponse::new(error_codes::TE_04, format!("{}", data), None),
+            ),
+            data @ Self::RefundExceedsAmount
+            | data @ Self::CaptureExceedsAuthorization
+            | data @ Self::FeeExceedsAmount
+            | data @ Self::TransferBlocked
+            | data @ Self::DisputeNotCovered
+            | data @ Self::DepositDeclined
+            | data @ Self::FxRateUnavailable
+            | data @ Self::LedgerHistoryIncomplete => (
+                hyper::StatusCode::UNPROCESSABLE_ENTITY,
+                ApiErrorResponse::new(error_codes::TE_04, format!("{}", data), None),
+            ),
+            data @ Self::CurrencyNotHeld(currency) => (
+                hyper::StatusCode::UNPROCESSABLE_ENTITY,
+                ApiErrorResponse::new(
+                    error_codes::TE_04,
+                    format!("{}", data),
+                    Some(serde_json::json!({ "currency": currency })),
+                ),
+            ),
+            data @ Self::LimitExceeded(limit) => (
+                hyper::StatusCode::UNPROCESSABLE_ENTITY,
+                ApiErrorResponse::new(
+                    error_codes::TE_06,
                     format!("{}", data),
-                    None,
-                )),
-            )
-                .into_response(),
+                    Some(serde_json::json!({ "limit": limit })),
+                ),
+            ),
+            data @ Self::FundingSourceUnavailable => (
+                hyper::StatusCode::SERVICE_UNAVAILABLE,
+                ApiErrorResponse::new(error_codes::TE_00, format!("{}", data), None),
+            ),
+            data @ Self::Forbidden(_) => (
+                hyper::StatusCode::FORBIDDEN,
+                ApiErrorResponse::new(error_codes::TE_00, format!("{}", data), None),
+            ),
         }
     }
+
+    /// Whether a request that failed with this error is known to have left nothing behind and
+    /// may succeed when retried. Only then is its idempotency key released; any other error is
+    /// stored under the key and replayed.
+    ///
+    /// Database errors roll back the database transaction they hit, so they are retryable. A
+    /// request that fails on the database after it did something outside of it, like charging a
+    /// funding source, has to report another error.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::FundingSourceUnavailable
+                | Self::DatabaseError
+                | Self::TransactionDatabaseError
+                | Self::DatabaseInsertFailed(_)
+                | Self::DatabaseUpdationFailed(_)
+                | Self::DatabaseDeleteFailed(_)
+                | Self::RetrieveDataFailed(_)
+        )
+    }
+}
+
+impl axum::response::IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        let (status_code, body) = self.to_response_parts();
+        (status_code, axum::Json(body)).into_response()
+    }
 }
 
 impl<T: axum::response::IntoResponse + error_stack::Context + Copy> axum::response::IntoResponse
@@ -247,4 +387,216 @@ pub enum TransactionDbError {
     UnknownError,
     #[error("Element not found in storage")]
     NotFoundError,
+    #[error("Sender or recipient account not found")]
+    AccountNotFound,
+    #[error("Sender does not have enough balance")]
+    InsufficientBalance,
+    #[error("Amount overflowed the supported range")]
+    AmountOverflow,
+    #[error("Sender and recipient cannot be the same account")]
+    SameAccount,
+    #[error("Ledger postings of a journal do not balance")]
+    UnbalancedJournal,
+    #[error("Transaction cannot move to the requested status")]
+    InvalidStatusTransition,
+    #[error("Refund exceeds the refundable amount of the transaction")]
+    RefundExceedsAmount,
+    #[error("Capture exceeds the authorized amount of the transaction")]
+    CaptureExceedsAuthorization,
+    #[error("Authorization has expired")]
+    AuthorizationExpired,
+    #[error("Payment request has expired")]
+    PaymentRequestExpired,
+    #[error("Payment request is being paid by a transfer under review")]
+    PaymentRequestUnderReview,
+    #[error("Fee leaves nothing of the amount for the recipient")]
+    FeeExceedsAmount,
+    #[error("Transfer exceeds the {0} limit of the sender")]
+    LimitExceeded(LimitKind),
+    #[error("Bank account is already linked to the user")]
+    DuplicateBankAccount,
+    #[error("User already holds a {0} wallet")]
+    DuplicateWallet(Currency),
+    #[error("Account does not hold a {0} wallet")]
+    CurrencyNotHeld(Currency),
+    #[error("FX quote has expired")]
+    FxQuoteExpired,
+    #[error("Transaction was already disputed")]
+    DuplicateDispute,
+    #[error("Transaction has an open dispute")]
+    TransactionDisputed,
+    #[error("Transfer was blocked by risk checks")]
+    TransferBlocked,
+    #[error("Recipient's available balance cannot cover the disputed amount")]
+    DisputeNotCovered,
+}
+
+impl TransactionDbError {
+    /// Whether the error came from the database rather than from the transfer itself, in
+    /// which case the same transfer may go through when retried.
+    pub fn is_database_error(&self) -> bool {
+        matches!(
+            self,
+            Self::DBError
+                | Self::DBFilterError
+                | Self::DBInsertError
+                | Self::DBUpdateError
+                | Self::UnknownError
+                | Self::UnbalancedJournal
+        )
+    }
+}
+
+impl From<diesel::result::Error> for ContainerError<TransactionDbError> {
+    #[track_caller]
+    fn from(error: diesel::result::Error) -> Self {
+        let context = match error {
+            diesel::result::Error::NotFound => TransactionDbError::NotFoundError,
+            _ => TransactionDbError::DBError,
+        };
+        error_stack::Report::from(error)
+            .change_context(context)
+            .into()
+    }
+}
+
+impl From<MoneyError> for TransactionDbError {
+    fn from(error: MoneyError) -> Self {
+        match error {
+            MoneyError::NegativeAmount => Self::InsufficientBalance,
+            _ => Self::AmountOverflow,
+        }
+    }
+}
+
+impl From<diesel::result::Error> for ContainerError<UserDbError> {
+    #[track_caller]
+    fn from(error: diesel::result::Error) -> Self {
+        let context = match error {
+            diesel::result::Error::NotFound => UserDbError::NotFoundError,
+            _ => UserDbError::DBError,
+        };
+        error_stack::Report::from(error)
+            .change_context(context)
+            .into()
+    }
+}
+
+impl ErrorTransform<ContainerError<TransactionDbError>> for ContainerError<UserDbError> {}
+
+impl From<&TransactionDbError> for UserDbError {
+    fn from(error: &TransactionDbError) -> Self {
+        match error {
+            TransactionDbError::DBError => Self::DBError,
+            TransactionDbError::NotFoundError | TransactionDbError::AccountNotFound => {
+                Self::NotFoundError
+            }
+            _ => Self::DBUpdateError,
+        }
+    }
+}
+
+impl ErrorTransform<ContainerError<TransactionDbError>> for ContainerError<ApiError> {}
+
+impl From<&TransactionDbError> for ApiError {
+    fn from(error: &TransactionDbError) -> Self {
+        match error {
+            TransactionDbError::NotFoundError => Self::NotFoundError("transaction"),
+            TransactionDbError::AccountNotFound => Self::NotFoundError("user"),
+            TransactionDbError::InsufficientBalance => Self::InsufficientBalance,
+            TransactionDbError::AmountOverflow => {
+                Self::InvalidAmount("amount overflowed the supported range")
+            }
+            TransactionDbError::SameAccount => Self::ValidationError,
+            TransactionDbError::UnbalancedJournal => Self::TransactionDatabaseError,
+            TransactionDbError::InvalidStatusTransition => Self::InvalidStatusTransition,
+            TransactionDbError::RefundExceedsAmount => Self::RefundExceedsAmount,
+            TransactionDbError::CaptureExceedsAuthorization => Self::CaptureExceedsAuthorization,
+            TransactionDbError::AuthorizationExpired => Self::AuthorizationExpired,
+            TransactionDbError::PaymentRequestExpired => Self::PaymentRequestExpired,
+            TransactionDbError::PaymentRequestUnderReview => Self::PaymentRequestUnderReview,
+            TransactionDbError::FeeExceedsAmount => Self::FeeExceedsAmount,
+            TransactionDbError::LimitExceeded(limit) => Self::LimitExceeded(*limit),
+            TransactionDbError::DuplicateBankAccount => Self::BankAccountAlreadyLinked,
+            TransactionDbError::DuplicateWallet(currency) => Self::WalletAlreadyExists(*currency),
+            TransactionDbError::CurrencyNotHeld(currency) => Self::CurrencyNotHeld(*currency),
+            TransactionDbError::FxQuoteExpired => Self::FxQuoteExpired,
+            TransactionDbError::DuplicateDispute => Self::DisputeAlreadyRaised,
+            TransactionDbError::TransactionDisputed => Self::TransactionDisputed,
+            TransactionDbError::TransferBlocked => Self::TransferBlocked,
+            TransactionDbError::DisputeNotCovered => Self::DisputeNotCovered,
+            TransactionDbError::DBError
+            | TransactionDbError::DBFilterError
+            | TransactionDbError::DBInsertError
+            | TransactionDbError::DBUpdateError
+            | TransactionDbError::UnknownError => Self::TransactionDatabaseError,
+        }
+    }
+}
+
+/// Represents money arithmetic and parsing errors.
+#[derive(Debug, thiserror::Error, Clone, PartialEq)]
+pub enum MoneyError {
+    #[error("Amount cannot be negative")]
+    NegativeAmount,
+    #[error("Amount overflowed the supported range")]
+    Overflow,
+    #[error("Currency mismatch: expected {expected}, found {found}")]
+    CurrencyMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("Unsupported currency: {0}")]
+    UnsupportedCurrency(String),
+    #[error("Invalid amount format: {0}")]
+    InvalidFormat(String),
+}
+
+/// Represents errors in fee schedules.
+#[derive(Debug, thiserror::Error, Clone, PartialEq)]
+pub enum FeeError {
+    #[error("Fee amounts cannot be negative")]
+    NegativeAmount,
+    #[error("Basis points cannot exceed 10000, found {0}")]
+    InvalidBasisPoints(u32),
+    #[error("Minimum fee cannot exceed the maximum fee")]
+    MinimumExceedsMaximum,
+    #[error("Fee tiers need increasing upper bounds and a last tier without one")]
+    InvalidTiers,
+}
+
+/// Represents errors in exchange rates and conversions.
+#[derive(Debug, thiserror::Error, Clone, PartialEq)]
+pub enum FxError {
+    #[error("Invalid exchange rate: {0}")]
+    InvalidRate(String),
+    #[error(transparent)]
+    Money(#[from] MoneyError),
+}
+
+/// Represents errors returned by funding sources when charging a deposit.
+#[derive(Debug, thiserror::Error, Clone, PartialEq)]
+pub enum FundingError {
+    #[error("{0}")]
+    Declined(String),
+    #[error("Funding source is unavailable")]
+    Unavailable,
+}
+
+/// Represents errors returned by payout connectors when paying out a withdrawal.
+#[derive(Debug, thiserror::Error, Clone, PartialEq)]
+pub enum PayoutError {
+    #[error("{0}")]
+    Rejected(String),
+    #[error("Payout connector did not respond in time")]
+    TimedOut,
+}
+
+/// Represents errors in recurrence rules of standing instructions.
+#[derive(Debug, thiserror::Error, Clone, PartialEq)]
+pub enum RecurrenceError {
+    #[error("Invalid cron expression: {0}")]
+    InvalidCronExpression(String),
+    #[error("Day of month must be between 1 and 31, found {0}")]
+    InvalidDayOfMonth(u8),
 }
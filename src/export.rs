@@ -0,0 +1,166 @@
+//! Bulk export of transaction history.
+//!
+//! Every exported transaction becomes an [`ExportRow`] with the columns of [`EXPORT_COLUMNS`],
+//! in that order, whatever the format. Amounts are decimal strings in major units of the
+//! transaction's currency and timestamps are RFC 3339 in UTC with microsecond precision.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::MoneyError,
+    storage::{
+        enums::{TransactionStatus, TransactionType},
+        types::Transaction,
+    },
+    types::Metadata,
+    utils::{self, datetime},
+};
+
+/// Columns of an exported transaction, in the order CSV exports list them.
+pub const EXPORT_COLUMNS: [&str; 15] = [
+    "transaction_id",
+    "created_at",
+    "updated_at",
+    "transaction_type",
+    "status",
+    "sender_id",
+    "receiver_id",
+    "currency",
+    "amount",
+    "fee",
+    "net_amount",
+    "description",
+    "parent_transaction_id",
+    "failure_reason",
+    "metadata",
+];
+
+/// Represents the format transactions are exported in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma separated values with a header row, records terminated by CRLF.
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// Returns the content type of an export in this format.
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// Returns the file name of an export in this format.
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::Csv => "transactions.csv",
+            Self::Ndjson => "transactions.ndjson",
+        }
+    }
+
+    /// Returns what an export starts with before its first row: the header row for CSV.
+    pub fn header(self) -> String {
+        match self {
+            Self::Csv => utils::csv::record(EXPORT_COLUMNS),
+            Self::Ndjson => String::new(),
+        }
+    }
+
+    /// Renders a batch of transactions, one row or line each.
+    pub fn render(self, transactions: &[Transaction]) -> Result<String, MoneyError> {
+        let mut rendered = String::new();
+        for transaction in transactions {
+            let row = ExportRow::try_from(transaction)?;
+            match self {
+                Self::Csv => rendered.push_str(&row.to_csv_record()),
+                Self::Ndjson => {
+                    // Serializing strings and a string map cannot fail.
+                    rendered.push_str(&serde_json::to_string(&row).unwrap_or_default());
+                    rendered.push('\n');
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+}
+
+/// Represents an exported transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExportRow {
+    pub transaction_id: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub transaction_type: TransactionType,
+    pub status: TransactionStatus,
+    pub sender_id: String,
+    pub receiver_id: String,
+    pub currency: String,
+    /// The amount debited from the sender, fee included.
+    pub amount: String,
+    pub fee: String,
+    /// The amount credited to the receiver.
+    pub net_amount: String,
+    pub description: Option<String>,
+    pub parent_transaction_id: Option<String>,
+    pub failure_reason: Option<String>,
+    pub metadata: Option<Metadata>,
+}
+
+impl ExportRow {
+    /// Renders the row as a CSV record. Absent values are empty and metadata is a JSON object.
+    pub fn to_csv_record(&self) -> String {
+        let metadata = self
+            .metadata
+            .as_ref()
+            .and_then(|metadata| serde_json::to_string(metadata).ok())
+            .unwrap_or_default();
+
+        utils::csv::record([
+            self.transaction_id.as_str(),
+            &self.created_at,
+            &self.updated_at,
+            self.transaction_type.as_str(),
+            self.status.as_str(),
+            &self.sender_id,
+            &self.receiver_id,
+            &self.currency,
+            &self.amount,
+            &self.fee,
+            &self.net_amount,
+            self.description.as_deref().unwrap_or(""),
+            self.parent_transaction_id.as_deref().unwrap_or(""),
+            self.failure_reason.as_deref().unwrap_or(""),
+            &metadata,
+        ])
+    }
+}
+
+impl TryFrom<&Transaction> for ExportRow {
+    type Error = MoneyError;
+
+    fn try_from(transaction: &Transaction) -> Result<Self, Self::Error> {
+        Ok(Self {
+            transaction_id: transaction.transaction_id.clone(),
+            created_at: datetime::to_rfc3339(transaction.created_at),
+            updated_at: datetime::to_rfc3339(transaction.updated_at),
+            transaction_type: transaction.transaction_type,
+            status: transaction.status,
+            sender_id: transaction.sender_id.clone(),
+            receiver_id: transaction.recipient_id.clone(),
+            currency: transaction.currency.clone(),
+            amount: transaction.amount()?.to_decimal_string(),
+            fee: transaction.fee()?.to_decimal_string(),
+            net_amount: transaction.net_amount()?.to_decimal_string(),
+            description: transaction.description.clone(),
+            parent_transaction_id: transaction.parent_transaction_id.clone(),
+            failure_reason: transaction.failure_reason.clone(),
+            metadata: transaction.metadata.clone(),
+        })
+    }
+}
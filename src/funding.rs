@@ -0,0 +1,64 @@
+//! Funding sources that deposits are charged to.
+//!
+//! A deposit credits a user's balance only once its [`FundingSource`] confirms the charge, e.g.
+//! a card or bank payment. The source is picked by `deposit.funding_source` in the
+//! configuration.
+
+use crate::{configs, error::FundingError, types::Money};
+
+/// An external source of money that deposits are charged to.
+#[async_trait::async_trait]
+pub trait FundingSource: Send + Sync {
+    /// Name of the source, recorded with its deposits.
+    fn name(&self) -> &'static str;
+
+    /// Charges the payment method of a deposit, returning the reference of the charge at the
+    /// source. The deposit ID is passed along so the source can recognise a retried charge.
+    async fn charge(&self, charge: &FundingCharge) -> Result<String, FundingError>;
+}
+
+/// Represents a deposit to be charged to a funding source.
+#[derive(Clone, Debug)]
+pub struct FundingCharge {
+    pub deposit_id: String,
+    pub user_id: String,
+    pub amount: Money,
+    /// Token of the payment method, issued to the client by the funding source.
+    pub payment_token: String,
+}
+
+/// Creates the funding source selected in the configuration.
+pub fn from_config(config: &configs::Deposit) -> Box<dyn FundingSource> {
+    match config.funding_source {
+        configs::FundingSourceKind::Mock => Box::new(MockFundingSource),
+    }
+}
+
+/// Funding source for development and tests that charges every payment token except the ones
+/// below, without moving any real money.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MockFundingSource;
+
+impl MockFundingSource {
+    /// Payment token that the mock declines.
+    pub const DECLINED_TOKEN: &str = "tok_declined";
+    /// Payment token for which the mock behaves as if it were unreachable.
+    pub const UNAVAILABLE_TOKEN: &str = "tok_unavailable";
+}
+
+#[async_trait::async_trait]
+impl FundingSource for MockFundingSource {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn charge(&self, charge: &FundingCharge) -> Result<String, FundingError> {
+        match charge.payment_token.as_str() {
+            Self::DECLINED_TOKEN => {
+                Err(FundingError::Declined("Payment method was declined".into()))
+            }
+            Self::UNAVAILABLE_TOKEN => Err(FundingError::Unavailable),
+            _ => Ok(format!("mock_{}", charge.deposit_id)),
+        }
+    }
+}
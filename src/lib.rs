@@ -2,22 +2,38 @@
 
 /// Application modules
 pub mod app;
+/// Balance history of wallets
+pub mod balance_history;
 /// Configuration modules
 pub mod configs;
 /// Constant values
 pub mod consts;
 /// Error definitions
 pub mod error;
+/// Bulk export of transactions
+pub mod export;
+/// Funding sources for deposits
+pub mod funding;
 /// Logging setup
 pub mod logger;
+/// Payout connectors for withdrawals
+pub mod payout;
+/// Reconciliation of balances
+pub mod reconciliation;
+/// Risk rules
+pub mod risk;
 /// Route definitions
 pub mod routes;
+/// Monthly account statements
+pub mod statement;
 /// Storage layer
 pub mod storage;
 /// Type definitions
 pub mod types;
 /// Utility functions
 pub mod utils;
+/// Background workers
+pub mod workers;
 
 /// Macro for defining the service name
 #[macro_export]
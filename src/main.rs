@@ -1,11 +1,21 @@
 use std::sync::Arc;
 
-use dodopayments::{app, configs, logger};
+use dodopayments::{app, configs, logger, storage::ReconciliationInterface};
 
 /// Main function of the application.
+///
+/// Serves the API by default. `reconcile` instead runs the balance reconciliation once, prints
+/// its findings and exits with a failure status if any were found.
 #[allow(clippy::expect_used)]
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let command = std::env::args().nth(1);
+    if let Some(command) = command.as_deref()
+        && !matches!(command, "serve" | "reconcile")
+    {
+        return Err(format!("Unknown command: {command}, expected serve or reconcile").into());
+    }
+
     // Load configurations from environment variables and files.
     let configs = configs::Config::new().expect("Failed while parsing config");
 
@@ -18,6 +28,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await
         .expect("Failed while creating the app state");
 
+    if command.as_deref() == Some("reconcile") {
+        let (run, findings) = app_state
+            .db
+            .reconcile()
+            .await
+            .expect("Failed while reconciling balances");
+
+        println!(
+            "Reconciliation run {} checked {} wallets and found {} discrepancies",
+            run.run_id,
+            run.wallets_checked,
+            findings.len()
+        );
+        for finding in &findings {
+            println!(
+                "{} {} {}: expected {}, found {}",
+                finding.kind,
+                finding.account_id.as_deref().unwrap_or("*"),
+                finding.currency,
+                finding.expected_minor_units,
+                finding.actual_minor_units
+            );
+        }
+
+        if !findings.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Build and start the server.
     app::server_builder(Arc::new(app_state))
         .await
@@ -0,0 +1,124 @@
+//! Payout connectors that withdrawals are paid out through.
+//!
+//! A withdrawal debits the user's balance first and then hands the payout to a
+//! [`PayoutConnector`], e.g. a bank's payout API. The connector is picked by
+//! `withdrawal.connector` in the configuration. A payout the connector does not answer for in
+//! time stays PENDING until the recovery worker learns its outcome through
+//! [`PayoutConnector::status`].
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{configs, error::PayoutError, types::Money};
+
+/// An external rail that pays money out to bank accounts.
+#[async_trait::async_trait]
+pub trait PayoutConnector: Send + Sync {
+    /// Name of the connector, recorded with its withdrawals.
+    fn name(&self) -> &'static str;
+
+    /// Pays out a withdrawal, returning the reference of the payout at the connector. The
+    /// withdrawal ID is passed along so the connector can recognise a retried payout.
+    async fn dispatch(&self, payout: &Payout) -> Result<String, PayoutError>;
+
+    /// Looks up what became of the payout of a withdrawal, by withdrawal ID.
+    async fn status(&self, withdrawal_id: &str) -> Result<PayoutStatus, PayoutError>;
+}
+
+/// Represents what the connector knows about the payout of a withdrawal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PayoutStatus {
+    /// The payout was paid out, with its reference at the connector.
+    Paid(String),
+    /// The payout was rejected for good, with the reason.
+    Rejected(String),
+    /// The payout was received but has no outcome yet.
+    Processing,
+    /// The connector never received the payout.
+    NotFound,
+}
+
+/// Represents a withdrawal to be paid out to a bank account.
+#[derive(Clone, Debug)]
+pub struct Payout {
+    pub withdrawal_id: String,
+    pub amount: Money,
+    pub account_holder_name: String,
+    pub account_number: String,
+    pub ifsc: String,
+}
+
+/// Creates the payout connector selected in the configuration.
+pub fn from_config(config: &configs::Withdrawal) -> Box<dyn PayoutConnector> {
+    match config.connector {
+        configs::PayoutConnectorKind::Mock { outcome } => Box::new(MockPayoutConnector { outcome }),
+    }
+}
+
+/// Dispatches a payout, giving up once `timeout` passes without an answer from the connector.
+pub async fn dispatch(
+    connector: &dyn PayoutConnector,
+    payout: &Payout,
+    timeout: Duration,
+) -> Result<String, PayoutError> {
+    tokio::time::timeout(timeout, connector.dispatch(payout))
+        .await
+        .unwrap_or(Err(PayoutError::TimedOut))
+}
+
+/// Looks up the status of a payout, giving up once `timeout` passes without an answer from the
+/// connector.
+pub async fn status(
+    connector: &dyn PayoutConnector,
+    withdrawal_id: &str,
+    timeout: Duration,
+) -> Result<PayoutStatus, PayoutError> {
+    tokio::time::timeout(timeout, connector.status(withdrawal_id))
+        .await
+        .unwrap_or(Err(PayoutError::TimedOut))
+}
+
+/// Represents what the mock connector does with every payout.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MockPayoutOutcome {
+    /// The payout is paid out.
+    Succeed,
+    /// The bank rejects the payout.
+    Fail,
+    /// The connector never answers, and reports the payout as still processing.
+    Timeout,
+}
+
+/// Payout connector for development and tests that pays nothing out and gives every payout
+/// the same outcome.
+#[derive(Clone, Copy, Debug)]
+pub struct MockPayoutConnector {
+    pub outcome: MockPayoutOutcome,
+}
+
+#[async_trait::async_trait]
+impl PayoutConnector for MockPayoutConnector {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn dispatch(&self, payout: &Payout) -> Result<String, PayoutError> {
+        match self.outcome {
+            MockPayoutOutcome::Succeed => Ok(format!("mock_{}", payout.withdrawal_id)),
+            MockPayoutOutcome::Fail => {
+                Err(PayoutError::Rejected("Bank rejected the payout".into()))
+            }
+            MockPayoutOutcome::Timeout => std::future::pending().await,
+        }
+    }
+
+    async fn status(&self, withdrawal_id: &str) -> Result<PayoutStatus, PayoutError> {
+        Ok(match self.outcome {
+            MockPayoutOutcome::Succeed => PayoutStatus::Paid(format!("mock_{withdrawal_id}")),
+            MockPayoutOutcome::Fail => PayoutStatus::Rejected("Bank rejected the payout".into()),
+            MockPayoutOutcome::Timeout => PayoutStatus::Processing,
+        })
+    }
+}
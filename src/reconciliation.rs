@@ -0,0 +1,100 @@
+//! Reconciliation of cached wallet balances against the history they were built from.
+//!
+//! A [`BalanceSnapshot`] holds, for every wallet, its cached balance, the balance replayed from
+//! the transactions that moved money plus the adjustments booked outside of transactions, and
+//! the balance rebuilt from the ledger. [`BalanceSnapshot::discrepancies`] compares them and
+//! checks that the money held in wallets is exactly what the system accounts paid in.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::storage::enums::ReconciliationFindingKind;
+
+/// Identifies the balance of an account in one currency: its ID and ISO 4217 code.
+pub type BalanceKey = (String, String);
+
+/// Represents the balances of every account, taken inside one consistent database snapshot.
+#[derive(Clone, Debug, Default)]
+pub struct BalanceSnapshot {
+    /// Cached balances of the wallets of users.
+    pub wallets: HashMap<BalanceKey, i64>,
+    /// Balances replayed from the transactions that moved money, plus adjustments.
+    pub history: HashMap<BalanceKey, i64>,
+    /// Balances rebuilt from the ledger postings of every account, system accounts included.
+    pub ledger: HashMap<BalanceKey, i64>,
+}
+
+/// Represents a discrepancy between what a balance is and what it should be.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub kind: ReconciliationFindingKind,
+    /// The account whose balance is off, absent for findings about a whole currency.
+    pub account_id: Option<String>,
+    pub currency: String,
+    pub expected_minor_units: i64,
+    pub actual_minor_units: i64,
+}
+
+impl BalanceSnapshot {
+    /// Compares every wallet against its transaction history and its ledger postings, then
+    /// checks each currency for money that was created or destroyed. Accounts without a
+    /// wallet in a currency are system accounts; what they paid out must be held in wallets.
+    pub fn discrepancies(&self) -> Vec<Discrepancy> {
+        let mut wallets = self.wallets.iter().collect::<Vec<_>>();
+        wallets.sort();
+
+        let mut discrepancies = Vec::new();
+        for &((account_id, currency), &balance) in &wallets {
+            let key = (account_id.clone(), currency.clone());
+            for (kind, expected) in [
+                (
+                    ReconciliationFindingKind::BalanceMismatch,
+                    self.history.get(&key).copied().unwrap_or(0),
+                ),
+                (
+                    ReconciliationFindingKind::LedgerMismatch,
+                    self.ledger.get(&key).copied().unwrap_or(0),
+                ),
+            ] {
+                if expected != balance {
+                    discrepancies.push(Discrepancy {
+                        kind,
+                        account_id: Some(account_id.clone()),
+                        currency: currency.clone(),
+                        expected_minor_units: expected,
+                        actual_minor_units: balance,
+                    });
+                }
+            }
+        }
+
+        // Totals per currency: (held in wallets, paid out by system accounts).
+        let mut totals = BTreeMap::<&str, (i128, i128)>::new();
+        for &((_, currency), &balance) in &wallets {
+            totals.entry(currency.as_str()).or_default().0 += i128::from(balance);
+        }
+        for (key, &balance) in &self.ledger {
+            if !self.wallets.contains_key(key) {
+                totals.entry(key.1.as_str()).or_default().1 -= i128::from(balance);
+            }
+        }
+
+        for (currency, (held, paid_in)) in totals {
+            if held != paid_in {
+                discrepancies.push(Discrepancy {
+                    kind: ReconciliationFindingKind::MoneyNotConserved,
+                    account_id: None,
+                    currency: currency.to_string(),
+                    expected_minor_units: saturate(paid_in),
+                    actual_minor_units: saturate(held),
+                });
+            }
+        }
+
+        discrepancies
+    }
+}
+
+/// Clamps a total to the range of a stored amount.
+fn saturate(total: i128) -> i64 {
+    i64::try_from(total).unwrap_or(if total < 0 { i64::MIN } else { i64::MAX })
+}
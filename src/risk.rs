@@ -0,0 +1,336 @@
+//! Risk rules evaluated before a transfer moves any money.
+//!
+//! Each [`RiskRule`] looks at the transfer and the recent history of the accounts involved and
+//! decides whether it may go ahead, needs an admin to review it or is blocked. The
+//! [`RiskEngine`] runs every rule and keeps the most severe decision.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use time::PrimitiveDateTime;
+
+use crate::{configs, error::ValidationError, storage::enums::RiskDecision, types::Money};
+
+/// A check a transfer has to pass before it moves any money.
+pub trait RiskRule: Send + Sync {
+    /// Name of the rule, recorded with its hits.
+    fn name(&self) -> &'static str;
+
+    /// How far back the rule looks at the history of the accounts.
+    fn lookback(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Decides whether the transfer may go ahead.
+    fn evaluate(&self, input: &RiskInput) -> RiskOutcome;
+}
+
+/// Represents a transfer about to be made and the history of the accounts involved.
+#[derive(Clone, Debug)]
+pub struct RiskInput {
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub amount: Money,
+    pub now: PrimitiveDateTime,
+    pub history: RiskHistory,
+}
+
+/// Represents what the risk rules know of the accounts of a transfer before it is made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RiskHistory {
+    /// When the sender's account was created.
+    pub sender_created_at: PrimitiveDateTime,
+    /// Number of transfers the sender ever made to the recipient.
+    pub transfers_to_recipient: i64,
+    /// Transfers sent by the sender, or by the recipient to the sender, within the lookback of
+    /// the rules.
+    pub recent_transfers: Vec<RecentTransfer>,
+}
+
+/// Represents a transfer made before the one being evaluated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecentTransfer {
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub amount_minor_units: i64,
+    pub created_at: PrimitiveDateTime,
+}
+
+/// Represents the decision of a single rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RiskOutcome {
+    pub decision: RiskDecision,
+    pub score: u32,
+    /// Why the rule flagged the transfer; empty when it is allowed.
+    pub reason: String,
+}
+
+impl RiskOutcome {
+    /// Creates the outcome of a rule that found nothing wrong.
+    pub const fn allow() -> Self {
+        Self {
+            decision: RiskDecision::Allow,
+            score: 0,
+            reason: String::new(),
+        }
+    }
+}
+
+/// Represents a rule that flagged a transfer for review or blocked it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RiskHit {
+    pub rule: &'static str,
+    pub decision: RiskDecision,
+    pub score: u32,
+    pub reason: String,
+}
+
+/// Represents the combined decision of every rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RiskAssessment {
+    /// The most severe decision of any rule.
+    pub decision: RiskDecision,
+    /// Sum of the scores of every rule.
+    pub score: u32,
+    /// The rules that did not allow the transfer.
+    pub hits: Vec<RiskHit>,
+}
+
+/// Runs a set of risk rules against transfers.
+pub struct RiskEngine {
+    rules: Vec<Box<dyn RiskRule>>,
+}
+
+impl RiskEngine {
+    /// Creates an engine that runs `rules`.
+    pub fn new(rules: Vec<Box<dyn RiskRule>>) -> Self {
+        Self { rules }
+    }
+
+    /// Creates an engine that runs the built-in rules enabled in the configuration.
+    pub fn from_config(config: &configs::Risk) -> Self {
+        let mut rules: Vec<Box<dyn RiskRule>> = Vec::new();
+        if let Some(rule) = &config.new_account {
+            rules.push(Box::new(rule.clone()));
+        }
+        if let Some(rule) = &config.new_counterparty {
+            rules.push(Box::new(rule.clone()));
+        }
+        if let Some(rule) = &config.fan_out {
+            rules.push(Box::new(rule.clone()));
+        }
+        if let Some(rule) = &config.round_trip {
+            rules.push(Box::new(rule.clone()));
+        }
+
+        Self::new(rules)
+    }
+
+    /// Returns how far back the history of the accounts has to go for every rule.
+    pub fn lookback(&self) -> Duration {
+        self.rules
+            .iter()
+            .map(|rule| rule.lookback())
+            .max()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Runs every rule against a transfer.
+    pub fn evaluate(&self, input: &RiskInput) -> RiskAssessment {
+        let mut assessment = RiskAssessment {
+            decision: RiskDecision::Allow,
+            score: 0,
+            hits: Vec::new(),
+        };
+
+        for rule in &self.rules {
+            let outcome = rule.evaluate(input);
+            assessment.decision = assessment.decision.max(outcome.decision);
+            assessment.score = assessment.score.saturating_add(outcome.score);
+            if outcome.decision != RiskDecision::Allow {
+                assessment.hits.push(RiskHit {
+                    rule: rule.name(),
+                    decision: outcome.decision,
+                    score: outcome.score,
+                    reason: outcome.reason,
+                });
+            }
+        }
+
+        assessment
+    }
+}
+
+/// Represents what a built-in rule does with the transfers it flags.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub struct RuleAction {
+    /// REVIEW or BLOCK.
+    pub action: RiskDecision,
+    /// Score added to the assessment of flagged transfers.
+    pub score: u32,
+}
+
+impl RuleAction {
+    /// Validates that the rule does something with the transfers it flags.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.action == RiskDecision::Allow {
+            return Err(ValidationError::InvalidValue {
+                message: "Risk rules must REVIEW or BLOCK the transfers they flag".into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn flag(&self, reason: String) -> RiskOutcome {
+        RiskOutcome {
+            decision: self.action,
+            score: self.score,
+            reason,
+        }
+    }
+}
+
+/// Flags large transfers sent from accounts that were created recently.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct NewAccountRule {
+    /// Age (in seconds) below which an account counts as new.
+    pub max_account_age: u64,
+    /// Smallest amount flagged, in minor units.
+    pub min_amount_minor_units: i64,
+    #[serde(flatten)]
+    pub action: RuleAction,
+}
+
+impl RiskRule for NewAccountRule {
+    fn name(&self) -> &'static str {
+        "NEW_ACCOUNT_LARGE_AMOUNT"
+    }
+
+    fn evaluate(&self, input: &RiskInput) -> RiskOutcome {
+        let is_new =
+            input.history.sender_created_at > input.now - Duration::from_secs(self.max_account_age);
+        if !is_new || input.amount.minor_units() < self.min_amount_minor_units {
+            return RiskOutcome::allow();
+        }
+
+        self.action.flag(format!(
+            "Account created at {} sent {}",
+            input.history.sender_created_at, input.amount
+        ))
+    }
+}
+
+/// Flags the first transfer to a recipient when it is large.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct NewCounterpartyRule {
+    /// Smallest amount flagged, in minor units.
+    pub min_amount_minor_units: i64,
+    #[serde(flatten)]
+    pub action: RuleAction,
+}
+
+impl RiskRule for NewCounterpartyRule {
+    fn name(&self) -> &'static str {
+        "NEW_COUNTERPARTY"
+    }
+
+    fn evaluate(&self, input: &RiskInput) -> RiskOutcome {
+        if input.history.transfers_to_recipient > 0
+            || input.amount.minor_units() < self.min_amount_minor_units
+        {
+            return RiskOutcome::allow();
+        }
+
+        self.action.flag(format!(
+            "First transfer to {} is {}",
+            input.recipient_id, input.amount
+        ))
+    }
+}
+
+/// Flags senders that pay many different recipients within a short window.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct FanOutRule {
+    /// Length (in seconds) of the window the recipients are counted over.
+    pub window: u64,
+    /// Largest number of different recipients allowed within the window, including the
+    /// recipient of the transfer being made.
+    pub max_recipients: usize,
+    #[serde(flatten)]
+    pub action: RuleAction,
+}
+
+impl RiskRule for FanOutRule {
+    fn name(&self) -> &'static str {
+        "RAPID_FAN_OUT"
+    }
+
+    fn lookback(&self) -> Duration {
+        Duration::from_secs(self.window)
+    }
+
+    fn evaluate(&self, input: &RiskInput) -> RiskOutcome {
+        let since = input.now - self.lookback();
+        let mut recipients: Vec<&str> = input
+            .history
+            .recent_transfers
+            .iter()
+            .filter(|transfer| {
+                transfer.sender_id == input.sender_id && transfer.created_at >= since
+            })
+            .map(|transfer| transfer.recipient_id.as_str())
+            .chain([input.recipient_id.as_str()])
+            .collect();
+        recipients.sort_unstable();
+        recipients.dedup();
+
+        if recipients.len() <= self.max_recipients {
+            return RiskOutcome::allow();
+        }
+
+        self.action.flag(format!(
+            "Sent to {} recipients within {} seconds",
+            recipients.len(),
+            self.window
+        ))
+    }
+}
+
+/// Flags transfers that send money back to an account that recently paid the sender.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct RoundTripRule {
+    /// Length (in seconds) of the window within which a transfer back counts as a round trip.
+    pub window: u64,
+    /// Smallest amount flagged, in minor units.
+    pub min_amount_minor_units: i64,
+    #[serde(flatten)]
+    pub action: RuleAction,
+}
+
+impl RiskRule for RoundTripRule {
+    fn name(&self) -> &'static str {
+        "ROUND_TRIP"
+    }
+
+    fn lookback(&self) -> Duration {
+        Duration::from_secs(self.window)
+    }
+
+    fn evaluate(&self, input: &RiskInput) -> RiskOutcome {
+        let since = input.now - self.lookback();
+        let returned = input.history.recent_transfers.iter().any(|transfer| {
+            transfer.sender_id == input.recipient_id
+                && transfer.recipient_id == input.sender_id
+                && transfer.created_at >= since
+        });
+        if !returned || input.amount.minor_units() < self.min_amount_minor_units {
+            return RiskOutcome::allow();
+        }
+
+        self.action.flag(format!(
+            "{} paid {} within the last {} seconds",
+            input.recipient_id, input.sender_id, self.window
+        ))
+    }
+}
@@ -1,12 +1,34 @@
 //! Route definitions
 
+/// Admin routes
+pub mod admin;
 /// API Models
 pub mod api_models;
 /// Authentication routes
 pub mod auth;
+/// Bank account routes
+pub mod bank_account;
+/// Batch transfer routes
+pub mod batch_transfer;
+/// Deposit routes
+pub mod deposit;
+/// Dispute routes
+pub mod dispute;
+/// FX conversion routes
+pub mod fx;
 /// Health check route
 pub mod health;
+/// Idempotency key handling
+pub mod idempotency;
+/// Payment request routes
+pub mod payment_request;
+/// Scheduled transfer routes
+pub mod scheduled_transfer;
+/// Standing instruction routes
+pub mod standing_instruction;
 /// Transaction routes
 pub mod transaction;
 /// User routes
 pub mod user;
+/// Withdrawal routes
+pub mod withdrawal;
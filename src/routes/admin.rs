@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post, put},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError, TransactionDbError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{
+            DisputeResponse, FxRateResponse, GetTransactionResponse, LedgerEntryResponse,
+            LedgerQuery, LedgerResponse, ListFxRatesResponse, ListOpenDisputesResponse,
+            ListReconciliationRunsResponse, ListRiskReviewsResponse, ReconciliationReportResponse,
+            ResolveDisputeRequest, SetFxRateRequest, TransferLimitsResponse,
+            UpdateUserLimitsRequest,
+        },
+        auth::AdminResolver,
+    },
+    storage::{
+        DisputeInterface, FxInterface, LedgerInterface, ReconciliationInterface, RiskInterface,
+        TransferLimitInterface, UserInterface, WalletInterface,
+        types::{NewFxRate, NewUserLimits},
+    },
+    types::{Currency, limits::LimitWindows},
+    utils::datetime,
+};
+
+/// Number of reconciliation runs listed, most recent first.
+const RECENT_RECONCILIATION_RUNS: i64 = 20;
+
+/// Serves admin routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/ledger/:account_id", get(get_ledger))
+        .route("/users/:user_id/limits", put(update_user_limits))
+        .route("/fx-rates", get(list_fx_rates).put(set_fx_rate))
+        .route(
+            "/reconciliation/runs",
+            get(list_reconciliation_runs).post(run_reconciliation),
+        )
+        .route("/reconciliation/runs/:run_id", get(get_reconciliation_run))
+        .route("/risk-reviews", get(list_risk_reviews))
+        .route(
+            "/risk-reviews/:transaction_id/approve",
+            post(approve_risk_review),
+        )
+        .route(
+            "/risk-reviews/:transaction_id/reject",
+            post(reject_risk_review),
+        )
+        .route("/disputes", get(list_open_disputes))
+        .route("/disputes/:dispute_id/review", post(review_dispute))
+        .route("/disputes/:dispute_id/resolve", post(resolve_dispute))
+        .with_state(app_state)
+}
+
+/// Rebuilds the balance of an account from its ledger postings.
+async fn get_ledger(
+    State(app_state): State<Arc<AppState>>,
+    Path(account_id): Path<String>,
+    Query(params): Query<LedgerQuery>,
+    _admin: AdminResolver,
+) -> Result<Json<LedgerResponse>, ContainerError<ApiError>> {
+    let as_of = params.as_of.map(datetime::to_utc);
+
+    // The balance an account held before the ledger was introduced is booked as one opening
+    // balance when it was, so the journal cannot tell what the account held before then.
+    if let Some(as_of) = as_of {
+        let opening = app_state
+            .db
+            .get_ledger_opening(&account_id, params.currency)
+            .await?;
+        if opening.is_some_and(|opening| as_of < opening) {
+            return Err(ApiError::LedgerHistoryIncomplete.into());
+        }
+    }
+
+    let ledger_entries = app_state
+        .db
+        .get_ledger_entries(&account_id, params.currency, as_of)
+        .await?;
+    let ledger_balance = app_state
+        .db
+        .get_ledger_balance(&account_id, params.currency, as_of)
+        .await?;
+
+    let cached_balance = match as_of {
+        Some(_) => None,
+        // System accounts and users without a wallet in the currency have no cached balance.
+        None => match app_state.db.get_wallet(&account_id, params.currency).await {
+            Ok(wallet) => Some(wallet.balance_minor_units),
+            Err(err) if *err.get_inner() == TransactionDbError::NotFoundError => None,
+            Err(err) => {
+                logger::error!(?err);
+                return Err(ApiError::RetrieveDataFailed("wallets").into());
+            }
+        },
+    };
+
+    let mut running_balance = 0_i64;
+    let entries = ledger_entries
+        .into_iter()
+        .map(|entry| {
+            running_balance += entry.signed_minor_units();
+            LedgerEntryResponse {
+                entry_id: entry.entry_id,
+                journal_id: entry.journal_id,
+                direction: entry.direction.to_string(),
+                amount_minor_units: entry.amount_minor_units,
+                currency: entry.currency,
+                running_balance_minor_units: running_balance,
+                created_at: entry.created_at.to_string(),
+            }
+        })
+        .collect();
+
+    Ok(Json(LedgerResponse {
+        account_id,
+        currency: params.currency,
+        ledger_balance_minor_units: ledger_balance,
+        cached_balance_minor_units: cached_balance,
+        is_consistent: cached_balance.map(|cached| cached == ledger_balance),
+        entries,
+    }))
+}
+
+/// Replaces the transfer limit overrides of a user. Limits left out of the request fall back
+/// to the configured defaults.
+async fn update_user_limits(
+    State(app_state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    _admin: AdminResolver,
+    Json(payload): Json<UpdateUserLimitsRequest>,
+) -> Result<Json<TransferLimitsResponse>, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let user = app_state
+        .db
+        .get_user_by_user_id(&user_id)
+        .await
+        .change_error(ApiError::NotFoundError("user"))?;
+
+    let now = datetime::now();
+    let limits = app_state
+        .db
+        .set_user_limits(NewUserLimits::try_from((user.user_id, payload))?)
+        .await?;
+    let usage = app_state
+        .db
+        .get_limit_usage(&user_id, Currency::default(), now)
+        .await?;
+
+    logger::info!("Transfer limits updated for user_id: {}", user_id);
+
+    Ok(Json((limits, usage, LimitWindows::at(now)).try_into()?))
+}
+
+/// Sets the mid-market rate of a currency pair, replacing its previous rate. Quotes already
+/// given keep the rate they locked.
+async fn set_fx_rate(
+    State(app_state): State<Arc<AppState>>,
+    _admin: AdminResolver,
+    Json(payload): Json<SetFxRateRequest>,
+) -> Result<Json<FxRateResponse>, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let rate = app_state
+        .db
+        .set_fx_rate(NewFxRate::new(
+            payload.base_currency,
+            payload.quote_currency,
+            payload.rate,
+        ))
+        .await?;
+
+    logger::info!(
+        "FX rate set for {}/{}: {}",
+        payload.base_currency,
+        payload.quote_currency,
+        payload.rate
+    );
+
+    Ok(Json(rate.try_into()?))
+}
+
+/// Lists the rates of all currency pairs.
+async fn list_fx_rates(
+    State(app_state): State<Arc<AppState>>,
+    _admin: AdminResolver,
+) -> Result<Json<ListFxRatesResponse>, ContainerError<ApiError>> {
+    let rates = app_state
+        .db
+        .list_fx_rates()
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Json(ListFxRatesResponse { rates }))
+}
+
+/// Reconciles every wallet now, recording and returning the discrepancies found.
+async fn run_reconciliation(
+    State(app_state): State<Arc<AppState>>,
+    _admin: AdminResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let (run, findings) = app_state.db.reconcile().await?;
+
+    logger::info!(
+        "Reconciliation run {} found {} discrepancies",
+        run.run_id,
+        findings.len()
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ReconciliationReportResponse::from((run, findings))),
+    ))
+}
+
+/// Lists the most recent reconciliation runs, scheduled or not, newest first.
+async fn list_reconciliation_runs(
+    State(app_state): State<Arc<AppState>>,
+    _admin: AdminResolver,
+) -> Result<Json<ListReconciliationRunsResponse>, ContainerError<ApiError>> {
+    let runs = app_state
+        .db
+        .list_reconciliation_runs(RECENT_RECONCILIATION_RUNS)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(Json(ListReconciliationRunsResponse { runs }))
+}
+
+/// Gets a reconciliation run with the discrepancies it found.
+async fn get_reconciliation_run(
+    State(app_state): State<Arc<AppState>>,
+    Path(run_id): Path<String>,
+    _admin: AdminResolver,
+) -> Result<Json<ReconciliationReportResponse>, ContainerError<ApiError>> {
+    let run = app_state
+        .db
+        .get_reconciliation_run(&run_id)
+        .await
+        .change_error(ApiError::NotFoundError("reconciliation run"))?;
+    let findings = app_state.db.list_reconciliation_findings(&run_id).await?;
+
+    Ok(Json((run, findings).into()))
+}
+
+/// Lists the transfers held for review by the risk rules, oldest first.
+async fn list_risk_reviews(
+    State(app_state): State<Arc<AppState>>,
+    _admin: AdminResolver,
+) -> Result<Json<ListRiskReviewsResponse>, ContainerError<ApiError>> {
+    let reviews = app_state
+        .db
+        .list_risk_reviews()
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Json(ListRiskReviewsResponse { reviews }))
+}
+
+/// Approves a transfer held for review, moving the money from the sender to the recipient.
+async fn approve_risk_review(
+    State(app_state): State<Arc<AppState>>,
+    Path(transaction_id): Path<String>,
+    _admin: AdminResolver,
+) -> Result<Json<GetTransactionResponse>, ContainerError<ApiError>> {
+    let transaction = app_state.db.approve_risk_review(&transaction_id).await?;
+
+    logger::info!(
+        "Risk review approved for transaction_id: {}",
+        transaction_id
+    );
+
+    Ok(Json(transaction.try_into()?))
+}
+
+/// Rejects a transfer held for review, releasing the amount held on the sender's account.
+async fn reject_risk_review(
+    State(app_state): State<Arc<AppState>>,
+    Path(transaction_id): Path<String>,
+    _admin: AdminResolver,
+) -> Result<Json<GetTransactionResponse>, ContainerError<ApiError>> {
+    let transaction = app_state.db.reject_risk_review(&transaction_id).await?;
+
+    logger::info!(
+        "Risk review rejected for transaction_id: {}",
+        transaction_id
+    );
+
+    Ok(Json(transaction.try_into()?))
+}
+
+/// Lists the disputes waiting for an admin, OPEN or UNDER_REVIEW, oldest first.
+async fn list_open_disputes(
+    State(app_state): State<Arc<AppState>>,
+    _admin: AdminResolver,
+) -> Result<Json<ListOpenDisputesResponse>, ContainerError<ApiError>> {
+    let disputes = app_state
+        .db
+        .list_open_disputes()
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Json(ListOpenDisputesResponse { disputes }))
+}
+
+/// Takes an open dispute under review. Its amount stays frozen until it is resolved.
+async fn review_dispute(
+    State(app_state): State<Arc<AppState>>,
+    Path(dispute_id): Path<String>,
+    _admin: AdminResolver,
+) -> Result<Json<DisputeResponse>, ContainerError<ApiError>> {
+    app_state
+        .db
+        .get_dispute(&dispute_id)
+        .await
+        .change_error(ApiError::NotFoundError("dispute"))?;
+    let dispute = app_state.db.review_dispute(&dispute_id).await?;
+
+    logger::info!("Dispute under review with dispute_id: {}", dispute_id);
+
+    Ok(Json(dispute.try_into()?))
+}
+
+/// Resolves a dispute. A WON dispute reverses the transfer, returning the frozen amount from
+/// the recipient to the sender; a LOST dispute releases it to the recipient.
+async fn resolve_dispute(
+    State(app_state): State<Arc<AppState>>,
+    Path(dispute_id): Path<String>,
+    _admin: AdminResolver,
+    Json(payload): Json<ResolveDisputeRequest>,
+) -> Result<Json<DisputeResponse>, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    app_state
+        .db
+        .get_dispute(&dispute_id)
+        .await
+        .change_error(ApiError::NotFoundError("dispute"))?;
+    let dispute = app_state
+        .db
+        .resolve_dispute(&dispute_id, payload.outcome, payload.note)
+        .await?;
+
+    logger::info!("Dispute {} resolved as {}", dispute_id, dispute.status);
+
+    Ok(Json(dispute.try_into()?))
+}
@@ -1,10 +1,75 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    balance_history::{self, BalanceBucket, BalanceInterval},
+    configs, consts,
     error::{ValidationError, container::ContainerError},
-    types::{Email, Password},
+    export::ExportFormat,
+    storage::{
+        enums::{
+            DepositStatus, DisputeReason, DisputeStatus, FxQuoteStatus, LedgerDirection,
+            PaymentRequestStatus, ReconciliationFindingKind, RiskDecision, ScheduledTransferStatus,
+            StandingInstructionStatus, TransactionStatus, TransactionType, WithdrawalStatus,
+        },
+        types::{
+            PaymentRequestDirection, TransactionCursor, TransactionDirection, TransactionFilter,
+            TransactionSort,
+        },
+    },
+    types::{
+        AccountNumber, Currency, Email, ExchangeRate, Ifsc, Metadata, Money, Password, Recurrence,
+        metadata,
+    },
+    utils::datetime,
 };
 
+/// Represents an amount in a request body, either as integer minor units or as a decimal string.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AmountRequest {
+    /// Amount in minor units, e.g. `1050` for 10.50 INR.
+    MinorUnits(i64),
+    /// Amount in major units as a decimal string, e.g. `"10.50"`.
+    Decimal(String),
+}
+
+impl AmountRequest {
+    /// Converts the requested amount into an exact Money value.
+    pub fn to_money(&self, currency: Currency) -> Result<Money, ContainerError<ValidationError>> {
+        let money = match self {
+            Self::MinorUnits(minor_units) => Money::new(*minor_units, currency),
+            Self::Decimal(value) => Money::from_decimal_str(value, currency),
+        };
+
+        money.map_err(|error| {
+            ValidationError::InvalidValue {
+                message: error.to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+/// Represents an amount in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AmountResponse {
+    /// Amount in minor units, e.g. `1050` for 10.50 INR.
+    pub minor_units: i64,
+    /// Amount in major units as a decimal string, e.g. `"10.50"`.
+    pub decimal: String,
+    pub currency: Currency,
+}
+
+impl From<Money> for AmountResponse {
+    fn from(money: Money) -> Self {
+        Self {
+            minor_units: money.minor_units(),
+            decimal: money.to_decimal_string(),
+            currency: money.currency(),
+        }
+    }
+}
+
 /// Represents the sign-up request body.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SignUpRequest {
@@ -68,43 +133,53 @@ pub struct GetUserResponse {
     pub user_id: String,
     pub email: String,
     pub name: String,
-    pub balance_in_rs: f64,
+    /// Balance of every wallet of the user, oldest first.
+    pub balances: Vec<WalletBalanceResponse>,
     pub created_at: String,
     pub last_modified_at: String,
 }
 
-/// Represents the update user request body.
+/// Represents the balance of a wallet in a response body.
+#[derive(Serialize, Default, Deserialize, Debug)]
+pub struct WalletBalanceResponse {
+    pub currency: Currency,
+    /// Balance booked in the ledger.
+    pub ledger_balance: AmountResponse,
+    /// Ledger balance minus the funds held by active authorizations and transfers under review,
+    /// and frozen by open disputes.
+    pub available_balance: AmountResponse,
+}
+
+/// Represents the create wallet request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateWalletRequest {
+    /// ISO 4217 code of the currency to hold, e.g. `USD`.
+    pub currency: Currency,
+}
+
+/// Represents the update user request body. The balance cannot be edited; money is added
+/// through deposits.
 #[derive(Serialize, Default, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateUserRequest {
     pub name: Option<String>,
-    pub amount: Option<f64>,
 }
 
 impl UpdateUserRequest {
     /// Validates the update user request.
     pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
-        if self.name.is_none() && self.amount.is_none() {
+        let Some(name) = &self.name else {
             return Err(ValidationError::InvalidValue {
-                message: "At least one of name or amount must be provided for update".into(),
+                message: "Name must be provided for update".into(),
             }
             .into());
-        }
-        if let Some(amount) = self.amount {
-            if amount < 0.0 {
-                return Err(ValidationError::InvalidValue {
-                    message: "Amount cannot be negative".into(),
-                }
-                .into());
-            }
-        }
+        };
 
-        if let Some(name) = &self.name {
-            if name.is_empty() {
-                return Err(ValidationError::InvalidValue {
-                    message: "Name cannot be empty".into(),
-                }
-                .into());
+        if name.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                message: "Name cannot be empty".into(),
             }
+            .into());
         }
 
         Ok(())
@@ -116,7 +191,6 @@ impl UpdateUserRequest {
 pub struct UpdateUserResponse {
     pub user_id: String,
     pub name: String,
-    pub balance_in_rs: f64,
 }
 
 /// Represents the create transaction request body.
@@ -124,7 +198,17 @@ pub struct UpdateUserResponse {
 pub struct CreateTransactionRequest {
     pub sender_id: String,
     pub receiver_id: String,
-    pub amount: f64,
+    pub amount: AmountRequest,
+    /// Currency of the amount, INR if not given. The sender and receiver must both hold a
+    /// wallet in it.
+    #[serde(default)]
+    pub currency: Currency,
+    pub description: Option<String>,
+    /// Client supplied key-value pairs, e.g. an order reference.
+    pub metadata: Option<Metadata>,
+    /// Open FX quote of the sender to execute first. The amount and currency must match the
+    /// source of the quote, and the receiver is sent its converted amount instead.
+    pub fx_quote_id: Option<String>,
 }
 
 impl CreateTransactionRequest {
@@ -144,39 +228,1428 @@ impl CreateTransactionRequest {
             .into());
         }
 
-        if self.amount <= 0.0 {
+        if self.sender_id == self.receiver_id {
+            return Err(ValidationError::InvalidValue {
+                message: "Sender and receiver cannot be the same".into(),
+            }
+            .into());
+        }
+
+        if self.amount.to_money(self.currency)?.is_zero() {
             return Err(ValidationError::InvalidValue {
                 message: "Amount must be greater than zero".into(),
             }
             .into());
         }
 
+        validate_description(self.description.as_deref())?;
+
+        if let Some(metadata) = &self.metadata {
+            metadata.validate()?;
+        }
+
         Ok(())
     }
 }
 
+/// Rejects an FX quote on transfers that do not move the money right away, as the quote
+/// would expire before they run.
+fn reject_fx_quote(transfer: &CreateTransactionRequest) -> Result<(), ValidationError> {
+    if transfer.fx_quote_id.is_some() {
+        return Err(ValidationError::InvalidValue {
+            message: "An FX quote can only be used by an immediate transfer".into(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Validates the length of a transfer description.
+fn validate_description(description: Option<&str>) -> Result<(), ValidationError> {
+    if description
+        .is_some_and(|description| description.chars().count() > consts::MAX_DESCRIPTION_LENGTH)
+    {
+        return Err(ValidationError::InvalidValue {
+            message: format!(
+                "Description cannot be longer than {} characters",
+                consts::MAX_DESCRIPTION_LENGTH
+            ),
+        });
+    }
+
+    Ok(())
+}
+
 /// Represents the get transaction response body.
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetTransactionResponse {
     pub transaction_id: String,
     pub sender_id: String,
     pub receiver_id: String,
-    pub amount: f64,
+    /// The amount debited from the sender, fee included.
+    pub amount: AmountResponse,
+    /// The platform fee charged on the amount.
+    pub fee: AmountResponse,
+    /// The amount credited to the receiver, i.e. the amount less the fee.
+    pub net_amount: AmountResponse,
+    pub description: Option<String>,
+    pub metadata: Option<Metadata>,
+    pub status: TransactionStatus,
+    /// Why the transaction failed, only present for FAILED transactions.
+    pub failure_reason: Option<String>,
+    pub transaction_type: TransactionType,
+    /// The transaction this one refunds, only present for REFUND transactions.
+    pub parent_transaction_id: Option<String>,
+    /// The amount originally held, only present for transactions that were authorized first.
+    pub authorized_amount: Option<AmountResponse>,
+    /// When the hold lapses, only present for transactions that were authorized first.
+    pub expires_at: Option<String>,
+    /// The standing instruction that created the transaction, if any.
+    pub standing_instruction_id: Option<String>,
+    /// The payment request the transaction paid, if any.
+    pub payment_request_id: Option<String>,
+    /// The batch transfer the transaction is a leg of, if any.
+    pub batch_id: Option<String>,
     pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Represents the authorize transaction request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuthorizeTransactionRequest {
+    #[serde(flatten)]
+    pub transfer: CreateTransactionRequest,
+    /// Seconds until the hold expires; the configured default is used when absent.
+    pub expires_in: Option<u64>,
+}
+
+impl AuthorizeTransactionRequest {
+    /// Validates the authorize transaction request against the longest allowed hold.
+    pub fn validate(&self, max_hold_ttl: u64) -> Result<(), ContainerError<ValidationError>> {
+        self.transfer.validate()?;
+        reject_fx_quote(&self.transfer)?;
+
+        if let Some(expires_in) = self.expires_in
+            && (expires_in == 0 || expires_in > max_hold_ttl)
+        {
+            return Err(ValidationError::InvalidValue {
+                message: format!("Hold expiry must be between 1 and {max_hold_ttl} seconds"),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the capture transaction request body.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CaptureTransactionRequest {
+    /// Amount to capture; the whole authorized amount is captured when absent.
+    pub amount: Option<AmountRequest>,
+}
+
+impl CaptureTransactionRequest {
+    /// Validates the capture transaction request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if let Some(amount) = &self.amount
+            && amount.to_money(Currency::Inr)?.is_zero()
+        {
+            return Err(ValidationError::InvalidValue {
+                message: "Capture amount must be greater than zero".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the refund transaction request body.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RefundTransactionRequest {
+    /// Amount to refund; the whole remaining amount is refunded when absent.
+    pub amount: Option<AmountRequest>,
+    pub description: Option<String>,
+}
+
+impl RefundTransactionRequest {
+    /// Validates the refund transaction request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if let Some(amount) = &self.amount
+            && amount.to_money(Currency::Inr)?.is_zero()
+        {
+            return Err(ValidationError::InvalidValue {
+                message: "Refund amount must be greater than zero".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
 }
 
 /// Represents the list transactions request query parameters.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ListTransactionsRequest {
-    pub page: Option<u64>,
+    /// `next_cursor` or `prev_cursor` of a previous page; the first page when absent.
+    pub cursor: Option<String>,
     pub page_size: Option<u64>,
+    /// Whether to count every transaction matching the filters, which costs another query.
+    #[serde(default)]
+    pub include_total: bool,
+    /// SENT for transactions the user sent, RECEIVED for those they received; both when absent.
+    pub direction: Option<TransactionDirection>,
+    /// Only return transactions with this user on the other side.
+    pub counterparty_id: Option<String>,
+    pub status: Option<TransactionStatus>,
+    /// Only return transactions in this currency, which `min_amount` and `max_amount` are in.
+    pub currency: Option<Currency>,
+    /// Only return transactions created at or after this RFC 3339 timestamp.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<time::OffsetDateTime>,
+    /// Only return transactions created before this RFC 3339 timestamp.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<time::OffsetDateTime>,
+    /// Smallest amount to return, as a decimal string in major units, e.g. `"10.50"`.
+    pub min_amount: Option<String>,
+    /// Largest amount to return, as a decimal string in major units.
+    pub max_amount: Option<String>,
+    /// Only return transactions whose description contains this text, ignoring case.
+    pub search: Option<String>,
+    /// Only return transactions whose metadata has this key, set to `metadata_value`.
+    pub metadata_key: Option<String>,
+    pub metadata_value: Option<String>,
+    #[serde(default)]
+    pub sort: TransactionSort,
 }
 
-/// Represents the list transactions response body.
+impl ListTransactionsRequest {
+    /// Validates the list transactions request, returning the filter it sets on the
+    /// transactions of `account_id`. Amount bounds only match transactions in their currency,
+    /// INR unless `currency` is given.
+    pub fn to_filter(
+        &self,
+        account_id: String,
+    ) -> Result<TransactionFilter, ContainerError<ValidationError>> {
+        let invalid = |message: &str| -> ContainerError<ValidationError> {
+            ValidationError::InvalidValue {
+                message: message.into(),
+            }
+            .into()
+        };
+
+        let amount_currency = self.currency.unwrap_or_default();
+        let amount = |bound: &Option<String>| {
+            bound
+                .as_ref()
+                .map(|value| {
+                    AmountRequest::Decimal(value.clone())
+                        .to_money(amount_currency)
+                        .map(|money| money.minor_units())
+                })
+                .transpose()
+        };
+        let min_amount_minor_units = amount(&self.min_amount)?;
+        let max_amount_minor_units = amount(&self.max_amount)?;
+        if let (Some(min), Some(max)) = (min_amount_minor_units, max_amount_minor_units)
+            && min > max
+        {
+            return Err(invalid("min_amount cannot be greater than max_amount"));
+        }
+
+        if let (Some(from), Some(to)) = (self.from, self.to)
+            && from >= to
+        {
+            return Err(invalid("from must be before to"));
+        }
+        if self.counterparty_id.as_ref().is_some_and(String::is_empty) {
+            return Err(invalid("counterparty_id cannot be empty"));
+        }
+        if let Some(search) = &self.search
+            && (search.is_empty() || search.chars().count() > consts::MAX_DESCRIPTION_LENGTH)
+        {
+            return Err(invalid("search must be between 1 and 500 characters"));
+        }
+
+        let has_amount_bound = min_amount_minor_units.is_some() || max_amount_minor_units.is_some();
+
+        Ok(TransactionFilter {
+            account_id,
+            direction: self.direction,
+            counterparty_id: self.counterparty_id.clone(),
+            status: self.status,
+            currency: self
+                .currency
+                .or(has_amount_bound.then_some(amount_currency)),
+            created_from: self.from.map(datetime::to_utc),
+            created_to: self.to.map(datetime::to_utc),
+            min_amount_minor_units,
+            max_amount_minor_units,
+            search: self.search.clone(),
+            metadata: self.metadata_filter()?,
+            sort: self.sort,
+        })
+    }
+
+    /// Decodes the cursor if one was given, checking it was issued for the requested sort.
+    pub fn cursor(&self) -> Result<Option<TransactionCursor>, ContainerError<ValidationError>> {
+        let Some(encoded) = &self.cursor else {
+            return Ok(None);
+        };
+
+        let cursor = TransactionCursor::decode(encoded)?;
+        if cursor.sort != self.sort {
+            return Err(ValidationError::InvalidValue {
+                message: "The cursor was issued for a different sort".into(),
+            }
+            .into());
+        }
+
+        Ok(Some(cursor))
+    }
+
+    /// Validates the list transactions request, returning the metadata filter if one was given.
+    pub fn metadata_filter(&self) -> Result<Option<Metadata>, ContainerError<ValidationError>> {
+        match (&self.metadata_key, &self.metadata_value) {
+            (None, None) => Ok(None),
+            (Some(key), Some(value)) => {
+                metadata::validate_key(key)?;
+                Ok(Some(Metadata(
+                    [(key.clone(), value.clone())].into_iter().collect(),
+                )))
+            }
+            _ => Err(ValidationError::InvalidValue {
+                message: "metadata_key and metadata_value must be given together".into(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Represents the query parameters of the export transactions request.
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ExportTransactionsRequest {
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Only export transactions created at or after this RFC 3339 timestamp.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<time::OffsetDateTime>,
+    /// Only export transactions created before this RFC 3339 timestamp.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<time::OffsetDateTime>,
+}
+
+impl ExportTransactionsRequest {
+    /// Validates that the range of the export is not empty.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if let (Some(from), Some(to)) = (self.from, self.to)
+            && from >= to
+        {
+            return Err(ValidationError::InvalidValue {
+                message: "from must be before to".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the list transactions response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ListTransactionsResponse {
     pub transactions: Vec<GetTransactionResponse>,
+    pub page_size: u64,
+    /// Cursor of the next page, absent on the last page.
+    pub next_cursor: Option<String>,
+    /// Cursor of the previous page, absent on the first page.
+    pub prev_cursor: Option<String>,
+    /// How many transactions match the filters, only when the request sets `include_total`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_count: Option<u64>,
+}
+
+/// Represents the create scheduled transfer request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateScheduledTransferRequest {
+    #[serde(flatten)]
+    pub transfer: CreateTransactionRequest,
+    /// RFC 3339 timestamp at which the transfer is executed.
+    #[serde(with = "time::serde::rfc3339")]
+    pub execute_at: time::OffsetDateTime,
+}
+
+impl CreateScheduledTransferRequest {
+    /// Validates the create scheduled transfer request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        self.transfer.validate()?;
+        reject_fx_quote(&self.transfer)?;
+
+        if self.execute_at <= time::OffsetDateTime::now_utc() {
+            return Err(ValidationError::InvalidValue {
+                message: "Execution time must be in the future".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a scheduled transfer in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledTransferResponse {
+    pub scheduled_transfer_id: String,
+    /// ID of the transaction created when the transfer is executed.
+    pub transaction_id: String,
+    pub sender_id: String,
+    pub receiver_id: String,
+    pub amount: AmountResponse,
+    pub description: Option<String>,
+    pub metadata: Option<Metadata>,
+    pub execute_at: String,
+    pub status: ScheduledTransferStatus,
+    /// Why the execution failed, or for a SCHEDULED transfer why the last attempt did before it
+    /// is retried.
+    pub failure_reason: Option<String>,
+    pub executed_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Validates a requested page size against the configured maximum, returning the configured
+/// default when none was asked for.
+pub fn validate_page_size(
+    page_size: Option<u64>,
+    pagination: &configs::Pagination,
+) -> Result<u64, ContainerError<ValidationError>> {
+    match page_size {
+        None => Ok(pagination.default_page_size),
+        Some(page_size) if (1..=pagination.max_page_size).contains(&page_size) => Ok(page_size),
+        Some(_) => Err(ValidationError::InvalidValue {
+            message: format!(
+                "page_size must be between 1 and {}",
+                pagination.max_page_size
+            ),
+        }
+        .into()),
+    }
+}
+
+/// Returns the number of rows before a 1-based page, reading page 0 as the first page and
+/// rejecting pages too far out to query.
+pub fn page_offset(page: u64, page_size: u64) -> Result<i64, ContainerError<ValidationError>> {
+    page.saturating_sub(1)
+        .checked_mul(page_size)
+        .and_then(|offset| i64::try_from(offset).ok())
+        .ok_or_else(|| {
+            ValidationError::InvalidValue {
+                message: "page is out of range".into(),
+            }
+            .into()
+        })
+}
+
+/// Represents the list scheduled transfers request query parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListScheduledTransfersRequest {
+    pub status: Option<ScheduledTransferStatus>,
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+/// Represents the list scheduled transfers response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListScheduledTransfersResponse {
+    pub scheduled_transfers: Vec<ScheduledTransferResponse>,
+    pub total_count: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+/// Represents the create standing instruction request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateStandingInstructionRequest {
+    #[serde(flatten)]
+    pub transfer: CreateTransactionRequest,
+    pub recurrence: Recurrence,
+    /// RFC 3339 timestamp of the first run; defaults to now.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub start_at: Option<time::OffsetDateTime>,
+    /// RFC 3339 timestamp after which no more runs happen.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub end_at: Option<time::OffsetDateTime>,
+    /// Number of runs after which the instruction completes.
+    pub max_occurrences: Option<u32>,
+}
+
+impl CreateStandingInstructionRequest {
+    /// Validates the create standing instruction request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        self.transfer.validate()?;
+        reject_fx_quote(&self.transfer)?;
+
+        self.recurrence
+            .validate()
+            .map_err(|error| ValidationError::InvalidValue {
+                message: error.to_string(),
+            })?;
+
+        let start_at = self.start_at.unwrap_or_else(time::OffsetDateTime::now_utc);
+        if let Some(end_at) = self.end_at
+            && end_at <= start_at
+        {
+            return Err(ValidationError::InvalidValue {
+                message: "End time must be after the start time".into(),
+            }
+            .into());
+        }
+
+        if let Some(max_occurrences) = self.max_occurrences
+            && (max_occurrences == 0 || i32::try_from(max_occurrences).is_err())
+        {
+            return Err(ValidationError::InvalidValue {
+                message: "Maximum occurrences must be at least 1".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a standing instruction in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StandingInstructionResponse {
+    pub standing_instruction_id: String,
+    pub sender_id: String,
+    pub receiver_id: String,
+    pub amount: AmountResponse,
+    pub description: Option<String>,
+    pub metadata: Option<Metadata>,
+    pub recurrence: Recurrence,
+    pub start_at: String,
+    pub end_at: Option<String>,
+    pub max_occurrences: Option<u32>,
+    /// Number of runs so far, including failed ones but not skipped ones.
+    pub occurrences_count: u32,
+    /// When the next run is due, absent once the instruction is COMPLETED.
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    /// Why the last run failed, only present if it did.
+    pub last_failure_reason: Option<String>,
+    pub status: StandingInstructionStatus,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Represents the list standing instructions request query parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListStandingInstructionsRequest {
+    pub status: Option<StandingInstructionStatus>,
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+/// Represents the list standing instructions response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListStandingInstructionsResponse {
+    pub standing_instructions: Vec<StandingInstructionResponse>,
+    pub total_count: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+/// Represents the create payment request request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreatePaymentRequestRequest {
+    /// The user asked to pay.
+    pub payer_id: String,
+    pub amount: AmountRequest,
+    /// Currency of the amount, INR if not given. The payer and requester must both hold a
+    /// wallet in it.
+    #[serde(default)]
+    pub currency: Currency,
+    pub note: Option<String>,
+    /// Seconds until the request expires; the configured default is used when absent.
+    pub expires_in: Option<u64>,
+}
+
+impl CreatePaymentRequestRequest {
+    /// Validates the create payment request request against the longest allowed expiry.
+    pub fn validate(&self, max_ttl: u64) -> Result<(), ContainerError<ValidationError>> {
+        if self.payer_id.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                message: "Payer ID cannot be empty".into(),
+            }
+            .into());
+        }
+
+        if self.amount.to_money(self.currency)?.is_zero() {
+            return Err(ValidationError::InvalidValue {
+                message: "Amount must be greater than zero".into(),
+            }
+            .into());
+        }
+
+        if let Some(expires_in) = self.expires_in
+            && (expires_in == 0 || expires_in > max_ttl)
+        {
+            return Err(ValidationError::InvalidValue {
+                message: format!("Request expiry must be between 1 and {max_ttl} seconds"),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a payment request in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentRequestResponse {
+    pub payment_request_id: String,
+    /// The user asking for the money.
+    pub requester_id: String,
+    /// The user asked to pay.
+    pub payer_id: String,
+    pub amount: AmountResponse,
+    pub note: Option<String>,
+    pub status: PaymentRequestStatus,
+    /// The transaction that paid the request, only present for PAID requests.
+    pub transaction_id: Option<String>,
+    pub expires_at: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Represents the list payment requests request query parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListPaymentRequestsRequest {
+    /// INCOMING for requests the user was asked to pay, OUTGOING for requests they made; both
+    /// when absent.
+    pub direction: Option<PaymentRequestDirection>,
+    pub status: Option<PaymentRequestStatus>,
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+/// Represents the list payment requests response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListPaymentRequestsResponse {
+    pub payment_requests: Vec<PaymentRequestResponse>,
     pub total_count: u64,
     pub page: u64,
     pub page_size: u64,
 }
+
+/// Represents the ledger query parameters.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LedgerQuery {
+    /// Only postings written at or before this RFC 3339 timestamp are considered.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub as_of: Option<time::OffsetDateTime>,
+    /// Currency of the postings and balances, INR if not given.
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+/// Represents a single ledger posting in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LedgerEntryResponse {
+    pub entry_id: String,
+    pub journal_id: String,
+    pub direction: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    /// Balance of the account in minor units after this posting.
+    pub running_balance_minor_units: i64,
+    pub created_at: String,
+}
+
+/// Represents the ledger response body for an account.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LedgerResponse {
+    pub account_id: String,
+    pub currency: Currency,
+    /// Balance in minor units rebuilt from the journal. System accounts may be negative.
+    pub ledger_balance_minor_units: i64,
+    /// Balance cached on the wallet of the user in the currency, only present for user
+    /// accounts without `as_of`.
+    pub cached_balance_minor_units: Option<i64>,
+    /// Whether the cached balance matches the journal, only present with the cached balance.
+    pub is_consistent: Option<bool>,
+    pub entries: Vec<LedgerEntryResponse>,
+}
+
+/// Represents the create batch transfer request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateBatchTransferRequest {
+    pub sender_id: String,
+    /// The transfers of the batch, which all complete or all fail together.
+    pub legs: Vec<BatchTransferLeg>,
+    /// Currency of every leg, INR if not given. The sender and each receiver must hold a
+    /// wallet in it.
+    #[serde(default)]
+    pub currency: Currency,
+    pub description: Option<String>,
+}
+
+/// Represents one transfer of a batch transfer request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchTransferLeg {
+    pub receiver_id: String,
+    pub amount: AmountRequest,
+    pub description: Option<String>,
+    /// Client supplied key-value pairs, e.g. an employee reference.
+    pub metadata: Option<Metadata>,
+}
+
+impl CreateBatchTransferRequest {
+    /// Validates the create batch transfer request against the largest allowed batch.
+    pub fn validate(&self, max_legs: usize) -> Result<(), ContainerError<ValidationError>> {
+        if self.sender_id.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                message: "Sender ID cannot be empty".into(),
+            }
+            .into());
+        }
+
+        if self.legs.is_empty() || self.legs.len() > max_legs {
+            return Err(ValidationError::InvalidValue {
+                message: format!("A batch must contain between 1 and {max_legs} transfers"),
+            }
+            .into());
+        }
+
+        for leg in &self.legs {
+            if leg.receiver_id.is_empty() {
+                return Err(ValidationError::InvalidValue {
+                    message: "Receiver ID cannot be empty".into(),
+                }
+                .into());
+            }
+
+            if leg.receiver_id == self.sender_id {
+                return Err(ValidationError::InvalidValue {
+                    message: "Sender and receiver cannot be the same".into(),
+                }
+                .into());
+            }
+
+            if leg.amount.to_money(self.currency)?.is_zero() {
+                return Err(ValidationError::InvalidValue {
+                    message: "Amount must be greater than zero".into(),
+                }
+                .into());
+            }
+
+            validate_description(leg.description.as_deref())?;
+
+            if let Some(metadata) = &leg.metadata {
+                metadata.validate()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a batch transfer in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchTransferResponse {
+    pub batch_id: String,
+    pub sender_id: String,
+    /// The sum of the amounts of every transfer in the batch.
+    pub total_amount: AmountResponse,
+    pub leg_count: u32,
+    pub description: Option<String>,
+    /// The transactions of the batch, in the order of the legs of the request.
+    pub transactions: Vec<GetTransactionResponse>,
+    pub created_at: String,
+}
+
+/// Represents an amount limit and how much of it is left in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AmountLimitResponse {
+    pub limit: AmountResponse,
+    pub used: AmountResponse,
+    pub remaining: AmountResponse,
+    /// When the total starts over.
+    pub resets_at: String,
+}
+
+/// Represents a count limit and how much of it is left in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CountLimitResponse {
+    pub limit: i64,
+    pub used: i64,
+    pub remaining: i64,
+}
+
+/// Represents the transfer limits of a user and how much of each remains.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransferLimitsResponse {
+    /// The largest amount of a single transfer.
+    pub max_transfer: AmountResponse,
+    /// The total that can be sent per UTC day.
+    pub daily_outgoing: AmountLimitResponse,
+    /// The total that can be sent per UTC calendar month.
+    pub monthly_outgoing: AmountLimitResponse,
+    /// The number of transfers that can be sent within any hour.
+    pub transfers_per_hour: CountLimitResponse,
+}
+
+/// Represents the update user limits request body. Limits left out fall back to the
+/// configured defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UpdateUserLimitsRequest {
+    pub max_transfer: Option<AmountRequest>,
+    pub daily_outgoing: Option<AmountRequest>,
+    pub monthly_outgoing: Option<AmountRequest>,
+    pub transfers_per_hour: Option<i32>,
+}
+
+impl UpdateUserLimitsRequest {
+    /// Validates that every given limit is positive.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        for amount in [
+            &self.max_transfer,
+            &self.daily_outgoing,
+            &self.monthly_outgoing,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if amount.to_money(Currency::Inr)?.is_zero() {
+                return Err(ValidationError::InvalidValue {
+                    message: "Limits must be greater than zero".into(),
+                }
+                .into());
+            }
+        }
+
+        if self.transfers_per_hour.is_some_and(|count| count <= 0) {
+            return Err(ValidationError::InvalidValue {
+                message: "Limits must be greater than zero".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a risk rule that flagged a transfer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RiskRuleHitResponse {
+    pub rule: String,
+    pub decision: RiskDecision,
+    pub score: i32,
+    pub reason: String,
+    pub created_at: String,
+}
+
+/// Represents a transfer waiting for review and the rules that flagged it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RiskReviewResponse {
+    pub transaction: GetTransactionResponse,
+    /// The sum of the scores of the rules that flagged the transfer.
+    pub risk_score: i64,
+    pub rule_hits: Vec<RiskRuleHitResponse>,
+}
+
+/// Represents the list risk reviews response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListRiskReviewsResponse {
+    /// The transfers waiting for review, oldest first.
+    pub reviews: Vec<RiskReviewResponse>,
+}
+
+/// Represents the create deposit request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateDepositRequest {
+    pub amount: AmountRequest,
+    /// Currency of the amount, INR if not given. The user must hold a wallet in it.
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+impl CreateDepositRequest {
+    /// Validates the create deposit request against the largest allowed deposit.
+    pub fn validate(
+        &self,
+        max_amount_minor_units: i64,
+    ) -> Result<(), ContainerError<ValidationError>> {
+        let amount = self.amount.to_money(self.currency)?;
+        if amount.is_zero() {
+            return Err(ValidationError::InvalidValue {
+                message: "Amount must be greater than zero".into(),
+            }
+            .into());
+        }
+
+        if amount.minor_units() > max_amount_minor_units {
+            return Err(ValidationError::InvalidValue {
+                message: format!("Amount cannot exceed {max_amount_minor_units} minor units"),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the confirm deposit request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfirmDepositRequest {
+    /// Token of the payment method to charge, issued to the client by the funding source.
+    pub payment_token: String,
+}
+
+impl ConfirmDepositRequest {
+    /// Validates the confirm deposit request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if self.payment_token.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                message: "Payment token cannot be empty".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a deposit in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DepositResponse {
+    pub deposit_id: String,
+    pub user_id: String,
+    pub amount: AmountResponse,
+    pub status: DepositStatus,
+    /// The funding source the deposit is charged to.
+    pub funding_source: String,
+    /// Reference of the charge at the funding source, only present for COMPLETED deposits.
+    pub source_reference: Option<String>,
+    /// Why the funding source declined the charge, only present for FAILED deposits.
+    pub failure_reason: Option<String>,
+    /// The transaction that credited the deposit, only present for COMPLETED deposits.
+    pub transaction_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Represents the link bank account request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateBankAccountRequest {
+    pub account_holder_name: String,
+    pub account_number: AccountNumber,
+    pub ifsc: Ifsc,
+}
+
+impl CreateBankAccountRequest {
+    /// Validates the link bank account request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if self.account_holder_name.trim().is_empty() {
+            return Err(ValidationError::InvalidValue {
+                message: "Account holder name cannot be empty".into(),
+            }
+            .into());
+        }
+
+        self.account_number.validate()?;
+        self.ifsc.validate()
+    }
+}
+
+/// Represents a linked bank account in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BankAccountResponse {
+    pub bank_account_id: String,
+    pub account_holder_name: String,
+    /// The account number with all but its last four digits hidden.
+    pub account_number: String,
+    pub ifsc: String,
+    pub created_at: String,
+}
+
+/// Represents the list bank accounts response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListBankAccountsResponse {
+    pub bank_accounts: Vec<BankAccountResponse>,
+}
+
+/// Represents the create withdrawal request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateWithdrawalRequest {
+    /// The linked bank account to pay out to.
+    pub bank_account_id: String,
+    pub amount: AmountRequest,
+    /// Currency of the amount, INR if not given. The user must hold a wallet in it.
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+impl CreateWithdrawalRequest {
+    /// Validates the create withdrawal request against the largest allowed withdrawal.
+    pub fn validate(
+        &self,
+        max_amount_minor_units: i64,
+    ) -> Result<(), ContainerError<ValidationError>> {
+        if self.bank_account_id.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                message: "Bank account ID cannot be empty".into(),
+            }
+            .into());
+        }
+
+        let amount = self.amount.to_money(self.currency)?;
+        if amount.is_zero() {
+            return Err(ValidationError::InvalidValue {
+                message: "Amount must be greater than zero".into(),
+            }
+            .into());
+        }
+
+        if amount.minor_units() > max_amount_minor_units {
+            return Err(ValidationError::InvalidValue {
+                message: format!("Amount cannot exceed {max_amount_minor_units} minor units"),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the list withdrawals request query parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListWithdrawalsRequest {
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+/// Represents a withdrawal in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WithdrawalResponse {
+    pub withdrawal_id: String,
+    pub user_id: String,
+    pub bank_account_id: String,
+    pub amount: AmountResponse,
+    pub status: WithdrawalStatus,
+    /// The connector the payout was dispatched through.
+    pub connector: String,
+    /// Reference of the payout at the connector, only present for COMPLETED withdrawals.
+    pub connector_reference: Option<String>,
+    /// Why the payout failed, only present for FAILED withdrawals.
+    pub failure_reason: Option<String>,
+    /// The transaction that debited the withdrawal.
+    pub transaction_id: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Represents the list withdrawals response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListWithdrawalsResponse {
+    pub withdrawals: Vec<WithdrawalResponse>,
+    pub total_count: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+/// Validates the free text of a dispute, which cannot be blank or longer than
+/// `consts::MAX_DISPUTE_TEXT_LENGTH` characters.
+fn validate_dispute_text(field: &str, text: &str) -> Result<(), ValidationError> {
+    if text.trim().is_empty() {
+        return Err(ValidationError::InvalidValue {
+            message: format!("{field} cannot be empty"),
+        });
+    }
+
+    if text.chars().count() > consts::MAX_DISPUTE_TEXT_LENGTH {
+        return Err(ValidationError::InvalidValue {
+            message: format!(
+                "{field} cannot be longer than {} characters",
+                consts::MAX_DISPUTE_TEXT_LENGTH
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+/// Represents the create dispute request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateDisputeRequest {
+    /// The transfer to dispute, sent or received by the user.
+    pub transaction_id: String,
+    pub reason: DisputeReason,
+    /// What happened, in the user's words, and anything that supports the dispute.
+    pub evidence: String,
+}
+
+impl CreateDisputeRequest {
+    /// Validates the create dispute request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if self.transaction_id.is_empty() {
+            return Err(ValidationError::InvalidValue {
+                message: "Transaction ID cannot be empty".into(),
+            }
+            .into());
+        }
+
+        Ok(validate_dispute_text("Evidence", &self.evidence)?)
+    }
+}
+
+/// Represents the resolve dispute request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolveDisputeRequest {
+    /// WON to reverse the transfer, LOST to release the frozen funds to the recipient.
+    pub outcome: DisputeStatus,
+    /// Why the dispute was resolved this way, shown to both parties.
+    pub note: Option<String>,
+}
+
+impl ResolveDisputeRequest {
+    /// Validates the resolve dispute request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if !self.outcome.is_resolved() {
+            return Err(ValidationError::InvalidValue {
+                message: "Outcome must be WON or LOST".into(),
+            }
+            .into());
+        }
+
+        if let Some(note) = &self.note {
+            validate_dispute_text("Note", note)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the list disputes request query parameters.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListDisputesRequest {
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+/// Represents a dispute in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DisputeResponse {
+    pub dispute_id: String,
+    /// The disputed transfer.
+    pub transaction_id: String,
+    /// The user who raised the dispute, either the sender or the recipient of the transfer.
+    pub opened_by: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    /// What the recipient received and had not refunded, frozen in their balance while the
+    /// dispute is open.
+    pub amount: AmountResponse,
+    pub reason: DisputeReason,
+    pub evidence: String,
+    pub status: DisputeStatus,
+    /// Why an admin resolved the dispute as they did, if they said.
+    pub resolution_note: Option<String>,
+    /// The transaction that returned the money to the sender, only present for WON disputes.
+    pub reversal_transaction_id: Option<String>,
+    /// When an admin resolved the dispute, only present for WON and LOST disputes.
+    pub resolved_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Represents the list disputes response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListDisputesResponse {
+    pub disputes: Vec<DisputeResponse>,
+    pub total_count: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
+/// Represents the list open disputes response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListOpenDisputesResponse {
+    /// The disputes waiting for an admin, oldest first.
+    pub disputes: Vec<DisputeResponse>,
+}
+
+/// Represents the create FX quote request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateFxQuoteRequest {
+    /// Currency of the wallet to convert from, which the amount is given in.
+    pub source_currency: Currency,
+    /// Currency of the wallet to convert into.
+    pub target_currency: Currency,
+    pub amount: AmountRequest,
+}
+
+impl CreateFxQuoteRequest {
+    /// Validates the create FX quote request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if self.source_currency == self.target_currency {
+            return Err(ValidationError::InvalidValue {
+                message: "Source and target currencies must differ".into(),
+            }
+            .into());
+        }
+
+        if self.amount.to_money(self.source_currency)?.is_zero() {
+            return Err(ValidationError::InvalidValue {
+                message: "Amount must be greater than zero".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents an FX quote in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FxQuoteResponse {
+    pub quote_id: String,
+    pub user_id: String,
+    /// The amount debited from the source wallet.
+    pub source_amount: AmountResponse,
+    /// The amount credited to the target wallet, after the spread.
+    pub target_amount: AmountResponse,
+    /// The spread kept by the platform, in the target currency.
+    pub spread: AmountResponse,
+    /// The mid-market rate the quote was priced at.
+    pub mid_rate: ExchangeRate,
+    /// The rate the user gets, i.e. the mid-market rate less the spread.
+    pub rate: ExchangeRate,
+    pub status: FxQuoteStatus,
+    /// The quote cannot be executed from this time on.
+    pub expires_at: String,
+    /// When the mid-market rate was set.
+    pub rate_updated_at: String,
+    /// The transaction that debited the source wallet, only present for EXECUTED quotes.
+    pub debit_transaction_id: Option<String>,
+    /// The transaction that credited the target wallet, only present for EXECUTED quotes.
+    pub credit_transaction_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Represents the set FX rate request body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetFxRateRequest {
+    pub base_currency: Currency,
+    pub quote_currency: Currency,
+    /// Units of the quote currency one unit of the base currency buys, as a decimal string
+    /// with up to eight decimal places, e.g. `"83.25"`.
+    pub rate: ExchangeRate,
+}
+
+impl SetFxRateRequest {
+    /// Validates the set FX rate request.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        if self.base_currency == self.quote_currency {
+            return Err(ValidationError::InvalidValue {
+                message: "Base and quote currencies must differ".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents the exchange rate of a currency pair in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FxRateResponse {
+    pub base_currency: Currency,
+    pub quote_currency: Currency,
+    pub rate: ExchangeRate,
+    pub updated_at: String,
+}
+
+/// Represents the list FX rates response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListFxRatesResponse {
+    pub rates: Vec<FxRateResponse>,
+}
+
+/// Represents a discrepancy found by a reconciliation run in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReconciliationFindingResponse {
+    pub finding_id: String,
+    pub kind: ReconciliationFindingKind,
+    /// The account whose balance is off, absent for findings about a whole currency.
+    pub account_id: Option<String>,
+    pub currency: String,
+    /// What the balance should be in minor units, from the transaction history, the ledger or
+    /// the system accounts depending on the kind.
+    pub expected_minor_units: i64,
+    /// What the balance is in minor units.
+    pub actual_minor_units: i64,
+    pub created_at: String,
+}
+
+/// Represents a reconciliation run in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReconciliationRunResponse {
+    pub run_id: String,
+    pub wallets_checked: i32,
+    pub findings_count: i32,
+    pub started_at: String,
+    pub completed_at: String,
+}
+
+/// Represents a reconciliation run with its findings in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReconciliationReportResponse {
+    #[serde(flatten)]
+    pub run: ReconciliationRunResponse,
+    pub findings: Vec<ReconciliationFindingResponse>,
+}
+
+/// Represents the list reconciliation runs response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListReconciliationRunsResponse {
+    pub runs: Vec<ReconciliationRunResponse>,
+}
+
+/// Represents the format a statement is rendered in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Json,
+    Csv,
+    /// A standalone page laid out for printing.
+    Html,
+}
+
+/// Represents the query parameters of the get statement request.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StatementRequest {
+    /// ISO 4217 code of the wallet, INR by default.
+    #[serde(default)]
+    pub currency: Currency,
+    #[serde(default)]
+    pub format: StatementFormat,
+}
+
+/// Represents a transaction or an adjustment on a statement in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatementLineResponse {
+    /// The transaction, or the journal of an adjustment.
+    pub transaction_id: String,
+    /// None for adjustments booked outside of transactions, such as opening balances.
+    pub transaction_type: Option<TransactionType>,
+    /// CREDIT for money received, DEBIT for money sent.
+    pub direction: LedgerDirection,
+    /// The other side of the transaction.
+    pub counterparty_id: String,
+    pub description: Option<String>,
+    /// What the wallet was credited or debited.
+    pub amount: AmountResponse,
+    /// The balance of the wallet after the transaction.
+    pub balance: AmountResponse,
+    pub created_at: String,
+}
+
+/// Represents the monthly statement of a wallet in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatementResponse {
+    pub user_id: String,
+    pub year: i32,
+    pub month: u8,
+    pub currency: Currency,
+    /// The first instant of the month, in UTC.
+    pub period_start: String,
+    /// The first instant after the month, in UTC.
+    pub period_end: String,
+    pub opening_balance: AmountResponse,
+    pub closing_balance: AmountResponse,
+    pub total_credits: AmountResponse,
+    pub total_debits: AmountResponse,
+    pub transactions: Vec<StatementLineResponse>,
+}
+
+/// Represents the query parameters of the balance history request.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BalanceHistoryRequest {
+    /// ISO 4217 code of the wallet, INR by default.
+    #[serde(default)]
+    pub currency: Currency,
+    /// First day of the history, `YYYY-MM-DD`; by default the history covers 30 days.
+    #[serde(default, with = "datetime::iso_date::option")]
+    pub from: Option<time::Date>,
+    /// Last day of the history, `YYYY-MM-DD`; today by default.
+    #[serde(default, with = "datetime::iso_date::option")]
+    pub to: Option<time::Date>,
+    #[serde(default)]
+    pub interval: BalanceInterval,
+}
+
+impl BalanceHistoryRequest {
+    /// Validates the balance history request, returning the buckets of its points. The history
+    /// cannot go past `today` and has at most `consts::MAX_BALANCE_HISTORY_POINTS` points.
+    pub fn buckets(
+        &self,
+        today: time::Date,
+    ) -> Result<Vec<BalanceBucket>, ContainerError<ValidationError>> {
+        let invalid = |message: String| -> ContainerError<ValidationError> {
+            ValidationError::InvalidValue { message }.into()
+        };
+
+        let to = self.to.unwrap_or(today);
+        if to > today {
+            return Err(invalid("to cannot be in the future".into()));
+        }
+        let from = match self.from {
+            Some(from) => from,
+            None => to.saturating_sub(time::Duration::days(
+                consts::DEFAULT_BALANCE_HISTORY_DAYS - 1,
+            )),
+        };
+        if from > to {
+            return Err(invalid("from cannot be after to".into()));
+        }
+
+        let buckets = balance_history::buckets(from, to, self.interval)
+            .take(consts::MAX_BALANCE_HISTORY_POINTS + 1)
+            .collect::<Vec<_>>();
+        if buckets.len() > consts::MAX_BALANCE_HISTORY_POINTS {
+            return Err(invalid(format!(
+                "The history cannot have more than {} points; use a longer interval",
+                consts::MAX_BALANCE_HISTORY_POINTS
+            )));
+        }
+
+        Ok(buckets)
+    }
+}
+
+/// Represents a point of a balance history in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BalancePointResponse {
+    /// First day the point covers.
+    #[serde(with = "datetime::iso_date")]
+    pub period_start: time::Date,
+    /// Last day the point covers.
+    #[serde(with = "datetime::iso_date")]
+    pub period_end: time::Date,
+    /// The balance at the end of `period_end`, or now for a period that is not over.
+    pub closing_balance: AmountResponse,
+}
+
+/// Represents the balance history of a wallet in a response body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BalanceHistoryResponse {
+    pub user_id: String,
+    pub currency: Currency,
+    pub interval: BalanceInterval,
+    pub points: Vec<BalancePointResponse>,
+}
@@ -3,9 +3,11 @@ use std::sync::Arc;
 use axum::{extract::FromRequestParts, http::request::Parts};
 use hyper::HeaderMap;
 use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use subtle::ConstantTimeEq;
 
 use crate::{
     app::AppState,
+    consts,
     error::{
         ApiError,
         container::{ContainerError, ResultContainerExt},
@@ -34,6 +36,44 @@ impl FromRequestParts<Arc<AppState>> for AuthResolver {
     }
 }
 
+/// Struct for resolving admin authentication.
+#[derive(Clone, Copy)]
+pub struct AdminResolver;
+
+#[async_trait::async_trait]
+impl FromRequestParts<Arc<AppState>> for AdminResolver {
+    type Rejection = ContainerError<ApiError>;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let api_key = parts
+            .headers
+            .get(consts::ADMIN_API_KEY_HEADER)
+            .and_then(|h| h.to_str().ok())
+            .filter(|h| !h.is_empty());
+
+        match api_key {
+            Some(key) if api_key_matches(key, &state.config.secrets.admin_api_key) => Ok(Self),
+            Some(_) => {
+                logger::error!("Admin API key mismatch");
+                Err(ApiError::UnAuthenticated.into())
+            }
+            None => {
+                logger::error!("Admin API key header not found or empty");
+                Err(ApiError::HeadersError("admin API key").into())
+            }
+        }
+    }
+}
+
+/// Compares an API key in constant time, so response timing does not reveal how much of the
+/// key was right.
+fn api_key_matches(candidate: &str, expected: &str) -> bool {
+    candidate.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
 /// Authenticates the JWT key from the headers.
 async fn authenticate_jwt_key(
     state: &Arc<AppState>,
@@ -72,9 +112,7 @@ async fn decode_jwt(
 ) -> Result<Claims, ContainerError<ApiError>> {
     let secret = state.config.secrets.jwt_secret.as_bytes();
     let key = DecodingKey::from_secret(secret);
-    Ok(
-        decode::<Claims>(token, &key, &Validation::new(Algorithm::HS256))
-            .map(|token_data| token_data.claims)
-            .change_error(ApiError::UnAuthenticated)?,
-    )
+    decode::<Claims>(token, &key, &Validation::new(Algorithm::HS256))
+        .map(|token_data| token_data.claims)
+        .change_error(ApiError::UnAuthenticated)
 }
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{BankAccountResponse, CreateBankAccountRequest, ListBankAccountsResponse},
+        auth::AuthResolver,
+    },
+    storage::{BankAccountInterface, types::NewBankAccount},
+    utils::{datetime, generate_nano_id},
+};
+
+/// Serves bank account routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_bank_account))
+        .route("/", get(list_bank_accounts))
+        .route("/:bank_account_id", get(get_bank_account))
+        .with_state(app_state)
+}
+
+/// Links an external bank account to the authenticated user, to withdraw their balance to.
+///
+/// The account number must have 9 to 18 digits and the IFSC must identify a bank branch, e.g.
+/// `HDFC0001234`. An account can only be linked once.
+async fn create_bank_account(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    Json(payload): Json<CreateBankAccountRequest>,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let bank_account = app_state
+        .db
+        .create_bank_account(NewBankAccount {
+            bank_account_id: format!("ba_{}", generate_nano_id(20)),
+            user_id: claims.user_id,
+            account_holder_name: payload.account_holder_name.trim().to_string(),
+            account_number: payload.account_number.0,
+            ifsc: payload.ifsc.0,
+            created_at: datetime::now(),
+        })
+        .await?;
+
+    logger::info!(
+        "Bank account linked with bank_account_id: {}",
+        bank_account.bank_account_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(BankAccountResponse::from(bank_account)),
+    ))
+}
+
+/// Lists the bank accounts of the authenticated user, oldest first.
+async fn list_bank_accounts(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let bank_accounts = app_state.db.list_bank_accounts(&claims.user_id).await?;
+
+    let response = ListBankAccountsResponse {
+        bank_accounts: bank_accounts
+            .into_iter()
+            .map(BankAccountResponse::from)
+            .collect(),
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a bank account by ID. Only the user who linked it can see it.
+async fn get_bank_account(
+    State(app_state): State<Arc<AppState>>,
+    Path(bank_account_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let bank_account = app_state
+        .db
+        .get_bank_account(&bank_account_id)
+        .await
+        .change_error(ApiError::NotFoundError("bank account"))?;
+
+    if bank_account.user_id != claims.user_id {
+        return Err(ApiError::NotFoundError("bank account").into());
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(BankAccountResponse::from(bank_account)),
+    ))
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{BatchTransferResponse, CreateBatchTransferRequest},
+        auth::AuthResolver,
+        idempotency::{self, IdempotencyKey},
+    },
+    storage::{
+        TransferBatchInterface,
+        enums::{TransactionStatus, TransactionType},
+        types::{NewTransaction, NewTransferBatch},
+    },
+    utils::{datetime, generate_nano_id},
+};
+
+/// Serves batch transfer routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_batch_transfer))
+        .route("/:batch_id", get(get_batch_transfer))
+        .with_state(app_state)
+}
+
+/// Pays several receivers from one sender, all or nothing.
+///
+/// Only the sender may create a batch transfer. Every receiver must exist and the sender must
+/// cover the total of the batch; otherwise no transfer of the batch happens. When an
+/// `Idempotency-Key` header is sent, retries with the same key and payload replay the original
+/// response instead of moving the money again.
+async fn create_batch_transfer(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    Json(payload): Json<CreateBatchTransferRequest>,
+) -> Result<Response, ContainerError<ApiError>> {
+    payload
+        .validate(app_state.config.batch_transfer.max_legs)
+        .change_error(ApiError::ValidationError)?;
+
+    if payload.sender_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the sender can create a batch transfer").into());
+    }
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payload,
+        StatusCode::CREATED,
+        |payload| execute_batch_transfer(&app_state, payload),
+    )
+    .await
+}
+
+/// Moves the money for a validated create batch transfer request.
+async fn execute_batch_transfer(
+    app_state: &Arc<AppState>,
+    payload: CreateBatchTransferRequest,
+) -> Result<BatchTransferResponse, ContainerError<ApiError>> {
+    let batch_id = format!("bat_{}", generate_nano_id(20));
+    let now = datetime::now();
+
+    let legs = payload
+        .legs
+        .into_iter()
+        .map(|leg| {
+            let amount = leg
+                .amount
+                .to_money(payload.currency)
+                .change_error(ApiError::ValidationError)?;
+
+            Ok(NewTransaction {
+                transaction_id: format!("txn_{}", generate_nano_id(20)),
+                sender_id: payload.sender_id.clone(),
+                recipient_id: leg.receiver_id,
+                amount_minor_units: amount.minor_units(),
+                currency: amount.currency().to_string(),
+                description: leg.description,
+                created_at: now,
+                status: TransactionStatus::Pending,
+                updated_at: now,
+                transaction_type: TransactionType::Transfer,
+                parent_transaction_id: None,
+                authorized_amount_minor_units: None,
+                expires_at: None,
+                standing_instruction_id: None,
+                payment_request_id: None,
+                batch_id: Some(batch_id.clone()),
+                metadata: leg.metadata,
+            })
+        })
+        .collect::<Result<Vec<_>, ContainerError<ApiError>>>()?;
+
+    let batch = NewTransferBatch::new(
+        batch_id,
+        payload.sender_id,
+        payload.currency,
+        payload.description,
+        &legs,
+    )
+    .change_error(ApiError::InvalidAmount("batch total is out of range"))?;
+
+    let created = app_state.db.create_transfer_batch(batch, legs).await?;
+
+    logger::info!(
+        "Batch transfer completed with batch_id: {} and {} transfers",
+        created.0.batch_id,
+        created.1.len()
+    );
+
+    BatchTransferResponse::try_from(created)
+}
+
+/// Gets a batch transfer by ID with its transactions. Only the sender can see it.
+async fn get_batch_transfer(
+    State(app_state): State<Arc<AppState>>,
+    Path(batch_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let batch = app_state.db.get_transfer_batch(&batch_id).await?;
+
+    if batch.0.sender_id != claims.user_id {
+        return Err(ApiError::NotFoundError("batch transfer").into());
+    }
+
+    let response = BatchTransferResponse::try_from(batch)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
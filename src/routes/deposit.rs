@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError, FundingError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    funding::FundingCharge,
+    logger,
+    routes::{
+        api_models::{ConfirmDepositRequest, CreateDepositRequest, DepositResponse},
+        auth::AuthResolver,
+        idempotency::{self, IdempotencyKey},
+    },
+    storage::{
+        DepositInterface, WalletInterface,
+        enums::DepositStatus,
+        types::{Deposit, NewDeposit},
+    },
+    utils::{datetime, generate_nano_id},
+};
+
+/// Serves deposit routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_deposit))
+        .route("/:deposit_id", get(get_deposit))
+        .route("/:deposit_id/confirm", post(confirm_deposit))
+        .with_state(app_state)
+}
+
+/// Creates a deposit intent for the authenticated user, into their wallet in the currency of
+/// the deposit.
+///
+/// Nothing is charged until the deposit is confirmed with a payment token, and the balance is
+/// only credited once the funding source accepts the charge.
+async fn create_deposit(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    Json(payload): Json<CreateDepositRequest>,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    payload
+        .validate(app_state.config.deposit.max_amount_minor_units)
+        .change_error(ApiError::ValidationError)?;
+
+    let amount = payload
+        .amount
+        .to_money(payload.currency)
+        .change_error(ApiError::ValidationError)?;
+    app_state
+        .db
+        .get_wallet(&claims.user_id, payload.currency)
+        .await
+        .change_error(ApiError::CurrencyNotHeld(payload.currency))?;
+    let now = datetime::now();
+
+    let deposit = app_state
+        .db
+        .create_deposit(NewDeposit {
+            deposit_id: format!("dep_{}", generate_nano_id(20)),
+            user_id: claims.user_id,
+            amount_minor_units: amount.minor_units(),
+            currency: amount.currency().to_string(),
+            status: DepositStatus::Pending,
+            funding_source: app_state.funding_source.name().to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+
+    logger::info!("Deposit created with deposit_id: {}", deposit.deposit_id);
+
+    let response = DepositResponse::try_from(deposit)?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Gets a deposit by ID. Only the user who made it can see it.
+async fn get_deposit(
+    State(app_state): State<Arc<AppState>>,
+    Path(deposit_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let deposit = find_deposit(&app_state, &deposit_id, &claims.user_id).await?;
+
+    let response = DepositResponse::try_from(deposit)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Charges a pending deposit to the funding source and credits the user's balance.
+///
+/// A declined charge fails the deposit. If the funding source cannot be reached the deposit
+/// stays PENDING and can be confirmed again.
+async fn confirm_deposit(
+    State(app_state): State<Arc<AppState>>,
+    Path(deposit_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    Json(payload): Json<ConfirmDepositRequest>,
+) -> Result<Response, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let deposit = find_deposit(&app_state, &deposit_id, &claims.user_id).await?;
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payload,
+        StatusCode::OK,
+        |payload| execute_deposit(&app_state, deposit, payload.payment_token),
+    )
+    .await
+}
+
+/// Charges the funding source for a pending deposit and records the outcome.
+async fn execute_deposit(
+    app_state: &Arc<AppState>,
+    deposit: Deposit,
+    payment_token: String,
+) -> Result<DepositResponse, ContainerError<ApiError>> {
+    if deposit.status != DepositStatus::Pending {
+        return Err(ApiError::InvalidStatusTransition.into());
+    }
+
+    let charge = FundingCharge {
+        amount: deposit
+            .amount()
+            .change_error(ApiError::UnknownError("Invalid amount stored for deposit"))?,
+        deposit_id: deposit.deposit_id,
+        user_id: deposit.user_id,
+        payment_token,
+    };
+
+    let deposit = match app_state.funding_source.charge(&charge).await {
+        // The funding source was charged, so a retry must not charge it again.
+        Ok(source_reference) => app_state
+            .db
+            .complete_deposit(&charge.deposit_id, source_reference)
+            .await
+            .change_error(ApiError::UnknownError(
+                "The deposit was charged but could not be recorded",
+            ))?,
+        Err(FundingError::Declined(reason)) => {
+            app_state
+                .db
+                .fail_deposit(&charge.deposit_id, reason)
+                .await?;
+            logger::info!("Deposit declined with deposit_id: {}", charge.deposit_id);
+            return Err(ApiError::DepositDeclined.into());
+        }
+        Err(FundingError::Unavailable) => {
+            return Err(ApiError::FundingSourceUnavailable.into());
+        }
+    };
+
+    logger::info!("Deposit completed with deposit_id: {}", deposit.deposit_id);
+
+    DepositResponse::try_from(deposit)
+}
+
+/// Gets a deposit made by the user, hiding the deposits of other users.
+async fn find_deposit(
+    app_state: &Arc<AppState>,
+    deposit_id: &str,
+    user_id: &str,
+) -> Result<Deposit, ContainerError<ApiError>> {
+    let deposit = app_state
+        .db
+        .get_deposit(deposit_id)
+        .await
+        .change_error(ApiError::NotFoundError("deposit"))?;
+
+    if deposit.user_id != user_id {
+        return Err(ApiError::NotFoundError("deposit").into());
+    }
+
+    Ok(deposit)
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{
+            self, CreateDisputeRequest, DisputeResponse, ListDisputesRequest, ListDisputesResponse,
+        },
+        auth::AuthResolver,
+    },
+    storage::{
+        DisputeInterface, TransactionInterface,
+        types::{Dispute, NewDispute},
+    },
+    utils::generate_nano_id,
+};
+
+/// Serves dispute routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_dispute))
+        .route("/", get(list_disputes))
+        .route("/:dispute_id", get(get_dispute))
+        .with_state(app_state)
+}
+
+/// Raises a dispute against a completed transfer.
+///
+/// Either the sender or the recipient of the transfer may dispute it, once. What the recipient
+/// received and has not refunded is frozen in their balance until an admin resolves the
+/// dispute, and the transfer cannot be refunded meanwhile. The dispute is refused if the
+/// recipient's available balance cannot cover that amount.
+async fn create_dispute(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    Json(payload): Json<CreateDisputeRequest>,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let transaction = app_state
+        .db
+        .get_transaction_by_id(&payload.transaction_id)
+        .await?;
+    if transaction.sender_id != claims.user_id && transaction.recipient_id != claims.user_id {
+        return Err(
+            ApiError::Forbidden("only the sender or recipient can dispute a transaction").into(),
+        );
+    }
+
+    let dispute = app_state
+        .db
+        .create_dispute(NewDispute {
+            dispute_id: format!("dsp_{}", generate_nano_id(20)),
+            transaction_id: transaction.transaction_id,
+            opened_by: claims.user_id,
+            reason: payload.reason,
+            evidence: payload.evidence,
+        })
+        .await?;
+
+    logger::info!(
+        "Dispute created with dispute_id: {} for transaction_id: {}",
+        dispute.dispute_id,
+        dispute.transaction_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(DisputeResponse::try_from(dispute)?),
+    ))
+}
+
+/// Lists the disputes against transfers the authenticated user sent or received, newest
+/// first, with pagination.
+async fn list_disputes(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ListDisputesRequest>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = api_models::validate_page_size(params.page_size, &app_state.config.pagination)
+        .change_error(ApiError::ValidationError)?;
+    let offset =
+        api_models::page_offset(page, page_size).change_error(ApiError::ValidationError)?;
+
+    let (disputes, total_count) = app_state
+        .db
+        .list_disputes(&claims.user_id, page_size as i64, offset)
+        .await?;
+
+    let response = ListDisputesResponse {
+        disputes: disputes
+            .into_iter()
+            .map(DisputeResponse::try_from)
+            .collect::<Result<_, _>>()?,
+        total_count: total_count as u64,
+        page,
+        page_size,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a dispute by ID. Only the sender and recipient of the disputed transfer can see it.
+async fn get_dispute(
+    State(app_state): State<Arc<AppState>>,
+    Path(dispute_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let dispute = find_dispute(&app_state, &dispute_id, &claims.user_id).await?;
+
+    let response = DisputeResponse::try_from(dispute)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a dispute over a transfer of the user, hiding the disputes of other users.
+async fn find_dispute(
+    app_state: &Arc<AppState>,
+    dispute_id: &str,
+    user_id: &str,
+) -> Result<Dispute, ContainerError<ApiError>> {
+    let dispute = app_state
+        .db
+        .get_dispute(dispute_id)
+        .await
+        .change_error(ApiError::NotFoundError("dispute"))?;
+
+    if dispute.sender_id != user_id && dispute.recipient_id != user_id {
+        return Err(ApiError::NotFoundError("dispute").into());
+    }
+
+    Ok(dispute)
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError, FxError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{CreateFxQuoteRequest, FxQuoteResponse},
+        auth::AuthResolver,
+        idempotency::{self, IdempotencyKey},
+    },
+    storage::{
+        FxInterface, WalletInterface,
+        types::{FxQuote, NewFxQuote},
+    },
+    utils::datetime,
+};
+
+/// Serves FX routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/quotes", post(create_quote))
+        .route("/quotes/:quote_id", get(get_quote))
+        .route("/quotes/:quote_id/execute", post(execute_quote))
+        .with_state(app_state)
+}
+
+/// Quotes a conversion between two wallets of the authenticated user.
+///
+/// The quote locks the current mid-market rate of the pair, less the configured spread, until
+/// it expires. Nothing moves until the quote is executed.
+async fn create_quote(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    Json(payload): Json<CreateFxQuoteRequest>,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let amount = payload
+        .amount
+        .to_money(payload.source_currency)
+        .change_error(ApiError::ValidationError)?;
+    for currency in [payload.source_currency, payload.target_currency] {
+        app_state
+            .db
+            .get_wallet(&claims.user_id, currency)
+            .await
+            .change_error(ApiError::CurrencyNotHeld(currency))?;
+    }
+
+    let rate = app_state
+        .db
+        .get_fx_rate(payload.source_currency, payload.target_currency)
+        .await
+        .change_error(ApiError::FxRateUnavailable)?;
+    if rate.is_stale(datetime::now(), app_state.config.fx.max_rate_age) {
+        return Err(ApiError::FxRateUnavailable.into());
+    }
+
+    let quote = NewFxQuote::new(
+        claims.user_id,
+        amount,
+        payload.target_currency,
+        &rate,
+        app_state.config.fx.spread_basis_points,
+        app_state.config.fx.quote_ttl,
+    )
+    .map_err(|err| match err {
+        FxError::Money(_) => ApiError::InvalidAmount("amount overflowed the supported range"),
+        FxError::InvalidRate(_) => ApiError::UnknownError("Invalid rate stored for currency pair"),
+    })?;
+    if quote.target_amount_minor_units == 0 {
+        return Err(ApiError::InvalidAmount("amount is too small to convert").into());
+    }
+
+    let quote = app_state.db.create_fx_quote(quote).await?;
+
+    logger::info!("FX quote created with quote_id: {}", quote.quote_id);
+
+    let response = FxQuoteResponse::try_from(quote)?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Gets an FX quote by ID. Only the user who asked for it can see it.
+async fn get_quote(
+    State(app_state): State<Arc<AppState>>,
+    Path(quote_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let quote = find_quote(&app_state, &quote_id, &claims.user_id).await?;
+
+    let response = FxQuoteResponse::try_from(quote)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Executes an open FX quote at its locked rate, debiting the source wallet and crediting the
+/// target wallet.
+///
+/// A quote can only be executed once and not after it expires; a new quote must be asked for
+/// at the current rate instead.
+async fn execute_quote(
+    State(app_state): State<Arc<AppState>>,
+    Path(quote_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+) -> Result<Response, ContainerError<ApiError>> {
+    find_quote(&app_state, &quote_id, &claims.user_id).await?;
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        quote_id,
+        StatusCode::OK,
+        |quote_id| execute_conversion(&app_state, quote_id),
+    )
+    .await
+}
+
+/// Converts the money for a quote of the caller.
+async fn execute_conversion(
+    app_state: &Arc<AppState>,
+    quote_id: String,
+) -> Result<FxQuoteResponse, ContainerError<ApiError>> {
+    let quote = app_state.db.execute_fx_quote(&quote_id).await?;
+
+    logger::info!("FX quote executed with quote_id: {}", quote.quote_id);
+
+    FxQuoteResponse::try_from(quote)
+}
+
+/// Gets an FX quote of the user, hiding the quotes of other users.
+pub(crate) async fn find_quote(
+    app_state: &Arc<AppState>,
+    quote_id: &str,
+    user_id: &str,
+) -> Result<FxQuote, ContainerError<ApiError>> {
+    let quote = app_state
+        .db
+        .get_fx_quote(quote_id)
+        .await
+        .change_error(ApiError::NotFoundError("fx quote"))?;
+
+    if quote.user_id != user_id {
+        return Err(ApiError::NotFoundError("fx quote").into());
+    }
+
+    Ok(quote)
+}
@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{FromRequestParts, OriginalUri},
+    http::{HeaderValue, StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    app::AppState,
+    consts,
+    error::{
+        ApiError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    storage::{
+        IdempotencyInterface,
+        types::{IdempotencyClaim, NewIdempotencyKey},
+    },
+    utils::datetime,
+};
+
+/// Maximum length of a client supplied idempotency key.
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Struct for resolving the optional `Idempotency-Key` header.
+#[derive(Clone, Debug)]
+pub struct IdempotencyKey(pub Option<RequestKey>);
+
+/// Represents an idempotency key together with the request it was sent on.
+#[derive(Clone, Debug)]
+pub struct RequestKey {
+    key: String,
+    /// Method and path of the request, path parameters included.
+    scope: String,
+}
+
+#[async_trait::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IdempotencyKey {
+    type Rejection = ContainerError<ApiError>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(consts::IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(Self(None));
+        };
+
+        let key = value
+            .to_str()
+            .change_error(ApiError::HeadersError("Idempotency-Key"))?;
+
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+            return Err(ApiError::HeadersError("Idempotency-Key").into());
+        }
+
+        // Nested routers strip their prefix from the URI, the original keeps the whole path.
+        let path = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or(parts.uri.path(), |uri| uri.path());
+
+        Ok(Self(Some(RequestKey {
+            key: key.to_string(),
+            scope: format!("{} {}", parts.method, path),
+        })))
+    }
+}
+
+/// Represents what the handler should do after checking the idempotency key.
+pub enum IdempotencyOutcome {
+    /// The key was claimed and the request should be executed.
+    Proceed(IdempotencyGuard),
+    /// The request was already completed and its stored response should be replayed.
+    Replay(Response),
+}
+
+/// Represents an idempotency key claimed by the current request.
+///
+/// The claim is a lease: if the request dies before it stores a response, e.g. because it was
+/// cancelled, a retry takes the key over once `locked_until` has passed.
+pub struct IdempotencyGuard {
+    user_id: String,
+    idempotency_key: String,
+    locked_until: time::PrimitiveDateTime,
+}
+
+/// Computes the fingerprint of a request payload sent to `scope`, the method and path of the
+/// request.
+///
+/// Keys are shared by every route of a user, so the same body sent to another endpoint or
+/// about another resource must not match.
+pub fn fingerprint<T: serde::Serialize>(
+    scope: &str,
+    payload: &T,
+) -> Result<String, ContainerError<ApiError>> {
+    let payload = serde_json::to_vec(payload).change_error(ApiError::EncodingError)?;
+    let mut hasher = Sha256::new();
+    hasher.update(scope.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(payload);
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Claims the idempotency key for a user, or resolves the response of an earlier request that
+/// used the same key.
+pub async fn begin<T: serde::Serialize>(
+    app_state: &Arc<AppState>,
+    user_id: &str,
+    request_key: RequestKey,
+    payload: &T,
+) -> Result<IdempotencyOutcome, ContainerError<ApiError>> {
+    let request_fingerprint = fingerprint(&request_key.scope, payload)?;
+    let idempotency_key = request_key.key;
+    let created_at = datetime::now();
+    let expires_at =
+        created_at + std::time::Duration::from_secs(app_state.config.idempotency.key_ttl);
+    let locked_until =
+        created_at + std::time::Duration::from_secs(app_state.config.idempotency.lock_ttl);
+
+    let claim = app_state
+        .db
+        .claim_idempotency_key(NewIdempotencyKey {
+            user_id: user_id.to_string(),
+            idempotency_key: idempotency_key.clone(),
+            request_fingerprint: request_fingerprint.clone(),
+            created_at,
+            expires_at,
+            locked_until: Some(locked_until),
+        })
+        .await?;
+
+    let existing = match claim {
+        IdempotencyClaim::Claimed => {
+            return Ok(IdempotencyOutcome::Proceed(IdempotencyGuard {
+                user_id: user_id.to_string(),
+                idempotency_key,
+                locked_until,
+            }));
+        }
+        IdempotencyClaim::Existing(existing) => existing,
+    };
+
+    if existing.request_fingerprint != request_fingerprint {
+        return Err(ApiError::IdempotencyKeyReused.into());
+    }
+
+    match (existing.response_status_code, existing.response_body) {
+        (Some(status_code), Some(body)) => {
+            let status_code = u16::try_from(status_code)
+                .ok()
+                .and_then(|code| StatusCode::from_u16(code).ok())
+                .ok_or(ApiError::UnknownError(
+                    "Invalid stored idempotent status code",
+                ))?;
+
+            logger::info!(
+                "Replaying response for idempotency key: {}",
+                existing.idempotency_key
+            );
+
+            let mut response = (status_code, Json(body)).into_response();
+            response.headers_mut().insert(
+                consts::IDEMPOTENT_REPLAYED_HEADER,
+                HeaderValue::from_static("true"),
+            );
+            Ok(IdempotencyOutcome::Replay(response))
+        }
+        _ => Err(ApiError::IdempotentRequestInProgress.into()),
+    }
+}
+
+/// Runs `operation` for a request, making it safe to retry when an idempotency key is given.
+///
+/// The response is stored under the key and replayed on retries, errors included, so that a
+/// retry never repeats what a failed request may already have done. Only errors known to leave
+/// nothing behind release the key, so that the request can be retried.
+pub async fn run<T, R, F, Fut>(
+    app_state: &Arc<AppState>,
+    user_id: &str,
+    idempotency_key: Option<RequestKey>,
+    payload: T,
+    status_code: StatusCode,
+    operation: F,
+) -> Result<Response, ContainerError<ApiError>>
+where
+    T: serde::Serialize,
+    R: serde::Serialize,
+    F: FnOnce(T) -> Fut,
+    Fut: std::future::Future<Output = Result<R, ContainerError<ApiError>>>,
+{
+    let idempotency_guard = match idempotency_key {
+        Some(key) => match begin(app_state, user_id, key, &payload).await? {
+            IdempotencyOutcome::Replay(response) => return Ok(response),
+            IdempotencyOutcome::Proceed(guard) => Some(guard),
+        },
+        None => None,
+    };
+
+    let result = operation(payload).await;
+
+    if let Some(guard) = idempotency_guard {
+        match &result {
+            Ok(response) => guard.store(app_state, status_code, response).await,
+            Err(error) if error.get_inner().is_retryable() => guard.release(app_state).await,
+            Err(error) => {
+                let (error_status_code, body) = error.get_inner().to_response_parts();
+                guard.store(app_state, error_status_code, &body).await
+            }
+        }
+    }
+
+    Ok((status_code, Json(result?)).into_response())
+}
+
+impl IdempotencyGuard {
+    /// Stores the response so that retries with the same key replay it.
+    pub async fn store<T: serde::Serialize>(
+        self,
+        app_state: &Arc<AppState>,
+        status_code: StatusCode,
+        body: &T,
+    ) {
+        let body = match serde_json::to_value(body) {
+            Ok(body) => body,
+            Err(error) => {
+                logger::error!(?error, "Failed to encode idempotent response");
+                return self.release(app_state).await;
+            }
+        };
+
+        if let Err(error) = app_state
+            .db
+            .store_idempotent_response(
+                &self.user_id,
+                &self.idempotency_key,
+                self.locked_until,
+                i32::from(status_code.as_u16()),
+                body,
+            )
+            .await
+        {
+            logger::error!(?error, "Failed to store idempotent response");
+        }
+    }
+
+    /// Releases the key so that the request can be retried.
+    pub async fn release(self, app_state: &Arc<AppState>) {
+        if let Err(error) = app_state
+            .db
+            .release_idempotency_key(&self.user_id, &self.idempotency_key, self.locked_until)
+            .await
+        {
+            logger::error!(?error, "Failed to release idempotency key");
+        }
+    }
+}
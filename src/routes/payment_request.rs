@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{
+            self, CreatePaymentRequestRequest, ListPaymentRequestsRequest,
+            ListPaymentRequestsResponse, PaymentRequestResponse,
+        },
+        auth::AuthResolver,
+        idempotency::{self, IdempotencyKey},
+    },
+    storage::{
+        PaymentRequestInterface, UserInterface,
+        enums::PaymentRequestStatus,
+        types::{NewPaymentRequest, PaymentRequest},
+    },
+    utils::{datetime, generate_nano_id},
+};
+
+/// Serves payment request routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_payment_request))
+        .route("/", get(list_payment_requests))
+        .route("/:payment_request_id", get(get_payment_request))
+        .route("/:payment_request_id/pay", post(pay_payment_request))
+        .route(
+            "/:payment_request_id/decline",
+            post(decline_payment_request),
+        )
+        .route("/:payment_request_id/cancel", post(cancel_payment_request))
+        .with_state(app_state)
+}
+
+/// Asks another user to pay the authenticated user an amount.
+///
+/// No money moves until the payer pays the request. Requests expire after
+/// `payment_request.default_ttl` seconds unless the request asks for another expiry.
+async fn create_payment_request(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    Json(payload): Json<CreatePaymentRequestRequest>,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    payload
+        .validate(app_state.config.payment_request.max_ttl)
+        .change_error(ApiError::ValidationError)?;
+
+    if payload.payer_id == claims.user_id {
+        return Err(ApiError::ValidationError.into());
+    }
+
+    app_state
+        .db
+        .get_user_by_user_id(&payload.payer_id)
+        .await
+        .change_error(ApiError::NotFoundError("user"))?;
+
+    let amount = payload
+        .amount
+        .to_money(payload.currency)
+        .change_error(ApiError::ValidationError)?;
+    let expires_in = payload
+        .expires_in
+        .unwrap_or(app_state.config.payment_request.default_ttl);
+    let now = datetime::now();
+
+    let payment_request = app_state
+        .db
+        .create_payment_request(NewPaymentRequest {
+            payment_request_id: format!("pr_{}", generate_nano_id(20)),
+            requester_id: claims.user_id,
+            payer_id: payload.payer_id,
+            amount_minor_units: amount.minor_units(),
+            currency: amount.currency().to_string(),
+            note: payload.note,
+            status: PaymentRequestStatus::Pending,
+            expires_at: now + time::Duration::seconds(expires_in as i64),
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+
+    logger::info!(
+        "Payment requested with payment_request_id: {}",
+        payment_request.payment_request_id
+    );
+
+    let response = PaymentRequestResponse::try_from(payment_request)?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Lists the payment requests the user made or was asked to pay, with pagination.
+async fn list_payment_requests(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ListPaymentRequestsRequest>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = api_models::validate_page_size(params.page_size, &app_state.config.pagination)
+        .change_error(ApiError::ValidationError)?;
+    let offset =
+        api_models::page_offset(page, page_size).change_error(ApiError::ValidationError)?;
+
+    let (payment_requests, total_count) = app_state
+        .db
+        .list_payment_requests(
+            &claims.user_id,
+            params.direction,
+            params.status,
+            page_size as i64,
+            offset,
+        )
+        .await?;
+
+    let response = ListPaymentRequestsResponse {
+        payment_requests: payment_requests
+            .into_iter()
+            .map(PaymentRequestResponse::try_from)
+            .collect::<Result<_, _>>()?,
+        total_count: total_count as u64,
+        page,
+        page_size,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a payment request by ID. Only its requester and payer can see it.
+async fn get_payment_request(
+    State(app_state): State<Arc<AppState>>,
+    Path(payment_request_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let payment_request =
+        find_payment_request(&app_state, &payment_request_id, &claims.user_id).await?;
+
+    let response = PaymentRequestResponse::try_from(payment_request)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Pays a pending payment request with a transfer from the payer to the requester.
+///
+/// Only the payer may pay a request. If the payer cannot cover the amount the request stays
+/// PENDING and can be paid later.
+async fn pay_payment_request(
+    State(app_state): State<Arc<AppState>>,
+    Path(payment_request_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+) -> Result<Response, ContainerError<ApiError>> {
+    let payment_request =
+        find_payment_request(&app_state, &payment_request_id, &claims.user_id).await?;
+    if payment_request.payer_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the payer can pay a payment request").into());
+    }
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payment_request_id,
+        StatusCode::OK,
+        |payment_request_id| execute_payment(&app_state, payment_request_id),
+    )
+    .await
+}
+
+/// Moves the money for a payment request the caller is allowed to pay.
+async fn execute_payment(
+    app_state: &Arc<AppState>,
+    payment_request_id: String,
+) -> Result<PaymentRequestResponse, ContainerError<ApiError>> {
+    let payment_request = app_state
+        .db
+        .pay_payment_request(&payment_request_id)
+        .await?;
+
+    logger::info!(
+        "Payment request paid with payment_request_id: {}",
+        payment_request.payment_request_id
+    );
+
+    PaymentRequestResponse::try_from(payment_request)
+}
+
+/// Declines a pending payment request. Only the payer may decline a request.
+async fn decline_payment_request(
+    State(app_state): State<Arc<AppState>>,
+    Path(payment_request_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let payment_request =
+        find_payment_request(&app_state, &payment_request_id, &claims.user_id).await?;
+    if payment_request.payer_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the payer can decline a payment request").into());
+    }
+
+    let payment_request = app_state
+        .db
+        .update_payment_request_status(&payment_request_id, PaymentRequestStatus::Declined)
+        .await?;
+
+    let response = PaymentRequestResponse::try_from(payment_request)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Withdraws a pending payment request. Only the requester may cancel a request.
+async fn cancel_payment_request(
+    State(app_state): State<Arc<AppState>>,
+    Path(payment_request_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let payment_request =
+        find_payment_request(&app_state, &payment_request_id, &claims.user_id).await?;
+    if payment_request.requester_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the requester can cancel a payment request").into());
+    }
+
+    let payment_request = app_state
+        .db
+        .update_payment_request_status(&payment_request_id, PaymentRequestStatus::Cancelled)
+        .await?;
+
+    let response = PaymentRequestResponse::try_from(payment_request)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a payment request the user is a party to, hiding the requests of other users.
+async fn find_payment_request(
+    app_state: &Arc<AppState>,
+    payment_request_id: &str,
+    user_id: &str,
+) -> Result<PaymentRequest, ContainerError<ApiError>> {
+    let payment_request = app_state.db.get_payment_request(payment_request_id).await?;
+
+    if payment_request.requester_id != user_id && payment_request.payer_id != user_id {
+        return Err(ApiError::NotFoundError("payment request").into());
+    }
+
+    Ok(payment_request)
+}
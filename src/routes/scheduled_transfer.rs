@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{
+            self, CreateScheduledTransferRequest, ListScheduledTransfersRequest,
+            ListScheduledTransfersResponse, ScheduledTransferResponse,
+        },
+        auth::AuthResolver,
+    },
+    storage::{
+        ScheduledTransferInterface, UserInterface, enums::ScheduledTransferStatus,
+        types::NewScheduledTransfer,
+    },
+    utils::{datetime, generate_nano_id},
+};
+
+/// Serves scheduled transfer routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_scheduled_transfer))
+        .route("/", get(list_scheduled_transfers))
+        .route("/:scheduled_transfer_id", get(get_scheduled_transfer))
+        .route(
+            "/:scheduled_transfer_id/cancel",
+            post(cancel_scheduled_transfer),
+        )
+        .with_state(app_state)
+}
+
+/// Schedules a transfer to execute at a future time.
+///
+/// Only the sender may schedule a transfer. Funds are not reserved; the sender's balance is
+/// checked when the transfer is executed.
+async fn create_scheduled_transfer(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    Json(payload): Json<CreateScheduledTransferRequest>,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    if payload.transfer.sender_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the sender can schedule a transfer").into());
+    }
+
+    app_state
+        .db
+        .get_user_by_user_id(&payload.transfer.receiver_id)
+        .await
+        .change_error(ApiError::NotFoundError("user"))?;
+
+    let amount = payload
+        .transfer
+        .amount
+        .to_money(payload.transfer.currency)
+        .change_error(ApiError::ValidationError)?;
+    let now = datetime::now();
+
+    let scheduled_transfer = app_state
+        .db
+        .create_scheduled_transfer(NewScheduledTransfer {
+            scheduled_transfer_id: format!("st_{}", generate_nano_id(20)),
+            transaction_id: format!("txn_{}", generate_nano_id(20)),
+            sender_id: payload.transfer.sender_id,
+            recipient_id: payload.transfer.receiver_id,
+            amount_minor_units: amount.minor_units(),
+            currency: amount.currency().to_string(),
+            description: payload.transfer.description,
+            execute_at: datetime::to_utc(payload.execute_at),
+            status: ScheduledTransferStatus::Scheduled,
+            created_at: now,
+            updated_at: now,
+            metadata: payload.transfer.metadata,
+        })
+        .await?;
+
+    logger::info!(
+        "Transfer scheduled with scheduled_transfer_id: {}",
+        scheduled_transfer.scheduled_transfer_id
+    );
+
+    let response = ScheduledTransferResponse::try_from(scheduled_transfer)?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Lists the scheduled transfers of the user with pagination.
+async fn list_scheduled_transfers(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ListScheduledTransfersRequest>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = api_models::validate_page_size(params.page_size, &app_state.config.pagination)
+        .change_error(ApiError::ValidationError)?;
+    let offset =
+        api_models::page_offset(page, page_size).change_error(ApiError::ValidationError)?;
+
+    let (scheduled_transfers, total_count) = app_state
+        .db
+        .list_scheduled_transfers(&claims.user_id, params.status, page_size as i64, offset)
+        .await?;
+
+    let response = ListScheduledTransfersResponse {
+        scheduled_transfers: scheduled_transfers
+            .into_iter()
+            .map(ScheduledTransferResponse::try_from)
+            .collect::<Result<_, _>>()?,
+        total_count: total_count as u64,
+        page,
+        page_size,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a scheduled transfer of the user by ID.
+async fn get_scheduled_transfer(
+    State(app_state): State<Arc<AppState>>,
+    Path(scheduled_transfer_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let scheduled_transfer = app_state
+        .db
+        .get_scheduled_transfer(&scheduled_transfer_id)
+        .await?;
+
+    if scheduled_transfer.sender_id != claims.user_id {
+        return Err(ApiError::NotFoundError("scheduled transfer").into());
+    }
+
+    let response = ScheduledTransferResponse::try_from(scheduled_transfer)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Cancels a scheduled transfer that has not been executed yet.
+async fn cancel_scheduled_transfer(
+    State(app_state): State<Arc<AppState>>,
+    Path(scheduled_transfer_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let scheduled_transfer = app_state
+        .db
+        .get_scheduled_transfer(&scheduled_transfer_id)
+        .await?;
+
+    if scheduled_transfer.sender_id != claims.user_id {
+        return Err(ApiError::NotFoundError("scheduled transfer").into());
+    }
+
+    let scheduled_transfer = app_state
+        .db
+        .cancel_scheduled_transfer(&scheduled_transfer_id)
+        .await?;
+
+    logger::info!(
+        "Scheduled transfer cancelled with scheduled_transfer_id: {}",
+        scheduled_transfer.scheduled_transfer_id
+    );
+
+    let response = ScheduledTransferResponse::try_from(scheduled_transfer)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger,
+    routes::{
+        api_models::{
+            self, CreateStandingInstructionRequest, ListStandingInstructionsRequest,
+            ListStandingInstructionsResponse, StandingInstructionResponse,
+        },
+        auth::AuthResolver,
+    },
+    storage::{
+        StandingInstructionInterface, UserInterface,
+        enums::{RecurrenceFrequency, StandingInstructionStatus},
+        types::NewStandingInstruction,
+    },
+    utils::{datetime, generate_nano_id},
+};
+
+/// Serves standing instruction routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_standing_instruction))
+        .route("/", get(list_standing_instructions))
+        .route("/:standing_instruction_id", get(get_standing_instruction))
+        .route(
+            "/:standing_instruction_id/pause",
+            post(pause_standing_instruction),
+        )
+        .route(
+            "/:standing_instruction_id/resume",
+            post(resume_standing_instruction),
+        )
+        .with_state(app_state)
+}
+
+/// Creates a standing instruction that repeats a transfer on a recurrence.
+///
+/// Only the sender may create a standing instruction. Funds are not reserved; the sender's
+/// balance is checked on every run, and a run that fails does not stop later ones.
+async fn create_standing_instruction(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    Json(payload): Json<CreateStandingInstructionRequest>,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    if payload.transfer.sender_id != claims.user_id {
+        return Err(
+            ApiError::Forbidden("only the sender can create a standing instruction").into(),
+        );
+    }
+
+    app_state
+        .db
+        .get_user_by_user_id(&payload.transfer.receiver_id)
+        .await
+        .change_error(ApiError::NotFoundError("user"))?;
+
+    let amount = payload
+        .transfer
+        .amount
+        .to_money(payload.transfer.currency)
+        .change_error(ApiError::ValidationError)?;
+    let now = datetime::now();
+
+    let mut new_standing_instruction = NewStandingInstruction {
+        standing_instruction_id: format!("si_{}", generate_nano_id(20)),
+        sender_id: payload.transfer.sender_id,
+        recipient_id: payload.transfer.receiver_id,
+        amount_minor_units: amount.minor_units(),
+        currency: amount.currency().to_string(),
+        description: payload.transfer.description,
+        frequency: RecurrenceFrequency::Daily,
+        day_of_month: None,
+        cron_expression: None,
+        start_at: payload.start_at.map(datetime::to_utc).unwrap_or(now),
+        end_at: payload.end_at.map(datetime::to_utc),
+        max_occurrences: payload
+            .max_occurrences
+            .map(|max_occurrences| max_occurrences as i32),
+        next_run_at: None,
+        status: StandingInstructionStatus::Active,
+        created_at: now,
+        updated_at: now,
+        metadata: payload.transfer.metadata,
+    };
+    new_standing_instruction.set_recurrence(&payload.recurrence);
+
+    let standing_instruction = app_state
+        .db
+        .create_standing_instruction(new_standing_instruction)
+        .await?;
+
+    logger::info!(
+        "Standing instruction created with standing_instruction_id: {}",
+        standing_instruction.standing_instruction_id
+    );
+
+    let response = StandingInstructionResponse::try_from(standing_instruction)?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Lists the standing instructions of the user with pagination.
+async fn list_standing_instructions(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ListStandingInstructionsRequest>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = api_models::validate_page_size(params.page_size, &app_state.config.pagination)
+        .change_error(ApiError::ValidationError)?;
+    let offset =
+        api_models::page_offset(page, page_size).change_error(ApiError::ValidationError)?;
+
+    let (standing_instructions, total_count) = app_state
+        .db
+        .list_standing_instructions(&claims.user_id, params.status, page_size as i64, offset)
+        .await?;
+
+    let response = ListStandingInstructionsResponse {
+        standing_instructions: standing_instructions
+            .into_iter()
+            .map(StandingInstructionResponse::try_from)
+            .collect::<Result<_, _>>()?,
+        total_count: total_count as u64,
+        page,
+        page_size,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a standing instruction of the user by ID.
+async fn get_standing_instruction(
+    State(app_state): State<Arc<AppState>>,
+    Path(standing_instruction_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let standing_instruction = app_state
+        .db
+        .get_standing_instruction(&standing_instruction_id)
+        .await?;
+
+    if standing_instruction.sender_id != claims.user_id {
+        return Err(ApiError::NotFoundError("standing instruction").into());
+    }
+
+    let response = StandingInstructionResponse::try_from(standing_instruction)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Pauses an active standing instruction until it is resumed.
+async fn pause_standing_instruction(
+    State(app_state): State<Arc<AppState>>,
+    Path(standing_instruction_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let standing_instruction = app_state
+        .db
+        .get_standing_instruction(&standing_instruction_id)
+        .await?;
+
+    if standing_instruction.sender_id != claims.user_id {
+        return Err(ApiError::NotFoundError("standing instruction").into());
+    }
+
+    let standing_instruction = app_state
+        .db
+        .pause_standing_instruction(&standing_instruction_id)
+        .await?;
+
+    logger::info!(
+        "Standing instruction paused with standing_instruction_id: {}",
+        standing_instruction.standing_instruction_id
+    );
+
+    let response = StandingInstructionResponse::try_from(standing_instruction)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Resumes a paused standing instruction. Runs that fell due while it was paused are skipped.
+async fn resume_standing_instruction(
+    State(app_state): State<Arc<AppState>>,
+    Path(standing_instruction_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let standing_instruction = app_state
+        .db
+        .get_standing_instruction(&standing_instruction_id)
+        .await?;
+
+    if standing_instruction.sender_id != claims.user_id {
+        return Err(ApiError::NotFoundError("standing instruction").into());
+    }
+
+    let standing_instruction = app_state
+        .db
+        .resume_standing_instruction(&standing_instruction_id)
+        .await?;
+
+    logger::info!(
+        "Standing instruction resumed with standing_instruction_id: {}",
+        standing_instruction.standing_instruction_id
+    );
+
+    let response = StandingInstructionResponse::try_from(standing_instruction)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
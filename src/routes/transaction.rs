@@ -2,178 +2,506 @@ use std::sync::Arc;
 
 use crate::{
     app::AppState,
+    consts,
     error::{
-        ApiError,
+        ApiError, TransactionDbError,
         container::{ContainerError, ResultContainerExt},
     },
+    logger,
     routes::{
         api_models::{
-            CreateTransactionRequest, GetTransactionResponse, ListTransactionsRequest,
-            ListTransactionsResponse,
+            self, AmountRequest, AuthorizeTransactionRequest, CaptureTransactionRequest,
+            CreateTransactionRequest, ExportTransactionsRequest, GetTransactionResponse,
+            ListTransactionsRequest, ListTransactionsResponse, RefundTransactionRequest,
         },
         auth::AuthResolver,
+        fx,
+        idempotency::{self, IdempotencyKey},
     },
     storage::{
-        TransactionInterface,
+        RiskInterface, TransactionInterface, WalletInterface,
+        enums::{TransactionStatus, TransactionType},
         types::{self, Transaction},
     },
+    types::{Currency, Money},
     utils::{datetime, generate_nano_id},
 };
 use axum::{
     Json, Router,
+    body::Body,
     extract::{Path, Query, State},
-    response::IntoResponse,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
-use diesel::BoolExpressionMethods;
-use error_stack::ResultExt;
+use futures_util::{StreamExt, stream};
 
 /// Serves transaction routes.
 pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/", post(create_transaction))
+        .route("/authorize", post(authorize_transaction))
+        .route("/export", get(export_transactions))
         .route("/:transaction_id", get(get_transaction))
+        .route("/:transaction_id/capture", post(capture_transaction))
+        .route("/:transaction_id/void", post(void_transaction))
+        .route("/:transaction_id/refund", post(refund_transaction))
         .route("/", get(list_transactions))
         .with_state(app_state)
 }
 
 /// Creates a new transaction.
+///
+/// Only the sender may create a transaction. When an `Idempotency-Key` header is sent, retries
+/// with the same key and payload replay the original response instead of moving the money
+/// again.
 async fn create_transaction(
     State(app_state): State<Arc<AppState>>,
     AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
     Json(payload): Json<CreateTransactionRequest>,
-) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+) -> Result<Response, ContainerError<ApiError>> {
     payload.validate().change_error(ApiError::ValidationError)?;
 
+    if payload.sender_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the sender can create a transaction").into());
+    }
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payload,
+        StatusCode::CREATED,
+        |payload| execute_transaction(&app_state, payload),
+    )
+    .await
+}
+
+/// Moves the money of a validated create transaction request if the risk rules allow it.
+///
+/// Transfers flagged for review are held as UNDER_REVIEW until an admin approves or rejects
+/// them. Blocked transfers are recorded as FAILED and rejected.
+///
+/// With an FX quote, the quote is executed and the converted amount is transferred in one
+/// database transaction, so a rejected transfer leaves the quote OPEN and nothing converted.
+async fn execute_transaction(
+    app_state: &Arc<AppState>,
+    mut payload: CreateTransactionRequest,
+) -> Result<GetTransactionResponse, ContainerError<ApiError>> {
+    let quote_id = payload.fx_quote_id.take();
+    if let Some(quote_id) = &quote_id {
+        let converted = quoted_transfer_amount(app_state, &payload, quote_id).await?;
+        payload.amount = AmountRequest::MinorUnits(converted.minor_units());
+        payload.currency = converted.currency();
+    }
+
+    let new_transaction = new_transfer(payload, None)?;
+    let transaction_id = new_transaction.transaction_id.clone();
+
+    let transaction = match &quote_id {
+        Some(quote_id) => {
+            app_state
+                .db
+                .submit_fx_transfer(quote_id, new_transaction)
+                .await
+        }
+        None => app_state.db.submit_transfer(new_transaction).await,
+    }
+    .inspect_err(|err| {
+        if *err.get_inner() == TransactionDbError::TransferBlocked {
+            logger::info!("Transfer blocked by risk checks, transaction_id: {transaction_id}");
+        }
+    })?;
+
+    if let Some(quote_id) = quote_id {
+        logger::info!("FX quote executed for a transfer with quote_id: {quote_id}");
+    }
+
+    GetTransactionResponse::try_from(transaction)
+}
+
+/// Returns the converted amount a create transaction request sends through its FX quote.
+///
+/// The request must send exactly the source amount of the quote, and the receiver must hold a
+/// wallet in its target currency.
+async fn quoted_transfer_amount(
+    app_state: &Arc<AppState>,
+    payload: &CreateTransactionRequest,
+    quote_id: &str,
+) -> Result<Money, ContainerError<ApiError>> {
+    let quote = fx::find_quote(app_state, quote_id, &payload.sender_id).await?;
+
+    let amount = payload
+        .amount
+        .to_money(payload.currency)
+        .change_error(ApiError::ValidationError)?;
+    let source_amount = quote
+        .source_amount()
+        .change_error(ApiError::UnknownError("Invalid amount stored for fx quote"))?;
+    if amount != source_amount {
+        return Err(
+            ApiError::InvalidAmount("amount must match the source amount of the fx quote").into(),
+        );
+    }
+
+    let target_amount = quote
+        .target_amount()
+        .change_error(ApiError::UnknownError("Invalid amount stored for fx quote"))?;
+    app_state
+        .db
+        .get_wallet(&payload.receiver_id, target_amount.currency())
+        .await
+        .change_error(ApiError::CurrencyNotHeld(target_amount.currency()))?;
+
+    Ok(target_amount)
+}
+
+/// Builds a new PENDING transfer, optionally holding the amount until `expires_at`.
+fn new_transfer(
+    payload: CreateTransactionRequest,
+    expires_at: Option<time::PrimitiveDateTime>,
+) -> Result<types::NewTransaction, ContainerError<ApiError>> {
+    let amount = payload
+        .amount
+        .to_money(payload.currency)
+        .change_error(ApiError::ValidationError)?;
     let transaction_id = generate_nano_id(20);
     let created_at = datetime::now();
 
-    let new_transaction = types::NewTransaction {
+    Ok(types::NewTransaction {
         transaction_id: format!("txn_{}", transaction_id.clone()),
         sender_id: payload.sender_id,
         recipient_id: payload.receiver_id,
-        amount_in_rs: payload.amount,
+        amount_minor_units: amount.minor_units(),
+        currency: amount.currency().to_string(),
         created_at,
-        description: None,
-        status: "PENDING".to_string(),
+        description: payload.description,
+        status: TransactionStatus::Pending,
         updated_at: datetime::now(),
-    };
+        transaction_type: TransactionType::Transfer,
+        parent_transaction_id: None,
+        authorized_amount_minor_units: expires_at.map(|_| amount.minor_units()),
+        expires_at,
+        standing_instruction_id: None,
+        payment_request_id: None,
+        batch_id: None,
+        metadata: payload.metadata,
+    })
+}
+
+/// Authorizes a transaction, holding the amount on the sender's account.
+///
+/// Only the sender may authorize a transaction. The hold reduces the sender's available balance
+/// without crediting the receiver until the authorization is captured, voided or expires.
+async fn authorize_transaction(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    Json(payload): Json<AuthorizeTransactionRequest>,
+) -> Result<Response, ContainerError<ApiError>> {
+    payload
+        .validate(app_state.config.authorization.max_hold_ttl)
+        .change_error(ApiError::ValidationError)?;
+
+    if payload.transfer.sender_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the sender can authorize a transaction").into());
+    }
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payload,
+        StatusCode::CREATED,
+        |payload| execute_authorization(&app_state, payload),
+    )
+    .await
+}
+
+/// Holds the funds for a validated authorize transaction request.
+async fn execute_authorization(
+    app_state: &Arc<AppState>,
+    payload: AuthorizeTransactionRequest,
+) -> Result<GetTransactionResponse, ContainerError<ApiError>> {
+    let hold_ttl = payload
+        .expires_in
+        .unwrap_or(app_state.config.authorization.hold_ttl);
+    let expires_at = datetime::now() + std::time::Duration::from_secs(hold_ttl);
+
+    let new_transaction = new_transfer(payload.transfer, Some(expires_at))?;
+
+    let transaction = app_state.db.authorize_transaction(new_transaction).await?;
+
+    GetTransactionResponse::try_from(transaction)
+}
+
+/// Captures an authorized transaction, fully or partially.
+///
+/// Only the recipient may capture a transaction. Capturing less than the authorized amount
+/// releases the rest of the hold.
+async fn capture_transaction(
+    State(app_state): State<Arc<AppState>>,
+    Path(transaction_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    Json(payload): Json<CaptureTransactionRequest>,
+) -> Result<Response, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let authorization = app_state.db.get_transaction_by_id(&transaction_id).await?;
+    if authorization.recipient_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the recipient can capture a transaction").into());
+    }
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payload,
+        StatusCode::OK,
+        |payload| execute_capture(&app_state, authorization, payload),
+    )
+    .await
+}
+
+/// Moves the captured funds for a validated capture transaction request, in the currency of
+/// the authorization.
+async fn execute_capture(
+    app_state: &Arc<AppState>,
+    authorization: Transaction,
+    payload: CaptureTransactionRequest,
+) -> Result<GetTransactionResponse, ContainerError<ApiError>> {
+    let currency = transaction_currency(&authorization)?;
+    let amount = payload
+        .amount
+        .map(|amount| amount.to_money(currency))
+        .transpose()
+        .change_error(ApiError::ValidationError)?;
 
     let transaction = app_state
         .db
-        .create_transaction(new_transaction)
-        .await
-        .change_error(ApiError::TransactionDatabaseError)?;
+        .capture_transaction(&authorization.transaction_id, amount)
+        .await?;
 
-    let response = GetTransactionResponse {
-        transaction_id: transaction.transaction_id,
-        sender_id: transaction.sender_id,
-        receiver_id: transaction.recipient_id,
-        amount: transaction.amount_in_rs,
-        created_at: transaction.created_at.to_string(),
+    GetTransactionResponse::try_from(transaction)
+}
+
+/// Voids an authorized transaction, releasing its hold.
+///
+/// Only the recipient may void a transaction.
+async fn void_transaction(
+    State(app_state): State<Arc<AppState>>,
+    Path(transaction_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let authorization = app_state.db.get_transaction_by_id(&transaction_id).await?;
+    if authorization.recipient_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the recipient can void a transaction").into());
+    }
+
+    let transaction = app_state.db.void_transaction(&transaction_id).await?;
+
+    let response = GetTransactionResponse::try_from(transaction)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Refunds a completed transaction, fully or partially.
+///
+/// Only the recipient of the original transaction may refund it. The refund is recorded as a
+/// linked REFUND transaction and the original moves to REFUNDED or PARTIALLY_REFUNDED.
+async fn refund_transaction(
+    State(app_state): State<Arc<AppState>>,
+    Path(transaction_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    Json(payload): Json<RefundTransactionRequest>,
+) -> Result<Response, ContainerError<ApiError>> {
+    payload.validate().change_error(ApiError::ValidationError)?;
+
+    let original = app_state.db.get_transaction_by_id(&transaction_id).await?;
+    if original.recipient_id != claims.user_id {
+        return Err(ApiError::Forbidden("only the recipient can refund a transaction").into());
+    }
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payload,
+        StatusCode::CREATED,
+        |payload| execute_refund(&app_state, original, payload),
+    )
+    .await
+}
+
+/// Books the refund for a validated refund transaction request, in the currency of the
+/// original transaction.
+async fn execute_refund(
+    app_state: &Arc<AppState>,
+    original: Transaction,
+    payload: RefundTransactionRequest,
+) -> Result<GetTransactionResponse, ContainerError<ApiError>> {
+    let currency = transaction_currency(&original)?;
+    let amount = payload
+        .amount
+        .map(|amount| amount.to_money(currency))
+        .transpose()
+        .change_error(ApiError::ValidationError)?;
+
+    let refund = types::NewRefund {
+        transaction_id: format!("txn_{}", generate_nano_id(20)),
+        parent_transaction_id: original.transaction_id,
+        amount,
+        description: payload.description,
     };
 
-    Ok((axum::http::StatusCode::CREATED, Json(response)))
+    let transaction = app_state.db.refund_transaction(refund).await?;
+
+    GetTransactionResponse::try_from(transaction)
 }
 
-/// Gets a transaction by ID.
+/// Returns the currency a stored transaction was made in.
+fn transaction_currency(transaction: &Transaction) -> Result<Currency, ContainerError<ApiError>> {
+    transaction
+        .currency
+        .parse()
+        .change_error(ApiError::UnknownError(
+            "Invalid currency stored for transaction",
+        ))
+}
+
+/// Gets a transaction of the user by ID. Only its sender and recipient can see it.
 async fn get_transaction(
     State(app_state): State<Arc<AppState>>,
     Path(transaction_id): Path<String>,
     AuthResolver(claims): AuthResolver,
 ) -> Result<impl IntoResponse, ContainerError<ApiError>> {
-    let transaction = app_state
-        .db
-        .get_transaction_by_id(&transaction_id)
-        .await
-        .change_error(ApiError::TransactionDatabaseError)?;
+    let transaction = app_state.db.get_transaction_by_id(&transaction_id).await?;
 
-    let response = GetTransactionResponse {
-        transaction_id: transaction.transaction_id,
-        sender_id: transaction.sender_id,
-        receiver_id: transaction.recipient_id,
-        amount: transaction.amount_in_rs,
-        created_at: transaction.created_at.to_string(),
-    };
+    if transaction.sender_id != claims.user_id && transaction.recipient_id != claims.user_id {
+        return Err(ApiError::NotFoundError("transaction").into());
+    }
+
+    let response = GetTransactionResponse::try_from(transaction)?;
 
-    Ok((axum::http::StatusCode::OK, Json(response)))
+    Ok((StatusCode::OK, Json(response)))
 }
 
-/// Lists transactions for a user with pagination.
+/// Lists transactions for a user a page at a time, filtered by direction, counterparty, status,
+/// currency, creation time, amount, description text and metadata, and sorted by creation time
+/// or amount.
+///
+/// Pages are read from the cursor of a previous page rather than an offset, so deep pages are
+/// as cheap as the first and transactions arriving meanwhile do not shift them.
 async fn list_transactions(
     State(app_state): State<Arc<AppState>>,
     Query(params): Query<ListTransactionsRequest>,
     AuthResolver(claims): AuthResolver,
 ) -> Result<impl IntoResponse, ContainerError<ApiError>> {
-    use crate::error::container::ResultContainerExt;
-
-    let page = params.page.unwrap_or(1);
-    let page_size = params.page_size.unwrap_or(10);
-    let user_id = &claims.user_id;
+    let filter = params
+        .to_filter(claims.user_id.clone())
+        .change_error(ApiError::ValidationError)?;
+    let cursor = params.cursor().change_error(ApiError::ValidationError)?;
+    let page_size = api_models::validate_page_size(params.page_size, &app_state.config.pagination)
+        .change_error(ApiError::ValidationError)?;
 
-    let (transactions, total_count) =
-        get_paginated_transactions(app_state.clone(), user_id, page, page_size)
-            .await
-            .change_error(ApiError::TransactionDatabaseError)?;
+    let page = app_state
+        .db
+        .list_transactions(
+            &filter,
+            cursor.as_ref(),
+            page_size as i64,
+            params.include_total,
+        )
+        .await
+        .change_error(ApiError::TransactionDatabaseError)?;
 
     let response = ListTransactionsResponse {
-        transactions,
-        total_count,
-        page,
+        transactions: page
+            .transactions
+            .into_iter()
+            .map(GetTransactionResponse::try_from)
+            .collect::<Result<_, _>>()?,
         page_size,
+        next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+        prev_cursor: page.prev_cursor.map(|cursor| cursor.encode()),
+        total_count: page.total_count.map(|total_count| total_count as u64),
     };
 
-    Ok((axum::http::StatusCode::OK, Json(response)))
+    Ok((StatusCode::OK, Json(response)))
 }
 
-async fn get_paginated_transactions(
-    app_state: Arc<AppState>,
-    user_id: &str,
-    page: u64,
-    page_size: u64,
-) -> Result<(Vec<GetTransactionResponse>, u64), ContainerError<ApiError>> {
-    use crate::error::container::ResultContainerExt;
-    use diesel::ExpressionMethods;
-    use diesel::QueryDsl;
-    use diesel::dsl::count;
-    use diesel_async::RunQueryDsl;
+/// Exports the transactions of the authenticated user, oldest first, as CSV or NDJSON.
+///
+/// Rows are streamed from a database cursor as a chunked response, so memory stays flat however
+/// long the history is, and every row comes from one consistent snapshot. If the export fails
+/// part way, the response is cut short instead of ending cleanly.
+async fn export_transactions(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ExportTransactionsRequest>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<Response, ContainerError<ApiError>> {
+    params.validate().change_error(ApiError::ValidationError)?;
 
-    use crate::storage::schema::transactions::dsl::*;
+    let format = params.format;
+    let from = params.from.map(datetime::to_utc);
+    let to = params.to.map(datetime::to_utc);
+    let user_id = claims.user_id;
 
-    let mut conn = app_state
-        .db
-        .get_conn()
-        .await
-        .change_error(ApiError::DatabaseError)?;
+    logger::info!("Transaction export started for user_id: {}", user_id);
 
-    let offset = (page - 1) * page_size;
+    let (batches_tx, batches_rx) = tokio::sync::mpsc::channel(consts::EXPORT_BUFFERED_BATCHES);
+    let export = tokio::spawn(async move {
+        let exported = app_state
+            .db
+            .export_transactions(&user_id, from, to, batches_tx)
+            .await;
+        (user_id, exported)
+    });
 
-    let transaction_list: Vec<Transaction> = transactions
-        .filter(sender_id.eq(user_id).or(recipient_id.eq(user_id)))
-        .limit(page_size as i64)
-        .offset(offset as i64)
-        .load(&mut conn) // Use load instead of get_results
-        .await
-        .change_error(ApiError::TransactionDatabaseError)?;
+    let rows = stream::unfold(
+        (batches_rx, Some(export)),
+        move |(mut batches, export)| async move {
+            if let Some(batch) = batches.recv().await {
+                let chunk = format.render(&batch).map_err(std::io::Error::other);
+                return Some((chunk, (batches, export)));
+            }
 
-    let mut transaction_response_list: Vec<GetTransactionResponse> = Vec::new();
-
-    for transaction in transaction_list {
-        transaction_response_list.push(GetTransactionResponse {
-            transaction_id: transaction.transaction_id,
-            sender_id: transaction.sender_id,
-            receiver_id: transaction.recipient_id,
-            amount: transaction.amount_in_rs,
-            created_at: transaction.created_at.to_string(),
-        });
-    }
-
-    let total_transaction: i64 = transactions
-        .filter(sender_id.eq(user_id).or(recipient_id.eq(user_id)))
-        .select(count(transaction_id)) // Select count
-        .get_result(&mut conn)
-        .await
-        .change_error(ApiError::DatabaseError)?;
+            match export?.await {
+                Ok((user_id, Ok(exported))) => {
+                    logger::info!(
+                        "Transaction export finished for user_id: {} with {} transactions",
+                        user_id,
+                        exported
+                    );
+                    None
+                }
+                Ok((_, Err(error))) => {
+                    logger::error!(?error, "Transaction export failed");
+                    let failed = std::io::Error::other("transaction export failed");
+                    Some((Err(failed), (batches, None)))
+                }
+                Err(error) => {
+                    logger::error!(?error, "Transaction export task failed");
+                    let failed = std::io::Error::other("transaction export failed");
+                    Some((Err(failed), (batches, None)))
+                }
+            }
+        },
+    );
+    let header = stream::once(async move { Ok(format.header()) });
 
-    Ok((transaction_response_list, total_transaction as u64))
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", format.file_name()),
+            ),
+        ],
+        Body::from_stream(header.chain(rows)),
+    )
+        .into_response())
 }
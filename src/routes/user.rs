@@ -5,21 +5,24 @@ use std::sync::Arc;
 
 use crate::{
     error::{
-        ApiError,
+        ApiError, MoneyError,
         container::{ContainerError, ResultContainerExt},
     },
     logger,
     routes::{api_models, auth::AuthResolver},
+    statement::{Statement, StatementPeriod},
     storage::{
-        UserInterface,
-        types::{UserNew, UserUpdateInternal},
+        TransactionInterface, TransferLimitInterface, UserInterface, WalletInterface,
+        types::{NewWallet, UserNew, UserUpdateInternal},
     },
-    types::Claims,
-    utils,
+    types::{Claims, Currency, Money, limits::LimitWindows},
+    utils::{self, datetime},
 };
 use axum::{
     Json,
-    extract::State,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{Html, IntoResponse, Response},
     routing::{get, post, put},
 };
 
@@ -27,13 +30,15 @@ use crate::app::AppState;
 
 /// Serves the user routes.
 pub fn serve(_app_state: Arc<AppState>) -> axum::Router<Arc<AppState>> {
-    let router = axum::Router::new()
+    axum::Router::new()
         .route("/signup", post(sign_up))
         .route("/login", post(login))
         .route("/", get(get_user_profile))
-        .route("/", put(update_user));
-
-    router
+        .route("/", put(update_user))
+        .route("/limits", get(get_user_limits))
+        .route("/wallets", post(create_wallet))
+        .route("/statements/:year/:month", get(get_statement))
+        .route("/balance/history", get(get_balance_history))
 }
 
 /// Handles the sign-up request.
@@ -93,7 +98,7 @@ async fn login(
     }))
 }
 
-/// Handles the get user profile request.
+/// Handles the get user profile request, listing the balance of every wallet of the user.
 async fn get_user_profile(
     State(app_state): State<Arc<AppState>>,
     AuthResolver(user_info): AuthResolver,
@@ -104,9 +109,44 @@ async fn get_user_profile(
         .await
         .change_error(ApiError::NotFoundError("user"))?;
 
+    let wallets = app_state.db.list_wallets(&user.user_id).await?;
+    let mut balances = Vec::with_capacity(wallets.len());
+    for wallet in wallets {
+        let currency = wallet
+            .currency
+            .parse()
+            .change_error(ApiError::UnknownError("Invalid currency stored for wallet"))?;
+        let held_minor_units = app_state
+            .db
+            .get_held_amount(&user.user_id, currency)
+            .await?;
+        balances.push((wallet, held_minor_units).try_into()?);
+    }
+
     logger::info!("User profile fetched with user_id: {}", user.user_id);
 
-    Ok(Json(user.into()))
+    Ok(Json((user, balances).into()))
+}
+
+/// Handles the create wallet request, opening an empty wallet in a currency the user does not
+/// hold yet.
+async fn create_wallet(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(user_info): AuthResolver,
+    Json(payload): Json<api_models::CreateWalletRequest>,
+) -> Result<(StatusCode, Json<api_models::WalletBalanceResponse>), ContainerError<ApiError>> {
+    let wallet = app_state
+        .db
+        .create_wallet(NewWallet::new(user_info.user_id, payload.currency))
+        .await?;
+
+    logger::info!(
+        "Wallet created for user_id: {} in {}",
+        wallet.user_id,
+        wallet.currency
+    );
+
+    Ok((StatusCode::CREATED, Json((wallet, 0).try_into()?)))
 }
 
 /// Handles the update user request.
@@ -127,10 +167,7 @@ async fn update_user(
 
     let updated_user = app_state
         .db
-        .update_user(
-            &user.user_id,
-            UserUpdateInternal::new(update_request.name, update_request.amount),
-        )
+        .update_user(&user.user_id, UserUpdateInternal::new(update_request.name))
         .await
         .change_error(ApiError::DatabaseUpdationFailed("users"))?;
 
@@ -138,3 +175,134 @@ async fn update_user(
 
     Ok(Json(updated_user.into()))
 }
+
+/// Handles the get user limits request, showing how much of each transfer limit remains for
+/// transfers in INR.
+async fn get_user_limits(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(user_info): AuthResolver,
+) -> Result<Json<api_models::TransferLimitsResponse>, ContainerError<ApiError>> {
+    let now = datetime::now();
+    let limits = app_state.db.get_transfer_limits(&user_info.user_id).await?;
+    let usage = app_state
+        .db
+        .get_limit_usage(&user_info.user_id, Currency::default(), now)
+        .await?;
+
+    Ok(Json((limits, usage, LimitWindows::at(now)).try_into()?))
+}
+
+/// Handles the get statement request, listing the transactions of one wallet of the user in a
+/// calendar month with the balance after each of them.
+///
+/// Renders as JSON by default, as CSV with `format=csv` or as a printable HTML page with
+/// `format=html`.
+async fn get_statement(
+    State(app_state): State<Arc<AppState>>,
+    Path((year, month)): Path<(i32, u8)>,
+    Query(params): Query<api_models::StatementRequest>,
+    AuthResolver(user_info): AuthResolver,
+) -> Result<Response, ContainerError<ApiError>> {
+    let period = StatementPeriod::new(year, month).change_error(ApiError::ValidationError)?;
+    if period.start() > datetime::now() {
+        return Err(ApiError::ValidationError.into());
+    }
+
+    app_state
+        .db
+        .get_wallet(&user_info.user_id, params.currency)
+        .await
+        .change_error(ApiError::CurrencyNotHeld(params.currency))?;
+
+    let (opening_balance, transactions, adjustments) = app_state
+        .db
+        .get_statement_activity(&user_info.user_id, params.currency, &period)
+        .await?;
+    let statement = Statement::new(
+        user_info.user_id,
+        params.currency,
+        period,
+        opening_balance,
+        transactions,
+        adjustments,
+    )
+    .change_error(ApiError::UnknownError("Invalid balance in statement"))?;
+
+    logger::info!(
+        "Statement fetched for user_id: {} for {} in {}",
+        statement.account_id,
+        statement.period,
+        params.currency
+    );
+
+    let response = match params.format {
+        api_models::StatementFormat::Json => {
+            Json(api_models::StatementResponse::from(statement)).into_response()
+        }
+        api_models::StatementFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", statement.file_name("csv")),
+                ),
+            ],
+            statement.to_csv(),
+        )
+            .into_response(),
+        api_models::StatementFormat::Html => Html(statement.to_html()).into_response(),
+    };
+
+    Ok(response)
+}
+
+/// Handles the balance history request, returning the closing balance of a wallet for every
+/// day, week or month of a range of days, the last one being its current balance.
+async fn get_balance_history(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<api_models::BalanceHistoryRequest>,
+    AuthResolver(user_info): AuthResolver,
+) -> Result<Json<api_models::BalanceHistoryResponse>, ContainerError<ApiError>> {
+    let buckets = params
+        .buckets(datetime::now().date())
+        .change_error(ApiError::ValidationError)?;
+
+    app_state
+        .db
+        .get_wallet(&user_info.user_id, params.currency)
+        .await
+        .change_error(ApiError::CurrencyNotHeld(params.currency))?;
+
+    let days = buckets.iter().map(|bucket| bucket.end).collect::<Vec<_>>();
+    let closing_balances = app_state
+        .db
+        .get_closing_balances(&user_info.user_id, params.currency, &days)
+        .await?;
+
+    let points = buckets
+        .into_iter()
+        .map(|bucket| {
+            let balance = closing_balances.get(&bucket.end).copied().unwrap_or(0);
+            Ok(api_models::BalancePointResponse {
+                period_start: bucket.start,
+                period_end: bucket.end,
+                closing_balance: Money::new(balance, params.currency)?.into(),
+            })
+        })
+        .collect::<Result<Vec<_>, MoneyError>>()
+        .change_error(ApiError::UnknownError("Invalid balance in balance history"))?;
+
+    logger::info!(
+        "Balance history fetched for user_id: {} with {} points in {}",
+        user_info.user_id,
+        points.len(),
+        params.currency
+    );
+
+    Ok(Json(api_models::BalanceHistoryResponse {
+        user_id: user_info.user_id,
+        currency: params.currency,
+        interval: params.interval,
+        points,
+    }))
+}
@@ -23,10 +23,9 @@ pub fn generate_password_hash(password: String) -> Result<String, ContainerError
 /// Verifies if the candidate password is correct.
 pub fn is_correct_password(
     candidate: &String,
-    password: &String,
+    password: &str,
 ) -> Result<bool, ContainerError<UserError>> {
-    let parsed_hash =
-        PasswordHash::new(&password).change_context(UserError::InternalServerError)?;
+    let parsed_hash = PasswordHash::new(password).change_context(UserError::InternalServerError)?;
     let result = Argon2::default().verify_password(candidate.as_bytes(), &parsed_hash);
     match result {
         Ok(_) => Ok(true),
@@ -0,0 +1,227 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+
+use crate::{
+    app::AppState,
+    error::{
+        ApiError, PayoutError,
+        container::{ContainerError, ResultContainerExt},
+    },
+    logger, payout,
+    routes::{
+        api_models::{
+            self, CreateWithdrawalRequest, ListWithdrawalsRequest, ListWithdrawalsResponse,
+            WithdrawalResponse,
+        },
+        auth::AuthResolver,
+        idempotency::{self, IdempotencyKey},
+    },
+    storage::{
+        BankAccountInterface, WithdrawalInterface,
+        enums::WithdrawalStatus,
+        types::{NewWithdrawal, Withdrawal},
+    },
+    utils::{datetime, generate_nano_id},
+};
+
+/// Serves withdrawal routes.
+pub fn serve(app_state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(create_withdrawal))
+        .route("/", get(list_withdrawals))
+        .route("/:withdrawal_id", get(get_withdrawal))
+        .with_state(app_state)
+}
+
+/// Withdraws an amount from the authenticated user's balance to one of their bank accounts.
+///
+/// The balance is debited into a PENDING withdrawal before the payout is dispatched through
+/// the payout connector. If the connector rejects the payout, the withdrawal fails and the
+/// money is returned to the balance. If it does not answer within `withdrawal.timeout` seconds,
+/// or its answer cannot be recorded, the withdrawal is returned PENDING and stays so until the
+/// recovery worker learns the outcome of the payout. Once the debit is committed the request
+/// always succeeds, so a retry with the same `Idempotency-Key` replays it instead of paying out
+/// twice.
+async fn create_withdrawal(
+    State(app_state): State<Arc<AppState>>,
+    AuthResolver(claims): AuthResolver,
+    IdempotencyKey(idempotency_key): IdempotencyKey,
+    Json(payload): Json<CreateWithdrawalRequest>,
+) -> Result<Response, ContainerError<ApiError>> {
+    payload
+        .validate(app_state.config.withdrawal.max_amount_minor_units)
+        .change_error(ApiError::ValidationError)?;
+
+    idempotency::run(
+        &app_state,
+        &claims.user_id,
+        idempotency_key,
+        payload,
+        StatusCode::CREATED,
+        |payload| execute_withdrawal(&app_state, claims.user_id.clone(), payload),
+    )
+    .await
+}
+
+/// Debits a withdrawal from the user and pays it out.
+async fn execute_withdrawal(
+    app_state: &Arc<AppState>,
+    user_id: String,
+    payload: CreateWithdrawalRequest,
+) -> Result<WithdrawalResponse, ContainerError<ApiError>> {
+    let bank_account = app_state
+        .db
+        .get_bank_account(&payload.bank_account_id)
+        .await
+        .change_error(ApiError::NotFoundError("bank account"))?;
+    if bank_account.user_id != user_id {
+        return Err(ApiError::NotFoundError("bank account").into());
+    }
+
+    let amount = payload
+        .amount
+        .to_money(payload.currency)
+        .change_error(ApiError::ValidationError)?;
+    let now = datetime::now();
+
+    let withdrawal = app_state
+        .db
+        .create_withdrawal(NewWithdrawal {
+            withdrawal_id: format!("wd_{}", generate_nano_id(20)),
+            user_id,
+            bank_account_id: bank_account.bank_account_id,
+            amount_minor_units: amount.minor_units(),
+            currency: amount.currency().to_string(),
+            status: WithdrawalStatus::Pending,
+            connector: app_state.payout_connector.name().to_string(),
+            transaction_id: format!("txn_{}", generate_nano_id(20)),
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+
+    logger::info!(
+        "Withdrawal created with withdrawal_id: {}",
+        withdrawal.withdrawal_id
+    );
+
+    let payout = payout::Payout {
+        withdrawal_id: withdrawal.withdrawal_id.clone(),
+        amount,
+        account_holder_name: bank_account.account_holder_name,
+        account_number: bank_account.account_number,
+        ifsc: bank_account.ifsc,
+    };
+    let timeout = Duration::from_secs(app_state.config.withdrawal.timeout);
+
+    // The withdrawal and its debit are committed, so from here on the request succeeds with the
+    // withdrawal, and that is what a retry replays. A withdrawal whose outcome cannot be
+    // recorded stays PENDING for the recovery worker.
+    let settled =
+        match payout::dispatch(app_state.payout_connector.as_ref(), &payout, timeout).await {
+            Ok(reference) => {
+                app_state
+                    .db
+                    .complete_withdrawal(&withdrawal.withdrawal_id, reference)
+                    .await
+            }
+            Err(error @ PayoutError::Rejected(_)) => {
+                logger::info!(
+                    "Payout failed for withdrawal_id: {}, returning funds: {}",
+                    withdrawal.withdrawal_id,
+                    error
+                );
+                app_state
+                    .db
+                    .fail_withdrawal(&withdrawal.withdrawal_id, error.to_string())
+                    .await
+            }
+            Err(PayoutError::TimedOut) => {
+                logger::warn!(
+                    "Payout timed out for withdrawal_id: {}, leaving it pending",
+                    withdrawal.withdrawal_id
+                );
+                Ok(withdrawal.clone())
+            }
+        };
+
+    let withdrawal = settled.unwrap_or_else(|error| {
+        logger::error!(
+            ?error,
+            "Failed to record the payout of withdrawal_id: {}, leaving it pending",
+            withdrawal.withdrawal_id
+        );
+        withdrawal
+    });
+
+    WithdrawalResponse::try_from(withdrawal)
+}
+
+/// Lists the withdrawals of the authenticated user, newest first, with pagination.
+async fn list_withdrawals(
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ListWithdrawalsRequest>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = api_models::validate_page_size(params.page_size, &app_state.config.pagination)
+        .change_error(ApiError::ValidationError)?;
+    let offset =
+        api_models::page_offset(page, page_size).change_error(ApiError::ValidationError)?;
+
+    let (withdrawals, total_count) = app_state
+        .db
+        .list_withdrawals(&claims.user_id, page_size as i64, offset)
+        .await?;
+
+    let response = ListWithdrawalsResponse {
+        withdrawals: withdrawals
+            .into_iter()
+            .map(WithdrawalResponse::try_from)
+            .collect::<Result<_, _>>()?,
+        total_count: total_count as u64,
+        page,
+        page_size,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a withdrawal and the status of its payout by ID. Only the user who made it can see it.
+async fn get_withdrawal(
+    State(app_state): State<Arc<AppState>>,
+    Path(withdrawal_id): Path<String>,
+    AuthResolver(claims): AuthResolver,
+) -> Result<impl IntoResponse, ContainerError<ApiError>> {
+    let withdrawal = find_withdrawal(&app_state, &withdrawal_id, &claims.user_id).await?;
+
+    let response = WithdrawalResponse::try_from(withdrawal)?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Gets a withdrawal made by the user, hiding the withdrawals of other users.
+async fn find_withdrawal(
+    app_state: &Arc<AppState>,
+    withdrawal_id: &str,
+    user_id: &str,
+) -> Result<Withdrawal, ContainerError<ApiError>> {
+    let withdrawal = app_state
+        .db
+        .get_withdrawal(withdrawal_id)
+        .await
+        .change_error(ApiError::NotFoundError("withdrawal"))?;
+
+    if withdrawal.user_id != user_id {
+        return Err(ApiError::NotFoundError("withdrawal").into());
+    }
+
+    Ok(withdrawal)
+}
@@ -0,0 +1,384 @@
+//! Monthly account statements.
+//!
+//! A [`Statement`] lists every transaction that moved money in or out of one wallet during a
+//! calendar month, with the balance after each of them, and renders as CSV or printable HTML.
+//! Transactions count from when they were created, the same transactions the reconciliation
+//! replays, and adjustments booked in the ledger outside of transactions are listed alongside
+//! them, so the closing balance of the current month is the balance of the wallet.
+
+use std::fmt::Write;
+
+use time::{Month, PrimitiveDateTime};
+
+use crate::{
+    error::{MoneyError, ValidationError},
+    storage::{
+        enums::{LedgerDirection, TransactionType},
+        types::{LedgerEntry, Transaction},
+    },
+    types::{Currency, Money},
+    utils,
+};
+
+/// Represents the calendar month a statement covers, in UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatementPeriod {
+    start: time::Date,
+}
+
+impl StatementPeriod {
+    /// Creates the period of `month` (1 to 12) of `year`.
+    pub fn new(year: i32, month: u8) -> Result<Self, ValidationError> {
+        let invalid = || ValidationError::InvalidValue {
+            message: format!("{year}-{month:02} is not a valid statement period"),
+        };
+
+        let month = Month::try_from(month).map_err(|_| invalid())?;
+        let start = time::Date::from_calendar_date(year, month, 1).map_err(|_| invalid())?;
+        // The period must end within the supported range of dates too.
+        let period = Self { start };
+        period.next_month_start().ok_or_else(invalid)?;
+
+        Ok(period)
+    }
+
+    /// Returns the year of the period.
+    pub fn year(&self) -> i32 {
+        self.start.year()
+    }
+
+    /// Returns the month of the period, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.start.month().into()
+    }
+
+    /// Returns the first instant of the period.
+    pub fn start(&self) -> PrimitiveDateTime {
+        self.start.midnight()
+    }
+
+    /// Returns the first instant after the period.
+    pub fn end(&self) -> PrimitiveDateTime {
+        self.next_month_start()
+            .map_or(PrimitiveDateTime::MAX, |date| date.midnight())
+    }
+
+    fn next_month_start(&self) -> Option<time::Date> {
+        match self.start.month() {
+            Month::December => {
+                time::Date::from_calendar_date(self.start.year() + 1, Month::January, 1).ok()
+            }
+            month => self.start.replace_month(month.next()).ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for StatementPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{:02}", self.year(), self.month())
+    }
+}
+
+/// Represents a transaction or an adjustment on a statement, with the balance of the wallet
+/// after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatementLine {
+    /// The transaction, or the journal of an adjustment.
+    pub transaction_id: String,
+    /// None for adjustments booked in the ledger outside of transactions, such as opening
+    /// balances.
+    pub transaction_type: Option<TransactionType>,
+    /// CREDIT for money received, DEBIT for money sent.
+    pub direction: LedgerDirection,
+    /// The other side of the transaction.
+    pub counterparty_id: String,
+    pub description: Option<String>,
+    /// What the wallet was credited or debited: the amount less the fee when receiving, the
+    /// amount when sending.
+    pub amount: Money,
+    pub balance: Money,
+    pub created_at: PrimitiveDateTime,
+}
+
+/// Represents the statement of a wallet for one month.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statement {
+    pub account_id: String,
+    pub period: StatementPeriod,
+    pub opening_balance: Money,
+    pub closing_balance: Money,
+    pub total_credits: Money,
+    pub total_debits: Money,
+    pub lines: Vec<StatementLine>,
+}
+
+impl Statement {
+    /// Builds the statement of the `currency` wallet of `account_id` from its balance when the
+    /// period opened, the transactions of the period and the ledger postings of the adjustments
+    /// booked during the period, each oldest first. Adjustments carry the postings of the other
+    /// side of their journal, which name the counterparty. Transactions and postings that did
+    /// not move money of the wallet are skipped.
+    pub fn new(
+        account_id: String,
+        currency: Currency,
+        period: StatementPeriod,
+        opening_balance_minor_units: i64,
+        transactions: Vec<Transaction>,
+        adjustments: Vec<LedgerEntry>,
+    ) -> Result<Self, MoneyError> {
+        let opening_balance = Money::new(opening_balance_minor_units, currency)?;
+        let mut lines = Vec::with_capacity(transactions.len() + adjustments.len());
+
+        for entry in &adjustments {
+            if entry.account_id != account_id || entry.currency != currency.code() {
+                continue;
+            }
+
+            let counterparty_id = adjustments
+                .iter()
+                .find(|other| {
+                    other.journal_id == entry.journal_id && other.account_id != account_id
+                })
+                .map_or_else(String::new, |other| other.account_id.clone());
+            lines.push(StatementLine {
+                transaction_id: entry.journal_id.clone(),
+                transaction_type: None,
+                direction: entry.direction,
+                counterparty_id,
+                description: None,
+                amount: Money::new(entry.amount_minor_units, currency)?,
+                balance: opening_balance,
+                created_at: entry.created_at,
+            });
+        }
+
+        for transaction in transactions {
+            if transaction.currency != currency.code() {
+                continue;
+            }
+
+            let mut entries = Vec::with_capacity(1);
+            if transaction.sender_id == account_id {
+                entries.push((
+                    LedgerDirection::Debit,
+                    transaction.amount()?,
+                    transaction.recipient_id.clone(),
+                ));
+            }
+            if transaction.recipient_id == account_id {
+                entries.push((
+                    LedgerDirection::Credit,
+                    transaction.net_amount()?,
+                    transaction.sender_id.clone(),
+                ));
+            }
+
+            for (direction, amount, counterparty_id) in entries {
+                lines.push(StatementLine {
+                    transaction_id: transaction.transaction_id.clone(),
+                    transaction_type: Some(transaction.transaction_type),
+                    direction,
+                    counterparty_id,
+                    description: transaction.description.clone(),
+                    amount,
+                    balance: opening_balance,
+                    created_at: transaction.created_at,
+                });
+            }
+        }
+
+        // The sort is stable, so adjustments come before the transactions booked at the same
+        // instant, like the opening balances they usually are.
+        lines.sort_by_key(|line| line.created_at);
+
+        let mut balance = opening_balance;
+        let mut total_credits = Money::zero(currency);
+        let mut total_debits = Money::zero(currency);
+        for line in &mut lines {
+            match line.direction {
+                LedgerDirection::Credit => {
+                    balance = balance.checked_add(line.amount)?;
+                    total_credits = total_credits.checked_add(line.amount)?;
+                }
+                LedgerDirection::Debit => {
+                    balance = balance.checked_sub(line.amount)?;
+                    total_debits = total_debits.checked_add(line.amount)?;
+                }
+            }
+            line.balance = balance;
+        }
+
+        Ok(Self {
+            account_id,
+            period,
+            opening_balance,
+            closing_balance: balance,
+            total_credits,
+            total_debits,
+            lines,
+        })
+    }
+
+    /// Returns the currency of the statement.
+    pub fn currency(&self) -> Currency {
+        self.opening_balance.currency()
+    }
+
+    /// Returns a file name for the statement with the given extension.
+    pub fn file_name(&self, extension: &str) -> String {
+        format!("statement-{}-{}.{extension}", self.period, self.currency())
+    }
+
+    /// Renders the statement as CSV: the opening balance, one row per transaction, the totals
+    /// and the closing balance. Amounts are decimal strings in major units.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "date,transaction_id,type,counterparty_id,description,credit,debit,balance,currency\r\n",
+        );
+        let currency = self.currency().code();
+
+        let mut row = |fields: [&str; 9]| csv.push_str(&utils::csv::record(fields));
+
+        row([
+            &self.period.start().to_string(),
+            "",
+            "",
+            "",
+            "Opening balance",
+            "",
+            "",
+            &self.opening_balance.to_decimal_string(),
+            currency,
+        ]);
+        for line in &self.lines {
+            let (credit, debit) = line.split_amount();
+            row([
+                &line.created_at.to_string(),
+                &line.transaction_id,
+                line.type_name(),
+                &line.counterparty_id,
+                line.description.as_deref().unwrap_or(""),
+                &credit,
+                &debit,
+                &line.balance.to_decimal_string(),
+                currency,
+            ]);
+        }
+        row([
+            "",
+            "",
+            "",
+            "",
+            "Totals",
+            &self.total_credits.to_decimal_string(),
+            &self.total_debits.to_decimal_string(),
+            "",
+            currency,
+        ]);
+        row([
+            &self.period.end().to_string(),
+            "",
+            "",
+            "",
+            "Closing balance",
+            "",
+            "",
+            &self.closing_balance.to_decimal_string(),
+            currency,
+        ]);
+
+        csv
+    }
+
+    /// Renders the statement as a standalone HTML page laid out for printing.
+    pub fn to_html(&self) -> String {
+        let currency = self.currency();
+        let mut html = String::new();
+
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Statement {period} {currency}</title>\n<style>\n\
+             body {{ font-family: sans-serif; margin: 2em; }}\n\
+             table {{ border-collapse: collapse; width: 100%; }}\n\
+             th, td {{ border-bottom: 1px solid #ccc; padding: 0.4em; text-align: left; }}\n\
+             td.amount, th.amount {{ text-align: right; font-variant-numeric: tabular-nums; }}\n\
+             @media print {{ body {{ margin: 0; }} tr {{ page-break-inside: avoid; }} }}\n\
+             </style>\n</head>\n<body>\n<h1>Statement for {period}</h1>\n\
+             <p>Account {account} &middot; {currency} &middot; {start} to {end} UTC</p>\n\
+             <table class=\"summary\">\n\
+             <tr><th>Opening balance</th><td class=\"amount\">{opening}</td></tr>\n\
+             <tr><th>Total credits</th><td class=\"amount\">{credits}</td></tr>\n\
+             <tr><th>Total debits</th><td class=\"amount\">{debits}</td></tr>\n\
+             <tr><th>Closing balance</th><td class=\"amount\">{closing}</td></tr>\n\
+             </table>\n<h2>Transactions</h2>\n<table class=\"transactions\">\n\
+             <tr><th>Date</th><th>Transaction</th><th>Type</th><th>Counterparty</th>\
+             <th>Description</th><th class=\"amount\">Credit</th><th class=\"amount\">Debit</th>\
+             <th class=\"amount\">Balance</th></tr>\n",
+            period = self.period,
+            account = html_escape(&self.account_id),
+            start = self.period.start(),
+            end = self.period.end(),
+            opening = self.opening_balance.to_decimal_string(),
+            credits = self.total_credits.to_decimal_string(),
+            debits = self.total_debits.to_decimal_string(),
+            closing = self.closing_balance.to_decimal_string(),
+        );
+
+        if self.lines.is_empty() {
+            html.push_str("<tr><td colspan=\"8\">No transactions in this period.</td></tr>\n");
+        }
+        for line in &self.lines {
+            let (credit, debit) = line.split_amount();
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td class=\"amount\">{credit}</td><td class=\"amount\">{debit}</td>\
+                 <td class=\"amount\">{}</td></tr>",
+                line.created_at,
+                html_escape(&line.transaction_id),
+                line.type_name(),
+                html_escape(&line.counterparty_id),
+                html_escape(line.description.as_deref().unwrap_or("")),
+                line.balance.to_decimal_string(),
+            );
+        }
+
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+impl StatementLine {
+    /// Returns the name of the type of the line, ADJUSTMENT for adjustments.
+    fn type_name(&self) -> &'static str {
+        self.transaction_type
+            .map_or("ADJUSTMENT", TransactionType::as_str)
+    }
+
+    /// Returns the amount as a decimal string in the credit or the debit column, leaving the
+    /// other empty.
+    fn split_amount(&self) -> (String, String) {
+        let amount = self.amount.to_decimal_string();
+        match self.direction {
+            LedgerDirection::Credit => (amount, String::new()),
+            LedgerDirection::Debit => (String::new(), amount),
+        }
+    }
+}
+
+/// Escapes text for use in HTML element content and attribute values.
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
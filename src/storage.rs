@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use diesel_async::{
     AsyncPgConnection,
@@ -10,12 +10,16 @@ use diesel_async::{
 use error_stack::ResultExt;
 
 use crate::{
-    configs::Database,
+    configs::{CatchUpPolicy, Database, Fees, Risk},
     error::{self, container::ContainerError},
+    risk::{RiskEngine, RiskHistory},
+    statement::StatementPeriod,
+    types::{Currency, Money, TransferLimits, limits::LimitUsage},
 };
 
 pub mod caching;
 pub mod db;
+pub mod enums;
 pub mod schema;
 pub mod transformers;
 pub mod types;
@@ -24,13 +28,21 @@ pub mod types;
 #[derive(Clone)]
 pub struct Storage {
     pg_pool: Arc<Pool<AsyncPgConnection>>,
+    fees: Arc<Fees>,
+    transfer_limits: TransferLimits,
+    risk: Arc<RiskEngine>,
 }
 
 type DeadPoolConnType = Object<AsyncPgConnection>;
 
 impl Storage {
     /// Create a new storage interface from configuration
-    pub async fn new(database: &Database) -> error_stack::Result<Self, error::StorageError> {
+    pub async fn new(
+        database: &Database,
+        fees: &Fees,
+        transfer_limits: &TransferLimits,
+        risk: &Risk,
+    ) -> error_stack::Result<Self, error::StorageError> {
         let database_url = format!(
             "postgres://{}:{}@{}:{}/{}",
             database.username, database.password, database.host, database.port, database.dbname,
@@ -50,6 +62,9 @@ impl Storage {
             .change_context(error::StorageError::DBPoolError)?;
         Ok(Self {
             pg_pool: Arc::new(pool),
+            fees: Arc::new(fees.clone()),
+            transfer_limits: *transfer_limits,
+            risk: Arc::new(RiskEngine::from_config(risk)),
         })
     }
 
@@ -82,6 +97,7 @@ impl Cacheable<types::Transaction> for Storage {
 }
 
 /// User Interface
+#[allow(async_fn_in_trait)]
 pub trait UserInterface {
     /// Error type
     type Error;
@@ -109,7 +125,551 @@ pub trait UserInterface {
     ) -> Result<types::User, ContainerError<Self::Error>>;
 }
 
+/// Idempotency Interface
+#[allow(async_fn_in_trait)]
+pub trait IdempotencyInterface {
+    /// Error type
+    type Error;
+
+    /// Claim an idempotency key for a user, returning the existing record if it is still live.
+    /// A key left without a response after its lease lapsed is claimed again
+    async fn claim_idempotency_key(
+        &self,
+        key: types::NewIdempotencyKey,
+    ) -> Result<types::IdempotencyClaim, ContainerError<Self::Error>>;
+    /// Store the response of the request that claimed an idempotency key, unless another
+    /// request took over the key after the lease `locked_until` lapsed
+    async fn store_idempotent_response(
+        &self,
+        user_id: &str,
+        idempotency_key: &str,
+        locked_until: time::PrimitiveDateTime,
+        status_code: i32,
+        body: serde_json::Value,
+    ) -> Result<(), ContainerError<Self::Error>>;
+    /// Release an idempotency key whose request did not complete, unless another request took
+    /// over the key after the lease `locked_until` lapsed
+    async fn release_idempotency_key(
+        &self,
+        user_id: &str,
+        idempotency_key: &str,
+        locked_until: time::PrimitiveDateTime,
+    ) -> Result<(), ContainerError<Self::Error>>;
+}
+
+/// Ledger Interface
+#[allow(async_fn_in_trait)]
+pub trait LedgerInterface {
+    /// Error type
+    type Error;
+
+    /// Get the ledger postings of an account in a currency, up to and including `as_of` when
+    /// provided
+    async fn get_ledger_entries(
+        &self,
+        account_id: &str,
+        currency: Currency,
+        as_of: Option<time::PrimitiveDateTime>,
+    ) -> Result<Vec<types::LedgerEntry>, ContainerError<Self::Error>>;
+    /// Get the balance of an account in a currency in minor units derived from its ledger
+    /// postings
+    async fn get_ledger_balance(
+        &self,
+        account_id: &str,
+        currency: Currency,
+        as_of: Option<time::PrimitiveDateTime>,
+    ) -> Result<i64, ContainerError<Self::Error>>;
+    /// Get when the latest opening balance of an account in a currency was booked, if it held
+    /// money before the ledger was introduced. Its balance cannot be rebuilt for earlier instants
+    async fn get_ledger_opening(
+        &self,
+        account_id: &str,
+        currency: Currency,
+    ) -> Result<Option<time::PrimitiveDateTime>, ContainerError<Self::Error>>;
+}
+
+/// Scheduled Transfer Interface
+#[allow(async_fn_in_trait)]
+pub trait ScheduledTransferInterface {
+    /// Error type
+    type Error;
+
+    /// Create a scheduled transfer
+    async fn create_scheduled_transfer(
+        &self,
+        scheduled_transfer: types::NewScheduledTransfer,
+    ) -> Result<types::ScheduledTransfer, ContainerError<Self::Error>>;
+    /// Get scheduled transfer by id
+    async fn get_scheduled_transfer(
+        &self,
+        scheduled_transfer_id: &str,
+    ) -> Result<types::ScheduledTransfer, ContainerError<Self::Error>>;
+    /// List the scheduled transfers of a sender, newest first, with the total count
+    async fn list_scheduled_transfers(
+        &self,
+        sender_id: &str,
+        status: Option<enums::ScheduledTransferStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<types::ScheduledTransfer>, i64), ContainerError<Self::Error>>;
+    /// Cancel a scheduled transfer that has not been executed yet
+    async fn cancel_scheduled_transfer(
+        &self,
+        scheduled_transfer_id: &str,
+    ) -> Result<types::ScheduledTransfer, ContainerError<Self::Error>>;
+    /// Execute the next due scheduled transfer not claimed by another worker, recording its
+    /// outcome. An attempt that hits a database error is retried after `retry_delay`, doubled
+    /// after every further attempt, until `max_attempts` fail. Returns `None` when no transfer
+    /// is due.
+    async fn execute_next_scheduled_transfer(
+        &self,
+        retry_delay: time::Duration,
+        max_attempts: u32,
+    ) -> Result<Option<types::ScheduledTransfer>, ContainerError<Self::Error>>;
+}
+
+/// Standing Instruction Interface
+#[allow(async_fn_in_trait)]
+pub trait StandingInstructionInterface {
+    /// Error type
+    type Error;
+
+    /// Create a standing instruction
+    async fn create_standing_instruction(
+        &self,
+        standing_instruction: types::NewStandingInstruction,
+    ) -> Result<types::StandingInstruction, ContainerError<Self::Error>>;
+    /// Get standing instruction by id
+    async fn get_standing_instruction(
+        &self,
+        standing_instruction_id: &str,
+    ) -> Result<types::StandingInstruction, ContainerError<Self::Error>>;
+    /// List the standing instructions of a sender, newest first, with the total count
+    async fn list_standing_instructions(
+        &self,
+        sender_id: &str,
+        status: Option<enums::StandingInstructionStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<types::StandingInstruction>, i64), ContainerError<Self::Error>>;
+    /// Pause an active standing instruction
+    async fn pause_standing_instruction(
+        &self,
+        standing_instruction_id: &str,
+    ) -> Result<types::StandingInstruction, ContainerError<Self::Error>>;
+    /// Resume a paused standing instruction, skipping the runs missed while it was paused
+    async fn resume_standing_instruction(
+        &self,
+        standing_instruction_id: &str,
+    ) -> Result<types::StandingInstruction, ContainerError<Self::Error>>;
+    /// Execute the next due run of a standing instruction not claimed by another worker,
+    /// handling runs overdue by more than `grace` according to `policy`. A run that hits a
+    /// database error is retried after `retry_delay`, doubled after every further attempt,
+    /// until `max_attempts` fail. Returns `None` when no run is due.
+    async fn execute_next_standing_instruction(
+        &self,
+        policy: CatchUpPolicy,
+        grace: time::Duration,
+        retry_delay: time::Duration,
+        max_attempts: u32,
+    ) -> Result<Option<types::StandingInstruction>, ContainerError<Self::Error>>;
+}
+
+/// Payment Request Interface
+#[allow(async_fn_in_trait)]
+pub trait PaymentRequestInterface {
+    /// Error type
+    type Error;
+
+    /// Create a payment request
+    async fn create_payment_request(
+        &self,
+        payment_request: types::NewPaymentRequest,
+    ) -> Result<types::PaymentRequest, ContainerError<Self::Error>>;
+    /// Get payment request by id
+    async fn get_payment_request(
+        &self,
+        payment_request_id: &str,
+    ) -> Result<types::PaymentRequest, ContainerError<Self::Error>>;
+    /// List the payment requests a user made or was asked to pay, newest first, with the total
+    /// count. Both directions are listed when `direction` is absent
+    async fn list_payment_requests(
+        &self,
+        user_id: &str,
+        direction: Option<types::PaymentRequestDirection>,
+        status: Option<enums::PaymentRequestStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<types::PaymentRequest>, i64), ContainerError<Self::Error>>;
+    /// Pay a pending payment request with a transfer from the payer to the requester
+    async fn pay_payment_request(
+        &self,
+        payment_request_id: &str,
+    ) -> Result<types::PaymentRequest, ContainerError<Self::Error>>;
+    /// Move a pending payment request to a status that does not move money
+    async fn update_payment_request_status(
+        &self,
+        payment_request_id: &str,
+        status: enums::PaymentRequestStatus,
+    ) -> Result<types::PaymentRequest, ContainerError<Self::Error>>;
+    /// Expire pending payment requests that have lapsed, returning how many were expired
+    async fn expire_payment_requests(&self) -> Result<usize, ContainerError<Self::Error>>;
+}
+
+/// Transfer Batch Interface
+#[allow(async_fn_in_trait)]
+pub trait TransferBatchInterface {
+    /// Error type
+    type Error;
+
+    /// Create a batch of transfers from one sender that all complete or all roll back,
+    /// returning the batch and its transactions
+    async fn create_transfer_batch(
+        &self,
+        batch: types::NewTransferBatch,
+        legs: Vec<types::NewTransaction>,
+    ) -> Result<(types::TransferBatch, Vec<types::Transaction>), ContainerError<Self::Error>>;
+    /// Get a transfer batch by id with its transactions
+    async fn get_transfer_batch(
+        &self,
+        batch_id: &str,
+    ) -> Result<(types::TransferBatch, Vec<types::Transaction>), ContainerError<Self::Error>>;
+}
+
+/// Transfer Limit Interface
+#[allow(async_fn_in_trait)]
+pub trait TransferLimitInterface {
+    /// Error type
+    type Error;
+
+    /// Get the limits of a user, i.e. the configured defaults with the user's overrides applied
+    async fn get_transfer_limits(
+        &self,
+        user_id: &str,
+    ) -> Result<TransferLimits, ContainerError<Self::Error>>;
+    /// Replace the limit overrides of a user, returning the resulting limits
+    async fn set_user_limits(
+        &self,
+        limits: types::NewUserLimits,
+    ) -> Result<TransferLimits, ContainerError<Self::Error>>;
+    /// Get what a user sent in a currency within the limit windows that contain `now`
+    async fn get_limit_usage(
+        &self,
+        user_id: &str,
+        currency: Currency,
+        now: time::PrimitiveDateTime,
+    ) -> Result<LimitUsage, ContainerError<Self::Error>>;
+}
+
+/// Deposit Interface
+#[allow(async_fn_in_trait)]
+pub trait DepositInterface {
+    /// Error type
+    type Error;
+
+    /// Create a PENDING deposit
+    async fn create_deposit(
+        &self,
+        deposit: types::NewDeposit,
+    ) -> Result<types::Deposit, ContainerError<Self::Error>>;
+    /// Get deposit by id
+    async fn get_deposit(
+        &self,
+        deposit_id: &str,
+    ) -> Result<types::Deposit, ContainerError<Self::Error>>;
+    /// Complete a deposit confirmed by its funding source, crediting the user's balance
+    async fn complete_deposit(
+        &self,
+        deposit_id: &str,
+        source_reference: String,
+    ) -> Result<types::Deposit, ContainerError<Self::Error>>;
+    /// Mark a deposit declined by its funding source as FAILED
+    async fn fail_deposit(
+        &self,
+        deposit_id: &str,
+        reason: String,
+    ) -> Result<types::Deposit, ContainerError<Self::Error>>;
+}
+
+/// Bank Account Interface
+#[allow(async_fn_in_trait)]
+pub trait BankAccountInterface {
+    /// Error type
+    type Error;
+
+    /// Link a bank account to a user
+    async fn create_bank_account(
+        &self,
+        bank_account: types::NewBankAccount,
+    ) -> Result<types::BankAccount, ContainerError<Self::Error>>;
+    /// Get bank account by id
+    async fn get_bank_account(
+        &self,
+        bank_account_id: &str,
+    ) -> Result<types::BankAccount, ContainerError<Self::Error>>;
+    /// List the bank accounts of a user, oldest first
+    async fn list_bank_accounts(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<types::BankAccount>, ContainerError<Self::Error>>;
+}
+
+/// Withdrawal Interface
+#[allow(async_fn_in_trait)]
+pub trait WithdrawalInterface {
+    /// Error type
+    type Error;
+
+    /// Create a PENDING withdrawal, debiting the user's balance
+    async fn create_withdrawal(
+        &self,
+        withdrawal: types::NewWithdrawal,
+    ) -> Result<types::Withdrawal, ContainerError<Self::Error>>;
+    /// Get withdrawal by id
+    async fn get_withdrawal(
+        &self,
+        withdrawal_id: &str,
+    ) -> Result<types::Withdrawal, ContainerError<Self::Error>>;
+    /// List the withdrawals of a user with pagination, newest first
+    async fn list_withdrawals(
+        &self,
+        user_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<types::Withdrawal>, i64), ContainerError<Self::Error>>;
+    /// Complete a withdrawal paid out by its connector
+    async fn complete_withdrawal(
+        &self,
+        withdrawal_id: &str,
+        connector_reference: String,
+    ) -> Result<types::Withdrawal, ContainerError<Self::Error>>;
+    /// Mark a withdrawal whose payout failed as FAILED, returning the money to the user
+    async fn fail_withdrawal(
+        &self,
+        withdrawal_id: &str,
+        reason: String,
+    ) -> Result<types::Withdrawal, ContainerError<Self::Error>>;
+    /// Claim the oldest withdrawal still PENDING that was created before a time and no other
+    /// worker holds, holding it until `claimed_until`
+    async fn claim_stale_withdrawal(
+        &self,
+        created_before: time::PrimitiveDateTime,
+        claimed_until: time::PrimitiveDateTime,
+    ) -> Result<Option<types::Withdrawal>, ContainerError<Self::Error>>;
+}
+
+/// Dispute Interface
+#[allow(async_fn_in_trait)]
+pub trait DisputeInterface {
+    /// Error type
+    type Error;
+
+    /// Raise an OPEN dispute against a completed transfer, freezing what its recipient received
+    /// and has not refunded yet
+    async fn create_dispute(
+        &self,
+        dispute: types::NewDispute,
+    ) -> Result<types::Dispute, ContainerError<Self::Error>>;
+    /// Get dispute by id
+    async fn get_dispute(
+        &self,
+        dispute_id: &str,
+    ) -> Result<types::Dispute, ContainerError<Self::Error>>;
+    /// List the disputes against transfers a user sent or received, newest first, with the
+    /// total count
+    async fn list_disputes(
+        &self,
+        user_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<types::Dispute>, i64), ContainerError<Self::Error>>;
+    /// List the disputes waiting for an admin, oldest first
+    async fn list_open_disputes(&self) -> Result<Vec<types::Dispute>, ContainerError<Self::Error>>;
+    /// Move an OPEN dispute to UNDER_REVIEW
+    async fn review_dispute(
+        &self,
+        dispute_id: &str,
+    ) -> Result<types::Dispute, ContainerError<Self::Error>>;
+    /// Resolve a dispute as WON, reversing the transfer, or LOST, releasing the frozen funds
+    async fn resolve_dispute(
+        &self,
+        dispute_id: &str,
+        outcome: enums::DisputeStatus,
+        resolution_note: Option<String>,
+    ) -> Result<types::Dispute, ContainerError<Self::Error>>;
+}
+
+/// Wallet Interface
+#[allow(async_fn_in_trait)]
+pub trait WalletInterface {
+    /// Error type
+    type Error;
+
+    /// Open an empty wallet for a user in a new currency
+    async fn create_wallet(
+        &self,
+        wallet: types::NewWallet,
+    ) -> Result<types::Wallet, ContainerError<Self::Error>>;
+    /// Get the wallet of a user in a currency
+    async fn get_wallet(
+        &self,
+        user_id: &str,
+        currency: Currency,
+    ) -> Result<types::Wallet, ContainerError<Self::Error>>;
+    /// List the wallets of a user, oldest first
+    async fn list_wallets(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<types::Wallet>, ContainerError<Self::Error>>;
+    /// Store the closing balance of every wallet for each day up to and including `through`
+    /// that has no snapshot yet, returning how many were stored
+    async fn snapshot_balances(
+        &self,
+        through: time::Date,
+    ) -> Result<usize, ContainerError<Self::Error>>;
+    /// Get the closing balance of the wallet of a user in a currency at the end of each day
+    async fn get_closing_balances(
+        &self,
+        user_id: &str,
+        currency: Currency,
+        days: &[time::Date],
+    ) -> Result<BTreeMap<time::Date, i64>, ContainerError<Self::Error>>;
+}
+
+/// FX Interface
+#[allow(async_fn_in_trait)]
+pub trait FxInterface {
+    /// Error type
+    type Error;
+
+    /// Set the rate of a currency pair, replacing its previous rate
+    async fn set_fx_rate(
+        &self,
+        rate: types::NewFxRate,
+    ) -> Result<types::FxRate, ContainerError<Self::Error>>;
+    /// Get the current rate of a currency pair
+    async fn get_fx_rate(
+        &self,
+        base: Currency,
+        quote: Currency,
+    ) -> Result<types::FxRate, ContainerError<Self::Error>>;
+    /// List the rates of all currency pairs
+    async fn list_fx_rates(&self) -> Result<Vec<types::FxRate>, ContainerError<Self::Error>>;
+    /// Create an OPEN quote
+    async fn create_fx_quote(
+        &self,
+        quote: types::NewFxQuote,
+    ) -> Result<types::FxQuote, ContainerError<Self::Error>>;
+    /// Get quote by id
+    async fn get_fx_quote(
+        &self,
+        quote_id: &str,
+    ) -> Result<types::FxQuote, ContainerError<Self::Error>>;
+    /// Execute an OPEN quote that has not expired, converting between the user's wallets
+    async fn execute_fx_quote(
+        &self,
+        quote_id: &str,
+    ) -> Result<types::FxQuote, ContainerError<Self::Error>>;
+}
+
+/// Reconciliation Interface
+#[allow(async_fn_in_trait)]
+pub trait ReconciliationInterface {
+    /// Error type
+    type Error;
+
+    /// Check every wallet against its history and the ledger, recording a run with the
+    /// discrepancies found
+    async fn reconcile(
+        &self,
+    ) -> Result<
+        (types::ReconciliationRun, Vec<types::ReconciliationFinding>),
+        ContainerError<Self::Error>,
+    >;
+    /// Reconcile like `reconcile` unless another instance is reconciling or a run started less
+    /// than `min_interval` ago, returning None when the run was skipped
+    async fn reconcile_if_due(
+        &self,
+        min_interval: time::Duration,
+    ) -> Result<
+        Option<(types::ReconciliationRun, Vec<types::ReconciliationFinding>)>,
+        ContainerError<Self::Error>,
+    >;
+    /// List the most recent reconciliation runs, newest first
+    async fn list_reconciliation_runs(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<types::ReconciliationRun>, ContainerError<Self::Error>>;
+    /// Get reconciliation run by id
+    async fn get_reconciliation_run(
+        &self,
+        run_id: &str,
+    ) -> Result<types::ReconciliationRun, ContainerError<Self::Error>>;
+    /// List the findings of a reconciliation run
+    async fn list_reconciliation_findings(
+        &self,
+        run_id: &str,
+    ) -> Result<Vec<types::ReconciliationFinding>, ContainerError<Self::Error>>;
+}
+
+/// Risk Interface
+#[allow(async_fn_in_trait)]
+pub trait RiskInterface {
+    /// Error type
+    type Error;
+
+    /// Get what the risk rules need to know of the accounts of a transfer, looking `lookback`
+    /// into the past
+    async fn get_risk_history(
+        &self,
+        sender_id: &str,
+        recipient_id: &str,
+        lookback: std::time::Duration,
+    ) -> Result<RiskHistory, ContainerError<Self::Error>>;
+    /// Run the risk rules against a transfer and make it, hold it for review or block it as they
+    /// decide
+    async fn submit_transfer(
+        &self,
+        transaction: types::NewTransaction,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Execute an FX quote and submit a transfer of its converted amount like `submit_transfer`,
+    /// in one database transaction so a rejected transfer converts nothing
+    async fn submit_fx_transfer(
+        &self,
+        quote_id: &str,
+        transaction: types::NewTransaction,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Hold a transfer flagged by the risk rules until an admin approves or rejects it
+    async fn hold_for_review(
+        &self,
+        transaction: types::NewTransaction,
+        hits: Vec<types::NewRiskRuleHit>,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Record a transfer blocked by the risk rules as FAILED
+    async fn block_transaction(
+        &self,
+        transaction: types::NewTransaction,
+        hits: Vec<types::NewRiskRuleHit>,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// List the transfers waiting for review, with the rules that flagged them
+    async fn list_risk_reviews(
+        &self,
+    ) -> Result<Vec<(types::Transaction, Vec<types::RiskRuleHit>)>, ContainerError<Self::Error>>;
+    /// Approve a transfer held for review, moving the money, or an authorization held for
+    /// review, leaving it authorized
+    async fn approve_risk_review(
+        &self,
+        transaction_id: &str,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Reject a transfer held for review, releasing its hold
+    async fn reject_risk_review(
+        &self,
+        transaction_id: &str,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+}
+
 /// Transaction Interface
+#[allow(async_fn_in_trait)]
 pub trait TransactionInterface {
     /// Error type
     type Error;
@@ -124,4 +684,69 @@ pub trait TransactionInterface {
         &self,
         transaction: types::NewTransaction,
     ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Authorize a transaction after the risk rules, holding the amount on the sender's account
+    /// until it is captured
+    async fn authorize_transaction(
+        &self,
+        transaction: types::NewTransaction,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Capture an authorized transaction, moving the given amount or the whole hold
+    async fn capture_transaction(
+        &self,
+        transaction_id: &str,
+        amount: Option<Money>,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Void an authorized transaction, releasing its hold
+    async fn void_transaction(
+        &self,
+        transaction_id: &str,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Expire authorizations whose hold has lapsed, returning how many were expired
+    async fn expire_authorizations(&self) -> Result<usize, ContainerError<Self::Error>>;
+    /// Get the amount held on an account in a currency by active authorizations, transfers
+    /// under review and open disputes
+    async fn get_held_amount(
+        &self,
+        account_id: &str,
+        currency: Currency,
+    ) -> Result<i64, ContainerError<Self::Error>>;
+    /// Refund a completed transaction, fully or partially, returning the refund transaction
+    async fn refund_transaction(
+        &self,
+        refund: types::NewRefund,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// Move a transaction to a new status, rejecting illegal transitions
+    async fn update_transaction_status(
+        &self,
+        transaction_id: &str,
+        status: enums::TransactionStatus,
+        failure_reason: Option<String>,
+    ) -> Result<types::Transaction, ContainerError<Self::Error>>;
+    /// List a page of up to `limit` transactions of an account matching a filter, in its order,
+    /// starting from a cursor, optionally with how many match
+    async fn list_transactions(
+        &self,
+        filter: &types::TransactionFilter,
+        cursor: Option<&types::TransactionCursor>,
+        limit: i64,
+        include_total: bool,
+    ) -> Result<types::TransactionPage, ContainerError<Self::Error>>;
+    /// Get the balance of an account in a currency when a period opened, the transactions that
+    /// moved it during the period and the postings of the journals that adjusted it outside of
+    /// transactions during the period, oldest first, read from one consistent snapshot
+    async fn get_statement_activity(
+        &self,
+        account_id: &str,
+        currency: Currency,
+        period: &StatementPeriod,
+    ) -> Result<(i64, Vec<types::Transaction>, Vec<types::LedgerEntry>), ContainerError<Self::Error>>;
+    /// Send the transactions of an account created within a range to `batches`, oldest first,
+    /// as they are fetched from a database cursor, returning how many were sent
+    async fn export_transactions(
+        &self,
+        account_id: &str,
+        from: Option<time::PrimitiveDateTime>,
+        to: Option<time::PrimitiveDateTime>,
+        batches: tokio::sync::mpsc::Sender<Vec<types::Transaction>>,
+    ) -> Result<usize, ContainerError<Self::Error>>;
 }
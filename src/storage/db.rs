@@ -1,22 +1,1074 @@
-use diesel::{ExpressionMethods, QueryDsl};
-use diesel_async::RunQueryDsl;
-use error_stack::ResultExt;
+use std::collections::{BTreeMap, HashMap};
+
+use diesel::{
+    BoolExpressionMethods, BoxableExpression, ExpressionMethods, OptionalExtension,
+    PgJsonbExpressionMethods, PgTextExpressionMethods, QueryDsl, TextExpressionMethods, pg::Pg,
+    result::DatabaseErrorKind, sql_types::Bool,
+};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
 use crate::{
+    balance_history,
+    configs::CatchUpPolicy,
+    consts,
     error::TransactionDbError,
     error::{
-        UserDbError,
+        MoneyError, UserDbError,
         container::{ContainerError, ResultContainerExt},
     },
-    storage::schema::transactions::dsl::transactions,
+    reconciliation::{BalanceKey, BalanceSnapshot},
+    risk::{RecentTransfer, RiskAssessment, RiskHistory, RiskInput},
+    statement::StatementPeriod,
     storage::{
-        Storage, TransactionInterface, UserInterface,
-        types::{NewTransaction, Transaction, User},
+        BankAccountInterface, DepositInterface, DisputeInterface, FxInterface,
+        IdempotencyInterface, LedgerInterface, PaymentRequestInterface, ReconciliationInterface,
+        RiskInterface, ScheduledTransferInterface, StandingInstructionInterface, Storage,
+        TransactionInterface, TransferBatchInterface, TransferLimitInterface, UserInterface,
+        WalletInterface, WithdrawalInterface,
+        enums::{
+            DepositStatus, DisputeStatus, FxQuoteStatus, PaymentRequestStatus, RiskDecision,
+            ScheduledTransferStatus, StandingInstructionStatus, TransactionStatus, TransactionType,
+            WithdrawalStatus,
+        },
+        types::{
+            Deposit, Dispute, FxQuote, IdempotencyClaim, LedgerEntry, NewBalanceSnapshot,
+            NewLedgerEntry, NewReconciliationFinding, NewReconciliationRun, NewRiskRuleHit,
+            NewTransaction, NewWallet, PaymentRequest, PaymentRequestDirection,
+            ReconciliationFinding, ReconciliationRun, RiskRuleHit, ScheduledTransfer,
+            StandingInstruction, Transaction, TransactionCursor, TransactionDirection,
+            TransactionFilter, TransactionPage, TransferBatch, UserLimits, Withdrawal,
+        },
     },
+    types::{
+        Currency, Money, TransferLimits,
+        limits::{LimitUsage, LimitWindows},
+    },
+    utils,
 };
-use diesel::dsl::exists;
-use diesel::{Connection, result::Error, select};
-use time::PrimitiveDateTime;
+
+/// Writes the postings of a single journal, rejecting journals whose debits and credits do not
+/// balance. Must be called inside the database transaction that moves the money.
+async fn post_journal(
+    conn: &mut AsyncPgConnection,
+    entries: Vec<NewLedgerEntry>,
+) -> Result<(), ContainerError<TransactionDbError>> {
+    use crate::storage::schema::ledger_entries::dsl::*;
+
+    let mut totals = std::collections::HashMap::<(&str, &str), i64>::new();
+    for entry in &entries {
+        let total = totals
+            .entry((entry.journal_id.as_str(), entry.currency.as_str()))
+            .or_default();
+        *total = total
+            .checked_add(entry.signed_minor_units())
+            .ok_or(TransactionDbError::AmountOverflow)?;
+    }
+
+    if entries.is_empty() || totals.values().any(|total| *total != 0) {
+        return Err(TransactionDbError::UnbalancedJournal.into());
+    }
+
+    diesel::insert_into(ledger_entries)
+        .values(entries)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Locks the wallets of user accounts in one currency in a stable order to avoid deadlocks
+/// between concurrent transfers in opposite directions, returning their cached balances.
+async fn lock_wallets(
+    conn: &mut AsyncPgConnection,
+    account_ids: &[&str],
+    _currency: Currency,
+) -> Result<HashMap<String, Money>, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::wallets::dsl::*;
+
+    let balances: Vec<(String, i64)> = wallets
+        .filter(user_id.eq_any(account_ids))
+        .filter(currency.eq(_currency.code()))
+        .select((user_id, balance_minor_units))
+        .order(user_id)
+        .for_update()
+        .load(conn)
+        .await?;
+
+    if balances.len() != account_ids.len() {
+        use crate::storage::schema::users::dsl::{user_id, users};
+
+        let existing: i64 = users
+            .filter(user_id.eq_any(account_ids))
+            .count()
+            .get_result(conn)
+            .await?;
+        if existing != account_ids.len() as i64 {
+            return Err(TransactionDbError::AccountNotFound.into());
+        }
+
+        return Err(TransactionDbError::CurrencyNotHeld(_currency).into());
+    }
+
+    balances
+        .into_iter()
+        .map(|(id_, balance)| Ok((id_, Money::new(balance, _currency)?)))
+        .collect::<Result<_, MoneyError>>()
+        .map_err(|err| TransactionDbError::from(err).into())
+}
+
+/// Sets the cached balance of a wallet locked by [`lock_wallets`].
+async fn set_wallet_balance(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    balance: Money,
+) -> Result<(), ContainerError<TransactionDbError>> {
+    use crate::storage::schema::wallets::dsl::*;
+
+    diesel::update(wallets)
+        .filter(user_id.eq(account_id))
+        .filter(currency.eq(balance.currency().code()))
+        .set((
+            balance_minor_units.eq(balance.minor_units()),
+            updated_at.eq(utils::datetime::now()),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the part of a locked account's balance that is not held by active authorizations,
+/// transfers under review or open disputes. Authorizations past their expiry no longer hold
+/// funds, even before they are marked EXPIRED.
+async fn available_balance(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    balance: Money,
+) -> Result<Money, ContainerError<TransactionDbError>> {
+    let held = Money::new(
+        held_minor_units(conn, account_id, balance.currency()).await?,
+        balance.currency(),
+    )
+    .map_err(TransactionDbError::from)?;
+
+    Ok(balance
+        .checked_sub(held)
+        .map_err(TransactionDbError::from)?)
+}
+
+/// Sums the amounts held on an account in one currency by authorizations that have not lapsed
+/// yet and by transfers under review it sent, and the amounts frozen by open disputes against
+/// transfers it received.
+async fn held_minor_units(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    _currency: Currency,
+) -> Result<i64, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+    use diesel::dsl::sql;
+    use diesel::sql_types::BigInt;
+
+    let frozen: i64 = {
+        use crate::storage::schema::disputes::dsl::*;
+
+        disputes
+            .filter(recipient_id.eq(account_id))
+            .filter(currency.eq(_currency.code()))
+            .filter(status.eq_any(OPEN_DISPUTE_STATUSES))
+            .select(sql::<BigInt>(
+                "COALESCE(SUM(amount_minor_units), 0)::BIGINT",
+            ))
+            .get_result(conn)
+            .await?
+    };
+
+    let held: i64 = transactions
+        .filter(sender_id.eq(account_id))
+        .filter(currency.eq(_currency.code()))
+        .filter(
+            status
+                .eq(TransactionStatus::Authorized)
+                .and(expires_at.gt(utils::datetime::now()))
+                .or(status.eq(TransactionStatus::UnderReview)),
+        )
+        .select(sql::<BigInt>(
+            "COALESCE(SUM(amount_minor_units), 0)::BIGINT",
+        ))
+        .get_result(conn)
+        .await?;
+
+    Ok(held
+        .checked_add(frozen)
+        .ok_or(TransactionDbError::AmountOverflow)?)
+}
+
+/// Statuses of disputes that are not resolved yet, whose amount is frozen.
+const OPEN_DISPUTE_STATUSES: [DisputeStatus; 2] = [DisputeStatus::Open, DisputeStatus::UnderReview];
+
+/// Sums the completed refunds of a transaction.
+async fn refunded_minor_units(
+    conn: &mut AsyncPgConnection,
+    _transaction_id: &str,
+) -> Result<i64, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+    use diesel::dsl::sql;
+    use diesel::sql_types::BigInt;
+
+    Ok(transactions
+        .filter(parent_transaction_id.eq(_transaction_id))
+        .filter(transaction_type.eq(TransactionType::Refund))
+        .filter(status.eq(TransactionStatus::Completed))
+        .select(sql::<BigInt>(
+            "COALESCE(SUM(amount_minor_units), 0)::BIGINT",
+        ))
+        .get_result(conn)
+        .await?)
+}
+
+/// Statuses of transfers that count against the limits of their sender: the money moved or is
+/// held, even if it was returned since.
+const LIMITED_STATUSES: [TransactionStatus; 6] = [
+    TransactionStatus::Authorized,
+    TransactionStatus::UnderReview,
+    TransactionStatus::Completed,
+    TransactionStatus::Reversed,
+    TransactionStatus::Refunded,
+    TransactionStatus::PartiallyRefunded,
+];
+
+/// Statuses of withdrawals that count against the limits of their user: the payout is in
+/// flight or went through.
+const LIMITED_WITHDRAWAL_STATUSES: [TransactionStatus; 2] =
+    [TransactionStatus::Pending, TransactionStatus::Completed];
+
+/// Filters the transactions that count against the limits of their sender: transfers in
+/// `LIMITED_STATUSES` and withdrawals in `LIMITED_WITHDRAWAL_STATUSES`.
+fn limited_transactions()
+-> Box<dyn BoxableExpression<crate::storage::schema::transactions::table, Pg, SqlType = Bool>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    Box::new(
+        transaction_type
+            .eq(TransactionType::Transfer)
+            .and(status.eq_any(LIMITED_STATUSES))
+            .or(transaction_type
+                .eq(TransactionType::Withdrawal)
+                .and(status.eq_any(LIMITED_WITHDRAWAL_STATUSES))),
+    )
+}
+
+/// Sums the amounts of the transfers and withdrawals an account sent in one currency since
+/// `since`.
+async fn outgoing_minor_units_since(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    _currency: Currency,
+    since: time::PrimitiveDateTime,
+) -> Result<i64, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+    use diesel::dsl::sql;
+    use diesel::sql_types::BigInt;
+
+    Ok(transactions
+        .filter(sender_id.eq(account_id))
+        .filter(currency.eq(_currency.code()))
+        .filter(limited_transactions())
+        .filter(created_at.ge(since))
+        .select(sql::<BigInt>(
+            "COALESCE(SUM(amount_minor_units), 0)::BIGINT",
+        ))
+        .get_result(conn)
+        .await?)
+}
+
+/// Counts the transfers and withdrawals an account sent since `since`.
+async fn transfers_since(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    since: time::PrimitiveDateTime,
+) -> Result<i64, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    Ok(transactions
+        .filter(sender_id.eq(account_id))
+        .filter(limited_transactions())
+        .filter(created_at.ge(since))
+        .count()
+        .get_result(conn)
+        .await?)
+}
+
+/// Returns what an account sent, through transfers and withdrawals, within the limit windows
+/// that contain `now`. Amounts only count what was sent in `currency`, while the transfer
+/// count spans every currency.
+async fn limit_usage(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    currency: Currency,
+    now: time::PrimitiveDateTime,
+) -> Result<LimitUsage, ContainerError<TransactionDbError>> {
+    let windows = LimitWindows::at(now);
+
+    Ok(LimitUsage {
+        daily_outgoing_minor_units: outgoing_minor_units_since(
+            conn,
+            account_id,
+            currency,
+            windows.day_start,
+        )
+        .await?,
+        monthly_outgoing_minor_units: outgoing_minor_units_since(
+            conn,
+            account_id,
+            currency,
+            windows.month_start,
+        )
+        .await?,
+        transfers_last_hour: transfers_since(conn, account_id, windows.hour_start).await?,
+    })
+}
+
+/// Returns the limits of an account: the defaults with its overrides, if it has any, applied.
+async fn transfer_limits(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    defaults: TransferLimits,
+) -> Result<TransferLimits, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::user_limits::dsl::*;
+
+    let overrides: Option<UserLimits> = user_limits
+        .filter(user_id.eq(account_id))
+        .first(conn)
+        .await
+        .optional()?;
+
+    Ok(overrides.map_or(defaults, |overrides| overrides.apply_to(defaults)))
+}
+
+/// Checks that an account can send transfers of `amounts` in `currency` without exceeding its
+/// limits, which apply to each currency in its own minor units. The account must already be
+/// locked, so that concurrent transfers of the same sender are counted one after the other.
+async fn check_transfer_limits(
+    conn: &mut AsyncPgConnection,
+    account_id: &str,
+    defaults: TransferLimits,
+    currency: Currency,
+    amounts: &[i64],
+) -> Result<(), ContainerError<TransactionDbError>> {
+    let limits = transfer_limits(conn, account_id, defaults).await?;
+    let usage = limit_usage(conn, account_id, currency, utils::datetime::now()).await?;
+
+    limits
+        .check(&usage, amounts)
+        .map_err(|limit| TransactionDbError::LimitExceeded(limit).into())
+}
+
+/// Moves money from one user account to another inside an open database transaction. Both
+/// accounts are locked, their cached balances updated and the transfer written to the ledger.
+/// The sender is debited `amount`, the recipient credited `amount` less `fee` and the fee
+/// credited to the platform revenue account. Funds held by active authorizations of the sender
+/// cannot be spent.
+async fn move_funds(
+    conn: &mut AsyncPgConnection,
+    journal_id: &str,
+    sender_id: &str,
+    recipient_id: &str,
+    amount: Money,
+    fee: Money,
+) -> Result<(), ContainerError<TransactionDbError>> {
+    let net_amount = amount.checked_sub(fee).map_err(TransactionDbError::from)?;
+    let balances = lock_wallets(conn, &[sender_id, recipient_id], amount.currency()).await?;
+
+    // Check if sender has enough balance
+    available_balance(conn, sender_id, balances[sender_id])
+        .await?
+        .checked_sub(amount)
+        .map_err(TransactionDbError::from)?;
+
+    let sender_balance = balances[sender_id]
+        .checked_sub(amount)
+        .map_err(TransactionDbError::from)?;
+    let recipient_balance = balances[recipient_id]
+        .checked_add(net_amount)
+        .map_err(TransactionDbError::from)?;
+
+    // Debit sender
+    set_wallet_balance(conn, sender_id, sender_balance).await?;
+
+    // Credit receiver
+    set_wallet_balance(conn, recipient_id, recipient_balance).await?;
+
+    // Record the transfer in the ledger
+    let mut entries = vec![
+        NewLedgerEntry::debit(journal_id, sender_id, amount),
+        NewLedgerEntry::credit(journal_id, recipient_id, net_amount),
+    ];
+    if !fee.is_zero() {
+        entries.push(NewLedgerEntry::credit(
+            journal_id,
+            consts::PLATFORM_REVENUE_ACCOUNT,
+            fee,
+        ));
+    }
+
+    post_journal(conn, entries).await
+}
+
+/// Inserts a transfer and moves its money inside an open database transaction, checking the
+/// sender's limits once their account is locked.
+async fn complete_transfer(
+    conn: &mut AsyncPgConnection,
+    transaction: NewTransaction,
+    amount: Money,
+    fee: Money,
+    limits: TransferLimits,
+) -> Result<Transaction, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    // Create transaction
+    let inserted_transaction: Transaction = diesel::insert_into(transactions)
+        .values((transaction, fee_minor_units.eq(fee.minor_units())))
+        .get_result(conn)
+        .await?;
+
+    move_funds(
+        conn,
+        &inserted_transaction.transaction_id,
+        &inserted_transaction.sender_id,
+        &inserted_transaction.recipient_id,
+        amount,
+        fee,
+    )
+    .await?;
+
+    // The sender is locked by now and the transaction is still PENDING, so it is not counted
+    // in the usage yet.
+    check_transfer_limits(
+        conn,
+        &inserted_transaction.sender_id,
+        limits,
+        amount.currency(),
+        &[amount.minor_units()],
+    )
+    .await?;
+
+    transition_status(
+        conn,
+        &inserted_transaction.transaction_id,
+        TransactionStatus::Completed,
+        None,
+    )
+    .await
+}
+
+/// Inserts a transfer flagged by the risk rules and holds its amount as UNDER_REVIEW inside an
+/// open database transaction, together with the rules that flagged it.
+async fn hold_transfer(
+    conn: &mut AsyncPgConnection,
+    transaction: NewTransaction,
+    amount: Money,
+    fee: Money,
+    limits: TransferLimits,
+    hits: Vec<NewRiskRuleHit>,
+) -> Result<Transaction, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::risk_rule_hits::dsl::risk_rule_hits;
+    use crate::storage::schema::transactions::dsl::*;
+
+    let inserted_transaction: Transaction = diesel::insert_into(transactions)
+        .values((transaction, fee_minor_units.eq(fee.minor_units())))
+        .get_result(conn)
+        .await?;
+
+    let sender = inserted_transaction.sender_id.as_str();
+    let balances = lock_wallets(
+        conn,
+        &[sender, &inserted_transaction.recipient_id],
+        amount.currency(),
+    )
+    .await?;
+
+    check_transfer_limits(
+        conn,
+        sender,
+        limits,
+        amount.currency(),
+        &[amount.minor_units()],
+    )
+    .await?;
+
+    available_balance(conn, sender, balances[sender])
+        .await?
+        .checked_sub(amount)
+        .map_err(TransactionDbError::from)?;
+
+    diesel::insert_into(risk_rule_hits)
+        .values(hits)
+        .execute(conn)
+        .await?;
+
+    transition_status(
+        conn,
+        &inserted_transaction.transaction_id,
+        TransactionStatus::UnderReview,
+        None,
+    )
+    .await
+}
+
+/// Records a transaction that was rejected, in its own database transaction since the attempt
+/// that failed was rolled back.
+async fn record_failed_transaction(
+    conn: &mut AsyncPgConnection,
+    transaction: NewTransaction,
+    failure: String,
+) -> Result<Transaction, ContainerError<TransactionDbError>> {
+    conn.build_transaction()
+        .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+            Box::pin(async move { insert_failed_transaction(conn, transaction, failure).await })
+        })
+        .await
+}
+
+/// Inserts a transaction that was rejected as FAILED inside an open database transaction.
+async fn insert_failed_transaction(
+    conn: &mut AsyncPgConnection,
+    transaction: NewTransaction,
+    failure: String,
+) -> Result<Transaction, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    let inserted_transaction: Transaction = diesel::insert_into(transactions)
+        .values(transaction)
+        .get_result(conn)
+        .await?;
+
+    transition_status(
+        conn,
+        &inserted_transaction.transaction_id,
+        TransactionStatus::Failed,
+        Some(failure),
+    )
+    .await
+}
+
+/// Inserts a transfer blocked by the risk rules as FAILED inside an open database transaction,
+/// together with the rules that blocked it.
+async fn block_transfer(
+    conn: &mut AsyncPgConnection,
+    transaction: NewTransaction,
+    hits: Vec<NewRiskRuleHit>,
+) -> Result<Transaction, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::risk_rule_hits::dsl::risk_rule_hits;
+    use crate::storage::schema::transactions::dsl::*;
+
+    let inserted_transaction: Transaction = diesel::insert_into(transactions)
+        .values(transaction)
+        .get_result(conn)
+        .await?;
+
+    diesel::insert_into(risk_rule_hits)
+        .values(hits)
+        .execute(conn)
+        .await?;
+
+    transition_status(
+        conn,
+        &inserted_transaction.transaction_id,
+        TransactionStatus::Failed,
+        Some("Blocked by risk checks".to_string()),
+    )
+    .await
+}
+
+/// Moves a transaction to a new status inside an open database transaction, rejecting illegal
+/// transitions. The row is locked so concurrent transitions are serialized.
+async fn transition_status(
+    conn: &mut AsyncPgConnection,
+    _transaction_id: &str,
+    new_status: TransactionStatus,
+    reason: Option<String>,
+) -> Result<Transaction, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    let current_status: TransactionStatus = transactions
+        .filter(transaction_id.eq(_transaction_id))
+        .select(status)
+        .for_update()
+        .first(conn)
+        .await?;
+
+    if !current_status.can_transition_to(new_status) {
+        return Err(TransactionDbError::InvalidStatusTransition.into());
+    }
+
+    Ok(diesel::update(transactions)
+        .filter(transaction_id.eq(_transaction_id))
+        .set((
+            status.eq(new_status),
+            failure_reason.eq(reason),
+            updated_at.eq(utils::datetime::now()),
+        ))
+        .get_result(conn)
+        .await?)
+}
+
+/// Locks a standing instruction until the end of the open database transaction, waiting for a
+/// worker that is running it to record the outcome.
+///
+/// Standing instructions are locked with `FOR NO KEY UPDATE`: the transactions of their runs
+/// reference them, and the `KEY SHARE` lock taken by that foreign key would otherwise wait on
+/// the worker holding the instruction.
+async fn lock_standing_instruction(
+    conn: &mut AsyncPgConnection,
+    _standing_instruction_id: &str,
+) -> Result<StandingInstruction, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::standing_instructions::dsl::*;
+
+    Ok(standing_instructions
+        .filter(standing_instruction_id.eq(_standing_instruction_id))
+        .for_no_key_update()
+        .first(conn)
+        .await?)
+}
+
+/// Sets the next run of a locked standing instruction, completing it when no run is left. The
+/// run moves on, so earlier attempts at the previous one no longer count.
+async fn reschedule_standing_instruction(
+    conn: &mut AsyncPgConnection,
+    _standing_instruction_id: &str,
+    next_run: Option<time::PrimitiveDateTime>,
+) -> Result<StandingInstruction, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::standing_instructions::dsl::*;
+
+    let new_status = match next_run {
+        Some(_) => StandingInstructionStatus::Active,
+        None => StandingInstructionStatus::Completed,
+    };
+
+    Ok(diesel::update(standing_instructions)
+        .filter(standing_instruction_id.eq(_standing_instruction_id))
+        .set((
+            status.eq(new_status),
+            next_run_at.eq(next_run),
+            attempts.eq(0),
+            retry_at.eq(None::<time::PrimitiveDateTime>),
+            updated_at.eq(utils::datetime::now()),
+        ))
+        .get_result(conn)
+        .await?)
+}
+
+/// Locks a payment request until the end of the open database transaction, so it is answered
+/// only once. Like standing instructions, payment requests are referenced by the transactions
+/// that pay them and are locked with `FOR NO KEY UPDATE`.
+async fn lock_payment_request(
+    conn: &mut AsyncPgConnection,
+    _payment_request_id: &str,
+) -> Result<PaymentRequest, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::payment_requests::dsl::*;
+
+    Ok(payment_requests
+        .filter(payment_request_id.eq(_payment_request_id))
+        .for_no_key_update()
+        .first(conn)
+        .await?)
+}
+
+/// Finds the transfer that already paid a payment request, if any.
+async fn find_payment_request_transfer(
+    conn: &mut AsyncPgConnection,
+    _payment_request_id: &str,
+) -> Result<Option<Transaction>, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    Ok(transactions
+        .filter(payment_request_id.eq(_payment_request_id))
+        .filter(status.ne(TransactionStatus::Failed))
+        .first(conn)
+        .await
+        .optional()?)
+}
+
+/// Locks a dispute until the end of the open database transaction, so concurrent reviews and
+/// resolutions of it are serialized.
+async fn lock_dispute(
+    conn: &mut AsyncPgConnection,
+    _dispute_id: &str,
+) -> Result<Dispute, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::disputes::dsl::*;
+
+    Ok(disputes
+        .filter(dispute_id.eq(_dispute_id))
+        .for_update()
+        .first(conn)
+        .await?)
+}
+
+/// Returns true if a transaction has a dispute that is not resolved yet.
+async fn open_dispute_exists(
+    conn: &mut AsyncPgConnection,
+    _transaction_id: &str,
+) -> Result<bool, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::disputes::dsl::*;
+
+    Ok(diesel::select(diesel::dsl::exists(
+        disputes
+            .filter(transaction_id.eq(_transaction_id))
+            .filter(status.eq_any(OPEN_DISPUTE_STATUSES)),
+    ))
+    .get_result(conn)
+    .await?)
+}
+
+/// Reads what the risk rules need to know of the accounts of a transfer, looking `lookback` into
+/// the past.
+async fn risk_history(
+    conn: &mut AsyncPgConnection,
+    _sender_id: &str,
+    _recipient_id: &str,
+    lookback: std::time::Duration,
+) -> Result<RiskHistory, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    let accounts: Vec<(String, time::PrimitiveDateTime)> = {
+        use crate::storage::schema::users::dsl::*;
+
+        users
+            .filter(user_id.eq_any([_sender_id, _recipient_id]))
+            .select((user_id, created_at))
+            .load(conn)
+            .await?
+    };
+    if accounts.len() != 2 {
+        return Err(TransactionDbError::AccountNotFound.into());
+    }
+    let sender_created_at = accounts
+        .into_iter()
+        .find(|(id_, _)| id_ == _sender_id)
+        .map(|(_, created)| created)
+        .ok_or(TransactionDbError::AccountNotFound)?;
+
+    let transfers_to_recipient = transactions
+        .filter(sender_id.eq(_sender_id))
+        .filter(recipient_id.eq(_recipient_id))
+        .filter(transaction_type.eq(TransactionType::Transfer))
+        .filter(status.eq_any(LIMITED_STATUSES))
+        .count()
+        .get_result(conn)
+        .await?;
+
+    let recent: Vec<(String, String, i64, time::PrimitiveDateTime)> = transactions
+        .filter(
+            sender_id
+                .eq(_sender_id)
+                .or(sender_id.eq(_recipient_id).and(recipient_id.eq(_sender_id))),
+        )
+        .filter(transaction_type.eq(TransactionType::Transfer))
+        .filter(status.eq_any(LIMITED_STATUSES))
+        .filter(created_at.ge(utils::datetime::now() - lookback))
+        .select((sender_id, recipient_id, amount_minor_units, created_at))
+        .load(conn)
+        .await?;
+
+    Ok(RiskHistory {
+        sender_created_at,
+        transfers_to_recipient,
+        recent_transfers: recent
+            .into_iter()
+            .map(|(sender, recipient, minor_units, created)| RecentTransfer {
+                sender_id: sender,
+                recipient_id: recipient,
+                amount_minor_units: minor_units,
+                created_at: created,
+            })
+            .collect(),
+    })
+}
+
+/// Executes an OPEN quote that has not expired inside an open database transaction, converting
+/// between the wallets of its user and marking it EXECUTED.
+async fn execute_quote(
+    conn: &mut AsyncPgConnection,
+    _quote_id: &str,
+) -> Result<FxQuote, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::fx_quotes::dsl::*;
+
+    let quote: FxQuote = fx_quotes
+        .filter(quote_id.eq(&_quote_id))
+        .for_update()
+        .first(conn)
+        .await?;
+
+    if !quote.status.can_transition_to(FxQuoteStatus::Executed) {
+        return Err(TransactionDbError::InvalidStatusTransition.into());
+    }
+    if quote.is_expired(utils::datetime::now()) {
+        return Err(TransactionDbError::FxQuoteExpired.into());
+    }
+
+    let source_amount = quote.source_amount().map_err(TransactionDbError::from)?;
+    let target_amount = quote.target_amount().map_err(TransactionDbError::from)?;
+    let spread = quote.spread().map_err(TransactionDbError::from)?;
+    let gross_amount = quote
+        .gross_target_amount()
+        .map_err(TransactionDbError::from)?;
+
+    // Lock both wallets in currency order, so conversions in opposite directions do not
+    // deadlock.
+    let mut currencies = [source_amount.currency(), target_amount.currency()];
+    currencies.sort_by_key(|currency| currency.code());
+    let mut balances = HashMap::new();
+    for wallet_currency in currencies {
+        let locked = lock_wallets(conn, &[&quote.user_id], wallet_currency).await?;
+        balances.insert(wallet_currency, locked[&quote.user_id]);
+    }
+    let source_balance = balances[&source_amount.currency()];
+    let target_balance = balances[&target_amount.currency()];
+
+    available_balance(conn, &quote.user_id, source_balance)
+        .await?
+        .checked_sub(source_amount)
+        .map_err(TransactionDbError::from)?;
+
+    let (debit, credit): (Transaction, Transaction) = {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let debit: Transaction = diesel::insert_into(transactions)
+            .values(quote.to_debit_transaction())
+            .get_result(conn)
+            .await?;
+        let credit = diesel::insert_into(transactions)
+            .values((
+                quote.to_credit_transaction(&debit.transaction_id),
+                fee_minor_units.eq(spread.minor_units()),
+            ))
+            .get_result(conn)
+            .await?;
+        (debit, credit)
+    };
+
+    set_wallet_balance(
+        conn,
+        &quote.user_id,
+        source_balance
+            .checked_sub(source_amount)
+            .map_err(TransactionDbError::from)?,
+    )
+    .await?;
+    set_wallet_balance(
+        conn,
+        &quote.user_id,
+        target_balance
+            .checked_add(target_amount)
+            .map_err(TransactionDbError::from)?,
+    )
+    .await?;
+
+    // The FX account is tracked by the ledger alone, taking in the source currency and paying
+    // out the target currency.
+    post_journal(
+        conn,
+        vec![
+            NewLedgerEntry::debit(&debit.transaction_id, &quote.user_id, source_amount),
+            NewLedgerEntry::credit(
+                &debit.transaction_id,
+                consts::SYSTEM_FX_ACCOUNT,
+                source_amount,
+            ),
+        ],
+    )
+    .await?;
+
+    let mut entries = vec![
+        NewLedgerEntry::debit(
+            &credit.transaction_id,
+            consts::SYSTEM_FX_ACCOUNT,
+            gross_amount,
+        ),
+        NewLedgerEntry::credit(&credit.transaction_id, &quote.user_id, target_amount),
+    ];
+    if !spread.is_zero() {
+        entries.push(NewLedgerEntry::credit(
+            &credit.transaction_id,
+            consts::PLATFORM_REVENUE_ACCOUNT,
+            spread,
+        ));
+    }
+    post_journal(conn, entries).await?;
+
+    for transaction in [&debit, &credit] {
+        transition_status(
+            conn,
+            &transaction.transaction_id,
+            TransactionStatus::Completed,
+            None,
+        )
+        .await?;
+    }
+
+    Ok(diesel::update(fx_quotes)
+        .filter(quote_id.eq(&_quote_id))
+        .set((
+            status.eq(FxQuoteStatus::Executed),
+            debit_transaction_id.eq(debit.transaction_id),
+            credit_transaction_id.eq(credit.transaction_id),
+            updated_at.eq(utils::datetime::now()),
+        ))
+        .get_result(conn)
+        .await?)
+}
+
+impl Storage {
+    /// Calculates the fee charged on a transfer of `amount` sent by `sender_id`, rejecting fees
+    /// that would leave nothing of the amount for the recipient.
+    fn transfer_fee(
+        &self,
+        sender_id: &str,
+        amount: Money,
+    ) -> Result<Money, ContainerError<TransactionDbError>> {
+        let fee = self
+            .fees
+            .schedule_for(sender_id)
+            .fee_for(amount)
+            .map_err(TransactionDbError::from)?;
+
+        if !fee.is_zero() && fee.minor_units() >= amount.minor_units() {
+            return Err(TransactionDbError::FeeExceedsAmount.into());
+        }
+
+        Ok(fee)
+    }
+
+    /// Runs the risk rules against a transfer before it is made. `earlier` are transfers of the
+    /// same request that are not stored yet, e.g. the previous legs of a batch, and count as
+    /// history of the accounts.
+    async fn assess_risk(
+        &self,
+        conn: &mut AsyncPgConnection,
+        transaction: &NewTransaction,
+        earlier: &[RecentTransfer],
+    ) -> Result<RiskAssessment, ContainerError<TransactionDbError>> {
+        if transaction.sender_id == transaction.recipient_id {
+            return Err(TransactionDbError::SameAccount.into());
+        }
+
+        let mut history = risk_history(
+            conn,
+            &transaction.sender_id,
+            &transaction.recipient_id,
+            self.risk.lookback(),
+        )
+        .await?;
+        for transfer in earlier {
+            if transfer.sender_id == transaction.sender_id
+                && transfer.recipient_id == transaction.recipient_id
+            {
+                history.transfers_to_recipient += 1;
+            }
+            history.recent_transfers.push(transfer.clone());
+        }
+
+        let input = RiskInput {
+            sender_id: transaction.sender_id.clone(),
+            recipient_id: transaction.recipient_id.clone(),
+            amount: transaction.amount().map_err(TransactionDbError::from)?,
+            now: utils::datetime::now(),
+            history,
+        };
+
+        Ok(self.risk.evaluate(&input))
+    }
+
+    /// Does what `submit_transfer` does inside an open database transaction. The FAILED record
+    /// of a blocked transfer or one the sender cannot cover is written before the error is
+    /// returned, so the caller has to commit it.
+    async fn submit_transfer_in(
+        &self,
+        conn: &mut AsyncPgConnection,
+        transaction: NewTransaction,
+    ) -> Result<Transaction, ContainerError<TransactionDbError>> {
+        let assessment = self.assess_risk(conn, &transaction, &[]).await?;
+        let hits: Vec<NewRiskRuleHit> = assessment
+            .hits
+            .into_iter()
+            .map(|hit| NewRiskRuleHit::new(&transaction.transaction_id, hit))
+            .collect();
+
+        if assessment.decision == RiskDecision::Block {
+            block_transfer(conn, transaction, hits).await?;
+            return Err(TransactionDbError::TransferBlocked.into());
+        }
+
+        let amount = transaction.amount().map_err(TransactionDbError::from)?;
+        let fee = self.transfer_fee(&transaction.sender_id, amount)?;
+        let limits = self.transfer_limits;
+        let decision = assessment.decision;
+        let failed_transaction = transaction.clone();
+
+        // A savepoint, so that a transfer the sender cannot cover is rolled back on its own.
+        let result = conn
+            .transaction::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    match decision {
+                        RiskDecision::Review => {
+                            hold_transfer(conn, transaction, amount, fee, limits, hits).await
+                        }
+                        _ => complete_transfer(conn, transaction, amount, fee, limits).await,
+                    }
+                })
+            })
+            .await;
+
+        match result {
+            Err(err) if *err.get_inner() == TransactionDbError::InsufficientBalance => {
+                let failure = err.get_inner().to_string();
+                insert_failed_transaction(conn, failed_transaction, failure).await?;
+
+                Err(err)
+            }
+            result => result,
+        }
+    }
+
+    /// Returns the transfer already created under the ID reserved for `transaction`, or submits
+    /// it through `submit_transfer_in` in a savepoint of the open database transaction.
+    ///
+    /// A rejected transfer keeps its FAILED record. A database error rolls back to the savepoint,
+    /// so the open transaction stays usable to record the attempt.
+    async fn submit_reserved_transfer(
+        &self,
+        conn: &mut AsyncPgConnection,
+        transaction: NewTransaction,
+    ) -> Result<Transaction, ContainerError<TransactionDbError>> {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let existing: Option<Transaction> = transactions
+            .filter(transaction_id.eq(&transaction.transaction_id))
+            .first(conn)
+            .await
+            .optional()?;
+        if let Some(existing) = existing {
+            return Ok(existing);
+        }
+
+        conn.transaction::<_, ContainerError<TransactionDbError>, _>(|conn| {
+            Box::pin(async move {
+                match self.submit_transfer_in(conn, transaction).await {
+                    Err(err) if !err.get_inner().is_database_error() => Ok(Err(err)),
+                    result => result.map(Ok),
+                }
+            })
+        })
+        .await
+        .and_then(|outcome| outcome)
+    }
+}
+
+/// Returns how long to wait before retrying work that hit a database error `attempts` times
+/// before, doubling `retry_delay` after every further attempt.
+fn retry_backoff(retry_delay: time::Duration, attempts: i32) -> time::Duration {
+    retry_delay.saturating_mul(1 << attempts.clamp(0, 16))
+}
 
 /// Implementation of the UserInterface for the Storage struct.
 impl UserInterface for Storage {
@@ -37,15 +1089,13 @@ impl UserInterface for Storage {
             .get_result(&mut conn)
             .await;
 
-        let output = match output {
+        match output {
             Err(err) => match err {
                 Error::NotFound => Err(err).change_error(UserDbError::NotFoundError),
                 _ => Err(err).change_error(UserDbError::DBFilterError),
             },
             Ok(user) => Ok(user),
-        };
-
-        output
+        }
     }
 
     /// Retrieves a user by their email address.
@@ -61,32 +1111,46 @@ impl UserInterface for Storage {
         let output: Result<super::types::User, diesel::result::Error> =
             users.filter(email.eq(_email)).get_result(&mut conn).await;
 
-        let output = match output {
+        match output {
             Err(err) => match err {
                 Error::NotFound => Err(err).change_error(UserDbError::NotFoundError),
                 _ => Err(err).change_error(UserDbError::DBFilterError),
             },
             Ok(user) => Ok(user),
-        };
-
-        output
+        }
     }
 
-    /// Creates a new user in the database.
+    /// Creates a new user in the database, with an empty wallet in the default currency.
     async fn create_user(
         &self,
         user: super::types::UserNew,
     ) -> Result<super::types::User, ContainerError<Self::Error>> {
-        use crate::storage::schema::users::dsl::*;
+        use crate::storage::schema::{users, wallets};
 
         let mut conn = self.get_conn().await.change_error(UserDbError::DBError)?;
 
-        let query = diesel::insert_into(users).values(user);
+        conn.build_transaction()
+            .run::<_, ContainerError<UserDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let inserted_user: super::types::User = diesel::insert_into(users::table)
+                        .values(user)
+                        .get_result(conn)
+                        .await
+                        .change_error(UserDbError::DBInsertError)?;
 
-        Ok(query
-            .get_result(&mut conn)
+                    diesel::insert_into(wallets::table)
+                        .values(NewWallet::new(
+                            inserted_user.user_id.clone(),
+                            Currency::default(),
+                        ))
+                        .execute(conn)
+                        .await
+                        .change_error(UserDbError::DBInsertError)?;
+
+                    Ok(inserted_user)
+                })
+            })
             .await
-            .change_error(UserDbError::DBInsertError)?)
     }
 
     /// Updates an existing user in the database.
@@ -99,14 +1163,12 @@ impl UserInterface for Storage {
 
         let mut conn = self.get_conn().await.change_error(UserDbError::DBError)?;
 
-        let query = diesel::update(users)
+        diesel::update(users)
             .filter(user_id.eq(_user_id))
-            .set(user_update);
-
-        Ok(query
+            .set(user_update)
             .get_result(&mut conn)
             .await
-            .change_error(UserDbError::DBUpdateError)?)
+            .change_error(UserDbError::DBUpdateError)
     }
 }
 
@@ -132,98 +1194,3958 @@ impl TransactionInterface for Storage {
             .get_result(&mut conn)
             .await;
 
-        let output = match output {
+        match output {
             Err(err) => match err {
                 Error::NotFound => Err(err).change_error(TransactionDbError::NotFoundError),
                 _ => Err(err).change_error(TransactionDbError::DBFilterError),
             },
             Ok(transaction) => Ok(transaction),
-        };
-
-        output
+        }
     }
 
     /// Creates a new transaction in the database.
+    ///
+    /// The transaction is inserted as PENDING and moved to COMPLETED once the money has moved.
+    /// If the sender cannot cover the amount, a FAILED transaction is recorded instead.
     async fn create_transaction(
         &self,
         transaction: super::types::NewTransaction,
     ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
-        use crate::error::container::ResultContainerExt;
-        use crate::storage::schema::transactions::dsl::*;
-        use crate::storage::schema::users::dsl::*;
-        use diesel::dsl::exists;
-        use diesel::{Connection, result::Error, select};
+        let amount = transaction.amount().map_err(TransactionDbError::from)?;
+
+        if transaction.sender_id == transaction.recipient_id {
+            return Err(TransactionDbError::SameAccount.into());
+        }
+
+        let fee = self.transfer_fee(&transaction.sender_id, amount)?;
+        let limits = self.transfer_limits;
 
         let mut conn = self
             .get_conn()
             .await
             .change_error(TransactionDbError::DBError)?;
 
+        let failed_transaction = transaction.clone();
+
         let result = conn
             .build_transaction()
-            .run::<_, Error, _>(|mut conn| {
-                Box::pin(async move {
-                    // Check if sender and receiver exists
-                    let sender_exists = select(exists(
-                        users.filter(user_id.eq(transaction.sender_id.clone())),
-                    ))
-                    .get_result::<bool>(conn)
-                    .await?;
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(
+                    async move { complete_transfer(conn, transaction, amount, fee, limits).await },
+                )
+            })
+            .await;
 
-                    let receiver_exists = select(exists(
-                        users.filter(user_id.eq(transaction.recipient_id.clone())),
-                    ))
-                    .get_result::<bool>(conn)
-                    .await?;
+        match result {
+            Err(err) if *err.get_inner() == TransactionDbError::InsufficientBalance => {
+                // Keep a record of the rejected transfer; the original insert was rolled back.
+                let failure = err.get_inner().to_string();
+                record_failed_transaction(&mut conn, failed_transaction, failure).await?;
 
-                    if !sender_exists || !receiver_exists {
-                        return Err(Error::NotFound);
-                    }
+                Err(err)
+            }
+            result => result,
+        }
+    }
+
+    /// Authorizes a transaction by holding its amount on the sender's account.
+    ///
+    /// No money moves and nothing is written to the ledger; the hold only reduces the sender's
+    /// available balance until the authorization is captured, voided or expires. The risk rules
+    /// run first in the same database transaction: a blocked authorization is recorded as FAILED
+    /// and rejected, and a flagged one is held as UNDER_REVIEW until an admin approves it. If the
+    /// sender cannot cover the amount, a FAILED transaction is recorded instead.
+    async fn authorize_transaction(
+        &self,
+        transaction: super::types::NewTransaction,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
 
-                    // Check if sender has enough balance
-                    use diesel::dsl::sum;
-                    use diesel::select;
+        let amount = transaction.amount().map_err(TransactionDbError::from)?;
+        let limits = self.transfer_limits;
 
-                    let sender_balance = users
-                        .filter(user_id.eq(transaction.sender_id.clone()))
-                        .select(balance_in_rs)
-                        .first::<f64>(conn)
-                        .await?;
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let assessment = self.assess_risk(conn, &transaction, &[]).await?;
+                    let hits: Vec<NewRiskRuleHit> = assessment
+                        .hits
+                        .into_iter()
+                        .map(|hit| NewRiskRuleHit::new(&transaction.transaction_id, hit))
+                        .collect();
 
-                    if sender_balance < transaction.amount_in_rs {
-                        return Err(Error::NotFound);
+                    if assessment.decision == RiskDecision::Block {
+                        block_transfer(conn, transaction, hits).await?;
+                        return Ok(Err(TransactionDbError::TransferBlocked.into()));
                     }
 
-                    // Debit sender
-                    diesel::update(users)
-                        .filter(user_id.eq(transaction.sender_id.clone()))
-                        .set(balance_in_rs.eq(balance_in_rs - transaction.amount_in_rs))
-                        .execute(conn)
-                        .await?;
+                    let decision = assessment.decision;
+                    let failed_transaction = transaction.clone();
 
-                    // Credit receiver
-                    diesel::update(users)
-                        .filter(user_id.eq(transaction.recipient_id.clone()))
-                        .set(balance_in_rs.eq(balance_in_rs + transaction.amount_in_rs))
-                        .execute(conn)
-                        .await?;
+                    // A savepoint, so that an authorization the sender cannot cover is rolled
+                    // back on its own.
+                    let result = conn
+                        .transaction::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                            Box::pin(async move {
+                                // The fee is charged on the captured amount, not on the hold.
+                                let no_fee = Money::zero(amount.currency());
+                                if decision == RiskDecision::Review {
+                                    return hold_transfer(
+                                        conn,
+                                        transaction,
+                                        amount,
+                                        no_fee,
+                                        limits,
+                                        hits,
+                                    )
+                                    .await;
+                                }
+
+                                let inserted_transaction: Transaction =
+                                    diesel::insert_into(transactions)
+                                        .values(transaction)
+                                        .get_result(conn)
+                                        .await?;
+
+                                let sender = inserted_transaction.sender_id.as_str();
+                                let balances = lock_wallets(
+                                    conn,
+                                    &[sender, &inserted_transaction.recipient_id],
+                                    amount.currency(),
+                                )
+                                .await?;
+
+                                // Holds count against the limits when they are placed, not when
+                                // captured.
+                                check_transfer_limits(
+                                    conn,
+                                    sender,
+                                    limits,
+                                    amount.currency(),
+                                    &[amount.minor_units()],
+                                )
+                                .await?;
 
-                    // Create transaction
-                    let query = diesel::insert_into(transactions).values(transaction);
+                                available_balance(conn, sender, balances[sender])
+                                    .await?
+                                    .checked_sub(amount)
+                                    .map_err(TransactionDbError::from)?;
 
-                    let inserted_transaction: Transaction = query.get_result(&mut conn).await?;
-                    Ok(inserted_transaction)
+                                transition_status(
+                                    conn,
+                                    &inserted_transaction.transaction_id,
+                                    TransactionStatus::Authorized,
+                                    None,
+                                )
+                                .await
+                            })
+                        })
+                        .await;
+
+                    match result {
+                        Err(err) if *err.get_inner() == TransactionDbError::InsufficientBalance => {
+                            let failure = err.get_inner().to_string();
+                            insert_failed_transaction(conn, failed_transaction, failure).await?;
+
+                            Ok(Err(err))
+                        }
+                        result => result.map(Ok),
+                    }
                 })
             })
-            .await;
+            .await
+            .and_then(|authorized| authorized)
+    }
 
-        match result {
-            Ok(transaction) => {
-                // Retrieve the created transaction
-                self.get_transaction_by_id(&transaction.transaction_id)
-                    .await
+    /// Captures an authorized transaction, moving the captured amount from the sender to the
+    /// recipient. A partial capture releases the rest of the hold.
+    async fn capture_transaction(
+        &self,
+        _transaction_id: &str,
+        capture_amount: Option<Money>,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _transaction_id = _transaction_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let authorization: Transaction = transactions
+                        .filter(transaction_id.eq(&_transaction_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if authorization.status != TransactionStatus::Authorized {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+                    if authorization
+                        .expires_at
+                        .is_some_and(|expiry| expiry <= utils::datetime::now())
+                    {
+                        return Err(TransactionDbError::AuthorizationExpired.into());
+                    }
+
+                    let authorized = authorization.amount().map_err(TransactionDbError::from)?;
+                    let amount = capture_amount.unwrap_or(authorized);
+                    authorized.checked_sub(amount).map_err(|err| match err {
+                        MoneyError::NegativeAmount => {
+                            TransactionDbError::CaptureExceedsAuthorization
+                        }
+                        err => TransactionDbError::from(err),
+                    })?;
+
+                    // The fee is charged on the captured amount, not on the hold.
+                    let fee = self.transfer_fee(&authorization.sender_id, amount)?;
+
+                    diesel::update(transactions)
+                        .filter(transaction_id.eq(&_transaction_id))
+                        .set((
+                            amount_minor_units.eq(amount.minor_units()),
+                            fee_minor_units.eq(fee.minor_units()),
+                        ))
+                        .execute(conn)
+                        .await?;
+
+                    // Release the hold before moving the money so it is not counted against
+                    // the sender's available balance.
+                    let captured = transition_status(
+                        conn,
+                        &_transaction_id,
+                        TransactionStatus::Completed,
+                        None,
+                    )
+                    .await?;
+
+                    move_funds(
+                        conn,
+                        &captured.transaction_id,
+                        &captured.sender_id,
+                        &captured.recipient_id,
+                        amount,
+                        fee,
+                    )
+                    .await?;
+
+                    Ok(captured)
+                })
+            })
+            .await
+    }
+
+    /// Voids an authorized transaction, releasing its hold without moving any money.
+    async fn void_transaction(
+        &self,
+        _transaction_id: &str,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        self.update_transaction_status(_transaction_id, TransactionStatus::Voided, None)
+            .await
+    }
+
+    /// Marks authorizations whose hold has lapsed as EXPIRED.
+    async fn expire_authorizations(&self) -> Result<usize, ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let now = utils::datetime::now();
+        diesel::update(transactions)
+            .filter(status.eq(TransactionStatus::Authorized))
+            .filter(expires_at.le(now))
+            .set((status.eq(TransactionStatus::Expired), updated_at.eq(now)))
+            .execute(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBUpdateError)
+    }
+
+    /// Sums the amounts held on an account in a currency by active authorizations.
+    async fn get_held_amount(
+        &self,
+        account_id: &str,
+        currency: Currency,
+    ) -> Result<i64, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        held_minor_units(&mut conn, account_id, currency).await
+    }
+
+    /// Refunds a completed transfer by moving money from its recipient back to its sender.
+    ///
+    /// Refunds return at most the net amount the recipient received: the platform keeps the
+    /// fee of the original transaction and refunds are not charged one, so a full refund takes
+    /// back exactly what the recipient was credited. The original transaction is locked while
+    /// the refund is booked, so concurrent refunds cannot together exceed it. Transactions with
+    /// an open dispute cannot be refunded until it is resolved.
+    async fn refund_transaction(
+        &self,
+        refund: super::types::NewRefund,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let original: Transaction = transactions
+                        .filter(transaction_id.eq(&refund.parent_transaction_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if original.transaction_type != TransactionType::Transfer
+                        || !original.status.is_refundable()
+                    {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    if open_dispute_exists(conn, &original.transaction_id).await? {
+                        return Err(TransactionDbError::TransactionDisputed.into());
+                    }
+
+                    let net_amount = original.net_amount().map_err(TransactionDbError::from)?;
+                    let refunded = Money::new(
+                        refunded_minor_units(conn, &original.transaction_id).await?,
+                        net_amount.currency(),
+                    )
+                    .map_err(TransactionDbError::from)?;
+                    let refundable = net_amount
+                        .checked_sub(refunded)
+                        .map_err(|_| TransactionDbError::RefundExceedsAmount)?;
+
+                    let amount = refund.amount.unwrap_or(refundable);
+                    if amount.is_zero() {
+                        return Err(TransactionDbError::RefundExceedsAmount.into());
+                    }
+                    let remaining = refundable.checked_sub(amount).map_err(|err| match err {
+                        MoneyError::NegativeAmount => TransactionDbError::RefundExceedsAmount,
+                        err => TransactionDbError::from(err),
+                    })?;
+
+                    let now = utils::datetime::now();
+                    let refund_transaction: Transaction = diesel::insert_into(transactions)
+                        .values(NewTransaction {
+                            transaction_id: refund.transaction_id,
+                            sender_id: original.recipient_id.clone(),
+                            recipient_id: original.sender_id.clone(),
+                            amount_minor_units: amount.minor_units(),
+                            currency: amount.currency().code().to_string(),
+                            description: refund.description,
+                            created_at: now,
+                            status: TransactionStatus::Pending,
+                            updated_at: now,
+                            transaction_type: TransactionType::Refund,
+                            parent_transaction_id: Some(original.transaction_id.clone()),
+                            authorized_amount_minor_units: None,
+                            expires_at: None,
+                            standing_instruction_id: None,
+                            payment_request_id: None,
+                            batch_id: None,
+                            // Refunds keep the client references of the transfer they return.
+                            metadata: original.metadata.clone(),
+                        })
+                        .get_result(conn)
+                        .await?;
+
+                    move_funds(
+                        conn,
+                        &refund_transaction.transaction_id,
+                        &refund_transaction.sender_id,
+                        &refund_transaction.recipient_id,
+                        amount,
+                        Money::zero(amount.currency()),
+                    )
+                    .await?;
+
+                    let original_status = if remaining.is_zero() {
+                        TransactionStatus::Refunded
+                    } else {
+                        TransactionStatus::PartiallyRefunded
+                    };
+                    transition_status(conn, &original.transaction_id, original_status, None)
+                        .await?;
+
+                    transition_status(
+                        conn,
+                        &refund_transaction.transaction_id,
+                        TransactionStatus::Completed,
+                        None,
+                    )
+                    .await
+                })
+            })
+            .await
+    }
+
+    /// Moves a transaction to a new status if the transition is legal.
+    async fn update_transaction_status(
+        &self,
+        _transaction_id: &str,
+        new_status: TransactionStatus,
+        reason: Option<String>,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let transaction_id = _transaction_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    transition_status(conn, &transaction_id, new_status, reason).await
+                })
+            })
+            .await
+    }
+
+    /// Lists a page of the transactions of an account matching the filter, starting after the
+    /// cursor, and counts every match only when `include_total` asks for it.
+    ///
+    /// Reads each side of the account from the cursor along its sort key, up to one row past
+    /// the page, and merges them. The cursor keys on `(created_at, id)`, behind the amount for
+    /// amount sorts, so pages stay put while new transactions arrive and deep pages cost the
+    /// same as the first.
+    async fn list_transactions(
+        &self,
+        filter: &TransactionFilter,
+        cursor: Option<&TransactionCursor>,
+        limit: i64,
+        include_total: bool,
+    ) -> Result<TransactionPage, ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let sort = filter.sort;
+        let ascending = cursor.map_or(sort.is_ascending(), TransactionCursor::scans_ascending);
+
+        let mut rows = Vec::new();
+        for side in filtered_sides(filter) {
+            let mut query = filtered_transactions(filter, side);
+            if let Some(cursor) = cursor {
+                let (_amount, _created_at, _id) =
+                    (cursor.amount_minor_units, cursor.created_at, cursor.id);
+                // The bound on created_at alone is redundant, but lets the index range over it.
+                query = match (sort.is_by_amount(), ascending) {
+                    (false, true) => query.filter(
+                        created_at
+                            .ge(_created_at)
+                            .and(created_at.gt(_created_at).or(id.gt(_id))),
+                    ),
+                    (false, false) => query.filter(
+                        created_at
+                            .le(_created_at)
+                            .and(created_at.lt(_created_at).or(id.lt(_id))),
+                    ),
+                    (true, true) => query.filter(
+                        amount_minor_units.ge(_amount).and(
+                            amount_minor_units.gt(_amount).or(created_at
+                                .gt(_created_at)
+                                .or(created_at.eq(_created_at).and(id.gt(_id)))),
+                        ),
+                    ),
+                    (true, false) => query.filter(
+                        amount_minor_units.le(_amount).and(
+                            amount_minor_units.lt(_amount).or(created_at
+                                .lt(_created_at)
+                                .or(created_at.eq(_created_at).and(id.lt(_id)))),
+                        ),
+                    ),
+                };
             }
-            Err(err) => Err(err).change_error(TransactionDbError::DBInsertError)?,
+
+            let side_rows: Vec<Transaction> = match (sort.is_by_amount(), ascending) {
+                (false, true) => query.order((created_at.asc(), id.asc())),
+                (false, false) => query.order((created_at.desc(), id.desc())),
+                (true, true) => query.order((amount_minor_units.asc(), created_at.asc(), id.asc())),
+                (true, false) => {
+                    query.order((amount_minor_units.desc(), created_at.desc(), id.desc()))
+                }
+            }
+            .limit(limit + 1)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+            rows.extend(side_rows);
+        }
+
+        if ascending == sort.is_ascending() {
+            rows.sort_by(|a, b| sort.compare(a, b));
+        } else {
+            rows.sort_by(|a, b| sort.compare(b, a));
         }
+
+        let total_count = if include_total {
+            let mut total = 0;
+            for side in filtered_sides(filter) {
+                total += filtered_transactions(filter, side)
+                    .count()
+                    .get_result::<i64>(&mut conn)
+                    .await
+                    .change_error(TransactionDbError::DBFilterError)?;
+            }
+            Some(total)
+        } else {
+            None
+        };
+
+        Ok(TransactionPage::new(
+            sort,
+            cursor,
+            rows,
+            limit as usize,
+            total_count,
+        ))
+    }
+
+    /// Reads the statement of a wallet inside one repeatable read, read only transaction, so
+    /// the opening balance and the activity of the period agree while transfers go on.
+    ///
+    /// Adjustments booked outside of transactions, such as opening balances carried over into
+    /// the ledger, count towards the opening balance of the periods after they were booked and
+    /// are listed with the activity of the period they were booked in, together with the other
+    /// postings of their journals.
+    async fn get_statement_activity(
+        &self,
+        account_id: &str,
+        _currency: Currency,
+        period: &StatementPeriod,
+    ) -> Result<(i64, Vec<Transaction>, Vec<LedgerEntry>), ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
+        use diesel::dsl::{exists, not, sql};
+        use diesel::sql_types::{BigInt, Nullable};
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let account = account_id.to_string();
+        let (start, end) = (period.start(), period.end());
+        conn.build_transaction()
+            .repeatable_read()
+            .read_only()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let sent: Option<i64> = transactions
+                        .filter(moved_money())
+                        .filter(sender_id.eq(&account))
+                        .filter(currency.eq(_currency.code()))
+                        .filter(created_at.lt(start))
+                        .select(sql::<Nullable<BigInt>>("SUM(amount_minor_units)::BIGINT"))
+                        .get_result(conn)
+                        .await?;
+                    let received: Option<i64> = transactions
+                        .filter(moved_money())
+                        .filter(recipient_id.eq(&account))
+                        .filter(currency.eq(_currency.code()))
+                        .filter(created_at.lt(start))
+                        .select(sql::<Nullable<BigInt>>(
+                            "SUM(amount_minor_units - fee_minor_units)::BIGINT",
+                        ))
+                        .get_result(conn)
+                        .await?;
+                    let adjustments: Option<i64> = {
+                        use crate::storage::schema::ledger_entries;
+
+                        ledger_entries::table
+                            .filter(ledger_entries::account_id.eq(&account))
+                            .filter(ledger_entries::currency.eq(_currency.code()))
+                            .filter(ledger_entries::created_at.lt(start))
+                            .filter(not(exists(
+                                transactions.filter(transaction_id.eq(ledger_entries::journal_id)),
+                            )))
+                            .select(sql::<Nullable<BigInt>>(SIGNED_POSTINGS_SQL))
+                            .get_result(conn)
+                            .await?
+                    };
+
+                    let opening_balance = [received, adjustments]
+                        .into_iter()
+                        .flatten()
+                        .try_fold(-sent.unwrap_or(0), i64::checked_add)
+                        .ok_or(TransactionDbError::AmountOverflow)?;
+
+                    let activity = transactions
+                        .filter(moved_money())
+                        .filter(sender_id.eq(&account).or(recipient_id.eq(&account)))
+                        .filter(currency.eq(_currency.code()))
+                        .filter(created_at.ge(start))
+                        .filter(created_at.lt(end))
+                        .order((created_at.asc(), id.asc()))
+                        .load::<Transaction>(conn)
+                        .await?;
+
+                    let adjustments = {
+                        use crate::storage::schema::ledger_entries;
+
+                        let journals: Vec<String> = ledger_entries::table
+                            .filter(ledger_entries::account_id.eq(&account))
+                            .filter(ledger_entries::currency.eq(_currency.code()))
+                            .filter(ledger_entries::created_at.ge(start))
+                            .filter(ledger_entries::created_at.lt(end))
+                            .filter(not(exists(
+                                transactions.filter(transaction_id.eq(ledger_entries::journal_id)),
+                            )))
+                            .select(ledger_entries::journal_id)
+                            .distinct()
+                            .load(conn)
+                            .await?;
+
+                        ledger_entries::table
+                            .filter(ledger_entries::journal_id.eq_any(journals))
+                            .order((ledger_entries::created_at.asc(), ledger_entries::id.asc()))
+                            .load::<LedgerEntry>(conn)
+                            .await?
+                    };
+
+                    Ok((opening_balance, activity, adjustments))
+                })
+            })
+            .await
+    }
+
+    /// Declares a cursor over the transactions of the account inside one repeatable read, read
+    /// only transaction and fetches it a batch at a time, so only the batches buffered in the
+    /// channel are held in memory. Stops early when the receiving side is dropped.
+    async fn export_transactions(
+        &self,
+        account_id: &str,
+        from: Option<time::PrimitiveDateTime>,
+        to: Option<time::PrimitiveDateTime>,
+        batches: tokio::sync::mpsc::Sender<Vec<Transaction>>,
+    ) -> Result<usize, ContainerError<Self::Error>> {
+        use diesel::sql_types::{Nullable, Text, Timestamp};
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let account = account_id.to_string();
+        conn.build_transaction()
+            .repeatable_read()
+            .read_only()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    diesel::sql_query(
+                        "DECLARE transaction_export NO SCROLL CURSOR FOR \
+                         SELECT * FROM transactions \
+                         WHERE (sender_id = $1 OR recipient_id = $1) \
+                         AND ($2::TIMESTAMP IS NULL OR created_at >= $2) \
+                         AND ($3::TIMESTAMP IS NULL OR created_at < $3) \
+                         ORDER BY created_at, id",
+                    )
+                    .bind::<Text, _>(&account)
+                    .bind::<Nullable<Timestamp>, _>(from)
+                    .bind::<Nullable<Timestamp>, _>(to)
+                    .execute(conn)
+                    .await?;
+
+                    let fetch = format!(
+                        "FETCH {} FROM transaction_export",
+                        consts::EXPORT_BATCH_SIZE
+                    );
+                    let mut exported = 0;
+                    loop {
+                        let batch = diesel::sql_query(&fetch).load::<Transaction>(conn).await?;
+                        if batch.is_empty() {
+                            break;
+                        }
+
+                        let fetched = batch.len();
+                        if batches.send(batch).await.is_err() {
+                            break;
+                        }
+                        exported += fetched;
+                    }
+
+                    Ok(exported)
+                })
+            })
+            .await
+    }
+}
+
+/// Implementation of the ScheduledTransferInterface for the Storage struct.
+impl ScheduledTransferInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Creates a new scheduled transfer in the database.
+    async fn create_scheduled_transfer(
+        &self,
+        scheduled_transfer: super::types::NewScheduledTransfer,
+    ) -> Result<super::types::ScheduledTransfer, ContainerError<Self::Error>> {
+        use crate::storage::schema::scheduled_transfers::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::insert_into(scheduled_transfers)
+            .values(scheduled_transfer)
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBInsertError)
+    }
+
+    /// Retrieves a scheduled transfer by its ID.
+    async fn get_scheduled_transfer(
+        &self,
+        _scheduled_transfer_id: &str,
+    ) -> Result<super::types::ScheduledTransfer, ContainerError<Self::Error>> {
+        use crate::storage::schema::scheduled_transfers::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(scheduled_transfers
+            .filter(scheduled_transfer_id.eq(_scheduled_transfer_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the scheduled transfers of a sender, newest first.
+    async fn list_scheduled_transfers(
+        &self,
+        _sender_id: &str,
+        _status: Option<ScheduledTransferStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<super::types::ScheduledTransfer>, i64), ContainerError<Self::Error>> {
+        use crate::storage::schema::scheduled_transfers::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let filtered = || {
+            let mut query = scheduled_transfers
+                .filter(sender_id.eq(_sender_id))
+                .into_boxed();
+            if let Some(_status) = _status {
+                query = query.filter(status.eq(_status));
+            }
+            query
+        };
+
+        let rows = filtered()
+            .order((created_at.desc(), id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        let total_count = filtered()
+            .count()
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        Ok((rows, total_count))
+    }
+
+    /// Cancels a scheduled transfer, unless a worker already executed it.
+    async fn cancel_scheduled_transfer(
+        &self,
+        _scheduled_transfer_id: &str,
+    ) -> Result<super::types::ScheduledTransfer, ContainerError<Self::Error>> {
+        use crate::storage::schema::scheduled_transfers::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _scheduled_transfer_id = _scheduled_transfer_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    // Waits for a worker that is executing the transfer to record its outcome.
+                    let current_status: ScheduledTransferStatus = scheduled_transfers
+                        .filter(scheduled_transfer_id.eq(&_scheduled_transfer_id))
+                        .select(status)
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if current_status != ScheduledTransferStatus::Scheduled {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    Ok(diesel::update(scheduled_transfers)
+                        .filter(scheduled_transfer_id.eq(&_scheduled_transfer_id))
+                        .set((
+                            status.eq(ScheduledTransferStatus::Cancelled),
+                            updated_at.eq(utils::datetime::now()),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Executes the next due scheduled transfer through `submit_transfer`, so it passes the risk
+    /// rules. A transfer held for review counts as executed; one they block fails.
+    ///
+    /// The row stays locked with `FOR UPDATE SKIP LOCKED` until its outcome is recorded, so
+    /// concurrent workers never pick up the same transfer. The transfer is made on the same
+    /// connection, in a savepoint, and is created under the ID reserved on the row, so an
+    /// execution retried after a crash records the existing outcome instead of moving the money
+    /// again. An attempt that hits a database error is rolled back to the savepoint and counted
+    /// on the row, which stays SCHEDULED until `retry_at` so the transfers due after it run in
+    /// the meantime; after `max_attempts` it fails. Any other error fails it right away.
+    async fn execute_next_scheduled_transfer(
+        &self,
+        retry_delay: time::Duration,
+        max_attempts: u32,
+    ) -> Result<Option<super::types::ScheduledTransfer>, ContainerError<Self::Error>> {
+        use crate::storage::schema::scheduled_transfers::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let now = utils::datetime::now();
+                    let due: Option<ScheduledTransfer> = scheduled_transfers
+                        .filter(status.eq(ScheduledTransferStatus::Scheduled))
+                        .filter(execute_at.le(now))
+                        .filter(retry_at.is_null().or(retry_at.le(now)))
+                        .order((execute_at, id))
+                        .for_update()
+                        .skip_locked()
+                        .first(conn)
+                        .await
+                        .optional()?;
+
+                    let Some(due) = due else {
+                        return Ok(None);
+                    };
+
+                    let outcome = self
+                        .submit_reserved_transfer(conn, due.to_new_transaction())
+                        .await;
+
+                    let attempt = due.attempts.saturating_add(1);
+                    let (new_status, reason) = match outcome {
+                        Ok(transaction) if transaction.status == TransactionStatus::Failed => {
+                            (ScheduledTransferStatus::Failed, transaction.failure_reason)
+                        }
+                        Ok(_) => (ScheduledTransferStatus::Executed, None),
+                        Err(err)
+                            if err.get_inner().is_database_error()
+                                && u32::try_from(attempt).unwrap_or(u32::MAX) < max_attempts =>
+                        {
+                            let retried: ScheduledTransfer = diesel::update(scheduled_transfers)
+                                .filter(scheduled_transfer_id.eq(&due.scheduled_transfer_id))
+                                .set((
+                                    attempts.eq(attempt),
+                                    retry_at.eq(now + retry_backoff(retry_delay, due.attempts)),
+                                    failure_reason.eq(err.get_inner().to_string()),
+                                    updated_at.eq(now),
+                                ))
+                                .get_result(conn)
+                                .await?;
+
+                            return Ok(Some(retried));
+                        }
+                        Err(err) => (
+                            ScheduledTransferStatus::Failed,
+                            Some(err.get_inner().to_string()),
+                        ),
+                    };
+
+                    let executed: ScheduledTransfer = diesel::update(scheduled_transfers)
+                        .filter(scheduled_transfer_id.eq(&due.scheduled_transfer_id))
+                        .set((
+                            status.eq(new_status),
+                            failure_reason.eq(reason),
+                            executed_at.eq(now),
+                            updated_at.eq(now),
+                        ))
+                        .get_result(conn)
+                        .await?;
+
+                    Ok(Some(executed))
+                })
+            })
+            .await
+    }
+}
+
+/// Implementation of the StandingInstructionInterface for the Storage struct.
+impl StandingInstructionInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Creates a new standing instruction in the database.
+    async fn create_standing_instruction(
+        &self,
+        standing_instruction: super::types::NewStandingInstruction,
+    ) -> Result<super::types::StandingInstruction, ContainerError<Self::Error>> {
+        use crate::storage::schema::standing_instructions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::insert_into(standing_instructions)
+            .values(standing_instruction)
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBInsertError)
+    }
+
+    /// Retrieves a standing instruction by its ID.
+    async fn get_standing_instruction(
+        &self,
+        _standing_instruction_id: &str,
+    ) -> Result<super::types::StandingInstruction, ContainerError<Self::Error>> {
+        use crate::storage::schema::standing_instructions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(standing_instructions
+            .filter(standing_instruction_id.eq(_standing_instruction_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the standing instructions of a sender, newest first.
+    async fn list_standing_instructions(
+        &self,
+        _sender_id: &str,
+        _status: Option<StandingInstructionStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<super::types::StandingInstruction>, i64), ContainerError<Self::Error>> {
+        use crate::storage::schema::standing_instructions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let filtered = || {
+            let mut query = standing_instructions
+                .filter(sender_id.eq(_sender_id))
+                .into_boxed();
+            if let Some(_status) = _status {
+                query = query.filter(status.eq(_status));
+            }
+            query
+        };
+
+        let rows = filtered()
+            .order((created_at.desc(), id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        let total_count = filtered()
+            .count()
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        Ok((rows, total_count))
+    }
+
+    /// Pauses an active standing instruction.
+    async fn pause_standing_instruction(
+        &self,
+        _standing_instruction_id: &str,
+    ) -> Result<super::types::StandingInstruction, ContainerError<Self::Error>> {
+        use crate::storage::schema::standing_instructions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _standing_instruction_id = _standing_instruction_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current =
+                        lock_standing_instruction(conn, &_standing_instruction_id).await?;
+
+                    if current.status != StandingInstructionStatus::Active {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    Ok(diesel::update(standing_instructions)
+                        .filter(standing_instruction_id.eq(&_standing_instruction_id))
+                        .set((
+                            status.eq(StandingInstructionStatus::Paused),
+                            updated_at.eq(utils::datetime::now()),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Resumes a paused standing instruction from its first run at or after now. Runs missed
+    /// while it was paused are skipped regardless of the catch-up policy.
+    async fn resume_standing_instruction(
+        &self,
+        _standing_instruction_id: &str,
+    ) -> Result<super::types::StandingInstruction, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _standing_instruction_id = _standing_instruction_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current =
+                        lock_standing_instruction(conn, &_standing_instruction_id).await?;
+
+                    if current.status != StandingInstructionStatus::Paused {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    let recurrence = current
+                        .recurrence()
+                        .change_error(TransactionDbError::UnknownError)?;
+                    let next_run = recurrence
+                        .occurrence_not_before(
+                            current.next_run_at.unwrap_or(current.start_at),
+                            utils::datetime::now(),
+                        )
+                        .filter(|next_run| !current.is_past_end(*next_run));
+
+                    reschedule_standing_instruction(conn, &_standing_instruction_id, next_run).await
+                })
+            })
+            .await
+    }
+
+    /// Executes the next due run of a standing instruction through `submit_transfer`, so every
+    /// run passes the risk rules.
+    ///
+    /// Rows are claimed with `FOR NO KEY UPDATE SKIP LOCKED`, so concurrent workers never run the
+    /// same instruction. The transfer of each run is made on the same connection, in a
+    /// savepoint, so it commits together with the advance of `next_run_at`. It is created under
+    /// an ID derived from the run number, so a run retried after a crash records the existing
+    /// outcome instead of moving the money again. A run that hits a database error is counted on
+    /// the instruction and retried at `retry_at`, `retry_delay` later and doubled after every
+    /// further attempt, so the instructions due after it run in the meantime; after
+    /// `max_attempts` it is recorded as failed. Any other error is recorded as the failure of the
+    /// run, which counts like any other. An instruction whose recurrence cannot be read is
+    /// marked FAILED. With the `Skip` policy, runs overdue by more than `grace` are skipped
+    /// without counting toward `max_occurrences`; with `Execute` every missed run is executed in
+    /// order.
+    async fn execute_next_standing_instruction(
+        &self,
+        policy: CatchUpPolicy,
+        grace: time::Duration,
+        retry_delay: time::Duration,
+        max_attempts: u32,
+    ) -> Result<Option<super::types::StandingInstruction>, ContainerError<Self::Error>> {
+        use crate::storage::schema::standing_instructions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let now = utils::datetime::now();
+                    let due: Option<StandingInstruction> = standing_instructions
+                        .filter(status.eq(StandingInstructionStatus::Active))
+                        .filter(next_run_at.le(now))
+                        .filter(retry_at.is_null().or(retry_at.le(now)))
+                        .order((next_run_at, id))
+                        // See `lock_standing_instruction` for why this is not `FOR UPDATE`.
+                        .for_no_key_update()
+                        .skip_locked()
+                        .first(conn)
+                        .await
+                        .optional()?;
+
+                    let Some(due) = due else {
+                        return Ok(None);
+                    };
+
+                    let recurrence = match due.recurrence() {
+                        Ok(recurrence) => recurrence,
+                        Err(err) => {
+                            let failed: StandingInstruction = diesel::update(standing_instructions)
+                                .filter(standing_instruction_id.eq(&due.standing_instruction_id))
+                                .set((
+                                    status.eq(StandingInstructionStatus::Failed),
+                                    last_failure_reason.eq(err.to_string()),
+                                    updated_at.eq(now),
+                                ))
+                                .get_result(conn)
+                                .await?;
+
+                            return Ok(Some(failed));
+                        }
+                    };
+                    let scheduled_run = due.next_run_at.unwrap_or(due.start_at);
+                    let run = match policy {
+                        CatchUpPolicy::Skip if scheduled_run + grace < now => {
+                            recurrence.occurrence_not_before(scheduled_run, now - grace)
+                        }
+                        CatchUpPolicy::Skip | CatchUpPolicy::Execute => Some(scheduled_run),
+                    }
+                    .filter(|run| !due.is_past_end(*run));
+
+                    // Every missed run was skipped and the next one is not due yet.
+                    let Some(run) = run.filter(|run| *run <= now) else {
+                        let rescheduled = reschedule_standing_instruction(
+                            conn,
+                            &due.standing_instruction_id,
+                            run,
+                        )
+                        .await?;
+                        return Ok(Some(rescheduled));
+                    };
+
+                    let occurrence = due.occurrences_count + 1;
+                    let outcome = self
+                        .submit_reserved_transfer(conn, due.to_new_transaction(occurrence))
+                        .await;
+
+                    let attempt = due.attempts.saturating_add(1);
+                    let reason = match outcome {
+                        Ok(transaction) if transaction.status == TransactionStatus::Failed => {
+                            transaction.failure_reason
+                        }
+                        Ok(_) => None,
+                        Err(err)
+                            if err.get_inner().is_database_error()
+                                && u32::try_from(attempt).unwrap_or(u32::MAX) < max_attempts =>
+                        {
+                            let retried: StandingInstruction =
+                                diesel::update(standing_instructions)
+                                    .filter(
+                                        standing_instruction_id.eq(&due.standing_instruction_id),
+                                    )
+                                    .set((
+                                        attempts.eq(attempt),
+                                        retry_at.eq(now + retry_backoff(retry_delay, due.attempts)),
+                                        last_failure_reason.eq(err.get_inner().to_string()),
+                                        updated_at.eq(now),
+                                    ))
+                                    .get_result(conn)
+                                    .await?;
+
+                            return Ok(Some(retried));
+                        }
+                        Err(err) => Some(err.get_inner().to_string()),
+                    };
+
+                    let next_run = recurrence
+                        .next_occurrence(run)
+                        .filter(|next_run| !due.is_past_end(*next_run))
+                        .filter(|_| !due.is_exhausted(occurrence));
+                    let new_status = match next_run {
+                        Some(_) => StandingInstructionStatus::Active,
+                        None => StandingInstructionStatus::Completed,
+                    };
+
+                    let executed: StandingInstruction = diesel::update(standing_instructions)
+                        .filter(standing_instruction_id.eq(&due.standing_instruction_id))
+                        .set((
+                            status.eq(new_status),
+                            occurrences_count.eq(occurrence),
+                            next_run_at.eq(next_run),
+                            last_run_at.eq(now),
+                            last_failure_reason.eq(reason),
+                            attempts.eq(0),
+                            retry_at.eq(None::<time::PrimitiveDateTime>),
+                            updated_at.eq(now),
+                        ))
+                        .get_result(conn)
+                        .await?;
+
+                    Ok(Some(executed))
+                })
+            })
+            .await
+    }
+}
+
+/// Implementation of the PaymentRequestInterface for the Storage struct.
+impl PaymentRequestInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Creates a new payment request in the database.
+    async fn create_payment_request(
+        &self,
+        payment_request: super::types::NewPaymentRequest,
+    ) -> Result<super::types::PaymentRequest, ContainerError<Self::Error>> {
+        use crate::storage::schema::payment_requests::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::insert_into(payment_requests)
+            .values(payment_request)
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBInsertError)
+    }
+
+    /// Retrieves a payment request by its ID.
+    async fn get_payment_request(
+        &self,
+        _payment_request_id: &str,
+    ) -> Result<super::types::PaymentRequest, ContainerError<Self::Error>> {
+        use crate::storage::schema::payment_requests::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(payment_requests
+            .filter(payment_request_id.eq(_payment_request_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the payment requests of a user, newest first.
+    async fn list_payment_requests(
+        &self,
+        user_id: &str,
+        direction: Option<PaymentRequestDirection>,
+        _status: Option<PaymentRequestStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<super::types::PaymentRequest>, i64), ContainerError<Self::Error>> {
+        use crate::storage::schema::payment_requests::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let filtered = || {
+            let mut query = match direction {
+                Some(PaymentRequestDirection::Incoming) => {
+                    payment_requests.filter(payer_id.eq(user_id)).into_boxed()
+                }
+                Some(PaymentRequestDirection::Outgoing) => payment_requests
+                    .filter(requester_id.eq(user_id))
+                    .into_boxed(),
+                None => payment_requests
+                    .filter(payer_id.eq(user_id).or(requester_id.eq(user_id)))
+                    .into_boxed(),
+            };
+            if let Some(_status) = _status {
+                query = query.filter(status.eq(_status));
+            }
+            query
+        };
+
+        let rows = filtered()
+            .order((created_at.desc(), id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        let total_count = filtered()
+            .count()
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        Ok((rows, total_count))
+    }
+
+    /// Pays a payment request through `submit_transfer`, so the transfer passes the risk rules.
+    ///
+    /// The transfer is made on the connection that holds the request locked, in the same
+    /// database transaction as marking the request PAID, so the two commit together and
+    /// concurrent attempts cannot pay it twice. If the payer cannot cover the amount or the
+    /// transfer is blocked, its FAILED record is committed and the request stays PENDING, so it
+    /// can be paid after a top-up. It also stays PENDING while the transfer is held for review,
+    /// and is marked PAID when an admin approves it.
+    async fn pay_payment_request(
+        &self,
+        _payment_request_id: &str,
+    ) -> Result<super::types::PaymentRequest, ContainerError<Self::Error>> {
+        use crate::storage::schema::payment_requests::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _payment_request_id = _payment_request_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current = lock_payment_request(conn, &_payment_request_id).await?;
+
+                    if !current.status.can_transition_to(PaymentRequestStatus::Paid) {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    let now = utils::datetime::now();
+                    if current.is_expired(now) {
+                        return Err(TransactionDbError::PaymentRequestExpired.into());
+                    }
+
+                    let transfer =
+                        match find_payment_request_transfer(conn, &_payment_request_id).await? {
+                            Some(transfer) => transfer,
+                            None => match self
+                                .submit_transfer_in(conn, current.to_new_transaction())
+                                .await
+                            {
+                                Ok(transfer) => transfer,
+                                // Commit the FAILED record of a rejected transfer.
+                                Err(err) if !err.get_inner().is_database_error() => {
+                                    return Ok(Err(err));
+                                }
+                                Err(err) => return Err(err),
+                            },
+                        };
+
+                    // A transfer held for review pays the request once an admin approves it.
+                    if transfer.status == TransactionStatus::UnderReview {
+                        return Ok(Ok(current));
+                    }
+
+                    Ok(Ok(diesel::update(payment_requests)
+                        .filter(payment_request_id.eq(&_payment_request_id))
+                        .set((
+                            status.eq(PaymentRequestStatus::Paid),
+                            transaction_id.eq(transfer.transaction_id),
+                            updated_at.eq(now),
+                        ))
+                        .get_result(conn)
+                        .await?))
+                })
+            })
+            .await
+            .and_then(|paid| paid)
+    }
+
+    /// Declines, cancels or expires a pending payment request, unless a transfer paying it is
+    /// under review.
+    async fn update_payment_request_status(
+        &self,
+        _payment_request_id: &str,
+        new_status: PaymentRequestStatus,
+    ) -> Result<super::types::PaymentRequest, ContainerError<Self::Error>> {
+        use crate::storage::schema::payment_requests::dsl::*;
+
+        if new_status == PaymentRequestStatus::Paid {
+            return Err(TransactionDbError::InvalidStatusTransition.into());
+        }
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _payment_request_id = _payment_request_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current = lock_payment_request(conn, &_payment_request_id).await?;
+
+                    if !current.status.can_transition_to(new_status) {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    let now = utils::datetime::now();
+                    if new_status != PaymentRequestStatus::Expired && current.is_expired(now) {
+                        return Err(TransactionDbError::PaymentRequestExpired.into());
+                    }
+
+                    // A transfer under review pays the request once approved, so it cannot be
+                    // declined or cancelled from under it.
+                    let transfer =
+                        find_payment_request_transfer(conn, &_payment_request_id).await?;
+                    if transfer
+                        .is_some_and(|transfer| transfer.status == TransactionStatus::UnderReview)
+                    {
+                        return Err(TransactionDbError::PaymentRequestUnderReview.into());
+                    }
+
+                    Ok(diesel::update(payment_requests)
+                        .filter(payment_request_id.eq(&_payment_request_id))
+                        .set((status.eq(new_status), updated_at.eq(now)))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Marks pending payment requests past their expiry as EXPIRED.
+    async fn expire_payment_requests(&self) -> Result<usize, ContainerError<Self::Error>> {
+        use crate::storage::schema::payment_requests::dsl::*;
+        use crate::storage::schema::transactions;
+        use diesel::{
+            NullableExpressionMethods,
+            dsl::{exists, not},
+        };
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let now = utils::datetime::now();
+        diesel::update(payment_requests)
+            .filter(status.eq(PaymentRequestStatus::Pending))
+            .filter(expires_at.le(now))
+            // Requests paid by a transfer under review are settled by the review instead.
+            .filter(not(exists(
+                transactions::table
+                    .filter(transactions::payment_request_id.eq(payment_request_id.nullable()))
+                    .filter(transactions::status.eq(TransactionStatus::UnderReview)),
+            )))
+            .set((status.eq(PaymentRequestStatus::Expired), updated_at.eq(now)))
+            .execute(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBUpdateError)
+    }
+}
+
+/// Implementation of the TransferBatchInterface for the Storage struct.
+impl TransferBatchInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Creates a batch of transfers inside a single database transaction.
+    ///
+    /// Every leg passes the risk rules as a transfer of its own, counting the legs before it, so
+    /// a batch paying many recipients trips the fan-out rule like separate transfers would.
+    /// The legs are assessed in the database transaction that makes them. Legs flagged for
+    /// review are held as UNDER_REVIEW while the others complete. A blocked leg rejects the batch
+    /// and is recorded as a FAILED transfer outside of it.
+    ///
+    /// The sender and every receiver are locked up front, so a missing receiver or a sender that
+    /// cannot cover the total rejects the batch before any money moves. A failure on any leg
+    /// rolls back the whole batch, and nothing else is recorded for a rejected batch.
+    async fn create_transfer_batch(
+        &self,
+        batch: super::types::NewTransferBatch,
+        legs: Vec<super::types::NewTransaction>,
+    ) -> Result<
+        (super::types::TransferBatch, Vec<super::types::Transaction>),
+        ContainerError<Self::Error>,
+    > {
+        use crate::storage::schema::{risk_rule_hits, transactions, transfer_batches};
+
+        let total = batch.total().map_err(TransactionDbError::from)?;
+
+        if legs.iter().any(|leg| leg.recipient_id == batch.sender_id) {
+            return Err(TransactionDbError::SameAccount.into());
+        }
+
+        // Every leg is charged its own fee, out of the amount of the leg.
+        let fees = legs
+            .iter()
+            .map(|leg| {
+                let amount = leg.amount().map_err(TransactionDbError::from)?;
+                self.transfer_fee(&batch.sender_id, amount)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let limits = self.transfer_limits;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let mut earlier = Vec::with_capacity(legs.len());
+                    let mut reviews = Vec::with_capacity(legs.len());
+                    for leg in &legs {
+                        let assessment = self.assess_risk(conn, leg, &earlier).await?;
+                        let hits: Vec<NewRiskRuleHit> = assessment
+                            .hits
+                            .into_iter()
+                            .map(|hit| NewRiskRuleHit::new(&leg.transaction_id, hit))
+                            .collect();
+
+                        match assessment.decision {
+                            RiskDecision::Allow => reviews.push(None),
+                            RiskDecision::Review => reviews.push(Some(hits)),
+                            RiskDecision::Block => {
+                                let mut blocked = leg.clone();
+                                blocked.batch_id = None;
+                                block_transfer(conn, blocked, hits).await?;
+                                return Ok(Err(TransactionDbError::TransferBlocked.into()));
+                            }
+                        }
+
+                        earlier.push(RecentTransfer {
+                            sender_id: leg.sender_id.clone(),
+                            recipient_id: leg.recipient_id.clone(),
+                            amount_minor_units: leg.amount_minor_units,
+                            created_at: leg.created_at,
+                        });
+                    }
+
+                    let mut account_ids: Vec<&str> = legs
+                        .iter()
+                        .map(|leg| leg.recipient_id.as_str())
+                        .chain([batch.sender_id.as_str()])
+                        .collect();
+                    account_ids.sort_unstable();
+                    account_ids.dedup();
+
+                    let balances = lock_wallets(conn, &account_ids, total.currency()).await?;
+
+                    // Every leg counts against the limits of the sender as a transfer of its own.
+                    let amounts: Vec<i64> = legs.iter().map(|leg| leg.amount_minor_units).collect();
+                    check_transfer_limits(
+                        conn,
+                        &batch.sender_id,
+                        limits,
+                        total.currency(),
+                        &amounts,
+                    )
+                    .await?;
+
+                    available_balance(conn, &batch.sender_id, balances[&batch.sender_id])
+                        .await?
+                        .checked_sub(total)
+                        .map_err(TransactionDbError::from)?;
+
+                    let inserted_batch: TransferBatch =
+                        diesel::insert_into(transfer_batches::table)
+                            .values(&batch)
+                            .get_result(conn)
+                            .await?;
+
+                    let mut made = Vec::with_capacity(legs.len());
+                    for ((leg, fee), review) in legs.into_iter().zip(fees).zip(reviews) {
+                        let amount = leg.amount().map_err(TransactionDbError::from)?;
+                        let inserted_transaction: Transaction =
+                            diesel::insert_into(transactions::table)
+                                .values((leg, transactions::fee_minor_units.eq(fee.minor_units())))
+                                .get_result(conn)
+                                .await?;
+
+                        // Held like any transfer flagged for review; the sender's available
+                        // balance already covered it with the rest of the batch.
+                        if let Some(hits) = review {
+                            diesel::insert_into(risk_rule_hits::table)
+                                .values(hits)
+                                .execute(conn)
+                                .await?;
+                            made.push(
+                                transition_status(
+                                    conn,
+                                    &inserted_transaction.transaction_id,
+                                    TransactionStatus::UnderReview,
+                                    None,
+                                )
+                                .await?,
+                            );
+                            continue;
+                        }
+
+                        move_funds(
+                            conn,
+                            &inserted_transaction.transaction_id,
+                            &inserted_transaction.sender_id,
+                            &inserted_transaction.recipient_id,
+                            amount,
+                            fee,
+                        )
+                        .await?;
+
+                        made.push(
+                            transition_status(
+                                conn,
+                                &inserted_transaction.transaction_id,
+                                TransactionStatus::Completed,
+                                None,
+                            )
+                            .await?,
+                        );
+                    }
+
+                    Ok(Ok((inserted_batch, made)))
+                })
+            })
+            .await
+            .and_then(|made| made)
+    }
+
+    /// Retrieves a transfer batch by its ID, with its transactions in the order of the legs.
+    async fn get_transfer_batch(
+        &self,
+        _batch_id: &str,
+    ) -> Result<
+        (super::types::TransferBatch, Vec<super::types::Transaction>),
+        ContainerError<Self::Error>,
+    > {
+        use crate::storage::schema::{transactions, transfer_batches};
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let batch: TransferBatch = transfer_batches::table
+            .filter(transfer_batches::batch_id.eq(_batch_id))
+            .first(&mut conn)
+            .await?;
+
+        let legs = transactions::table
+            .filter(transactions::batch_id.eq(_batch_id))
+            .order(transactions::id)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        Ok((batch, legs))
+    }
+}
+
+/// Implementation of the TransferLimitInterface for the Storage struct.
+impl TransferLimitInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Retrieves the limits of a user, with the user's overrides applied to the defaults.
+    async fn get_transfer_limits(
+        &self,
+        _user_id: &str,
+    ) -> Result<TransferLimits, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        transfer_limits(&mut conn, _user_id, self.transfer_limits).await
+    }
+
+    /// Replaces the limit overrides of a user. Limits without an override are reset to the
+    /// defaults.
+    async fn set_user_limits(
+        &self,
+        limits: super::types::NewUserLimits,
+    ) -> Result<TransferLimits, ContainerError<Self::Error>> {
+        use crate::storage::schema::user_limits::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let stored: UserLimits = diesel::insert_into(user_limits)
+            .values(&limits)
+            .on_conflict(user_id)
+            .do_update()
+            .set(&limits)
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBUpdateError)?;
+
+        Ok(stored.apply_to(self.transfer_limits))
+    }
+
+    /// Retrieves what a user sent in a currency within the limit windows that contain `now`.
+    async fn get_limit_usage(
+        &self,
+        _user_id: &str,
+        currency: Currency,
+        now: time::PrimitiveDateTime,
+    ) -> Result<LimitUsage, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        limit_usage(&mut conn, _user_id, currency, now).await
+    }
+}
+
+/// Implementation of the DepositInterface for the Storage struct.
+impl DepositInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Creates a new deposit in the database.
+    async fn create_deposit(
+        &self,
+        deposit: super::types::NewDeposit,
+    ) -> Result<super::types::Deposit, ContainerError<Self::Error>> {
+        use crate::storage::schema::deposits::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::insert_into(deposits)
+            .values(deposit)
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBInsertError)
+    }
+
+    /// Retrieves a deposit by its ID.
+    async fn get_deposit(
+        &self,
+        _deposit_id: &str,
+    ) -> Result<super::types::Deposit, ContainerError<Self::Error>> {
+        use crate::storage::schema::deposits::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(deposits
+            .filter(deposit_id.eq(_deposit_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Completes a pending deposit. A DEPOSIT transaction from the system funding account
+    /// credits the user's balance and is written to the ledger in the same database
+    /// transaction that marks the deposit COMPLETED.
+    async fn complete_deposit(
+        &self,
+        _deposit_id: &str,
+        reference: String,
+    ) -> Result<super::types::Deposit, ContainerError<Self::Error>> {
+        use crate::storage::schema::deposits::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _deposit_id = _deposit_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let deposit: Deposit = deposits
+                        .filter(deposit_id.eq(&_deposit_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if !deposit.status.can_transition_to(DepositStatus::Completed) {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    let amount = deposit.amount().map_err(TransactionDbError::from)?;
+                    let credit: Transaction = {
+                        use crate::storage::schema::transactions::dsl::*;
+
+                        diesel::insert_into(transactions)
+                            .values(deposit.to_new_transaction())
+                            .get_result(conn)
+                            .await?
+                    };
+
+                    // Only the user's cached balance is kept; the funding account is tracked
+                    // by the ledger alone.
+                    let balances =
+                        lock_wallets(conn, &[&deposit.user_id], amount.currency()).await?;
+                    let balance = balances[&deposit.user_id]
+                        .checked_add(amount)
+                        .map_err(TransactionDbError::from)?;
+                    set_wallet_balance(conn, &deposit.user_id, balance).await?;
+
+                    post_journal(
+                        conn,
+                        vec![
+                            NewLedgerEntry::debit(
+                                &credit.transaction_id,
+                                consts::SYSTEM_FUNDING_ACCOUNT,
+                                amount,
+                            ),
+                            NewLedgerEntry::credit(
+                                &credit.transaction_id,
+                                &deposit.user_id,
+                                amount,
+                            ),
+                        ],
+                    )
+                    .await?;
+
+                    transition_status(
+                        conn,
+                        &credit.transaction_id,
+                        TransactionStatus::Completed,
+                        None,
+                    )
+                    .await?;
+
+                    Ok(diesel::update(deposits)
+                        .filter(deposit_id.eq(&_deposit_id))
+                        .set((
+                            status.eq(DepositStatus::Completed),
+                            source_reference.eq(reference),
+                            transaction_id.eq(credit.transaction_id),
+                            updated_at.eq(utils::datetime::now()),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Marks a pending deposit as FAILED. No money moves.
+    async fn fail_deposit(
+        &self,
+        _deposit_id: &str,
+        reason: String,
+    ) -> Result<super::types::Deposit, ContainerError<Self::Error>> {
+        use crate::storage::schema::deposits::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _deposit_id = _deposit_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current_status: DepositStatus = deposits
+                        .filter(deposit_id.eq(&_deposit_id))
+                        .select(status)
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if !current_status.can_transition_to(DepositStatus::Failed) {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    Ok(diesel::update(deposits)
+                        .filter(deposit_id.eq(&_deposit_id))
+                        .set((
+                            status.eq(DepositStatus::Failed),
+                            failure_reason.eq(reason),
+                            updated_at.eq(utils::datetime::now()),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+}
+
+/// Implementation of the BankAccountInterface for the Storage struct.
+impl BankAccountInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Links a new bank account, rejecting accounts the user already linked.
+    async fn create_bank_account(
+        &self,
+        bank_account: super::types::NewBankAccount,
+    ) -> Result<super::types::BankAccount, ContainerError<Self::Error>> {
+        use crate::storage::schema::bank_accounts::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        match diesel::insert_into(bank_accounts)
+            .values(bank_account)
+            .get_result(&mut conn)
+            .await
+        {
+            Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                Err(TransactionDbError::DuplicateBankAccount.into())
+            }
+            result => result.change_error(TransactionDbError::DBInsertError),
+        }
+    }
+
+    /// Retrieves a bank account by its ID.
+    async fn get_bank_account(
+        &self,
+        _bank_account_id: &str,
+    ) -> Result<super::types::BankAccount, ContainerError<Self::Error>> {
+        use crate::storage::schema::bank_accounts::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(bank_accounts
+            .filter(bank_account_id.eq(_bank_account_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the bank accounts of a user, oldest first.
+    async fn list_bank_accounts(
+        &self,
+        _user_id: &str,
+    ) -> Result<Vec<super::types::BankAccount>, ContainerError<Self::Error>> {
+        use crate::storage::schema::bank_accounts::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        bank_accounts
+            .filter(user_id.eq(_user_id))
+            .order((created_at.asc(), id.asc()))
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+}
+
+/// Implementation of the WalletInterface for the Storage struct.
+impl WalletInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Opens a new wallet, rejecting currencies the user already holds.
+    async fn create_wallet(
+        &self,
+        wallet: super::types::NewWallet,
+    ) -> Result<super::types::Wallet, ContainerError<Self::Error>> {
+        use crate::storage::schema::wallets::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _currency = wallet.currency.parse().map_err(TransactionDbError::from)?;
+
+        match diesel::insert_into(wallets)
+            .values(wallet)
+            .get_result(&mut conn)
+            .await
+        {
+            Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                Err(TransactionDbError::DuplicateWallet(_currency).into())
+            }
+            result => result.change_error(TransactionDbError::DBInsertError),
+        }
+    }
+
+    /// Retrieves the wallet of a user in a currency.
+    async fn get_wallet(
+        &self,
+        _user_id: &str,
+        _currency: Currency,
+    ) -> Result<super::types::Wallet, ContainerError<Self::Error>> {
+        use crate::storage::schema::wallets::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(wallets
+            .filter(user_id.eq(_user_id))
+            .filter(currency.eq(_currency.code()))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the wallets of a user, oldest first.
+    async fn list_wallets(
+        &self,
+        _user_id: &str,
+    ) -> Result<Vec<super::types::Wallet>, ContainerError<Self::Error>> {
+        use crate::storage::schema::wallets::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        wallets
+            .filter(user_id.eq(_user_id))
+            .order((created_at.asc(), id.asc()))
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+
+    /// Walks every wallet back from its balance, inside one repeatable read transaction so the
+    /// balances and the transactions agree, to each day after its latest snapshot, or since
+    /// it was opened. Snapshots another instance stored meanwhile are left as they are.
+    async fn snapshot_balances(
+        &self,
+        through: time::Date,
+    ) -> Result<usize, ContainerError<Self::Error>> {
+        use crate::storage::schema::{balance_snapshots, wallets};
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .repeatable_read()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let all_wallets = wallets::table.load::<super::types::Wallet>(conn).await?;
+                    let latest: HashMap<BalanceKey, time::Date> = balance_snapshots::table
+                        .group_by((balance_snapshots::account_id, balance_snapshots::currency))
+                        .select((
+                            balance_snapshots::account_id,
+                            balance_snapshots::currency,
+                            diesel::dsl::max(balance_snapshots::snapshot_date),
+                        ))
+                        .load::<(String, String, Option<time::Date>)>(conn)
+                        .await?
+                        .into_iter()
+                        .filter_map(|(account, code, date)| Some(((account, code), date?)))
+                        .collect();
+
+                    let pending = all_wallets
+                        .into_iter()
+                        .filter_map(|wallet| {
+                            let key = (wallet.user_id.clone(), wallet.currency.clone());
+                            let start = match latest.get(&key) {
+                                Some(date) => date.next_day()?,
+                                None => wallet.created_at.date(),
+                            };
+                            (start <= through).then_some((wallet, start))
+                        })
+                        .collect::<Vec<_>>();
+                    let Some(since) = pending.iter().map(|(_, start)| *start).min() else {
+                        return Ok(0);
+                    };
+
+                    let flows = daily_flows(conn, since.midnight(), None).await?;
+                    let no_flows = BTreeMap::new();
+                    let now = utils::datetime::now();
+                    let mut snapshots = Vec::new();
+                    for (wallet, start) in pending {
+                        let key = (wallet.user_id, wallet.currency);
+                        let days = std::iter::successors(Some(start), |day| day.next_day())
+                            .take_while(|day| *day <= through);
+                        let closing = balance_history::closing_balances(
+                            wallet.balance_minor_units,
+                            flows.get(&key).unwrap_or(&no_flows),
+                            days,
+                        )
+                        .ok_or(TransactionDbError::AmountOverflow)?;
+
+                        snapshots.extend(closing.into_iter().map(|(day, balance)| {
+                            NewBalanceSnapshot {
+                                account_id: key.0.clone(),
+                                currency: key.1.clone(),
+                                snapshot_date: day,
+                                closing_balance_minor_units: balance,
+                                created_at: now,
+                            }
+                        }));
+                    }
+
+                    let mut stored = 0;
+                    for chunk in snapshots.chunks(consts::BALANCE_SNAPSHOT_INSERT_BATCH_SIZE) {
+                        stored += diesel::insert_into(balance_snapshots::table)
+                            .values(chunk)
+                            .on_conflict_do_nothing()
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(stored)
+                })
+            })
+            .await
+    }
+
+    /// Reads the snapshots of the days and walks the wallet back from its balance to the days
+    /// without one, inside one repeatable read, read only transaction.
+    async fn get_closing_balances(
+        &self,
+        _user_id: &str,
+        _currency: Currency,
+        days: &[time::Date],
+    ) -> Result<BTreeMap<time::Date, i64>, ContainerError<Self::Error>> {
+        use crate::storage::schema::{balance_snapshots, wallets};
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let account = _user_id.to_string();
+        let days = days.to_vec();
+        conn.build_transaction()
+            .repeatable_read()
+            .read_only()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let balance: i64 = wallets::table
+                        .filter(wallets::user_id.eq(&account))
+                        .filter(wallets::currency.eq(_currency.code()))
+                        .select(wallets::balance_minor_units)
+                        .first(conn)
+                        .await?;
+
+                    let mut closing: BTreeMap<time::Date, i64> = balance_snapshots::table
+                        .filter(balance_snapshots::account_id.eq(&account))
+                        .filter(balance_snapshots::currency.eq(_currency.code()))
+                        .filter(balance_snapshots::snapshot_date.eq_any(&days))
+                        .select((
+                            balance_snapshots::snapshot_date,
+                            balance_snapshots::closing_balance_minor_units,
+                        ))
+                        .load::<(time::Date, i64)>(conn)
+                        .await?
+                        .into_iter()
+                        .collect();
+
+                    let missing = days
+                        .iter()
+                        .copied()
+                        .filter(|day| !closing.contains_key(day))
+                        .collect::<Vec<_>>();
+                    if let Some(earliest) = missing.iter().min() {
+                        // Only what moved after the earliest missing day is walked back through.
+                        let since = earliest
+                            .next_day()
+                            .map_or(time::PrimitiveDateTime::MAX, |day| day.midnight());
+                        let flows = daily_flows(conn, since, Some(&account))
+                            .await?
+                            .remove(&(account.clone(), _currency.code().to_string()))
+                            .unwrap_or_default();
+                        closing.extend(
+                            balance_history::closing_balances(balance, &flows, missing)
+                                .ok_or(TransactionDbError::AmountOverflow)?,
+                        );
+                    }
+
+                    Ok(closing)
+                })
+            })
+            .await
+    }
+}
+
+/// Implementation of the FxInterface for the Storage struct.
+impl FxInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Sets the rate of a currency pair, replacing its previous rate.
+    async fn set_fx_rate(
+        &self,
+        rate: super::types::NewFxRate,
+    ) -> Result<super::types::FxRate, ContainerError<Self::Error>> {
+        use crate::storage::schema::fx_rates::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::insert_into(fx_rates)
+            .values(&rate)
+            .on_conflict((base_currency, quote_currency))
+            .do_update()
+            .set((
+                rate_scaled.eq(rate.rate_scaled),
+                updated_at.eq(rate.updated_at),
+            ))
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBInsertError)
+    }
+
+    /// Retrieves the current rate of a currency pair.
+    async fn get_fx_rate(
+        &self,
+        base: Currency,
+        quote: Currency,
+    ) -> Result<super::types::FxRate, ContainerError<Self::Error>> {
+        use crate::storage::schema::fx_rates::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(fx_rates
+            .filter(base_currency.eq(base.code()))
+            .filter(quote_currency.eq(quote.code()))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the rates of all currency pairs, ordered by pair.
+    async fn list_fx_rates(
+        &self,
+    ) -> Result<Vec<super::types::FxRate>, ContainerError<Self::Error>> {
+        use crate::storage::schema::fx_rates::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        fx_rates
+            .order((base_currency.asc(), quote_currency.asc()))
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+
+    /// Creates a new quote in the database.
+    async fn create_fx_quote(
+        &self,
+        quote: super::types::NewFxQuote,
+    ) -> Result<super::types::FxQuote, ContainerError<Self::Error>> {
+        use crate::storage::schema::fx_quotes::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::insert_into(fx_quotes)
+            .values(quote)
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBInsertError)
+    }
+
+    /// Retrieves a quote by its ID.
+    async fn get_fx_quote(
+        &self,
+        _quote_id: &str,
+    ) -> Result<super::types::FxQuote, ContainerError<Self::Error>> {
+        use crate::storage::schema::fx_quotes::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(fx_quotes
+            .filter(quote_id.eq(_quote_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Executes a quote at its locked rate. A CONVERSION transaction to the system FX account
+    /// debits the source wallet and a linked CONVERSION transaction from it credits the target
+    /// wallet, with the spread credited to the platform revenue account as its fee. Both are
+    /// written to the ledger in the same database transaction that marks the quote EXECUTED.
+    async fn execute_fx_quote(
+        &self,
+        _quote_id: &str,
+    ) -> Result<super::types::FxQuote, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _quote_id = _quote_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move { execute_quote(conn, &_quote_id).await })
+            })
+            .await
+    }
+}
+
+/// Implementation of the WithdrawalInterface for the Storage struct.
+impl WithdrawalInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Creates a pending withdrawal. A WITHDRAWAL transaction to the system payouts account
+    /// debits the user's available balance and is written to the ledger in the same database
+    /// transaction, so the money cannot be spent while the payout is in flight. Withdrawals
+    /// count against the transfer limits of the user, checked while their wallet is locked.
+    async fn create_withdrawal(
+        &self,
+        withdrawal: super::types::NewWithdrawal,
+    ) -> Result<super::types::Withdrawal, ContainerError<Self::Error>> {
+        use crate::storage::schema::withdrawals::dsl::*;
+
+        let limits = self.transfer_limits;
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let amount = Money::new(
+                        withdrawal.amount_minor_units,
+                        withdrawal
+                            .currency
+                            .parse()
+                            .map_err(TransactionDbError::from)?,
+                    )
+                    .map_err(TransactionDbError::from)?;
+
+                    // Only the user's cached balance is kept; the payouts account is tracked
+                    // by the ledger alone.
+                    let balances =
+                        lock_wallets(conn, &[&withdrawal.user_id], amount.currency()).await?;
+
+                    // Checked before the debit is inserted, so it is not counted in the usage.
+                    check_transfer_limits(
+                        conn,
+                        &withdrawal.user_id,
+                        limits,
+                        amount.currency(),
+                        &[amount.minor_units()],
+                    )
+                    .await?;
+
+                    let debit: Transaction = {
+                        use crate::storage::schema::transactions::dsl::*;
+
+                        diesel::insert_into(transactions)
+                            .values(withdrawal.to_new_transaction())
+                            .get_result(conn)
+                            .await?
+                    };
+
+                    available_balance(conn, &withdrawal.user_id, balances[&withdrawal.user_id])
+                        .await?
+                        .checked_sub(amount)
+                        .map_err(TransactionDbError::from)?;
+                    let balance = balances[&withdrawal.user_id]
+                        .checked_sub(amount)
+                        .map_err(TransactionDbError::from)?;
+                    set_wallet_balance(conn, &withdrawal.user_id, balance).await?;
+
+                    post_journal(
+                        conn,
+                        vec![
+                            NewLedgerEntry::debit(
+                                &debit.transaction_id,
+                                &withdrawal.user_id,
+                                amount,
+                            ),
+                            NewLedgerEntry::credit(
+                                &debit.transaction_id,
+                                consts::SYSTEM_PAYOUTS_ACCOUNT,
+                                amount,
+                            ),
+                        ],
+                    )
+                    .await?;
+
+                    Ok(diesel::insert_into(withdrawals)
+                        .values(withdrawal)
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Retrieves a withdrawal by its ID.
+    async fn get_withdrawal(
+        &self,
+        _withdrawal_id: &str,
+    ) -> Result<super::types::Withdrawal, ContainerError<Self::Error>> {
+        use crate::storage::schema::withdrawals::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(withdrawals
+            .filter(withdrawal_id.eq(_withdrawal_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the withdrawals of a user with pagination, newest first.
+    async fn list_withdrawals(
+        &self,
+        _user_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<super::types::Withdrawal>, i64), ContainerError<Self::Error>> {
+        use crate::storage::schema::withdrawals::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let rows = withdrawals
+            .filter(user_id.eq(_user_id))
+            .order((created_at.desc(), id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        let total_count = withdrawals
+            .filter(user_id.eq(_user_id))
+            .count()
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        Ok((rows, total_count))
+    }
+
+    /// Completes a pending withdrawal once the connector paid it out. The money already left
+    /// the user's balance when the withdrawal was created.
+    async fn complete_withdrawal(
+        &self,
+        _withdrawal_id: &str,
+        reference: String,
+    ) -> Result<super::types::Withdrawal, ContainerError<Self::Error>> {
+        use crate::storage::schema::withdrawals::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _withdrawal_id = _withdrawal_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let withdrawal: Withdrawal = withdrawals
+                        .filter(withdrawal_id.eq(&_withdrawal_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if !withdrawal
+                        .status
+                        .can_transition_to(WithdrawalStatus::Completed)
+                    {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    transition_status(
+                        conn,
+                        &withdrawal.transaction_id,
+                        TransactionStatus::Completed,
+                        None,
+                    )
+                    .await?;
+
+                    Ok(diesel::update(withdrawals)
+                        .filter(withdrawal_id.eq(&_withdrawal_id))
+                        .set((
+                            status.eq(WithdrawalStatus::Completed),
+                            connector_reference.eq(reference),
+                            updated_at.eq(utils::datetime::now()),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Fails a pending withdrawal whose payout did not go through. The debit is reversed under
+    /// the journal of the WITHDRAWAL transaction, which is marked FAILED, so the money is back
+    /// in the user's balance and the transaction nets to nothing in the ledger.
+    async fn fail_withdrawal(
+        &self,
+        _withdrawal_id: &str,
+        reason: String,
+    ) -> Result<super::types::Withdrawal, ContainerError<Self::Error>> {
+        use crate::storage::schema::withdrawals::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _withdrawal_id = _withdrawal_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let withdrawal: Withdrawal = withdrawals
+                        .filter(withdrawal_id.eq(&_withdrawal_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if !withdrawal
+                        .status
+                        .can_transition_to(WithdrawalStatus::Failed)
+                    {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    let amount = withdrawal.amount().map_err(TransactionDbError::from)?;
+                    let balances =
+                        lock_wallets(conn, &[&withdrawal.user_id], amount.currency()).await?;
+                    let balance = balances[&withdrawal.user_id]
+                        .checked_add(amount)
+                        .map_err(TransactionDbError::from)?;
+                    set_wallet_balance(conn, &withdrawal.user_id, balance).await?;
+
+                    post_journal(
+                        conn,
+                        vec![
+                            NewLedgerEntry::debit(
+                                &withdrawal.transaction_id,
+                                consts::SYSTEM_PAYOUTS_ACCOUNT,
+                                amount,
+                            ),
+                            NewLedgerEntry::credit(
+                                &withdrawal.transaction_id,
+                                &withdrawal.user_id,
+                                amount,
+                            ),
+                        ],
+                    )
+                    .await?;
+
+                    transition_status(
+                        conn,
+                        &withdrawal.transaction_id,
+                        TransactionStatus::Failed,
+                        Some(reason.clone()),
+                    )
+                    .await?;
+
+                    Ok(diesel::update(withdrawals)
+                        .filter(withdrawal_id.eq(&_withdrawal_id))
+                        .set((
+                            status.eq(WithdrawalStatus::Failed),
+                            failure_reason.eq(reason),
+                            updated_at.eq(utils::datetime::now()),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Claims the oldest withdrawal still PENDING that was created before a time, so a payout
+    /// left without an outcome can be checked with its connector. Withdrawals claimed by
+    /// another worker are skipped until their claim runs out, and rows being claimed are
+    /// skipped rather than waited for, so concurrent workers claim different withdrawals.
+    async fn claim_stale_withdrawal(
+        &self,
+        created_before: time::PrimitiveDateTime,
+        claimed_until: time::PrimitiveDateTime,
+    ) -> Result<Option<super::types::Withdrawal>, ContainerError<Self::Error>> {
+        use crate::storage::schema::withdrawals::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let now = utils::datetime::now();
+                    let claimed: Option<i32> = withdrawals
+                        .filter(status.eq(WithdrawalStatus::Pending))
+                        .filter(created_at.lt(created_before))
+                        .filter(
+                            recovery_claimed_until
+                                .is_null()
+                                .or(recovery_claimed_until.le(now)),
+                        )
+                        .order((created_at.asc(), id.asc()))
+                        .select(id)
+                        .for_update()
+                        .skip_locked()
+                        .first(conn)
+                        .await
+                        .optional()?;
+
+                    let Some(claimed) = claimed else {
+                        return Ok(None);
+                    };
+
+                    Ok(Some(
+                        diesel::update(withdrawals)
+                            .filter(id.eq(claimed))
+                            .set(recovery_claimed_until.eq(claimed_until))
+                            .get_result(conn)
+                            .await?,
+                    ))
+                })
+            })
+            .await
+    }
+}
+
+/// Statuses of transactions whose money moved and stayed moved. Refunds and reversals are
+/// transactions of their own, so refunded and reversed transfers still count in full.
+const SETTLED_STATUSES: [TransactionStatus; 4] = [
+    TransactionStatus::Completed,
+    TransactionStatus::Refunded,
+    TransactionStatus::PartiallyRefunded,
+    TransactionStatus::Reversed,
+];
+
+/// Returns the sides of its account the filter lists transactions on.
+fn filtered_sides(filter: &TransactionFilter) -> Vec<TransactionDirection> {
+    match filter.direction {
+        Some(side) => vec![side],
+        None => vec![TransactionDirection::Sent, TransactionDirection::Received],
+    }
+}
+
+/// Selects the transactions the filter's account sent or received, depending on `side`, that
+/// match every condition it sets. Each side is its own query so it can read the index on its
+/// account column. When the filter lists both sides, transfers to itself only count as sent.
+fn filtered_transactions(
+    filter: &TransactionFilter,
+    side: TransactionDirection,
+) -> crate::storage::schema::transactions::BoxedQuery<'_, Pg> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    let account = filter.account_id.as_str();
+    let mut query = match side {
+        TransactionDirection::Sent => transactions.filter(sender_id.eq(account)).into_boxed(),
+        TransactionDirection::Received => {
+            transactions.filter(recipient_id.eq(account)).into_boxed()
+        }
+    };
+    match (side, filter.counterparty_id.as_deref()) {
+        (TransactionDirection::Sent, Some(counterparty)) => {
+            query = query.filter(recipient_id.eq(counterparty));
+        }
+        (TransactionDirection::Received, Some(counterparty)) => {
+            query = query.filter(sender_id.eq(counterparty));
+        }
+        (TransactionDirection::Sent, None) => {}
+        (TransactionDirection::Received, None) => {
+            if filter.direction.is_none() {
+                query = query.filter(sender_id.ne(account));
+            }
+        }
+    }
+
+    if let Some(_status) = filter.status {
+        query = query.filter(status.eq(_status));
+    }
+    if let Some(_currency) = filter.currency {
+        query = query.filter(currency.eq(_currency.code()));
+    }
+    if let Some(from) = filter.created_from {
+        query = query.filter(created_at.ge(from));
+    }
+    if let Some(to) = filter.created_to {
+        query = query.filter(created_at.lt(to));
+    }
+    if let Some(min) = filter.min_amount_minor_units {
+        query = query.filter(amount_minor_units.ge(min));
+    }
+    if let Some(max) = filter.max_amount_minor_units {
+        query = query.filter(amount_minor_units.le(max));
+    }
+    if let Some(pattern) = filter.search_pattern() {
+        query = query.filter(description.ilike(pattern));
+    }
+    if let Some(pairs) = filter.metadata.clone() {
+        query = query.filter(metadata.contains(pairs));
+    }
+
+    query
+}
+
+/// Filters the transactions that moved money: those in `SETTLED_STATUSES`, and withdrawals
+/// still PENDING, since their payout is in flight.
+fn moved_money()
+-> Box<dyn BoxableExpression<crate::storage::schema::transactions::table, Pg, SqlType = Bool>> {
+    use crate::storage::schema::transactions::dsl::*;
+
+    Box::new(
+        status.eq_any(SETTLED_STATUSES).or(transaction_type
+            .eq(TransactionType::Withdrawal)
+            .and(status.eq(TransactionStatus::Pending))),
+    )
+}
+
+/// Replays the balances of every account from the transactions that moved money: senders are
+/// debited the amount and recipients credited the amount less the fee.
+async fn replay_transactions(
+    conn: &mut AsyncPgConnection,
+) -> Result<HashMap<BalanceKey, i64>, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+    use diesel::dsl::sql;
+    use diesel::sql_types::BigInt;
+
+    let sent: Vec<(String, String, i64)> = transactions
+        .filter(moved_money())
+        .group_by((sender_id, currency))
+        .select((
+            sender_id,
+            currency,
+            sql::<BigInt>("SUM(amount_minor_units)::BIGINT"),
+        ))
+        .load(conn)
+        .await?;
+    let received: Vec<(String, String, i64)> = transactions
+        .filter(moved_money())
+        .group_by((recipient_id, currency))
+        .select((
+            recipient_id,
+            currency,
+            sql::<BigInt>("SUM(amount_minor_units - fee_minor_units)::BIGINT"),
+        ))
+        .load(conn)
+        .await?;
+
+    let mut balances = HashMap::<BalanceKey, i64>::new();
+    for (account, code, total) in sent {
+        *balances.entry((account, code)).or_default() -= total;
+    }
+    for (account, code, total) in received {
+        *balances.entry((account, code)).or_default() += total;
+    }
+
+    Ok(balances)
+}
+
+/// Sums, per account, currency and UTC day, what the transactions that moved money since
+/// `since` credited to each account less what they debited from it. Only covers `account`
+/// when one is given.
+async fn daily_flows(
+    conn: &mut AsyncPgConnection,
+    since: time::PrimitiveDateTime,
+    account: Option<&str>,
+) -> Result<HashMap<BalanceKey, BTreeMap<time::Date, i64>>, ContainerError<TransactionDbError>> {
+    use crate::storage::schema::transactions::dsl::*;
+    use diesel::dsl::sql;
+    use diesel::sql_types::{BigInt, Date, Text};
+
+    // Keeps the rows of `account` on one side of the transaction, or every row without one.
+    let involves = |side: Box<dyn BoxableExpression<_, Pg, SqlType = Bool>>| match account {
+        Some(_) => side,
+        None => Box::new(sql::<Bool>("TRUE")),
+    };
+    let account = account.unwrap_or_default().to_string();
+
+    // The rows are selected as raw SQL because Diesel cannot check a group by on the day.
+    let sent: Vec<(String, String, time::Date, i64)> = transactions
+        .filter(moved_money())
+        .filter(created_at.ge(since))
+        .filter(involves(Box::new(sender_id.eq(account.clone()))))
+        .group_by((sender_id, currency, sql::<Date>("created_at::date")))
+        .select(sql::<(Text, Text, Date, BigInt)>(
+            "sender_id, currency, created_at::date, SUM(amount_minor_units)::BIGINT",
+        ))
+        .load(conn)
+        .await?;
+    let received: Vec<(String, String, time::Date, i64)> = transactions
+        .filter(moved_money())
+        .filter(created_at.ge(since))
+        .filter(involves(Box::new(recipient_id.eq(account.clone()))))
+        .group_by((recipient_id, currency, sql::<Date>("created_at::date")))
+        .select(sql::<(Text, Text, Date, BigInt)>(
+            "recipient_id, currency, created_at::date, \
+             SUM(amount_minor_units - fee_minor_units)::BIGINT",
+        ))
+        .load(conn)
+        .await?;
+
+    let mut flows = HashMap::<BalanceKey, BTreeMap<time::Date, i64>>::new();
+    for (sign, rows) in [(-1, sent), (1, received)] {
+        for (_account, code, day, total) in rows {
+            let flow = flows
+                .entry((_account, code))
+                .or_default()
+                .entry(day)
+                .or_default();
+            *flow = flow
+                .checked_add(sign * total)
+                .ok_or(TransactionDbError::AmountOverflow)?;
+        }
+    }
+
+    Ok(flows)
+}
+
+/// Signed sum of ledger postings: credits add to an account's balance, debits take from it.
+const SIGNED_POSTINGS_SQL: &str = "SUM(CASE WHEN direction = 'CREDIT' THEN amount_minor_units \
+     ELSE -amount_minor_units END)::BIGINT";
+
+diesel::define_sql_function! {
+    /// Takes a transaction-level advisory lock if it is free, without waiting. The lock is
+    /// released when the transaction ends.
+    fn pg_try_advisory_xact_lock(key: diesel::sql_types::BigInt) -> Bool;
+}
+
+/// Checks every wallet against its history and the ledger in one repeatable read transaction,
+/// recording a run with the discrepancies found.
+async fn reconcile_balances(
+    conn: &mut AsyncPgConnection,
+) -> Result<(ReconciliationRun, Vec<ReconciliationFinding>), ContainerError<TransactionDbError>> {
+    conn.build_transaction()
+        .repeatable_read()
+        .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+            Box::pin(async move { reconcile_in(conn).await })
+        })
+        .await
+}
+
+/// Does what `reconcile_balances` does inside an open repeatable read transaction.
+async fn reconcile_in(
+    conn: &mut AsyncPgConnection,
+) -> Result<(ReconciliationRun, Vec<ReconciliationFinding>), ContainerError<TransactionDbError>> {
+    let started_at = utils::datetime::now();
+
+    let wallets: Vec<(String, String, i64)> = {
+        use crate::storage::schema::wallets::dsl::*;
+
+        wallets
+            .select((user_id, currency, balance_minor_units))
+            .load(conn)
+            .await?
+    };
+    let (ledger, adjustments) = ledger_balances(conn).await?;
+    let mut history = replay_transactions(conn).await?;
+    for (key, adjustment) in adjustments {
+        *history.entry(key).or_default() += adjustment;
+    }
+    let snapshot = BalanceSnapshot {
+        wallets: wallets
+            .into_iter()
+            .map(|(account, code, balance)| ((account, code), balance))
+            .collect(),
+        history,
+        ledger,
+    };
+    let discrepancies = snapshot.discrepancies();
+
+    let run = NewReconciliationRun {
+        run_id: format!("rcn_{}", utils::generate_nano_id(20)),
+        wallets_checked: snapshot.wallets.len() as i32,
+        findings_count: discrepancies.len() as i32,
+        started_at,
+        completed_at: utils::datetime::now(),
+    };
+    let findings = discrepancies
+        .into_iter()
+        .map(|discrepancy| {
+            NewReconciliationFinding::new(&run.run_id, discrepancy, run.completed_at)
+        })
+        .collect::<Vec<_>>();
+
+    let run: ReconciliationRun = {
+        use crate::storage::schema::reconciliation_runs::dsl::*;
+
+        diesel::insert_into(reconciliation_runs)
+            .values(run)
+            .get_result(conn)
+            .await?
+    };
+    if findings.is_empty() {
+        return Ok((run, Vec::new()));
+    }
+    let findings = {
+        use crate::storage::schema::reconciliation_findings::dsl::*;
+
+        diesel::insert_into(reconciliation_findings)
+            .values(findings)
+            .get_results(conn)
+            .await?
+    };
+
+    Ok((run, findings))
+}
+
+/// Sums the ledger postings of every account in each currency, returning the balances and,
+/// separately, the adjustments: postings of journals that belong to no transaction, such as
+/// opening balances.
+#[allow(clippy::type_complexity)]
+async fn ledger_balances(
+    conn: &mut AsyncPgConnection,
+) -> Result<(HashMap<BalanceKey, i64>, HashMap<BalanceKey, i64>), ContainerError<TransactionDbError>>
+{
+    use crate::storage::schema::{ledger_entries::dsl::*, transactions};
+    use diesel::dsl::{exists, not, sql};
+    use diesel::sql_types::BigInt;
+
+    let balances: Vec<(String, String, i64)> = ledger_entries
+        .group_by((account_id, currency))
+        .select((account_id, currency, sql::<BigInt>(SIGNED_POSTINGS_SQL)))
+        .load(conn)
+        .await?;
+    let adjustments: Vec<(String, String, i64)> = ledger_entries
+        .filter(not(exists(
+            transactions::table.filter(transactions::transaction_id.eq(journal_id)),
+        )))
+        .group_by((account_id, currency))
+        .select((account_id, currency, sql::<BigInt>(SIGNED_POSTINGS_SQL)))
+        .load(conn)
+        .await?;
+
+    let by_key = |rows: Vec<(String, String, i64)>| {
+        rows.into_iter()
+            .map(|(account, code, balance)| ((account, code), balance))
+            .collect()
+    };
+
+    Ok((by_key(balances), by_key(adjustments)))
+}
+
+/// Implementation of the ReconciliationInterface for the Storage struct.
+impl ReconciliationInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Reconciles every wallet inside one repeatable read transaction, so the balances,
+    /// transactions and ledger are compared as of the same moment while transfers go on.
+    async fn reconcile(
+        &self,
+    ) -> Result<(ReconciliationRun, Vec<ReconciliationFinding>), ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        reconcile_balances(&mut conn).await
+    }
+
+    /// Reconciles every wallet while holding the reconciliation advisory lock, skipping the run
+    /// when another connection holds the lock or a run started less than `min_interval` ago.
+    ///
+    /// The lock is taken inside the reconciliation transaction and released when it ends, so it
+    /// cannot outlive the run on a pooled connection. An instance that takes it next sees the
+    /// recorded run and skips, unless the run committed after its snapshot was taken, in the
+    /// same statement that took the lock; at worst that reconciles twice in an interval.
+    async fn reconcile_if_due(
+        &self,
+        min_interval: time::Duration,
+    ) -> Result<Option<(ReconciliationRun, Vec<ReconciliationFinding>)>, ContainerError<Self::Error>>
+    {
+        use crate::storage::schema::reconciliation_runs::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .repeatable_read()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let locked: bool =
+                        diesel::select(pg_try_advisory_xact_lock(consts::RECONCILIATION_LOCK_ID))
+                            .get_result(conn)
+                            .await?;
+                    if !locked {
+                        return Ok(None);
+                    }
+
+                    let latest: Option<time::PrimitiveDateTime> = reconciliation_runs
+                        .select(diesel::dsl::max(started_at))
+                        .first(conn)
+                        .await?;
+                    if latest.is_some_and(|latest| latest + min_interval > utils::datetime::now()) {
+                        return Ok(None);
+                    }
+
+                    reconcile_in(conn).await.map(Some)
+                })
+            })
+            .await
+    }
+
+    /// Lists the most recent reconciliation runs, newest first.
+    async fn list_reconciliation_runs(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<ReconciliationRun>, ContainerError<Self::Error>> {
+        use crate::storage::schema::reconciliation_runs::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        reconciliation_runs
+            .order((started_at.desc(), id.desc()))
+            .limit(limit)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+
+    /// Retrieves a reconciliation run by its ID.
+    async fn get_reconciliation_run(
+        &self,
+        _run_id: &str,
+    ) -> Result<ReconciliationRun, ContainerError<Self::Error>> {
+        use crate::storage::schema::reconciliation_runs::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(reconciliation_runs
+            .filter(run_id.eq(_run_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the findings of a reconciliation run, per-wallet findings first.
+    async fn list_reconciliation_findings(
+        &self,
+        _run_id: &str,
+    ) -> Result<Vec<ReconciliationFinding>, ContainerError<Self::Error>> {
+        use crate::storage::schema::reconciliation_findings::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        reconciliation_findings
+            .filter(run_id.eq(_run_id))
+            .order(id.asc())
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+}
+
+/// Implementation of the RiskInterface for the Storage struct.
+impl RiskInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Retrieves the age of the sender's account, how often it paid the recipient and the
+    /// transfers between them and from the sender within `lookback`. Transfers count once their
+    /// money moved or was held.
+    async fn get_risk_history(
+        &self,
+        _sender_id: &str,
+        _recipient_id: &str,
+        lookback: std::time::Duration,
+    ) -> Result<RiskHistory, ContainerError<Self::Error>> {
+        if _sender_id == _recipient_id {
+            return Err(TransactionDbError::SameAccount.into());
+        }
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        risk_history(&mut conn, _sender_id, _recipient_id, lookback).await
+    }
+
+    /// Runs the risk rules against a transfer, then makes it if they allow it, holds it as
+    /// UNDER_REVIEW if they flag it, or records it as FAILED and rejects it with
+    /// `TransferBlocked` if they block it.
+    async fn submit_transfer(
+        &self,
+        transaction: super::types::NewTransaction,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        // The assessment and the transfer share one database transaction, so the history the
+        // rules saw cannot change before the transfer is made. A rejected transfer commits its
+        // FAILED record.
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    match self.submit_transfer_in(conn, transaction).await {
+                        Err(err) if !err.get_inner().is_database_error() => Ok(Err(err)),
+                        result => result.map(Ok),
+                    }
+                })
+            })
+            .await
+            .and_then(|submitted| submitted)
+    }
+
+    /// Executes an FX quote and submits a transfer of its converted amount. The risk rules run
+    /// before anything moves, in the same database transaction as the conversion and the
+    /// transfer: if the transfer is rejected, the conversion is rolled back and the quote stays
+    /// OPEN. If the sender cannot cover either, a FAILED transfer is recorded instead.
+    async fn submit_fx_transfer(
+        &self,
+        quote_id: &str,
+        transaction: super::types::NewTransaction,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        let limits = self.transfer_limits;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _quote_id = quote_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let assessment = self.assess_risk(conn, &transaction, &[]).await?;
+                    let hits: Vec<NewRiskRuleHit> = assessment
+                        .hits
+                        .into_iter()
+                        .map(|hit| NewRiskRuleHit::new(&transaction.transaction_id, hit))
+                        .collect();
+
+                    if assessment.decision == RiskDecision::Block {
+                        block_transfer(conn, transaction, hits).await?;
+                        return Ok(Err(TransactionDbError::TransferBlocked.into()));
+                    }
+
+                    let amount = transaction.amount().map_err(TransactionDbError::from)?;
+                    let fee = self.transfer_fee(&transaction.sender_id, amount)?;
+                    let decision = assessment.decision;
+                    let failed_transaction = transaction.clone();
+
+                    // A savepoint, so that a rejected transfer rolls back the conversion too.
+                    let result = conn
+                        .transaction::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                            Box::pin(async move {
+                                execute_quote(conn, &_quote_id).await?;
+
+                                match decision {
+                                    RiskDecision::Review => {
+                                        hold_transfer(conn, transaction, amount, fee, limits, hits)
+                                            .await
+                                    }
+                                    _ => {
+                                        complete_transfer(conn, transaction, amount, fee, limits)
+                                            .await
+                                    }
+                                }
+                            })
+                        })
+                        .await;
+
+                    match result {
+                        Err(err) if *err.get_inner() == TransactionDbError::InsufficientBalance => {
+                            let failure = err.get_inner().to_string();
+                            insert_failed_transaction(conn, failed_transaction, failure).await?;
+
+                            Ok(Err(err))
+                        }
+                        result => result.map(Ok),
+                    }
+                })
+            })
+            .await
+            .and_then(|submitted| submitted)
+    }
+
+    /// Holds a transfer flagged by the risk rules as UNDER_REVIEW.
+    ///
+    /// Like an authorization, the hold reduces the sender's available balance and counts
+    /// against their limits without moving any money. The fee is fixed when the transfer is
+    /// held. If the sender cannot cover the amount, a FAILED transaction is recorded instead.
+    async fn hold_for_review(
+        &self,
+        transaction: super::types::NewTransaction,
+        hits: Vec<super::types::NewRiskRuleHit>,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        let amount = transaction.amount().map_err(TransactionDbError::from)?;
+
+        if transaction.sender_id == transaction.recipient_id {
+            return Err(TransactionDbError::SameAccount.into());
+        }
+
+        let fee = self.transfer_fee(&transaction.sender_id, amount)?;
+        let limits = self.transfer_limits;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let failed_transaction = transaction.clone();
+
+        let result = conn
+            .build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    hold_transfer(conn, transaction, amount, fee, limits, hits).await
+                })
+            })
+            .await;
+
+        match result {
+            Err(err) if *err.get_inner() == TransactionDbError::InsufficientBalance => {
+                let failure = err.get_inner().to_string();
+                record_failed_transaction(&mut conn, failed_transaction, failure).await?;
+
+                Err(err)
+            }
+            result => result,
+        }
+    }
+
+    /// Records a transfer blocked by the risk rules as FAILED, together with the rules that
+    /// blocked it. No money moves.
+    async fn block_transaction(
+        &self,
+        transaction: super::types::NewTransaction,
+        hits: Vec<super::types::NewRiskRuleHit>,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move { block_transfer(conn, transaction, hits).await })
+            })
+            .await
+    }
+
+    /// Lists the transfers under review, oldest first, with the rules that flagged them.
+    async fn list_risk_reviews(
+        &self,
+    ) -> Result<
+        Vec<(super::types::Transaction, Vec<super::types::RiskRuleHit>)>,
+        ContainerError<Self::Error>,
+    > {
+        use crate::storage::schema::risk_rule_hits;
+        use crate::storage::schema::transactions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let under_review: Vec<Transaction> = transactions
+            .filter(status.eq(TransactionStatus::UnderReview))
+            .order(created_at.asc())
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        let ids: Vec<&str> = under_review
+            .iter()
+            .map(|transaction| transaction.transaction_id.as_str())
+            .collect();
+        let hits: Vec<RiskRuleHit> = risk_rule_hits::table
+            .filter(risk_rule_hits::transaction_id.eq_any(ids))
+            .order(risk_rule_hits::id.asc())
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        let mut hits_by_transaction: HashMap<String, Vec<RiskRuleHit>> = HashMap::new();
+        for hit in hits {
+            hits_by_transaction
+                .entry(hit.transaction_id.clone())
+                .or_default()
+                .push(hit);
+        }
+
+        Ok(under_review
+            .into_iter()
+            .map(|transaction| {
+                let transaction_hits = hits_by_transaction
+                    .remove(&transaction.transaction_id)
+                    .unwrap_or_default();
+                (transaction, transaction_hits)
+            })
+            .collect())
+    }
+
+    /// Approves a transfer under review, releasing its hold and moving the money with the fee
+    /// fixed when it was held. A payment request the transfer pays is marked PAID. An
+    /// authorization under review becomes AUTHORIZED instead and keeps its hold until captured.
+    async fn approve_risk_review(
+        &self,
+        _transaction_id: &str,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _transaction_id = _transaction_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let held: Transaction = transactions
+                        .filter(transaction_id.eq(&_transaction_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if held.status != TransactionStatus::UnderReview {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    // An approved authorization keeps holding its amount until it is captured.
+                    if held.authorized_amount_minor_units.is_some() {
+                        return transition_status(
+                            conn,
+                            &_transaction_id,
+                            TransactionStatus::Authorized,
+                            None,
+                        )
+                        .await;
+                    }
+
+                    let amount = held.amount().map_err(TransactionDbError::from)?;
+                    let fee = held.fee().map_err(TransactionDbError::from)?;
+
+                    // Release the hold before moving the money so it is not counted against
+                    // the sender's available balance.
+                    let approved = transition_status(
+                        conn,
+                        &_transaction_id,
+                        TransactionStatus::Completed,
+                        None,
+                    )
+                    .await?;
+
+                    move_funds(
+                        conn,
+                        &approved.transaction_id,
+                        &approved.sender_id,
+                        &approved.recipient_id,
+                        amount,
+                        fee,
+                    )
+                    .await?;
+
+                    if let Some(request_id) = &approved.payment_request_id {
+                        use crate::storage::schema::payment_requests;
+
+                        diesel::update(payment_requests::table)
+                            .filter(payment_requests::payment_request_id.eq(request_id))
+                            .filter(payment_requests::status.eq(PaymentRequestStatus::Pending))
+                            .set((
+                                payment_requests::status.eq(PaymentRequestStatus::Paid),
+                                payment_requests::transaction_id.eq(&approved.transaction_id),
+                                payment_requests::updated_at.eq(utils::datetime::now()),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(approved)
+                })
+            })
+            .await
+    }
+
+    /// Rejects a transfer under review as FAILED, releasing its hold.
+    async fn reject_risk_review(
+        &self,
+        _transaction_id: &str,
+    ) -> Result<super::types::Transaction, ContainerError<Self::Error>> {
+        use crate::storage::schema::transactions::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _transaction_id = _transaction_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current_status: TransactionStatus = transactions
+                        .filter(transaction_id.eq(&_transaction_id))
+                        .select(status)
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    // Only transfers under review may be rejected, not other pending ones.
+                    if current_status != TransactionStatus::UnderReview {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    transition_status(
+                        conn,
+                        &_transaction_id,
+                        TransactionStatus::Failed,
+                        Some("Rejected in risk review".to_string()),
+                    )
+                    .await
+                })
+            })
+            .await
+    }
+}
+
+/// Implementation of the DisputeInterface for the Storage struct.
+impl DisputeInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Raises a dispute against a completed or partially refunded transfer.
+    ///
+    /// The transfer is locked while the dispute is raised, so it cannot be refunded meanwhile.
+    /// The disputed amount is what the recipient was credited less what they refunded, which
+    /// is frozen in their balance until the dispute is resolved. A dispute is only raised if
+    /// the recipient's available balance covers the whole amount, so a WON dispute can always
+    /// return it.
+    async fn create_dispute(
+        &self,
+        dispute: super::types::NewDispute,
+    ) -> Result<super::types::Dispute, ContainerError<Self::Error>> {
+        use crate::storage::schema::{disputes, transactions};
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let original: Transaction = transactions::table
+                        .filter(transactions::transaction_id.eq(&dispute.transaction_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+
+                    if original.transaction_type != TransactionType::Transfer
+                        || !original.status.is_refundable()
+                    {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    let disputed: bool = diesel::select(diesel::dsl::exists(
+                        disputes::table
+                            .filter(disputes::transaction_id.eq(&original.transaction_id)),
+                    ))
+                    .get_result(conn)
+                    .await?;
+                    if disputed {
+                        return Err(TransactionDbError::DuplicateDispute.into());
+                    }
+
+                    let net_amount = original.net_amount().map_err(TransactionDbError::from)?;
+                    let refunded = Money::new(
+                        refunded_minor_units(conn, &original.transaction_id).await?,
+                        net_amount.currency(),
+                    )
+                    .map_err(TransactionDbError::from)?;
+                    // Transfers whose credit was refunded in full leave nothing to dispute.
+                    let amount = net_amount
+                        .checked_sub(refunded)
+                        .ok()
+                        .filter(|amount| !amount.is_zero())
+                        .ok_or(TransactionDbError::InvalidStatusTransition)?;
+
+                    let recipient = original.recipient_id.as_str();
+                    let balances = lock_wallets(conn, &[recipient], amount.currency()).await?;
+                    available_balance(conn, recipient, balances[recipient])
+                        .await?
+                        .checked_sub(amount)
+                        .map_err(|err| match err {
+                            MoneyError::NegativeAmount => TransactionDbError::DisputeNotCovered,
+                            err => err.into(),
+                        })?;
+
+                    let now = utils::datetime::now();
+                    Ok(diesel::insert_into(disputes::table)
+                        .values((
+                            disputes::dispute_id.eq(dispute.dispute_id),
+                            disputes::transaction_id.eq(original.transaction_id),
+                            disputes::opened_by.eq(dispute.opened_by),
+                            disputes::sender_id.eq(original.sender_id),
+                            disputes::recipient_id.eq(original.recipient_id),
+                            disputes::amount_minor_units.eq(amount.minor_units()),
+                            disputes::currency.eq(original.currency),
+                            disputes::reason.eq(dispute.reason),
+                            disputes::evidence.eq(dispute.evidence),
+                            disputes::status.eq(DisputeStatus::Open),
+                            disputes::created_at.eq(now),
+                            disputes::updated_at.eq(now),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Retrieves a dispute by its ID.
+    async fn get_dispute(
+        &self,
+        _dispute_id: &str,
+    ) -> Result<super::types::Dispute, ContainerError<Self::Error>> {
+        use crate::storage::schema::disputes::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        Ok(disputes
+            .filter(dispute_id.eq(_dispute_id))
+            .first(&mut conn)
+            .await?)
+    }
+
+    /// Lists the disputes against transfers a user sent or received with pagination, newest
+    /// first.
+    async fn list_disputes(
+        &self,
+        _user_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<super::types::Dispute>, i64), ContainerError<Self::Error>> {
+        use crate::storage::schema::disputes::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let rows = disputes
+            .filter(sender_id.eq(_user_id).or(recipient_id.eq(_user_id)))
+            .order((created_at.desc(), id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        let total_count = disputes
+            .filter(sender_id.eq(_user_id).or(recipient_id.eq(_user_id)))
+            .count()
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)?;
+
+        Ok((rows, total_count))
+    }
+
+    /// Lists the disputes that are OPEN or UNDER_REVIEW, oldest first.
+    async fn list_open_disputes(
+        &self,
+    ) -> Result<Vec<super::types::Dispute>, ContainerError<Self::Error>> {
+        use crate::storage::schema::disputes::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        disputes
+            .filter(status.eq_any(OPEN_DISPUTE_STATUSES))
+            .order((created_at.asc(), id.asc()))
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+
+    /// Moves an OPEN dispute to UNDER_REVIEW. Its amount stays frozen.
+    async fn review_dispute(
+        &self,
+        _dispute_id: &str,
+    ) -> Result<super::types::Dispute, ContainerError<Self::Error>> {
+        use crate::storage::schema::disputes::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _dispute_id = _dispute_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current = lock_dispute(conn, &_dispute_id).await?;
+                    if !current.status.can_transition_to(DisputeStatus::UnderReview) {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    Ok(diesel::update(disputes)
+                        .filter(dispute_id.eq(&_dispute_id))
+                        .set((
+                            status.eq(DisputeStatus::UnderReview),
+                            updated_at.eq(utils::datetime::now()),
+                        ))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+
+    /// Resolves an open dispute, releasing its frozen amount.
+    ///
+    /// A WON dispute returns the frozen amount from the recipient to the sender through a
+    /// REVERSAL transaction linked to the transfer, without a fee, and the transfer moves to
+    /// REVERSED. The amount was frozen in full when the dispute was raised, so the recipient's
+    /// balance still covers it. As with refunds, the platform keeps the fee of the transfer, so
+    /// the sender gets back what the recipient was credited. A LOST dispute leaves the money with
+    /// the recipient.
+    async fn resolve_dispute(
+        &self,
+        _dispute_id: &str,
+        outcome: DisputeStatus,
+        note: Option<String>,
+    ) -> Result<super::types::Dispute, ContainerError<Self::Error>> {
+        use crate::storage::schema::{disputes, transactions};
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let _dispute_id = _dispute_id.to_string();
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    let current = lock_dispute(conn, &_dispute_id).await?;
+                    if !outcome.is_resolved() || !current.status.can_transition_to(outcome) {
+                        return Err(TransactionDbError::InvalidStatusTransition.into());
+                    }
+
+                    // Resolve the dispute before returning the money so its frozen amount is
+                    // not counted against the recipient's available balance.
+                    let now = utils::datetime::now();
+                    let resolved: Dispute = diesel::update(disputes::table)
+                        .filter(disputes::dispute_id.eq(&_dispute_id))
+                        .set((
+                            disputes::status.eq(outcome),
+                            disputes::resolution_note.eq(note),
+                            disputes::resolved_at.eq(now),
+                            disputes::updated_at.eq(now),
+                        ))
+                        .get_result(conn)
+                        .await?;
+
+                    if outcome != DisputeStatus::Won {
+                        return Ok(resolved);
+                    }
+
+                    let original: Transaction = transactions::table
+                        .filter(transactions::transaction_id.eq(&resolved.transaction_id))
+                        .for_update()
+                        .first(conn)
+                        .await?;
+                    let amount = resolved.amount().map_err(TransactionDbError::from)?;
+
+                    let reversal: Transaction = diesel::insert_into(transactions::table)
+                        .values(resolved.to_reversal_transaction(original.metadata.clone()))
+                        .get_result(conn)
+                        .await?;
+
+                    move_funds(
+                        conn,
+                        &reversal.transaction_id,
+                        &reversal.sender_id,
+                        &reversal.recipient_id,
+                        amount,
+                        Money::zero(amount.currency()),
+                    )
+                    .await?;
+
+                    transition_status(
+                        conn,
+                        &reversal.transaction_id,
+                        TransactionStatus::Completed,
+                        None,
+                    )
+                    .await?;
+                    transition_status(
+                        conn,
+                        &original.transaction_id,
+                        TransactionStatus::Reversed,
+                        None,
+                    )
+                    .await?;
+
+                    Ok(diesel::update(disputes::table)
+                        .filter(disputes::dispute_id.eq(&_dispute_id))
+                        .set(disputes::reversal_transaction_id.eq(reversal.transaction_id))
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await
+    }
+}
+
+/// Implementation of the LedgerInterface for the Storage struct.
+impl LedgerInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Retrieves the ledger postings of an account in a currency in the order they were
+    /// written.
+    async fn get_ledger_entries(
+        &self,
+        _account_id: &str,
+        _currency: Currency,
+        as_of: Option<time::PrimitiveDateTime>,
+    ) -> Result<Vec<super::types::LedgerEntry>, ContainerError<Self::Error>> {
+        use crate::storage::schema::ledger_entries::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let mut query = ledger_entries
+            .filter(account_id.eq(_account_id))
+            .filter(currency.eq(_currency.code()))
+            .order((created_at.asc(), id.asc()))
+            .into_boxed();
+
+        if let Some(as_of) = as_of {
+            query = query.filter(created_at.le(as_of));
+        }
+
+        query
+            .load(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+
+    /// Computes the balance of an account in a currency by summing its ledger postings.
+    async fn get_ledger_balance(
+        &self,
+        _account_id: &str,
+        _currency: Currency,
+        as_of: Option<time::PrimitiveDateTime>,
+    ) -> Result<i64, ContainerError<Self::Error>> {
+        use crate::storage::schema::ledger_entries::dsl::*;
+        use diesel::dsl::sql;
+        use diesel::sql_types::BigInt;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        let mut query = ledger_entries
+            .filter(account_id.eq(_account_id))
+            .filter(currency.eq(_currency.code()))
+            .select(sql::<BigInt>(
+                "COALESCE(SUM(CASE WHEN direction = 'CREDIT' THEN amount_minor_units \
+                 ELSE -amount_minor_units END), 0)::BIGINT",
+            ))
+            .into_boxed();
+
+        if let Some(as_of) = as_of {
+            query = query.filter(created_at.le(as_of));
+        }
+
+        query
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+
+    /// Finds the latest posting of an account in a currency that belongs to an opening balance
+    /// booked when the ledger was introduced. The adjustments account has one per user.
+    async fn get_ledger_opening(
+        &self,
+        _account_id: &str,
+        _currency: Currency,
+    ) -> Result<Option<time::PrimitiveDateTime>, ContainerError<Self::Error>> {
+        use crate::storage::schema::ledger_entries::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        ledger_entries
+            .filter(account_id.eq(_account_id))
+            .filter(currency.eq(_currency.code()))
+            .filter(journal_id.like("opening\\_%"))
+            .select(diesel::dsl::max(created_at))
+            .get_result(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBFilterError)
+    }
+}
+
+/// Implementation of the IdempotencyInterface for the Storage struct.
+impl IdempotencyInterface for Storage {
+    type Error = TransactionDbError;
+
+    /// Reserves an idempotency key, purging the user's expired keys first so they can be reused.
+    /// A key of the same request whose lease lapsed without a response is taken over.
+    async fn claim_idempotency_key(
+        &self,
+        key: super::types::NewIdempotencyKey,
+    ) -> Result<IdempotencyClaim, ContainerError<Self::Error>> {
+        use crate::storage::schema::idempotency_keys::dsl::*;
+        use diesel::OptionalExtension;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        conn.build_transaction()
+            .run::<_, ContainerError<TransactionDbError>, _>(|conn| {
+                Box::pin(async move {
+                    diesel::delete(idempotency_keys)
+                        .filter(user_id.eq(&key.user_id))
+                        .filter(expires_at.lt(key.created_at))
+                        .execute(conn)
+                        .await?;
+
+                    let claimed = diesel::insert_into(idempotency_keys)
+                        .values(&key)
+                        .on_conflict((user_id, idempotency_key))
+                        .do_nothing()
+                        .execute(conn)
+                        .await?;
+
+                    if claimed == 1 {
+                        return Ok(IdempotencyClaim::Claimed);
+                    }
+
+                    // The request that claimed the key died without a response, e.g. it was
+                    // cancelled or could not store it; a retry of the same request takes over.
+                    let taken_over = diesel::update(idempotency_keys)
+                        .filter(user_id.eq(&key.user_id))
+                        .filter(idempotency_key.eq(&key.idempotency_key))
+                        .filter(request_fingerprint.eq(&key.request_fingerprint))
+                        .filter(response_status_code.is_null())
+                        .filter(locked_until.lt(key.created_at))
+                        .set(locked_until.eq(key.locked_until))
+                        .execute(conn)
+                        .await?;
+
+                    if taken_over == 1 {
+                        return Ok(IdempotencyClaim::Claimed);
+                    }
+
+                    let existing = idempotency_keys
+                        .filter(user_id.eq(&key.user_id))
+                        .filter(idempotency_key.eq(&key.idempotency_key))
+                        .first(conn)
+                        .await
+                        .optional()?
+                        .ok_or(TransactionDbError::NotFoundError)?;
+
+                    Ok(IdempotencyClaim::Existing(existing))
+                })
+            })
+            .await
+    }
+
+    /// Stores the response of a completed request against its idempotency key.
+    async fn store_idempotent_response(
+        &self,
+        _user_id: &str,
+        _idempotency_key: &str,
+        _locked_until: time::PrimitiveDateTime,
+        status_code: i32,
+        body: serde_json::Value,
+    ) -> Result<(), ContainerError<Self::Error>> {
+        use crate::storage::schema::idempotency_keys::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::update(idempotency_keys)
+            .filter(user_id.eq(_user_id))
+            .filter(idempotency_key.eq(_idempotency_key))
+            .filter(locked_until.eq(_locked_until))
+            .set((
+                response_status_code.eq(status_code),
+                response_body.eq(body),
+                locked_until.eq(None::<time::PrimitiveDateTime>),
+            ))
+            .execute(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBUpdateError)?;
+
+        Ok(())
+    }
+
+    /// Deletes an idempotency key so that the request can be retried.
+    async fn release_idempotency_key(
+        &self,
+        _user_id: &str,
+        _idempotency_key: &str,
+        _locked_until: time::PrimitiveDateTime,
+    ) -> Result<(), ContainerError<Self::Error>> {
+        use crate::storage::schema::idempotency_keys::dsl::*;
+
+        let mut conn = self
+            .get_conn()
+            .await
+            .change_error(TransactionDbError::DBError)?;
+
+        diesel::delete(idempotency_keys)
+            .filter(user_id.eq(_user_id))
+            .filter(idempotency_key.eq(_idempotency_key))
+            .filter(locked_until.eq(_locked_until))
+            .filter(response_status_code.is_null())
+            .execute(&mut conn)
+            .await
+            .change_error(TransactionDbError::DBUpdateError)?;
+
+        Ok(())
     }
 }
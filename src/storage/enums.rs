@@ -0,0 +1,410 @@
+//! Enums stored as text columns in the database.
+
+/// Defines an enum that is stored as a text column and serialized as its SCREAMING_SNAKE_CASE
+/// name.
+macro_rules! text_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident => $value:literal,
+            )+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(
+            Debug,
+            Clone,
+            Copy,
+            PartialEq,
+            Eq,
+            Hash,
+            serde::Serialize,
+            serde::Deserialize,
+            diesel::AsExpression,
+            diesel::FromSqlRow,
+        )]
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                #[serde(rename = $value)]
+                $variant,
+            )+
+        }
+
+        impl $name {
+            /// Returns the value stored in the database.
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value,)+
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = crate::error::ValidationError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    $($value => Ok(Self::$variant),)+
+                    _ => Err(crate::error::ValidationError::InvalidValue {
+                        message: format!("Invalid {}: {value}", stringify!($name)),
+                    }),
+                }
+            }
+        }
+
+        impl diesel::serialize::ToSql<diesel::sql_types::Text, diesel::pg::Pg> for $name {
+            fn to_sql<'b>(
+                &'b self,
+                out: &mut diesel::serialize::Output<'b, '_, diesel::pg::Pg>,
+            ) -> diesel::serialize::Result {
+                <str as diesel::serialize::ToSql<diesel::sql_types::Text, diesel::pg::Pg>>::to_sql(
+                    self.as_str(),
+                    out,
+                )
+            }
+        }
+
+        impl diesel::deserialize::FromSql<diesel::sql_types::Text, diesel::pg::Pg> for $name {
+            fn from_sql(bytes: diesel::pg::PgValue<'_>) -> diesel::deserialize::Result<Self> {
+                let value = <String as diesel::deserialize::FromSql<
+                    diesel::sql_types::Text,
+                    diesel::pg::Pg,
+                >>::from_sql(bytes)?;
+                Ok(value.parse()?)
+            }
+        }
+    };
+}
+
+text_enum! {
+    /// Represents the side of a ledger posting.
+    pub enum LedgerDirection {
+        /// Decreases the balance of a user account.
+        Debit => "DEBIT",
+        /// Increases the balance of a user account.
+        Credit => "CREDIT",
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of a transaction.
+    pub enum TransactionStatus {
+        /// The transaction is recorded but money has not moved yet.
+        Pending => "PENDING",
+        /// The amount is held on the sender's account until it is captured or voided.
+        Authorized => "AUTHORIZED",
+        /// The transfer was flagged by the risk rules and its amount is held on the sender's
+        /// account until an admin approves or rejects it.
+        UnderReview => "UNDER_REVIEW",
+        /// Money has moved from the sender to the recipient.
+        Completed => "COMPLETED",
+        /// The transaction was rejected and no money moved.
+        Failed => "FAILED",
+        /// The authorization was voided and its hold released.
+        Voided => "VOIDED",
+        /// The authorization lapsed before it was captured and its hold was released.
+        Expired => "EXPIRED",
+        /// The money of a completed transaction was returned to the sender by a won dispute.
+        Reversed => "REVERSED",
+        /// The full amount of a completed transaction was refunded by the recipient.
+        Refunded => "REFUNDED",
+        /// Part of the amount of a completed transaction was refunded by the recipient.
+        PartiallyRefunded => "PARTIALLY_REFUNDED",
+    }
+}
+
+impl TransactionStatus {
+    /// Returns true if a transaction in this status may move to `next`.
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Completed)
+                | (Self::Pending, Self::Failed)
+                | (Self::Pending, Self::Authorized)
+                | (Self::Pending, Self::UnderReview)
+                | (Self::UnderReview, Self::Completed)
+                | (Self::UnderReview, Self::Authorized)
+                | (Self::UnderReview, Self::Failed)
+                | (Self::Authorized, Self::Completed)
+                | (Self::Authorized, Self::Voided)
+                | (Self::Authorized, Self::Expired)
+                | (Self::Completed, Self::Reversed)
+                | (Self::PartiallyRefunded, Self::Reversed)
+                | (Self::Completed, Self::Refunded)
+                | (Self::Completed, Self::PartiallyRefunded)
+                | (Self::PartiallyRefunded, Self::PartiallyRefunded)
+                | (Self::PartiallyRefunded, Self::Refunded)
+        )
+    }
+
+    /// Returns true if the transaction can still be refunded.
+    pub const fn is_refundable(self) -> bool {
+        matches!(self, Self::Completed | Self::PartiallyRefunded)
+    }
+}
+
+text_enum! {
+    /// Represents the kind of movement a transaction records.
+    pub enum TransactionType {
+        /// A transfer from the sender to the recipient.
+        Transfer => "TRANSFER",
+        /// Money returned by the recipient of a transfer to its sender.
+        Refund => "REFUND",
+        /// Money added to the recipient's balance from an external funding source, sent by the
+        /// system funding account.
+        Deposit => "DEPOSIT",
+        /// Moves money from a user's balance out to their bank account.
+        Withdrawal => "WITHDRAWAL",
+        /// One leg of a currency conversion, between a user and the system FX account.
+        Conversion => "CONVERSION",
+        /// Money returned by the recipient of a transfer to its sender when a dispute against
+        /// the transfer was won.
+        Reversal => "REVERSAL",
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of a scheduled transfer.
+    pub enum ScheduledTransferStatus {
+        /// The transfer is waiting for its execution time.
+        Scheduled => "SCHEDULED",
+        /// The transfer was executed and the money moved.
+        Executed => "EXECUTED",
+        /// The transfer was attempted but rejected, e.g. for insufficient balance.
+        Failed => "FAILED",
+        /// The transfer was cancelled before it was executed.
+        Cancelled => "CANCELLED",
+    }
+}
+
+text_enum! {
+    /// Represents how often a standing instruction repeats.
+    pub enum RecurrenceFrequency {
+        /// Every day.
+        Daily => "DAILY",
+        /// Every week.
+        Weekly => "WEEKLY",
+        /// Every month on a given day.
+        Monthly => "MONTHLY",
+        /// On every minute matching a cron expression.
+        Cron => "CRON",
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of a standing instruction.
+    pub enum StandingInstructionStatus {
+        /// The instruction runs whenever an occurrence is due.
+        Active => "ACTIVE",
+        /// The instruction was paused by the sender and does not run until resumed.
+        Paused => "PAUSED",
+        /// The instruction reached its end date or maximum number of occurrences.
+        Completed => "COMPLETED",
+        /// The recurrence of the instruction can no longer be read, so it does not run again.
+        Failed => "FAILED",
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of a payment request.
+    pub enum PaymentRequestStatus {
+        /// The payer has not answered the request yet.
+        Pending => "PENDING",
+        /// The payer paid the request and the money moved to the requester.
+        Paid => "PAID",
+        /// The payer declined the request.
+        Declined => "DECLINED",
+        /// The request lapsed before the payer answered it.
+        Expired => "EXPIRED",
+        /// The requester withdrew the request before the payer answered it.
+        Cancelled => "CANCELLED",
+    }
+}
+
+impl PaymentRequestStatus {
+    /// Returns true if a payment request in this status may move to `next`.
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Paid)
+                | (Self::Pending, Self::Declined)
+                | (Self::Pending, Self::Expired)
+                | (Self::Pending, Self::Cancelled)
+        )
+    }
+}
+
+text_enum! {
+    /// Represents what the risk rules decided about a transfer, from least to most severe.
+    pub enum RiskDecision {
+        /// The transfer may go ahead.
+        Allow => "ALLOW",
+        /// The transfer is held until an admin approves or rejects it.
+        Review => "REVIEW",
+        /// The transfer is rejected.
+        Block => "BLOCK",
+    }
+}
+
+impl RiskDecision {
+    /// Returns the more severe of two decisions.
+    pub const fn max(self, other: Self) -> Self {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+
+    const fn severity(self) -> u8 {
+        match self {
+            Self::Allow => 0,
+            Self::Review => 1,
+            Self::Block => 2,
+        }
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of a deposit.
+    pub enum DepositStatus {
+        /// The deposit was created and waits to be confirmed with the funding source.
+        Pending => "PENDING",
+        /// The funding source confirmed the charge and the user's balance was credited.
+        Completed => "COMPLETED",
+        /// The funding source declined the charge and no money moved.
+        Failed => "FAILED",
+    }
+}
+
+impl DepositStatus {
+    /// Returns true if a deposit in this status may move to `next`.
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Completed) | (Self::Pending, Self::Failed)
+        )
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of a withdrawal.
+    pub enum WithdrawalStatus {
+        /// The user's balance was debited and the payout was handed to the connector.
+        Pending => "PENDING",
+        /// The connector paid the money out to the bank account.
+        Completed => "COMPLETED",
+        /// The payout failed and the money was returned to the user's balance.
+        Failed => "FAILED",
+    }
+}
+
+impl WithdrawalStatus {
+    /// Returns true if a withdrawal in this status may move to `next`.
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Completed) | (Self::Pending, Self::Failed)
+        )
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of an FX quote. An open quote past its expiry can no longer be
+    /// executed.
+    pub enum FxQuoteStatus {
+        /// The quote locks its rate and waits to be executed.
+        Open => "OPEN",
+        /// The quote was executed and the money converted.
+        Executed => "EXECUTED",
+    }
+}
+
+impl FxQuoteStatus {
+    /// Returns true if a quote in this status may move to `next`.
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!((self, next), (Self::Open, Self::Executed))
+    }
+}
+
+text_enum! {
+    /// Represents the kind of discrepancy a reconciliation run found.
+    pub enum ReconciliationFindingKind {
+        /// The cached balance of a wallet differs from the balance replayed from its
+        /// transaction history and adjustments.
+        BalanceMismatch => "BALANCE_MISMATCH",
+        /// The cached balance of a wallet differs from the balance rebuilt from its ledger
+        /// postings.
+        LedgerMismatch => "LEDGER_MISMATCH",
+        /// The wallets of a currency hold more or less money than the system accounts paid
+        /// into them.
+        MoneyNotConserved => "MONEY_NOT_CONSERVED",
+    }
+}
+
+text_enum! {
+    /// Represents why a transfer is disputed.
+    pub enum DisputeReason {
+        /// The sender did not make the transfer.
+        Unauthorized => "UNAUTHORIZED",
+        /// The transfer repeats one that was already made.
+        Duplicate => "DUPLICATE",
+        /// The transfer moved a different amount than agreed.
+        IncorrectAmount => "INCORRECT_AMOUNT",
+        /// The transfer went to the wrong recipient, or was not meant for the recipient.
+        SentInError => "SENT_IN_ERROR",
+        /// The goods or services paid for were not delivered.
+        NotReceived => "NOT_RECEIVED",
+        /// Any other reason, explained in the evidence.
+        Other => "OTHER",
+    }
+}
+
+text_enum! {
+    /// Represents the lifecycle of a dispute. The disputed amount is frozen in the recipient's
+    /// balance until the dispute is resolved.
+    pub enum DisputeStatus {
+        /// The dispute was raised and waits for an admin.
+        Open => "OPEN",
+        /// An admin is reviewing the dispute.
+        UnderReview => "UNDER_REVIEW",
+        /// The dispute was upheld and the transfer reversed.
+        Won => "WON",
+        /// The dispute was rejected and the frozen funds released to the recipient.
+        Lost => "LOST",
+    }
+}
+
+impl DisputeStatus {
+    /// Returns true if a dispute in this status may move to `next`.
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Open, Self::UnderReview)
+                | (Self::Open, Self::Won)
+                | (Self::Open, Self::Lost)
+                | (Self::UnderReview, Self::Won)
+                | (Self::UnderReview, Self::Lost)
+        )
+    }
+
+    /// Returns true if the dispute is not resolved yet, so its amount is frozen.
+    pub const fn is_open(self) -> bool {
+        matches!(self, Self::Open | Self::UnderReview)
+    }
+
+    /// Returns true if the dispute was resolved by an admin.
+    pub const fn is_resolved(self) -> bool {
+        matches!(self, Self::Won | Self::Lost)
+    }
+}
@@ -1,5 +1,298 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    balance_snapshots (id) {
+        id -> Int4,
+        #[max_length = 64]
+        account_id -> Varchar,
+        #[max_length = 3]
+        currency -> Varchar,
+        snapshot_date -> Date,
+        closing_balance_minor_units -> Int8,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    bank_accounts (id) {
+        id -> Int4,
+        #[max_length = 64]
+        bank_account_id -> Varchar,
+        #[max_length = 64]
+        user_id -> Varchar,
+        #[max_length = 255]
+        account_holder_name -> Varchar,
+        #[max_length = 18]
+        account_number -> Varchar,
+        #[max_length = 11]
+        ifsc -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    deposits (id) {
+        id -> Int4,
+        #[max_length = 64]
+        deposit_id -> Varchar,
+        #[max_length = 64]
+        user_id -> Varchar,
+        amount_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        #[max_length = 32]
+        status -> Varchar,
+        #[max_length = 32]
+        funding_source -> Varchar,
+        #[max_length = 255]
+        source_reference -> Nullable<Varchar>,
+        failure_reason -> Nullable<Text>,
+        #[max_length = 64]
+        transaction_id -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    disputes (id) {
+        id -> Int4,
+        #[max_length = 64]
+        dispute_id -> Varchar,
+        #[max_length = 64]
+        transaction_id -> Varchar,
+        #[max_length = 64]
+        opened_by -> Varchar,
+        #[max_length = 64]
+        sender_id -> Varchar,
+        #[max_length = 64]
+        recipient_id -> Varchar,
+        amount_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        #[max_length = 32]
+        reason -> Varchar,
+        evidence -> Text,
+        #[max_length = 32]
+        status -> Varchar,
+        resolution_note -> Nullable<Text>,
+        #[max_length = 64]
+        reversal_transaction_id -> Nullable<Varchar>,
+        resolved_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fx_quotes (id) {
+        id -> Int4,
+        #[max_length = 64]
+        quote_id -> Varchar,
+        #[max_length = 64]
+        user_id -> Varchar,
+        #[max_length = 3]
+        source_currency -> Varchar,
+        #[max_length = 3]
+        target_currency -> Varchar,
+        source_amount_minor_units -> Int8,
+        target_amount_minor_units -> Int8,
+        spread_minor_units -> Int8,
+        mid_rate_scaled -> Int8,
+        spread_basis_points -> Int4,
+        rate_updated_at -> Timestamp,
+        #[max_length = 32]
+        status -> Varchar,
+        expires_at -> Timestamp,
+        #[max_length = 64]
+        debit_transaction_id -> Nullable<Varchar>,
+        #[max_length = 64]
+        credit_transaction_id -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    fx_rates (id) {
+        id -> Int4,
+        #[max_length = 3]
+        base_currency -> Varchar,
+        #[max_length = 3]
+        quote_currency -> Varchar,
+        rate_scaled -> Int8,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    idempotency_keys (id) {
+        id -> Int4,
+        #[max_length = 64]
+        user_id -> Varchar,
+        #[max_length = 255]
+        idempotency_key -> Varchar,
+        #[max_length = 64]
+        request_fingerprint -> Varchar,
+        response_status_code -> Nullable<Int4>,
+        response_body -> Nullable<Jsonb>,
+        created_at -> Timestamp,
+        expires_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    ledger_entries (id) {
+        id -> Int8,
+        #[max_length = 64]
+        entry_id -> Varchar,
+        #[max_length = 64]
+        journal_id -> Varchar,
+        #[max_length = 64]
+        account_id -> Varchar,
+        #[max_length = 8]
+        direction -> Varchar,
+        amount_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    payment_requests (id) {
+        id -> Int4,
+        #[max_length = 64]
+        payment_request_id -> Varchar,
+        #[max_length = 64]
+        requester_id -> Varchar,
+        #[max_length = 64]
+        payer_id -> Varchar,
+        amount_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        note -> Nullable<Text>,
+        #[max_length = 32]
+        status -> Varchar,
+        #[max_length = 64]
+        transaction_id -> Nullable<Varchar>,
+        expires_at -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    reconciliation_findings (id) {
+        id -> Int4,
+        #[max_length = 64]
+        finding_id -> Varchar,
+        #[max_length = 64]
+        run_id -> Varchar,
+        #[max_length = 32]
+        kind -> Varchar,
+        #[max_length = 64]
+        account_id -> Nullable<Varchar>,
+        #[max_length = 3]
+        currency -> Varchar,
+        expected_minor_units -> Int8,
+        actual_minor_units -> Int8,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    reconciliation_runs (id) {
+        id -> Int4,
+        #[max_length = 64]
+        run_id -> Varchar,
+        wallets_checked -> Int4,
+        findings_count -> Int4,
+        started_at -> Timestamp,
+        completed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    risk_rule_hits (id) {
+        id -> Int4,
+        #[max_length = 64]
+        transaction_id -> Varchar,
+        #[max_length = 64]
+        rule -> Varchar,
+        #[max_length = 16]
+        decision -> Varchar,
+        score -> Int4,
+        reason -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    scheduled_transfers (id) {
+        id -> Int4,
+        #[max_length = 64]
+        scheduled_transfer_id -> Varchar,
+        #[max_length = 64]
+        transaction_id -> Varchar,
+        #[max_length = 64]
+        sender_id -> Varchar,
+        #[max_length = 64]
+        recipient_id -> Varchar,
+        amount_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        description -> Nullable<Text>,
+        execute_at -> Timestamp,
+        #[max_length = 32]
+        status -> Varchar,
+        failure_reason -> Nullable<Text>,
+        executed_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        metadata -> Nullable<Jsonb>,
+        attempts -> Int4,
+        retry_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    standing_instructions (id) {
+        id -> Int4,
+        #[max_length = 64]
+        standing_instruction_id -> Varchar,
+        #[max_length = 64]
+        sender_id -> Varchar,
+        #[max_length = 64]
+        recipient_id -> Varchar,
+        amount_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        description -> Nullable<Text>,
+        #[max_length = 32]
+        frequency -> Varchar,
+        day_of_month -> Nullable<Int2>,
+        #[max_length = 255]
+        cron_expression -> Nullable<Varchar>,
+        start_at -> Timestamp,
+        end_at -> Nullable<Timestamp>,
+        max_occurrences -> Nullable<Int4>,
+        occurrences_count -> Int4,
+        next_run_at -> Nullable<Timestamp>,
+        last_run_at -> Nullable<Timestamp>,
+        last_failure_reason -> Nullable<Text>,
+        #[max_length = 32]
+        status -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        metadata -> Nullable<Jsonb>,
+        attempts -> Int4,
+        retry_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     transactions (id) {
         id -> Int4,
@@ -9,12 +302,59 @@ diesel::table! {
         sender_id -> Varchar,
         #[max_length = 64]
         recipient_id -> Varchar,
-        amount_in_rs -> Float8,
+        amount_minor_units -> Int8,
         description -> Nullable<Text>,
         created_at -> Timestamp,
         #[max_length = 32]
         status -> Varchar,
         updated_at -> Timestamp,
+        #[max_length = 3]
+        currency -> Varchar,
+        failure_reason -> Nullable<Text>,
+        #[max_length = 32]
+        transaction_type -> Varchar,
+        #[max_length = 64]
+        parent_transaction_id -> Nullable<Varchar>,
+        authorized_amount_minor_units -> Nullable<Int8>,
+        expires_at -> Nullable<Timestamp>,
+        #[max_length = 64]
+        standing_instruction_id -> Nullable<Varchar>,
+        #[max_length = 64]
+        payment_request_id -> Nullable<Varchar>,
+        #[max_length = 64]
+        batch_id -> Nullable<Varchar>,
+        metadata -> Nullable<Jsonb>,
+        fee_minor_units -> Int8,
+    }
+}
+
+diesel::table! {
+    transfer_batches (id) {
+        id -> Int4,
+        #[max_length = 64]
+        batch_id -> Varchar,
+        #[max_length = 64]
+        sender_id -> Varchar,
+        total_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        leg_count -> Int4,
+        description -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_limits (id) {
+        id -> Int4,
+        #[max_length = 64]
+        user_id -> Varchar,
+        max_transfer_minor_units -> Nullable<Int8>,
+        daily_outgoing_minor_units -> Nullable<Int8>,
+        monthly_outgoing_minor_units -> Nullable<Int8>,
+        transfers_per_hour -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -29,13 +369,70 @@ diesel::table! {
         name -> Varchar,
         #[max_length = 255]
         password -> Varchar,
-        balance_in_rs -> Float8,
         created_at -> Timestamp,
         last_modified_at -> Timestamp,
     }
 }
 
+diesel::table! {
+    wallets (id) {
+        id -> Int4,
+        #[max_length = 64]
+        user_id -> Varchar,
+        #[max_length = 3]
+        currency -> Varchar,
+        balance_minor_units -> Int8,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    withdrawals (id) {
+        id -> Int4,
+        #[max_length = 64]
+        withdrawal_id -> Varchar,
+        #[max_length = 64]
+        user_id -> Varchar,
+        #[max_length = 64]
+        bank_account_id -> Varchar,
+        amount_minor_units -> Int8,
+        #[max_length = 3]
+        currency -> Varchar,
+        #[max_length = 32]
+        status -> Varchar,
+        #[max_length = 32]
+        connector -> Varchar,
+        #[max_length = 255]
+        connector_reference -> Nullable<Varchar>,
+        failure_reason -> Nullable<Text>,
+        #[max_length = 64]
+        transaction_id -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        recovery_claimed_until -> Nullable<Timestamp>,
+    }
+}
+
 diesel::allow_tables_to_appear_in_same_query!(
+    balance_snapshots,
+    bank_accounts,
+    deposits,
+    disputes,
+    fx_quotes,
+    fx_rates,
+    idempotency_keys,
+    ledger_entries,
+    payment_requests,
+    reconciliation_findings,
+    reconciliation_runs,
+    risk_rule_hits,
+    scheduled_transfers,
+    standing_instructions,
     transactions,
+    transfer_batches,
+    user_limits,
     users,
+    wallets,
+    withdrawals,
 );
@@ -5,8 +5,8 @@ use crate::{
         container::{ContainerError, ResultContainerExt},
     },
     routes::{api_models, user::password},
-    storage,
-    types::{self, Claims},
+    statement, storage,
+    types::{self, Claims, Money},
     utils,
 };
 
@@ -20,7 +20,6 @@ impl TryFrom<api_models::SignUpRequest> for storage::types::UserNew {
             name: new_user.name,
             password: password::generate_password_hash(new_user.password.0)
                 .change_error(error::ApiError::UnknownError("Failed to hash password"))?,
-            balance_in_rs: 0.0,
         };
         Ok(user)
     }
@@ -52,13 +51,43 @@ impl TryFrom<&storage::types::User> for Claims {
     }
 }
 
-impl From<storage::types::User> for api_models::GetUserResponse {
-    fn from(value: storage::types::User) -> Self {
+/// Builds the balance of a wallet from the wallet and the amount held on it by active
+/// authorizations.
+impl TryFrom<(storage::types::Wallet, i64)> for api_models::WalletBalanceResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(
+        (value, held_minor_units): (storage::types::Wallet, i64),
+    ) -> Result<Self, Self::Error> {
+        let balance = value
+            .balance()
+            .change_error(ApiError::UnknownError("Invalid balance stored for wallet"))?;
+        let held = Money::new(held_minor_units, balance.currency())
+            .change_error(ApiError::UnknownError("Invalid held amount for wallet"))?;
+        // Holds can outgrow the balance if it was lowered after they were placed.
+        let available = balance
+            .checked_sub(held)
+            .unwrap_or(Money::zero(balance.currency()));
+        Ok(Self {
+            currency: balance.currency(),
+            ledger_balance: balance.into(),
+            available_balance: available.into(),
+        })
+    }
+}
+
+/// Builds the user response from the user and the balances of their wallets.
+impl From<(storage::types::User, Vec<api_models::WalletBalanceResponse>)>
+    for api_models::GetUserResponse
+{
+    fn from(
+        (value, balances): (storage::types::User, Vec<api_models::WalletBalanceResponse>),
+    ) -> Self {
         Self {
             user_id: value.user_id,
             email: value.email,
             name: value.name,
-            balance_in_rs: value.balance_in_rs,
+            balances,
             created_at: value.created_at.to_string(),
             last_modified_at: value.last_modified_at.to_string(),
         }
@@ -70,7 +99,484 @@ impl From<storage::types::User> for api_models::UpdateUserResponse {
         Self {
             user_id: value.user_id,
             name: value.name,
-            balance_in_rs: value.balance_in_rs,
+        }
+    }
+}
+
+impl TryFrom<storage::types::Transaction> for api_models::GetTransactionResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::Transaction) -> Result<Self, Self::Error> {
+        let amount = value.amount().change_error(ApiError::UnknownError(
+            "Invalid amount stored for transaction",
+        ))?;
+        let fee = value
+            .fee()
+            .change_error(ApiError::UnknownError("Invalid fee stored for transaction"))?;
+        let net_amount = value
+            .net_amount()
+            .change_error(ApiError::UnknownError("Invalid fee stored for transaction"))?;
+        let authorized_amount = value
+            .authorized_amount()
+            .change_error(ApiError::UnknownError(
+                "Invalid authorized amount stored for transaction",
+            ))?;
+        Ok(Self {
+            transaction_id: value.transaction_id,
+            sender_id: value.sender_id,
+            receiver_id: value.recipient_id,
+            amount: amount.into(),
+            fee: fee.into(),
+            net_amount: net_amount.into(),
+            description: value.description,
+            metadata: value.metadata,
+            status: value.status,
+            failure_reason: value.failure_reason,
+            transaction_type: value.transaction_type,
+            parent_transaction_id: value.parent_transaction_id,
+            authorized_amount: authorized_amount.map(Into::into),
+            expires_at: value.expires_at.map(|expires_at| expires_at.to_string()),
+            standing_instruction_id: value.standing_instruction_id,
+            payment_request_id: value.payment_request_id,
+            batch_id: value.batch_id,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl TryFrom<storage::types::ScheduledTransfer> for api_models::ScheduledTransferResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::ScheduledTransfer) -> Result<Self, Self::Error> {
+        let amount = value.amount().change_error(ApiError::UnknownError(
+            "Invalid amount stored for scheduled transfer",
+        ))?;
+        Ok(Self {
+            scheduled_transfer_id: value.scheduled_transfer_id,
+            transaction_id: value.transaction_id,
+            sender_id: value.sender_id,
+            receiver_id: value.recipient_id,
+            amount: amount.into(),
+            description: value.description,
+            metadata: value.metadata,
+            execute_at: value.execute_at.to_string(),
+            status: value.status,
+            failure_reason: value.failure_reason,
+            executed_at: value.executed_at.map(|executed_at| executed_at.to_string()),
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl TryFrom<storage::types::StandingInstruction> for api_models::StandingInstructionResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::StandingInstruction) -> Result<Self, Self::Error> {
+        let amount = value.amount().change_error(ApiError::UnknownError(
+            "Invalid amount stored for standing instruction",
+        ))?;
+        let recurrence = value.recurrence().change_error(ApiError::UnknownError(
+            "Invalid recurrence stored for standing instruction",
+        ))?;
+        Ok(Self {
+            standing_instruction_id: value.standing_instruction_id,
+            sender_id: value.sender_id,
+            receiver_id: value.recipient_id,
+            amount: amount.into(),
+            description: value.description,
+            metadata: value.metadata,
+            recurrence,
+            start_at: value.start_at.to_string(),
+            end_at: value.end_at.map(|end_at| end_at.to_string()),
+            max_occurrences: value
+                .max_occurrences
+                .map(|max_occurrences| max_occurrences.unsigned_abs()),
+            occurrences_count: value.occurrences_count.unsigned_abs(),
+            next_run_at: value.next_run_at.map(|next_run_at| next_run_at.to_string()),
+            last_run_at: value.last_run_at.map(|last_run_at| last_run_at.to_string()),
+            last_failure_reason: value.last_failure_reason,
+            status: value.status,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl TryFrom<storage::types::PaymentRequest> for api_models::PaymentRequestResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::PaymentRequest) -> Result<Self, Self::Error> {
+        let amount = value.amount().change_error(ApiError::UnknownError(
+            "Invalid amount stored for payment request",
+        ))?;
+        Ok(Self {
+            payment_request_id: value.payment_request_id,
+            requester_id: value.requester_id,
+            payer_id: value.payer_id,
+            amount: amount.into(),
+            note: value.note,
+            status: value.status,
+            transaction_id: value.transaction_id,
+            expires_at: value.expires_at.to_string(),
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl
+    TryFrom<(
+        storage::types::TransferBatch,
+        Vec<storage::types::Transaction>,
+    )> for api_models::BatchTransferResponse
+{
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(
+        (batch, transactions): (
+            storage::types::TransferBatch,
+            Vec<storage::types::Transaction>,
+        ),
+    ) -> Result<Self, Self::Error> {
+        let total_amount = batch.total().change_error(ApiError::UnknownError(
+            "Invalid total stored for transfer batch",
+        ))?;
+        Ok(Self {
+            batch_id: batch.batch_id,
+            sender_id: batch.sender_id,
+            total_amount: total_amount.into(),
+            leg_count: batch.leg_count.unsigned_abs(),
+            description: batch.description,
+            transactions: transactions
+                .into_iter()
+                .map(api_models::GetTransactionResponse::try_from)
+                .collect::<Result<_, _>>()?,
+            created_at: batch.created_at.to_string(),
+        })
+    }
+}
+
+impl
+    TryFrom<(
+        types::TransferLimits,
+        types::limits::LimitUsage,
+        types::limits::LimitWindows,
+    )> for api_models::TransferLimitsResponse
+{
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(
+        (limits, usage, windows): (
+            types::TransferLimits,
+            types::limits::LimitUsage,
+            types::limits::LimitWindows,
+        ),
+    ) -> Result<Self, Self::Error> {
+        let amount = |minor_units: i64| {
+            Money::new(minor_units, types::Currency::Inr)
+                .map(api_models::AmountResponse::from)
+                .change_error(ApiError::UnknownError("Invalid transfer limit for user"))
+        };
+        // Usage can exceed a limit that was lowered after the money was sent.
+        let amount_limit = |limit: i64, used: i64, resets_at: time::PrimitiveDateTime| {
+            Ok::<_, ContainerError<ApiError>>(api_models::AmountLimitResponse {
+                limit: amount(limit)?,
+                used: amount(used)?,
+                remaining: amount(limit.saturating_sub(used).max(0))?,
+                resets_at: resets_at.to_string(),
+            })
+        };
+
+        Ok(Self {
+            max_transfer: amount(limits.max_transfer_minor_units)?,
+            daily_outgoing: amount_limit(
+                limits.daily_outgoing_minor_units,
+                usage.daily_outgoing_minor_units,
+                windows.day_end(),
+            )?,
+            monthly_outgoing: amount_limit(
+                limits.monthly_outgoing_minor_units,
+                usage.monthly_outgoing_minor_units,
+                windows.month_end(),
+            )?,
+            transfers_per_hour: api_models::CountLimitResponse {
+                limit: limits.transfers_per_hour,
+                used: usage.transfers_last_hour,
+                remaining: limits
+                    .transfers_per_hour
+                    .saturating_sub(usage.transfers_last_hour)
+                    .max(0),
+            },
+        })
+    }
+}
+
+impl TryFrom<(String, api_models::UpdateUserLimitsRequest)> for storage::types::NewUserLimits {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(
+        (user_id, value): (String, api_models::UpdateUserLimitsRequest),
+    ) -> Result<Self, Self::Error> {
+        let minor_units = |amount: Option<api_models::AmountRequest>| {
+            amount
+                .map(|amount| amount.to_money(types::Currency::Inr))
+                .transpose()
+                .map(|money| money.map(|money| money.minor_units()))
+                .change_error(ApiError::ValidationError)
+        };
+
+        Ok(Self {
+            user_id,
+            max_transfer_minor_units: minor_units(value.max_transfer)?,
+            daily_outgoing_minor_units: minor_units(value.daily_outgoing)?,
+            monthly_outgoing_minor_units: minor_units(value.monthly_outgoing)?,
+            transfers_per_hour: value.transfers_per_hour,
+            updated_at: utils::datetime::now(),
+        })
+    }
+}
+
+impl From<storage::types::RiskRuleHit> for api_models::RiskRuleHitResponse {
+    fn from(value: storage::types::RiskRuleHit) -> Self {
+        Self {
+            rule: value.rule,
+            decision: value.decision,
+            score: value.score,
+            reason: value.reason,
+            created_at: value.created_at.to_string(),
+        }
+    }
+}
+
+impl
+    TryFrom<(
+        storage::types::Transaction,
+        Vec<storage::types::RiskRuleHit>,
+    )> for api_models::RiskReviewResponse
+{
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(
+        (transaction, hits): (
+            storage::types::Transaction,
+            Vec<storage::types::RiskRuleHit>,
+        ),
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            transaction: transaction.try_into()?,
+            risk_score: hits.iter().map(|hit| i64::from(hit.score)).sum(),
+            rule_hits: hits.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl TryFrom<storage::types::Deposit> for api_models::DepositResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::Deposit) -> Result<Self, Self::Error> {
+        let amount = value
+            .amount()
+            .change_error(ApiError::UnknownError("Invalid amount stored for deposit"))?;
+        Ok(Self {
+            deposit_id: value.deposit_id,
+            user_id: value.user_id,
+            amount: amount.into(),
+            status: value.status,
+            funding_source: value.funding_source,
+            source_reference: value.source_reference,
+            failure_reason: value.failure_reason,
+            transaction_id: value.transaction_id,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl From<storage::types::BankAccount> for api_models::BankAccountResponse {
+    fn from(value: storage::types::BankAccount) -> Self {
+        Self {
+            account_number: value.masked_account_number(),
+            bank_account_id: value.bank_account_id,
+            account_holder_name: value.account_holder_name,
+            ifsc: value.ifsc,
+            created_at: value.created_at.to_string(),
+        }
+    }
+}
+
+impl TryFrom<storage::types::Dispute> for api_models::DisputeResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::Dispute) -> Result<Self, Self::Error> {
+        let amount = value
+            .amount()
+            .change_error(ApiError::UnknownError("Invalid amount stored for dispute"))?;
+        Ok(Self {
+            dispute_id: value.dispute_id,
+            transaction_id: value.transaction_id,
+            opened_by: value.opened_by,
+            sender_id: value.sender_id,
+            recipient_id: value.recipient_id,
+            amount: amount.into(),
+            reason: value.reason,
+            evidence: value.evidence,
+            status: value.status,
+            resolution_note: value.resolution_note,
+            reversal_transaction_id: value.reversal_transaction_id,
+            resolved_at: value.resolved_at.map(|resolved_at| resolved_at.to_string()),
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl TryFrom<storage::types::Withdrawal> for api_models::WithdrawalResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::Withdrawal) -> Result<Self, Self::Error> {
+        let amount = value.amount().change_error(ApiError::UnknownError(
+            "Invalid amount stored for withdrawal",
+        ))?;
+        Ok(Self {
+            withdrawal_id: value.withdrawal_id,
+            user_id: value.user_id,
+            bank_account_id: value.bank_account_id,
+            amount: amount.into(),
+            status: value.status,
+            connector: value.connector,
+            connector_reference: value.connector_reference,
+            failure_reason: value.failure_reason,
+            transaction_id: value.transaction_id,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl TryFrom<storage::types::FxQuote> for api_models::FxQuoteResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::FxQuote) -> Result<Self, Self::Error> {
+        let invalid = || ApiError::UnknownError("Invalid amount stored for FX quote");
+        let source_amount = value.source_amount().change_error(invalid())?;
+        let target_amount = value.target_amount().change_error(invalid())?;
+        let spread = value.spread().change_error(invalid())?;
+        let mid_rate = value
+            .mid_rate()
+            .change_error(ApiError::UnknownError("Invalid rate stored for FX quote"))?;
+        let rate = value
+            .customer_rate()
+            .change_error(ApiError::UnknownError("Invalid rate stored for FX quote"))?;
+        Ok(Self {
+            quote_id: value.quote_id,
+            user_id: value.user_id,
+            source_amount: source_amount.into(),
+            target_amount: target_amount.into(),
+            spread: spread.into(),
+            mid_rate,
+            rate,
+            status: value.status,
+            expires_at: value.expires_at.to_string(),
+            rate_updated_at: value.rate_updated_at.to_string(),
+            debit_transaction_id: value.debit_transaction_id,
+            credit_transaction_id: value.credit_transaction_id,
+            created_at: value.created_at.to_string(),
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl TryFrom<storage::types::FxRate> for api_models::FxRateResponse {
+    type Error = ContainerError<ApiError>;
+
+    fn try_from(value: storage::types::FxRate) -> Result<Self, Self::Error> {
+        let invalid = || ApiError::UnknownError("Invalid rate stored for currency pair");
+        Ok(Self {
+            base_currency: value.base_currency.parse().change_error(invalid())?,
+            quote_currency: value.quote_currency.parse().change_error(invalid())?,
+            rate: value.rate().change_error(invalid())?,
+            updated_at: value.updated_at.to_string(),
+        })
+    }
+}
+
+impl From<storage::types::ReconciliationFinding> for api_models::ReconciliationFindingResponse {
+    fn from(value: storage::types::ReconciliationFinding) -> Self {
+        Self {
+            finding_id: value.finding_id,
+            kind: value.kind,
+            account_id: value.account_id,
+            currency: value.currency,
+            expected_minor_units: value.expected_minor_units,
+            actual_minor_units: value.actual_minor_units,
+            created_at: value.created_at.to_string(),
+        }
+    }
+}
+
+impl From<storage::types::ReconciliationRun> for api_models::ReconciliationRunResponse {
+    fn from(value: storage::types::ReconciliationRun) -> Self {
+        Self {
+            run_id: value.run_id,
+            wallets_checked: value.wallets_checked,
+            findings_count: value.findings_count,
+            started_at: value.started_at.to_string(),
+            completed_at: value.completed_at.to_string(),
+        }
+    }
+}
+
+/// Builds the report of a reconciliation run from the run and its findings.
+impl
+    From<(
+        storage::types::ReconciliationRun,
+        Vec<storage::types::ReconciliationFinding>,
+    )> for api_models::ReconciliationReportResponse
+{
+    fn from(
+        (run, findings): (
+            storage::types::ReconciliationRun,
+            Vec<storage::types::ReconciliationFinding>,
+        ),
+    ) -> Self {
+        Self {
+            run: run.into(),
+            findings: findings.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<statement::StatementLine> for api_models::StatementLineResponse {
+    fn from(line: statement::StatementLine) -> Self {
+        Self {
+            transaction_id: line.transaction_id,
+            transaction_type: line.transaction_type,
+            direction: line.direction,
+            counterparty_id: line.counterparty_id,
+            description: line.description,
+            amount: line.amount.into(),
+            balance: line.balance.into(),
+            created_at: line.created_at.to_string(),
+        }
+    }
+}
+
+impl From<statement::Statement> for api_models::StatementResponse {
+    fn from(statement: statement::Statement) -> Self {
+        Self {
+            currency: statement.currency(),
+            year: statement.period.year(),
+            month: statement.period.month(),
+            period_start: statement.period.start().to_string(),
+            period_end: statement.period.end().to_string(),
+            user_id: statement.account_id,
+            opening_balance: statement.opening_balance.into(),
+            closing_balance: statement.closing_balance.into(),
+            total_credits: statement.total_credits.into(),
+            total_debits: statement.total_debits.into(),
+            transactions: statement.lines.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -1,8 +1,25 @@
-use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable, QueryableByName};
 
-use crate::utils;
+use crate::{
+    consts,
+    error::{FxError, MoneyError, RecurrenceError, ValidationError},
+    reconciliation::Discrepancy,
+    risk::RiskHit,
+    types::{
+        AccountNumber, Currency, ExchangeRate, Metadata, Money, Recurrence, TransferLimits, fx,
+    },
+    utils,
+};
 
-use super::schema;
+use super::{
+    enums::{
+        DepositStatus, DisputeReason, DisputeStatus, FxQuoteStatus, LedgerDirection,
+        PaymentRequestStatus, ReconciliationFindingKind, RecurrenceFrequency, RiskDecision,
+        ScheduledTransferStatus, StandingInstructionStatus, TransactionStatus, TransactionType,
+        WithdrawalStatus,
+    },
+    schema,
+};
 
 /// Represents a user in the database.
 #[derive(Debug, Clone, Identifiable, Queryable)]
@@ -13,24 +30,100 @@ pub struct User {
     pub email: String,
     pub name: String,
     pub password: String,
-    pub balance_in_rs: f64,
     pub created_at: time::PrimitiveDateTime,
     pub last_modified_at: time::PrimitiveDateTime,
 }
 
+/// Represents the balance a user holds in one currency in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::wallets)]
+pub struct Wallet {
+    pub id: i32,
+    pub user_id: String,
+    pub currency: String,
+    pub balance_minor_units: i64,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl Wallet {
+    /// Returns the balance of the wallet.
+    pub fn balance(&self) -> Result<Money, MoneyError> {
+        Money::new(self.balance_minor_units, self.currency.parse()?)
+    }
+}
+
+/// Represents a new, empty wallet to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::wallets)]
+pub struct NewWallet {
+    pub user_id: String,
+    pub currency: String,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl NewWallet {
+    /// Creates a new, empty wallet for the user in the given currency.
+    pub fn new(user_id: String, currency: Currency) -> Self {
+        let now = utils::datetime::now();
+        Self {
+            user_id,
+            currency: currency.to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
 /// Represents a transaction in the database.
-#[derive(Debug, Clone, Identifiable, Queryable, Insertable)]
+#[derive(Debug, Clone, Identifiable, Queryable, QueryableByName, Insertable)]
 #[diesel(table_name = schema::transactions)]
 pub struct Transaction {
     pub id: i32,
     pub transaction_id: String,
     pub sender_id: String,
     pub recipient_id: String,
-    pub amount_in_rs: f64,
+    pub amount_minor_units: i64,
     pub description: Option<String>,
     pub created_at: time::PrimitiveDateTime,
-    pub status: String,
+    pub status: TransactionStatus,
     pub updated_at: time::PrimitiveDateTime,
+    pub currency: String,
+    pub failure_reason: Option<String>,
+    pub transaction_type: TransactionType,
+    pub parent_transaction_id: Option<String>,
+    pub authorized_amount_minor_units: Option<i64>,
+    pub expires_at: Option<time::PrimitiveDateTime>,
+    pub standing_instruction_id: Option<String>,
+    pub payment_request_id: Option<String>,
+    pub batch_id: Option<String>,
+    pub metadata: Option<Metadata>,
+    pub fee_minor_units: i64,
+}
+
+impl Transaction {
+    /// Returns the transferred amount, which is what the sender is debited.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+
+    /// Returns the platform fee charged on the amount.
+    pub fn fee(&self) -> Result<Money, MoneyError> {
+        Money::new(self.fee_minor_units, self.currency.parse()?)
+    }
+
+    /// Returns what the recipient is credited, i.e. the amount less the fee.
+    pub fn net_amount(&self) -> Result<Money, MoneyError> {
+        self.amount()?.checked_sub(self.fee()?)
+    }
+
+    /// Returns the amount held by the authorization, if the transaction was authorized first.
+    pub fn authorized_amount(&self) -> Result<Option<Money>, MoneyError> {
+        self.authorized_amount_minor_units
+            .map(|minor_units| Money::new(minor_units, self.currency.parse()?))
+            .transpose()
+    }
 }
 
 /// Represents a new user to be inserted into the database.
@@ -41,21 +134,155 @@ pub struct UserNew {
     pub email: String,
     pub name: String,
     pub password: String,
-    pub balance_in_rs: f64,
 }
 
 /// Represents a new transaction to be inserted into the database.
-#[derive(Insertable)]
+#[derive(Clone, Insertable)]
 #[diesel(table_name = schema::transactions)]
 pub struct NewTransaction {
     pub transaction_id: String,
     pub sender_id: String,
     pub recipient_id: String,
-    pub amount_in_rs: f64,
+    pub amount_minor_units: i64,
+    pub currency: String,
     pub description: Option<String>,
     pub created_at: time::PrimitiveDateTime,
-    pub status: String,
+    pub status: TransactionStatus,
     pub updated_at: time::PrimitiveDateTime,
+    pub transaction_type: TransactionType,
+    pub parent_transaction_id: Option<String>,
+    pub authorized_amount_minor_units: Option<i64>,
+    pub expires_at: Option<time::PrimitiveDateTime>,
+    pub standing_instruction_id: Option<String>,
+    pub payment_request_id: Option<String>,
+    pub batch_id: Option<String>,
+    pub metadata: Option<Metadata>,
+}
+
+impl NewTransaction {
+    /// Returns the amount to be transferred.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+}
+
+/// Represents a single posting in the ledger.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::ledger_entries)]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub entry_id: String,
+    pub journal_id: String,
+    pub account_id: String,
+    pub direction: LedgerDirection,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+impl LedgerEntry {
+    /// Returns the signed effect of the posting on the account balance.
+    pub fn signed_minor_units(&self) -> i64 {
+        match self.direction {
+            LedgerDirection::Credit => self.amount_minor_units,
+            LedgerDirection::Debit => -self.amount_minor_units,
+        }
+    }
+}
+
+/// Represents a new ledger posting to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::ledger_entries)]
+pub struct NewLedgerEntry {
+    pub entry_id: String,
+    pub journal_id: String,
+    pub account_id: String,
+    pub direction: LedgerDirection,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+impl NewLedgerEntry {
+    /// Creates a posting that decreases the balance of the account.
+    pub fn debit(journal_id: &str, account_id: &str, amount: Money) -> Self {
+        Self::new(journal_id, account_id, LedgerDirection::Debit, amount)
+    }
+
+    /// Creates a posting that increases the balance of the account.
+    pub fn credit(journal_id: &str, account_id: &str, amount: Money) -> Self {
+        Self::new(journal_id, account_id, LedgerDirection::Credit, amount)
+    }
+
+    /// Returns the signed effect of the posting on the account balance.
+    pub fn signed_minor_units(&self) -> i64 {
+        match self.direction {
+            LedgerDirection::Credit => self.amount_minor_units,
+            LedgerDirection::Debit => -self.amount_minor_units,
+        }
+    }
+
+    fn new(journal_id: &str, account_id: &str, direction: LedgerDirection, amount: Money) -> Self {
+        Self {
+            entry_id: format!("le_{}", utils::generate_nano_id(20)),
+            journal_id: journal_id.to_string(),
+            account_id: account_id.to_string(),
+            direction,
+            amount_minor_units: amount.minor_units(),
+            currency: amount.currency().to_string(),
+            created_at: utils::datetime::now(),
+        }
+    }
+}
+
+/// Represents a refund of a completed transaction.
+#[derive(Debug, Clone)]
+pub struct NewRefund {
+    /// ID of the refund transaction to create.
+    pub transaction_id: String,
+    /// ID of the transaction being refunded.
+    pub parent_transaction_id: String,
+    /// Amount to refund, or the whole remaining net amount when absent.
+    pub amount: Option<Money>,
+    pub description: Option<String>,
+}
+
+/// Represents a stored idempotency key in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::idempotency_keys)]
+pub struct IdempotencyKey {
+    pub id: i32,
+    pub user_id: String,
+    pub idempotency_key: String,
+    pub request_fingerprint: String,
+    pub response_status_code: Option<i32>,
+    pub response_body: Option<serde_json::Value>,
+    pub created_at: time::PrimitiveDateTime,
+    pub expires_at: time::PrimitiveDateTime,
+    /// Until when the request that claimed the key holds it, while it has no response yet.
+    pub locked_until: Option<time::PrimitiveDateTime>,
+}
+
+/// Represents a new idempotency key to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::idempotency_keys)]
+pub struct NewIdempotencyKey {
+    pub user_id: String,
+    pub idempotency_key: String,
+    pub request_fingerprint: String,
+    pub created_at: time::PrimitiveDateTime,
+    pub expires_at: time::PrimitiveDateTime,
+    pub locked_until: Option<time::PrimitiveDateTime>,
+}
+
+/// Represents the outcome of claiming an idempotency key.
+#[derive(Debug, Clone)]
+pub enum IdempotencyClaim {
+    /// The key was unused, or its earlier request died without a response, and is now reserved
+    /// for the current request.
+    Claimed,
+    /// The key is already in use by an earlier request.
+    Existing(IdempotencyKey),
 }
 
 /// Represents user data to be updated in the database.
@@ -63,18 +290,1239 @@ pub struct NewTransaction {
 #[diesel(table_name = schema::users)]
 pub struct UserUpdateInternal {
     pub name: Option<String>,
-    pub balance_in_rs: Option<f64>,
     pub last_modified_at: time::PrimitiveDateTime,
 }
 
 impl UserUpdateInternal {
     /// Creates a new UserUpdateInternal instance.
-    pub fn new(name: Option<String>, amount_in_rs: Option<f64>) -> Self {
+    pub fn new(name: Option<String>) -> Self {
         let last_modified_at = utils::datetime::now();
         Self {
             name,
-            balance_in_rs: amount_in_rs,
             last_modified_at,
         }
     }
 }
+
+/// Represents a transfer scheduled to execute at a future time.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::scheduled_transfers)]
+pub struct ScheduledTransfer {
+    pub id: i32,
+    pub scheduled_transfer_id: String,
+    pub transaction_id: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub description: Option<String>,
+    pub execute_at: time::PrimitiveDateTime,
+    pub status: ScheduledTransferStatus,
+    pub failure_reason: Option<String>,
+    pub executed_at: Option<time::PrimitiveDateTime>,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+    pub metadata: Option<Metadata>,
+    /// Number of attempts to execute the transfer that hit a database error.
+    pub attempts: i32,
+    /// When the transfer is retried after an attempt that hit a database error.
+    pub retry_at: Option<time::PrimitiveDateTime>,
+}
+
+impl ScheduledTransfer {
+    /// Returns the amount to be transferred.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+
+    /// Builds the transfer executed for this schedule, under its reserved transaction ID.
+    pub fn to_new_transaction(&self) -> NewTransaction {
+        let now = utils::datetime::now();
+        NewTransaction {
+            transaction_id: self.transaction_id.clone(),
+            sender_id: self.sender_id.clone(),
+            recipient_id: self.recipient_id.clone(),
+            amount_minor_units: self.amount_minor_units,
+            currency: self.currency.clone(),
+            description: self.description.clone(),
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Transfer,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+/// Represents a new scheduled transfer to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::scheduled_transfers)]
+pub struct NewScheduledTransfer {
+    pub scheduled_transfer_id: String,
+    pub transaction_id: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub description: Option<String>,
+    pub execute_at: time::PrimitiveDateTime,
+    pub status: ScheduledTransferStatus,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+    pub metadata: Option<Metadata>,
+}
+
+/// Represents a transfer repeated on a recurrence until it is completed.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::standing_instructions)]
+pub struct StandingInstruction {
+    pub id: i32,
+    pub standing_instruction_id: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub description: Option<String>,
+    pub frequency: RecurrenceFrequency,
+    pub day_of_month: Option<i16>,
+    pub cron_expression: Option<String>,
+    pub start_at: time::PrimitiveDateTime,
+    pub end_at: Option<time::PrimitiveDateTime>,
+    pub max_occurrences: Option<i32>,
+    pub occurrences_count: i32,
+    pub next_run_at: Option<time::PrimitiveDateTime>,
+    pub last_run_at: Option<time::PrimitiveDateTime>,
+    pub last_failure_reason: Option<String>,
+    pub status: StandingInstructionStatus,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+    pub metadata: Option<Metadata>,
+    /// Number of attempts at the due run that hit a database error.
+    pub attempts: i32,
+    /// When the due run is retried after an attempt that hit a database error.
+    pub retry_at: Option<time::PrimitiveDateTime>,
+}
+
+impl StandingInstruction {
+    /// Returns the amount transferred on every occurrence.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+
+    /// Rebuilds the recurrence from its stored columns.
+    pub fn recurrence(&self) -> Result<Recurrence, RecurrenceError> {
+        match self.frequency {
+            RecurrenceFrequency::Daily => Ok(Recurrence::Daily),
+            RecurrenceFrequency::Weekly => Ok(Recurrence::Weekly),
+            RecurrenceFrequency::Monthly => {
+                let day_of_month = self.day_of_month.unwrap_or_default();
+                Recurrence::monthly(u8::try_from(day_of_month).unwrap_or_default())
+            }
+            RecurrenceFrequency::Cron => Ok(Recurrence::Cron {
+                expression: self
+                    .cron_expression
+                    .as_deref()
+                    .unwrap_or_default()
+                    .parse()?,
+            }),
+        }
+    }
+
+    /// Returns true if the occurrence at `run_at` falls after the end of the instruction.
+    pub fn is_past_end(&self, run_at: time::PrimitiveDateTime) -> bool {
+        self.end_at.is_some_and(|end_at| run_at > end_at)
+    }
+
+    /// Returns true if no occurrences are left after `occurrences_count` runs.
+    pub fn is_exhausted(&self, occurrences_count: i32) -> bool {
+        self.max_occurrences
+            .is_some_and(|max_occurrences| occurrences_count >= max_occurrences)
+    }
+
+    /// Builds the transfer of the given occurrence, numbered from 1. The transaction ID is
+    /// derived from the occurrence so a run retried after a crash finds its earlier outcome.
+    pub fn to_new_transaction(&self, occurrence: i32) -> NewTransaction {
+        let now = utils::datetime::now();
+        NewTransaction {
+            transaction_id: format!("txn_{}_{occurrence}", self.standing_instruction_id),
+            sender_id: self.sender_id.clone(),
+            recipient_id: self.recipient_id.clone(),
+            amount_minor_units: self.amount_minor_units,
+            currency: self.currency.clone(),
+            description: self.description.clone(),
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Transfer,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: Some(self.standing_instruction_id.clone()),
+            payment_request_id: None,
+            batch_id: None,
+            metadata: self.metadata.clone(),
+        }
+    }
+}
+
+/// Represents a new standing instruction to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::standing_instructions)]
+pub struct NewStandingInstruction {
+    pub standing_instruction_id: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub description: Option<String>,
+    pub frequency: RecurrenceFrequency,
+    pub day_of_month: Option<i16>,
+    pub cron_expression: Option<String>,
+    pub start_at: time::PrimitiveDateTime,
+    pub end_at: Option<time::PrimitiveDateTime>,
+    pub max_occurrences: Option<i32>,
+    pub next_run_at: Option<time::PrimitiveDateTime>,
+    pub status: StandingInstructionStatus,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+    pub metadata: Option<Metadata>,
+}
+
+impl NewStandingInstruction {
+    /// Stores the recurrence in its columns and schedules the first occurrence at or after
+    /// `start_at`.
+    pub fn set_recurrence(&mut self, recurrence: &Recurrence) {
+        let (frequency, day_of_month, cron_expression) = match recurrence {
+            Recurrence::Daily => (RecurrenceFrequency::Daily, None, None),
+            Recurrence::Weekly => (RecurrenceFrequency::Weekly, None, None),
+            Recurrence::Monthly { day_of_month } => (
+                RecurrenceFrequency::Monthly,
+                Some(i16::from(*day_of_month)),
+                None,
+            ),
+            Recurrence::Cron { expression } => (
+                RecurrenceFrequency::Cron,
+                None,
+                Some(expression.to_string()),
+            ),
+        };
+
+        self.frequency = frequency;
+        self.day_of_month = day_of_month;
+        self.cron_expression = cron_expression;
+        self.next_run_at = recurrence
+            .first_occurrence(self.start_at)
+            .filter(|next_run_at| self.end_at.is_none_or(|end_at| *next_run_at <= end_at));
+        if self.next_run_at.is_none() {
+            self.status = StandingInstructionStatus::Completed;
+        }
+    }
+}
+
+/// Represents a request from one user asking another to pay them.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::payment_requests)]
+pub struct PaymentRequest {
+    pub id: i32,
+    pub payment_request_id: String,
+    pub requester_id: String,
+    pub payer_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub note: Option<String>,
+    pub status: PaymentRequestStatus,
+    pub transaction_id: Option<String>,
+    pub expires_at: time::PrimitiveDateTime,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl PaymentRequest {
+    /// Returns the requested amount.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+
+    /// Returns true if the request lapsed, even before the sweep marks it EXPIRED.
+    pub fn is_expired(&self, now: time::PrimitiveDateTime) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Builds the transfer from the payer to the requester that pays this request.
+    pub fn to_new_transaction(&self) -> NewTransaction {
+        let now = utils::datetime::now();
+        NewTransaction {
+            transaction_id: format!("txn_{}", utils::generate_nano_id(20)),
+            sender_id: self.payer_id.clone(),
+            recipient_id: self.requester_id.clone(),
+            amount_minor_units: self.amount_minor_units,
+            currency: self.currency.clone(),
+            description: self.note.clone(),
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Transfer,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: Some(self.payment_request_id.clone()),
+            batch_id: None,
+            metadata: None,
+        }
+    }
+}
+
+/// Represents a new payment request to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::payment_requests)]
+pub struct NewPaymentRequest {
+    pub payment_request_id: String,
+    pub requester_id: String,
+    pub payer_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub note: Option<String>,
+    pub status: PaymentRequestStatus,
+    pub expires_at: time::PrimitiveDateTime,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+/// Represents which side of a payment request a user is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentRequestDirection {
+    /// Requests the user was asked to pay.
+    Incoming,
+    /// Requests the user made of others.
+    Outgoing,
+}
+
+/// Represents which side of a transaction a user is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionDirection {
+    /// Transactions the user sent.
+    Sent,
+    /// Transactions the user received.
+    Received,
+}
+
+/// Represents the order transactions are listed in. Ties are broken by creation order, so
+/// pages are deterministic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionSort {
+    /// Newest first.
+    #[default]
+    CreatedAtDesc,
+    /// Oldest first.
+    CreatedAtAsc,
+    /// Largest amount first.
+    AmountDesc,
+    /// Smallest amount first.
+    AmountAsc,
+}
+
+/// Represents which transactions of an account to list, and in which order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionFilter {
+    pub account_id: String,
+    /// Only transactions the account sent or received; both when absent.
+    pub direction: Option<TransactionDirection>,
+    /// Only transactions with this account on the other side.
+    pub counterparty_id: Option<String>,
+    pub status: Option<TransactionStatus>,
+    /// Only transactions in this currency.
+    pub currency: Option<Currency>,
+    /// Only transactions created at or after this time.
+    pub created_from: Option<time::PrimitiveDateTime>,
+    /// Only transactions created before this time.
+    pub created_to: Option<time::PrimitiveDateTime>,
+    pub min_amount_minor_units: Option<i64>,
+    pub max_amount_minor_units: Option<i64>,
+    /// Only transactions whose description contains this text, ignoring case.
+    pub search: Option<String>,
+    /// Only transactions whose metadata has all of these pairs.
+    pub metadata: Option<Metadata>,
+    pub sort: TransactionSort,
+}
+
+impl TransactionFilter {
+    /// Returns the search text as an ILIKE pattern matching it anywhere, with the wildcards
+    /// of the text escaped so they match literally.
+    pub fn search_pattern(&self) -> Option<String> {
+        self.search.as_ref().map(|search| {
+            let escaped = search
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        })
+    }
+}
+
+impl TransactionSort {
+    /// Returns whether the sort lists smaller values first.
+    pub fn is_ascending(self) -> bool {
+        matches!(self, Self::CreatedAtAsc | Self::AmountAsc)
+    }
+
+    /// Returns whether the sort orders by amount before creation order.
+    pub fn is_by_amount(self) -> bool {
+        matches!(self, Self::AmountDesc | Self::AmountAsc)
+    }
+
+    /// Compares two transactions in the order of the sort.
+    pub fn compare(self, a: &Transaction, b: &Transaction) -> std::cmp::Ordering {
+        let key = |transaction: &Transaction| {
+            (
+                self.is_by_amount()
+                    .then_some(transaction.amount_minor_units),
+                transaction.created_at,
+                transaction.id,
+            )
+        };
+
+        let ordering = key(a).cmp(&key(b));
+        if self.is_ascending() {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+}
+
+/// Represents which side of a cursor a page of transactions is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CursorDirection {
+    /// The transactions that come after the cursor in the order of the sort.
+    After,
+    /// The transactions that come before the cursor in the order of the sort.
+    Before,
+}
+
+/// Represents a position in a sorted list of transactions: the sort key of a transaction, and
+/// which side of it to read the next page from. Clients only see it encoded as an opaque string.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TransactionCursor {
+    /// The sort the cursor was issued for; it means nothing in any other order.
+    pub sort: TransactionSort,
+    pub direction: CursorDirection,
+    pub amount_minor_units: i64,
+    pub created_at: time::PrimitiveDateTime,
+    pub id: i32,
+}
+
+impl TransactionCursor {
+    /// Creates a cursor at `transaction` in the order of `sort`.
+    pub fn new(
+        sort: TransactionSort,
+        direction: CursorDirection,
+        transaction: &Transaction,
+    ) -> Self {
+        Self {
+            sort,
+            direction,
+            amount_minor_units: transaction.amount_minor_units,
+            created_at: transaction.created_at,
+            id: transaction.id,
+        }
+    }
+
+    /// Returns whether pages from this cursor are read in ascending order of the sort key.
+    pub fn scans_ascending(&self) -> bool {
+        self.sort.is_ascending() == (self.direction == CursorDirection::After)
+    }
+
+    /// Encodes the cursor as an opaque URL safe string.
+    pub fn encode(&self) -> String {
+        use base64::Engine;
+
+        // Serializing plain values cannot fail.
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Decodes a cursor encoded by [`TransactionCursor::encode`].
+    pub fn decode(encoded: &str) -> Result<Self, ValidationError> {
+        use base64::Engine;
+
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| ValidationError::InvalidValue {
+                message: "The cursor is not valid".into(),
+            })
+    }
+}
+
+/// Represents a page of transactions, with the cursors of the pages around it.
+#[derive(Debug, Clone)]
+pub struct TransactionPage {
+    pub transactions: Vec<Transaction>,
+    /// Where the next page starts, absent on the last page.
+    pub next_cursor: Option<TransactionCursor>,
+    /// Where the previous page ends, absent on the first page.
+    pub prev_cursor: Option<TransactionCursor>,
+    /// How many transactions match the filter, when it was asked for.
+    pub total_count: Option<i64>,
+}
+
+impl TransactionPage {
+    /// Builds a page of up to `limit` transactions from the rows read from `cursor`, in the
+    /// order they were read in. One row more than `limit` means there are more rows past the
+    /// page in the direction they were read in.
+    pub fn new(
+        sort: TransactionSort,
+        cursor: Option<&TransactionCursor>,
+        mut rows: Vec<Transaction>,
+        limit: usize,
+        total_count: Option<i64>,
+    ) -> Self {
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+
+        let reading_backwards =
+            cursor.is_some_and(|cursor| cursor.direction == CursorDirection::Before);
+        if reading_backwards {
+            rows.reverse();
+        }
+
+        let next = |transaction: &Transaction| {
+            TransactionCursor::new(sort, CursorDirection::After, transaction)
+        };
+        let prev = |transaction: &Transaction| {
+            TransactionCursor::new(sort, CursorDirection::Before, transaction)
+        };
+        let (next_cursor, prev_cursor) = if reading_backwards {
+            (
+                rows.last().map(next),
+                rows.first().filter(|_| has_more).map(prev),
+            )
+        } else {
+            (
+                rows.last().filter(|_| has_more).map(next),
+                rows.first().filter(|_| cursor.is_some()).map(prev),
+            )
+        };
+
+        Self {
+            transactions: rows,
+            next_cursor,
+            prev_cursor,
+            total_count,
+        }
+    }
+}
+
+/// Represents a batch of transfers from one sender that succeeded together.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::transfer_batches)]
+pub struct TransferBatch {
+    pub id: i32,
+    pub batch_id: String,
+    pub sender_id: String,
+    pub total_minor_units: i64,
+    pub currency: String,
+    pub leg_count: i32,
+    pub description: Option<String>,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+impl TransferBatch {
+    /// Returns the sum of the amounts of every leg.
+    pub fn total(&self) -> Result<Money, MoneyError> {
+        Money::new(self.total_minor_units, self.currency.parse()?)
+    }
+}
+
+/// Represents a new transfer batch to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::transfer_batches)]
+pub struct NewTransferBatch {
+    pub batch_id: String,
+    pub sender_id: String,
+    pub total_minor_units: i64,
+    pub currency: String,
+    pub leg_count: i32,
+    pub description: Option<String>,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+impl NewTransferBatch {
+    /// Builds the batch row for the given legs, totalling their amounts in `currency`.
+    pub fn new(
+        batch_id: String,
+        sender_id: String,
+        currency: Currency,
+        description: Option<String>,
+        legs: &[NewTransaction],
+    ) -> Result<Self, MoneyError> {
+        let mut total = Money::zero(currency);
+        for leg in legs {
+            total = total.checked_add(leg.amount()?)?;
+        }
+
+        Ok(Self {
+            batch_id,
+            sender_id,
+            total_minor_units: total.minor_units(),
+            currency: total.currency().to_string(),
+            leg_count: i32::try_from(legs.len()).map_err(|_| MoneyError::Overflow)?,
+            description,
+            created_at: utils::datetime::now(),
+        })
+    }
+
+    /// Returns the sum of the amounts of every leg.
+    pub fn total(&self) -> Result<Money, MoneyError> {
+        Money::new(self.total_minor_units, self.currency.parse()?)
+    }
+}
+
+/// Represents the limits a user has that differ from the configured defaults.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::user_limits)]
+pub struct UserLimits {
+    pub id: i32,
+    pub user_id: String,
+    pub max_transfer_minor_units: Option<i64>,
+    pub daily_outgoing_minor_units: Option<i64>,
+    pub monthly_outgoing_minor_units: Option<i64>,
+    pub transfers_per_hour: Option<i32>,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl UserLimits {
+    /// Returns the limits of the user, taking the defaults for the limits without an override.
+    pub fn apply_to(&self, defaults: TransferLimits) -> TransferLimits {
+        TransferLimits {
+            max_transfer_minor_units: self
+                .max_transfer_minor_units
+                .unwrap_or(defaults.max_transfer_minor_units),
+            daily_outgoing_minor_units: self
+                .daily_outgoing_minor_units
+                .unwrap_or(defaults.daily_outgoing_minor_units),
+            monthly_outgoing_minor_units: self
+                .monthly_outgoing_minor_units
+                .unwrap_or(defaults.monthly_outgoing_minor_units),
+            transfers_per_hour: self
+                .transfers_per_hour
+                .map_or(defaults.transfers_per_hour, i64::from),
+        }
+    }
+}
+
+/// Represents the limit overrides of a user to be stored in the database. Limits left as
+/// `None` fall back to the configured defaults.
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = schema::user_limits, treat_none_as_null = true)]
+pub struct NewUserLimits {
+    pub user_id: String,
+    pub max_transfer_minor_units: Option<i64>,
+    pub daily_outgoing_minor_units: Option<i64>,
+    pub monthly_outgoing_minor_units: Option<i64>,
+    pub transfers_per_hour: Option<i32>,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+/// Represents a risk rule that flagged a transaction, in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::risk_rule_hits)]
+pub struct RiskRuleHit {
+    pub id: i32,
+    pub transaction_id: String,
+    pub rule: String,
+    pub decision: RiskDecision,
+    pub score: i32,
+    pub reason: String,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+/// Represents a risk rule that flagged a transaction, to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::risk_rule_hits)]
+pub struct NewRiskRuleHit {
+    pub transaction_id: String,
+    pub rule: String,
+    pub decision: RiskDecision,
+    pub score: i32,
+    pub reason: String,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+impl NewRiskRuleHit {
+    /// Creates the record of a rule that flagged the transaction `transaction_id`.
+    pub fn new(transaction_id: &str, hit: RiskHit) -> Self {
+        Self {
+            transaction_id: transaction_id.to_string(),
+            rule: hit.rule.to_string(),
+            decision: hit.decision,
+            score: i32::try_from(hit.score).unwrap_or(i32::MAX),
+            reason: hit.reason,
+            created_at: utils::datetime::now(),
+        }
+    }
+}
+
+/// Represents a deposit from an external funding source in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::deposits)]
+pub struct Deposit {
+    pub id: i32,
+    pub deposit_id: String,
+    pub user_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub status: DepositStatus,
+    pub funding_source: String,
+    pub source_reference: Option<String>,
+    pub failure_reason: Option<String>,
+    pub transaction_id: Option<String>,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl Deposit {
+    /// Returns the amount deposited.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+
+    /// Builds the transaction that credits the deposit to the user, sent by the system funding
+    /// account.
+    pub fn to_new_transaction(&self) -> NewTransaction {
+        let now = utils::datetime::now();
+        NewTransaction {
+            transaction_id: format!("txn_{}", utils::generate_nano_id(20)),
+            sender_id: consts::SYSTEM_FUNDING_ACCOUNT.to_string(),
+            recipient_id: self.user_id.clone(),
+            amount_minor_units: self.amount_minor_units,
+            currency: self.currency.clone(),
+            description: Some(format!("Deposit {}", self.deposit_id)),
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Deposit,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: None,
+        }
+    }
+}
+
+/// Represents a new deposit to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::deposits)]
+pub struct NewDeposit {
+    pub deposit_id: String,
+    pub user_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub status: DepositStatus,
+    pub funding_source: String,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+/// Represents a dispute against a transfer in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::disputes)]
+pub struct Dispute {
+    pub id: i32,
+    pub dispute_id: String,
+    pub transaction_id: String,
+    pub opened_by: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub reason: DisputeReason,
+    pub evidence: String,
+    pub status: DisputeStatus,
+    pub resolution_note: Option<String>,
+    pub reversal_transaction_id: Option<String>,
+    pub resolved_at: Option<time::PrimitiveDateTime>,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl Dispute {
+    /// Returns the disputed amount, which is frozen in the recipient's balance while the
+    /// dispute is open and returned to the sender if it is won.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+
+    /// Builds the transaction that returns the disputed amount from the recipient of the
+    /// transfer to its sender. Reversals keep the client references of the transfer.
+    pub fn to_reversal_transaction(&self, metadata: Option<Metadata>) -> NewTransaction {
+        let now = utils::datetime::now();
+        NewTransaction {
+            transaction_id: format!("txn_{}", utils::generate_nano_id(20)),
+            sender_id: self.recipient_id.clone(),
+            recipient_id: self.sender_id.clone(),
+            amount_minor_units: self.amount_minor_units,
+            currency: self.currency.clone(),
+            description: Some(format!("Dispute {}", self.dispute_id)),
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Reversal,
+            parent_transaction_id: Some(self.transaction_id.clone()),
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata,
+        }
+    }
+}
+
+/// Represents a dispute to raise against a transfer. Its amount, currency and parties are
+/// taken from the transfer when it is raised.
+#[derive(Debug, Clone)]
+pub struct NewDispute {
+    pub dispute_id: String,
+    /// ID of the disputed transfer.
+    pub transaction_id: String,
+    /// The sender or recipient of the transfer raising the dispute.
+    pub opened_by: String,
+    pub reason: DisputeReason,
+    pub evidence: String,
+}
+
+/// Represents an external bank account of a user in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::bank_accounts)]
+pub struct BankAccount {
+    pub id: i32,
+    pub bank_account_id: String,
+    pub user_id: String,
+    pub account_holder_name: String,
+    pub account_number: String,
+    pub ifsc: String,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+impl BankAccount {
+    /// Returns the account number with all but its last four digits hidden.
+    pub fn masked_account_number(&self) -> String {
+        AccountNumber(self.account_number.clone()).masked()
+    }
+}
+
+/// Represents a new bank account to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::bank_accounts)]
+pub struct NewBankAccount {
+    pub bank_account_id: String,
+    pub user_id: String,
+    pub account_holder_name: String,
+    pub account_number: String,
+    pub ifsc: String,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+/// Represents a withdrawal to an external bank account in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::withdrawals)]
+pub struct Withdrawal {
+    pub id: i32,
+    pub withdrawal_id: String,
+    pub user_id: String,
+    pub bank_account_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub status: WithdrawalStatus,
+    pub connector: String,
+    pub connector_reference: Option<String>,
+    pub failure_reason: Option<String>,
+    pub transaction_id: String,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+    /// Until when a recovery worker holds the withdrawal.
+    pub recovery_claimed_until: Option<time::PrimitiveDateTime>,
+}
+
+impl Withdrawal {
+    /// Returns the amount withdrawn.
+    pub fn amount(&self) -> Result<Money, MoneyError> {
+        Money::new(self.amount_minor_units, self.currency.parse()?)
+    }
+}
+
+/// Represents a new withdrawal to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::withdrawals)]
+pub struct NewWithdrawal {
+    pub withdrawal_id: String,
+    pub user_id: String,
+    pub bank_account_id: String,
+    pub amount_minor_units: i64,
+    pub currency: String,
+    pub status: WithdrawalStatus,
+    pub connector: String,
+    pub transaction_id: String,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl NewWithdrawal {
+    /// Builds the transaction that debits the withdrawal from the user, received by the system
+    /// payouts account.
+    pub fn to_new_transaction(&self) -> NewTransaction {
+        NewTransaction {
+            transaction_id: self.transaction_id.clone(),
+            sender_id: self.user_id.clone(),
+            recipient_id: consts::SYSTEM_PAYOUTS_ACCOUNT.to_string(),
+            amount_minor_units: self.amount_minor_units,
+            currency: self.currency.clone(),
+            description: Some(format!("Withdrawal {}", self.withdrawal_id)),
+            created_at: self.created_at,
+            status: TransactionStatus::Pending,
+            updated_at: self.updated_at,
+            transaction_type: TransactionType::Withdrawal,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: None,
+        }
+    }
+}
+
+/// Represents the exchange rate of a currency pair, as last set by an admin, in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::fx_rates)]
+pub struct FxRate {
+    pub id: i32,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate_scaled: i64,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl FxRate {
+    /// Returns the mid-market rate of the pair.
+    pub fn rate(&self) -> Result<ExchangeRate, FxError> {
+        ExchangeRate::from_scaled(self.rate_scaled)
+    }
+
+    /// Returns true if the rate was set longer than `max_age` seconds before `now`.
+    pub fn is_stale(&self, now: time::PrimitiveDateTime, max_age: u64) -> bool {
+        self.updated_at + time::Duration::seconds(max_age as i64) <= now
+    }
+}
+
+/// Represents a new exchange rate to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::fx_rates)]
+pub struct NewFxRate {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate_scaled: i64,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl NewFxRate {
+    /// Creates the rate of a currency pair, set now.
+    pub fn new(base: Currency, quote: Currency, rate: ExchangeRate) -> Self {
+        let now = utils::datetime::now();
+        Self {
+            base_currency: base.to_string(),
+            quote_currency: quote.to_string(),
+            rate_scaled: rate.scaled(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Represents a quote that locks an exchange rate for converting between two wallets of a
+/// user, in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::fx_quotes)]
+pub struct FxQuote {
+    pub id: i32,
+    pub quote_id: String,
+    pub user_id: String,
+    pub source_currency: String,
+    pub target_currency: String,
+    pub source_amount_minor_units: i64,
+    pub target_amount_minor_units: i64,
+    pub spread_minor_units: i64,
+    pub mid_rate_scaled: i64,
+    pub spread_basis_points: i32,
+    pub rate_updated_at: time::PrimitiveDateTime,
+    pub status: FxQuoteStatus,
+    pub expires_at: time::PrimitiveDateTime,
+    pub debit_transaction_id: Option<String>,
+    pub credit_transaction_id: Option<String>,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl FxQuote {
+    /// Returns the amount debited from the source wallet.
+    pub fn source_amount(&self) -> Result<Money, MoneyError> {
+        Money::new(
+            self.source_amount_minor_units,
+            self.source_currency.parse()?,
+        )
+    }
+
+    /// Returns the amount credited to the target wallet, i.e. the converted amount less the
+    /// spread.
+    pub fn target_amount(&self) -> Result<Money, MoneyError> {
+        Money::new(
+            self.target_amount_minor_units,
+            self.target_currency.parse()?,
+        )
+    }
+
+    /// Returns the spread kept by the platform, in the target currency.
+    pub fn spread(&self) -> Result<Money, MoneyError> {
+        Money::new(self.spread_minor_units, self.target_currency.parse()?)
+    }
+
+    /// Returns the source amount converted at the mid-market rate, before the spread.
+    pub fn gross_target_amount(&self) -> Result<Money, MoneyError> {
+        self.target_amount()?.checked_add(self.spread()?)
+    }
+
+    /// Returns the mid-market rate the quote was priced at.
+    pub fn mid_rate(&self) -> Result<ExchangeRate, FxError> {
+        ExchangeRate::from_scaled(self.mid_rate_scaled)
+    }
+
+    /// Returns the rate the customer gets, i.e. the mid-market rate less the spread.
+    pub fn customer_rate(&self) -> Result<ExchangeRate, FxError> {
+        self.mid_rate()?
+            .less_basis_points(self.spread_basis_points as u32)
+    }
+
+    /// Returns true if the quote lapsed and can no longer be executed.
+    pub fn is_expired(&self, now: time::PrimitiveDateTime) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Builds the transaction that debits the source amount from the user, received by the
+    /// system FX account.
+    pub fn to_debit_transaction(&self) -> NewTransaction {
+        let now = utils::datetime::now();
+        NewTransaction {
+            transaction_id: format!("txn_{}", utils::generate_nano_id(20)),
+            sender_id: self.user_id.clone(),
+            recipient_id: consts::SYSTEM_FX_ACCOUNT.to_string(),
+            amount_minor_units: self.source_amount_minor_units,
+            currency: self.source_currency.clone(),
+            description: Some(format!("Conversion {}", self.quote_id)),
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Conversion,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: None,
+        }
+    }
+
+    /// Builds the transaction that credits the converted amount to the user, sent by the
+    /// system FX account and linked to the debit. Its amount is the gross converted amount;
+    /// the spread is recorded as its fee.
+    pub fn to_credit_transaction(&self, debit_transaction_id: &str) -> NewTransaction {
+        let now = utils::datetime::now();
+        NewTransaction {
+            transaction_id: format!("txn_{}", utils::generate_nano_id(20)),
+            sender_id: consts::SYSTEM_FX_ACCOUNT.to_string(),
+            recipient_id: self.user_id.clone(),
+            amount_minor_units: self.target_amount_minor_units + self.spread_minor_units,
+            currency: self.target_currency.clone(),
+            description: Some(format!("Conversion {}", self.quote_id)),
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Conversion,
+            parent_transaction_id: Some(debit_transaction_id.to_string()),
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: None,
+        }
+    }
+}
+
+/// Represents a new FX quote to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::fx_quotes)]
+pub struct NewFxQuote {
+    pub quote_id: String,
+    pub user_id: String,
+    pub source_currency: String,
+    pub target_currency: String,
+    pub source_amount_minor_units: i64,
+    pub target_amount_minor_units: i64,
+    pub spread_minor_units: i64,
+    pub mid_rate_scaled: i64,
+    pub spread_basis_points: i32,
+    pub rate_updated_at: time::PrimitiveDateTime,
+    pub status: FxQuoteStatus,
+    pub expires_at: time::PrimitiveDateTime,
+    pub created_at: time::PrimitiveDateTime,
+    pub updated_at: time::PrimitiveDateTime,
+}
+
+impl NewFxQuote {
+    /// Prices a quote for converting `source` into `target` at `rate`, keeping a spread of
+    /// `spread_basis_points` of the converted amount. The quote can be executed for
+    /// `ttl_secs` seconds.
+    pub fn new(
+        user_id: String,
+        source: Money,
+        target: Currency,
+        rate: &FxRate,
+        spread_basis_points: u32,
+        ttl_secs: u64,
+    ) -> Result<Self, FxError> {
+        let mid_rate = rate.rate()?;
+        let gross = mid_rate.convert(source, target)?;
+        let spread = fx::spread_of(gross, spread_basis_points)?;
+        let net = gross.checked_sub(spread)?;
+
+        let now = utils::datetime::now();
+        Ok(Self {
+            quote_id: format!("fxq_{}", utils::generate_nano_id(20)),
+            user_id,
+            source_currency: source.currency().to_string(),
+            target_currency: target.to_string(),
+            source_amount_minor_units: source.minor_units(),
+            target_amount_minor_units: net.minor_units(),
+            spread_minor_units: spread.minor_units(),
+            mid_rate_scaled: mid_rate.scaled(),
+            spread_basis_points: spread_basis_points as i32,
+            rate_updated_at: rate.updated_at,
+            status: FxQuoteStatus::Open,
+            expires_at: now + time::Duration::seconds(ttl_secs as i64),
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Returns the amount the quote credits to the target wallet.
+    pub fn target_amount(&self) -> Result<Money, MoneyError> {
+        Money::new(
+            self.target_amount_minor_units,
+            self.target_currency.parse()?,
+        )
+    }
+}
+
+/// Represents a run of the reconciliation job in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::reconciliation_runs)]
+pub struct ReconciliationRun {
+    pub id: i32,
+    pub run_id: String,
+    pub wallets_checked: i32,
+    pub findings_count: i32,
+    pub started_at: time::PrimitiveDateTime,
+    pub completed_at: time::PrimitiveDateTime,
+}
+
+/// Represents a new reconciliation run to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::reconciliation_runs)]
+pub struct NewReconciliationRun {
+    pub run_id: String,
+    pub wallets_checked: i32,
+    pub findings_count: i32,
+    pub started_at: time::PrimitiveDateTime,
+    pub completed_at: time::PrimitiveDateTime,
+}
+
+/// Represents a discrepancy found by a reconciliation run in the database.
+#[derive(Debug, Clone, Identifiable, Queryable)]
+#[diesel(table_name = schema::reconciliation_findings)]
+pub struct ReconciliationFinding {
+    pub id: i32,
+    pub finding_id: String,
+    pub run_id: String,
+    pub kind: ReconciliationFindingKind,
+    pub account_id: Option<String>,
+    pub currency: String,
+    pub expected_minor_units: i64,
+    pub actual_minor_units: i64,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+/// Represents a new reconciliation finding to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::reconciliation_findings)]
+pub struct NewReconciliationFinding {
+    pub finding_id: String,
+    pub run_id: String,
+    pub kind: ReconciliationFindingKind,
+    pub account_id: Option<String>,
+    pub currency: String,
+    pub expected_minor_units: i64,
+    pub actual_minor_units: i64,
+    pub created_at: time::PrimitiveDateTime,
+}
+
+impl NewReconciliationFinding {
+    /// Records a discrepancy found by the run.
+    pub fn new(
+        run_id: &str,
+        discrepancy: Discrepancy,
+        created_at: time::PrimitiveDateTime,
+    ) -> Self {
+        Self {
+            finding_id: format!("rcf_{}", utils::generate_nano_id(20)),
+            run_id: run_id.to_string(),
+            kind: discrepancy.kind,
+            account_id: discrepancy.account_id,
+            currency: discrepancy.currency,
+            expected_minor_units: discrepancy.expected_minor_units,
+            actual_minor_units: discrepancy.actual_minor_units,
+            created_at,
+        }
+    }
+}
+
+/// Represents the closing balance of a wallet on a UTC day, to be inserted into the database.
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = schema::balance_snapshots)]
+pub struct NewBalanceSnapshot {
+    pub account_id: String,
+    pub currency: String,
+    pub snapshot_date: time::Date,
+    pub closing_balance_minor_units: i64,
+    pub created_at: time::PrimitiveDateTime,
+}
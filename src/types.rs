@@ -8,6 +8,22 @@ use crate::{
     logger,
 };
 
+pub mod bank;
+pub mod fee;
+pub mod fx;
+pub mod limits;
+pub mod metadata;
+pub mod money;
+pub mod recurrence;
+
+pub use bank::{AccountNumber, Ifsc};
+pub use fee::FeeSchedule;
+pub use fx::ExchangeRate;
+pub use limits::TransferLimits;
+pub use metadata::Metadata;
+pub use money::{Currency, Money};
+pub use recurrence::{CronSchedule, Recurrence};
+
 /// Maximum password length.
 pub const MAX_PASSWORD_LENGTH: usize = 70;
 /// Minimum password length.
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ValidationError, container::ContainerError};
+
+/// Shortest bank account number issued by Indian banks.
+pub const MIN_ACCOUNT_NUMBER_LENGTH: usize = 9;
+/// Longest bank account number issued by Indian banks.
+pub const MAX_ACCOUNT_NUMBER_LENGTH: usize = 18;
+
+/// Represents the number of a bank account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AccountNumber(pub String);
+
+impl AccountNumber {
+    /// Validates that the account number has 9 to 18 digits.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        let number = &self.0;
+        let has_valid_length =
+            (MIN_ACCOUNT_NUMBER_LENGTH..=MAX_ACCOUNT_NUMBER_LENGTH).contains(&number.len());
+
+        if !has_valid_length || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ValidationError::InvalidValue {
+                message: format!(
+                    "Account number must have {MIN_ACCOUNT_NUMBER_LENGTH} to \
+                     {MAX_ACCOUNT_NUMBER_LENGTH} digits"
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the account number with all but its last four digits hidden.
+    pub fn masked(&self) -> String {
+        let hidden = self.0.chars().count().saturating_sub(4);
+        self.0
+            .chars()
+            .enumerate()
+            .map(|(index, digit)| if index < hidden { 'X' } else { digit })
+            .collect()
+    }
+}
+
+/// Represents an Indian Financial System Code, identifying the bank branch of an account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Ifsc(pub String);
+
+impl Ifsc {
+    /// Validates the IFSC: four letters for the bank, a zero, then six letters or digits for
+    /// the branch, e.g. `HDFC0001234`.
+    pub fn validate(&self) -> Result<(), ContainerError<ValidationError>> {
+        let code = self.0.as_bytes();
+        let is_valid = code.len() == 11
+            && code[..4].iter().all(u8::is_ascii_uppercase)
+            && code[4] == b'0'
+            && code[5..]
+                .iter()
+                .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit());
+
+        if !is_valid {
+            return Err(ValidationError::InvalidValue {
+                message: "IFSC must be four letters, a zero and six letters or digits".into(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{FeeError, MoneyError},
+    types::Money,
+};
+
+/// Basis points in a whole, i.e. 100%.
+pub const BASIS_POINTS_PER_WHOLE: u32 = 10_000;
+
+/// Represents how the platform fee of a transfer is calculated from its amount. The fee is paid
+/// by the sender out of the transferred amount, so the receiver gets the amount less the fee.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeeSchedule {
+    #[serde(flatten)]
+    pub rule: FeeRule,
+    /// Smallest fee charged, in minor units.
+    #[serde(default)]
+    pub min_minor_units: Option<i64>,
+    /// Largest fee charged, in minor units.
+    #[serde(default)]
+    pub max_minor_units: Option<i64>,
+}
+
+/// Represents the rule a fee schedule applies before its minimum and maximum.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FeeRule {
+    /// No fee.
+    None,
+    /// The same fee for every amount.
+    Flat { amount_minor_units: i64 },
+    /// A share of the amount, in basis points.
+    Percentage { basis_points: u32 },
+    /// The fee of the first tier whose upper bound covers the amount.
+    Tiered { tiers: Vec<FeeTier> },
+}
+
+/// Represents one tier of a tiered fee schedule.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeeTier {
+    /// Largest amount, in minor units, the tier applies to. The last tier has no upper bound.
+    #[serde(default)]
+    pub up_to_minor_units: Option<i64>,
+    /// Fixed part of the fee, in minor units.
+    #[serde(default)]
+    pub flat_minor_units: i64,
+    /// Share of the amount added to the fixed part, in basis points.
+    #[serde(default)]
+    pub basis_points: u32,
+}
+
+impl FeeSchedule {
+    /// Creates a schedule that charges no fee.
+    pub const fn free() -> Self {
+        Self {
+            rule: FeeRule::None,
+            min_minor_units: None,
+            max_minor_units: None,
+        }
+    }
+
+    /// Validates a schedule built through deserialization.
+    pub fn validate(&self) -> Result<(), FeeError> {
+        let amounts = [self.min_minor_units, self.max_minor_units];
+        if amounts.iter().flatten().any(|amount| *amount < 0) {
+            return Err(FeeError::NegativeAmount);
+        }
+        if let (Some(min), Some(max)) = (self.min_minor_units, self.max_minor_units)
+            && min > max
+        {
+            return Err(FeeError::MinimumExceedsMaximum);
+        }
+
+        match &self.rule {
+            FeeRule::None => Ok(()),
+            FeeRule::Flat { amount_minor_units } if *amount_minor_units < 0 => {
+                Err(FeeError::NegativeAmount)
+            }
+            FeeRule::Flat { .. } => Ok(()),
+            FeeRule::Percentage { basis_points } => validate_basis_points(*basis_points),
+            FeeRule::Tiered { tiers } => validate_tiers(tiers),
+        }
+    }
+
+    /// Calculates the fee for a transfer of `amount`, in the currency of the amount. Percentages
+    /// are rounded half up to the nearest minor unit before the minimum and maximum apply.
+    pub fn fee_for(&self, amount: Money) -> Result<Money, MoneyError> {
+        let minor_units = amount.minor_units();
+
+        let fee = match &self.rule {
+            FeeRule::None => 0,
+            FeeRule::Flat { amount_minor_units } => *amount_minor_units,
+            FeeRule::Percentage { basis_points } => percentage_of(minor_units, *basis_points)?,
+            FeeRule::Tiered { tiers } => match tiers.iter().find(|tier| {
+                tier.up_to_minor_units
+                    .is_none_or(|up_to| minor_units <= up_to)
+            }) {
+                Some(tier) => tier
+                    .flat_minor_units
+                    .checked_add(percentage_of(minor_units, tier.basis_points)?)
+                    .ok_or(MoneyError::Overflow)?,
+                None => 0,
+            },
+        };
+
+        let fee = self.min_minor_units.map_or(fee, |min| fee.max(min));
+        let fee = self.max_minor_units.map_or(fee, |max| fee.min(max));
+
+        Money::new(fee, amount.currency())
+    }
+}
+
+/// Returns `basis_points` of `minor_units`, rounded half up.
+pub(crate) fn percentage_of(minor_units: i64, basis_points: u32) -> Result<i64, MoneyError> {
+    let whole = i128::from(BASIS_POINTS_PER_WHOLE);
+    let share = (i128::from(minor_units) * i128::from(basis_points) + whole / 2) / whole;
+
+    i64::try_from(share).map_err(|_| MoneyError::Overflow)
+}
+
+fn validate_basis_points(basis_points: u32) -> Result<(), FeeError> {
+    if basis_points > BASIS_POINTS_PER_WHOLE {
+        return Err(FeeError::InvalidBasisPoints(basis_points));
+    }
+
+    Ok(())
+}
+
+/// Tiers need increasing upper bounds and must end with a tier without one, so that every
+/// amount falls into exactly one tier.
+fn validate_tiers(tiers: &[FeeTier]) -> Result<(), FeeError> {
+    let Some((last, bounded)) = tiers.split_last() else {
+        return Err(FeeError::InvalidTiers);
+    };
+    if last.up_to_minor_units.is_some() {
+        return Err(FeeError::InvalidTiers);
+    }
+
+    let mut previous = None;
+    for tier in bounded {
+        let Some(up_to) = tier.up_to_minor_units else {
+            return Err(FeeError::InvalidTiers);
+        };
+        if previous.is_some_and(|previous| up_to <= previous) {
+            return Err(FeeError::InvalidTiers);
+        }
+        previous = Some(up_to);
+    }
+
+    for tier in tiers {
+        if tier.flat_minor_units < 0 {
+            return Err(FeeError::NegativeAmount);
+        }
+        validate_basis_points(tier.basis_points)?;
+    }
+
+    Ok(())
+}
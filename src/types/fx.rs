@@ -0,0 +1,124 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    error::{FxError, MoneyError},
+    types::{Currency, Money, fee},
+};
+
+/// Number of decimal places exchange rates are kept to.
+pub const RATE_DECIMALS: u32 = 8;
+/// Scaled value of a rate of one.
+const RATE_SCALE: i64 = 10_i64.pow(RATE_DECIMALS);
+
+/// Represents an exchange rate: how many major units of the quote currency one major unit of the
+/// base currency buys, e.g. `83.25` INR per USD. Rates are kept exactly to eight decimal places.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExchangeRate(i64);
+
+impl ExchangeRate {
+    /// Creates a rate from its value scaled by 10^8, rejecting rates that are not positive.
+    pub fn from_scaled(scaled: i64) -> Result<Self, FxError> {
+        if scaled <= 0 {
+            return Err(FxError::InvalidRate(scaled.to_string()));
+        }
+
+        Ok(Self(scaled))
+    }
+
+    /// Returns the rate scaled by 10^8, as stored in the database.
+    pub const fn scaled(self) -> i64 {
+        self.0
+    }
+
+    /// Parses a positive decimal string with up to eight decimal places, e.g. `"83.25"`.
+    pub fn from_decimal_str(value: &str) -> Result<Self, FxError> {
+        let invalid = || FxError::InvalidRate(value.to_string());
+        let decimals = RATE_DECIMALS as usize;
+
+        let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+            return Err(invalid());
+        }
+        if fraction.len() > decimals || (value.contains('.') && fraction.is_empty()) {
+            return Err(invalid());
+        }
+
+        let whole: i64 = whole.parse().map_err(|_| invalid())?;
+        let fraction: i64 = format!("{fraction:0<decimals$}").parse().unwrap_or(0);
+
+        let scaled = whole
+            .checked_mul(RATE_SCALE)
+            .and_then(|scaled| scaled.checked_add(fraction))
+            .ok_or_else(invalid)?;
+
+        Self::from_scaled(scaled).map_err(|_| invalid())
+    }
+
+    /// Formats the rate as a decimal string without trailing zeros, e.g. `"83.25"`.
+    pub fn to_decimal_string(self) -> String {
+        let decimals = RATE_DECIMALS as usize;
+        let fraction = format!("{:0decimals$}", self.0 % RATE_SCALE);
+        let fraction = fraction.trim_end_matches('0');
+
+        if fraction.is_empty() {
+            return (self.0 / RATE_SCALE).to_string();
+        }
+
+        format!("{}.{fraction}", self.0 / RATE_SCALE)
+    }
+
+    /// Converts `amount` into the quote currency `to`, rounding down to its minor unit.
+    pub fn convert(self, amount: Money, to: Currency) -> Result<Money, MoneyError> {
+        let numerator = i128::from(amount.minor_units())
+            .checked_mul(i128::from(self.0))
+            .and_then(|product| product.checked_mul(i128::from(to.minor_units_per_major())))
+            .ok_or(MoneyError::Overflow)?;
+        let denominator =
+            i128::from(RATE_SCALE) * i128::from(amount.currency().minor_units_per_major());
+
+        let minor_units =
+            i64::try_from(numerator / denominator).map_err(|_| MoneyError::Overflow)?;
+
+        Money::new(minor_units, to)
+    }
+
+    /// Returns the rate less `basis_points`, rounded down. This is the rate a customer gets
+    /// when the platform keeps a spread of `basis_points` on the converted amount.
+    pub fn less_basis_points(self, basis_points: u32) -> Result<Self, FxError> {
+        let whole = i128::from(fee::BASIS_POINTS_PER_WHOLE);
+        let kept = whole - i128::from(basis_points.min(fee::BASIS_POINTS_PER_WHOLE));
+        let scaled = i64::try_from(i128::from(self.0) * kept / whole)
+            .map_err(|_| FxError::InvalidRate(self.to_decimal_string()))?;
+
+        Self::from_scaled(scaled)
+    }
+}
+
+/// Returns the spread of `basis_points` the platform keeps on a converted amount, rounded half
+/// up like a percentage fee.
+pub fn spread_of(amount: Money, basis_points: u32) -> Result<Money, MoneyError> {
+    Money::new(
+        fee::percentage_of(amount.minor_units(), basis_points)?,
+        amount.currency(),
+    )
+}
+
+impl std::fmt::Display for ExchangeRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_decimal_string())
+    }
+}
+
+impl Serialize for ExchangeRate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_decimal_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ExchangeRate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::from_decimal_str(&value).map_err(serde::de::Error::custom)
+    }
+}
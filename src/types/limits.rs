@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use time::{Duration, PrimitiveDateTime, Time};
+
+use crate::error::ValidationError;
+
+/// Represents the spending and velocity limits on the transfers a user sends.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransferLimits {
+    /// Largest amount of a single transfer, in minor units.
+    pub max_transfer_minor_units: i64,
+    /// Largest total sent per UTC day, in minor units.
+    pub daily_outgoing_minor_units: i64,
+    /// Largest total sent per UTC calendar month, in minor units.
+    pub monthly_outgoing_minor_units: i64,
+    /// Largest number of transfers sent within any hour.
+    pub transfers_per_hour: i64,
+}
+
+/// Represents one of the limits of `TransferLimits`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LimitKind {
+    MaxTransfer,
+    DailyOutgoing,
+    MonthlyOutgoing,
+    TransfersPerHour,
+}
+
+impl std::fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MaxTransfer => write!(f, "maximum transfer amount"),
+            Self::DailyOutgoing => write!(f, "daily outgoing"),
+            Self::MonthlyOutgoing => write!(f, "monthly outgoing"),
+            Self::TransfersPerHour => write!(f, "transfers per hour"),
+        }
+    }
+}
+
+/// Represents what a user already sent within the windows of their limits.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LimitUsage {
+    /// Total sent since the start of the UTC day, in minor units.
+    pub daily_outgoing_minor_units: i64,
+    /// Total sent since the start of the UTC month, in minor units.
+    pub monthly_outgoing_minor_units: i64,
+    /// Number of transfers sent within the last hour.
+    pub transfers_last_hour: i64,
+}
+
+/// Represents the start of each window the limits are counted over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitWindows {
+    /// Start of the current UTC day.
+    pub day_start: PrimitiveDateTime,
+    /// Start of the current UTC month.
+    pub month_start: PrimitiveDateTime,
+    /// One hour before now; the hourly window is rolling.
+    pub hour_start: PrimitiveDateTime,
+}
+
+impl LimitWindows {
+    /// Returns the windows that contain `now`.
+    pub fn at(now: PrimitiveDateTime) -> Self {
+        let day_start = now.replace_time(Time::MIDNIGHT);
+        let month_start = day_start.replace_day(1).unwrap_or(day_start);
+
+        Self {
+            day_start,
+            month_start,
+            hour_start: now - Duration::HOUR,
+        }
+    }
+
+    /// Returns when the daily total starts over.
+    pub fn day_end(&self) -> PrimitiveDateTime {
+        self.day_start + Duration::DAY
+    }
+
+    /// Returns when the monthly total starts over.
+    pub fn month_end(&self) -> PrimitiveDateTime {
+        let date = self.month_start.date();
+        let next_month = match date.month() {
+            time::Month::December => date
+                .replace_year(date.year() + 1)
+                .and_then(|date| date.replace_month(time::Month::January)),
+            month => date.replace_month(month.next()),
+        };
+
+        next_month.map_or(self.month_start, |date| date.midnight())
+    }
+}
+
+impl TransferLimits {
+    /// Validates that every limit is positive.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let limits = [
+            self.max_transfer_minor_units,
+            self.daily_outgoing_minor_units,
+            self.monthly_outgoing_minor_units,
+            self.transfers_per_hour,
+        ];
+        if limits.iter().any(|limit| *limit <= 0) {
+            return Err(ValidationError::InvalidValue {
+                message: "Transfer limits must be positive".into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that sending transfers of `amounts` on top of `usage` stays within the limits,
+    /// returning the first limit that would be exceeded.
+    pub fn check(&self, usage: &LimitUsage, amounts: &[i64]) -> Result<(), LimitKind> {
+        if amounts
+            .iter()
+            .any(|amount| *amount > self.max_transfer_minor_units)
+        {
+            return Err(LimitKind::MaxTransfer);
+        }
+
+        let total = amounts
+            .iter()
+            .try_fold(0_i64, |total, amount| total.checked_add(*amount));
+        let exceeds = |used: i64, limit: i64| {
+            total
+                .and_then(|total| total.checked_add(used))
+                .is_none_or(|sent| sent > limit)
+        };
+        if exceeds(
+            usage.daily_outgoing_minor_units,
+            self.daily_outgoing_minor_units,
+        ) {
+            return Err(LimitKind::DailyOutgoing);
+        }
+        if exceeds(
+            usage.monthly_outgoing_minor_units,
+            self.monthly_outgoing_minor_units,
+        ) {
+            return Err(LimitKind::MonthlyOutgoing);
+        }
+
+        let count = i64::try_from(amounts.len()).unwrap_or(i64::MAX);
+        if usage.transfers_last_hour.saturating_add(count) > self.transfers_per_hour {
+            return Err(LimitKind::TransfersPerHour);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use diesel::{
+    deserialize::{FromSql, FromSqlRow},
+    expression::AsExpression,
+    pg::{Pg, PgValue},
+    serialize::{IsNull, Output, ToSql},
+    sql_types::Jsonb,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ValidationError;
+
+/// Maximum number of keys in a metadata object.
+pub const MAX_METADATA_KEYS: usize = 20;
+/// Maximum length of a metadata key, in characters.
+pub const MAX_METADATA_KEY_LENGTH: usize = 40;
+/// Maximum length of a metadata value, in characters.
+pub const MAX_METADATA_VALUE_LENGTH: usize = 500;
+
+/// Represents client supplied key-value pairs attached to a transaction, e.g. an order
+/// reference. Stored as a JSONB object of strings.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = Jsonb)]
+#[serde(transparent)]
+pub struct Metadata(pub BTreeMap<String, String>);
+
+impl Metadata {
+    /// Returns the value stored under `key`, if any.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// Validates the number of keys and the length of every key and value.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.0.len() > MAX_METADATA_KEYS {
+            return Err(ValidationError::InvalidValue {
+                message: format!("Metadata cannot have more than {MAX_METADATA_KEYS} keys"),
+            });
+        }
+
+        for (key, value) in &self.0 {
+            validate_key(key)?;
+
+            if value.chars().count() > MAX_METADATA_VALUE_LENGTH {
+                return Err(ValidationError::InvalidValue {
+                    message: format!(
+                        "Metadata value of {key} cannot be longer than {MAX_METADATA_VALUE_LENGTH} characters"
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validates a metadata key, which must be between 1 and `MAX_METADATA_KEY_LENGTH` characters.
+pub fn validate_key(key: &str) -> Result<(), ValidationError> {
+    if key.is_empty() || key.chars().count() > MAX_METADATA_KEY_LENGTH {
+        return Err(ValidationError::InvalidValue {
+            message: format!(
+                "Metadata keys must be between 1 and {MAX_METADATA_KEY_LENGTH} characters"
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+impl ToSql<Jsonb, Pg> for Metadata {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> diesel::serialize::Result {
+        // JSONB values are sent as a version byte followed by the JSON text.
+        out.write_all(&[1])?;
+        serde_json::to_writer(out, &self.0)?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Jsonb, Pg> for Metadata {
+    fn from_sql(bytes: PgValue<'_>) -> diesel::deserialize::Result<Self> {
+        let value = <serde_json::Value as FromSql<Jsonb, Pg>>::from_sql(bytes)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::MoneyError;
+
+/// Represents an ISO 4217 currency code.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    /// Indian Rupee, with paise as the minor unit.
+    #[default]
+    Inr,
+    /// United States Dollar, with cents as the minor unit.
+    Usd,
+    /// Euro, with cents as the minor unit.
+    Eur,
+    /// Pound Sterling, with pence as the minor unit.
+    Gbp,
+    /// Japanese Yen, which has no minor unit.
+    Jpy,
+}
+
+impl Currency {
+    /// Returns the ISO 4217 alphabetic code.
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::Inr => "INR",
+            Self::Usd => "USD",
+            Self::Eur => "EUR",
+            Self::Gbp => "GBP",
+            Self::Jpy => "JPY",
+        }
+    }
+
+    /// Returns the number of decimal places of the minor unit.
+    pub const fn exponent(self) -> u32 {
+        match self {
+            Self::Inr | Self::Usd | Self::Eur | Self::Gbp => 2,
+            Self::Jpy => 0,
+        }
+    }
+
+    /// Returns the number of minor units in one major unit.
+    pub const fn minor_units_per_major(self) -> i64 {
+        10_i64.pow(self.exponent())
+    }
+}
+
+impl std::str::FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        match code {
+            "INR" => Ok(Self::Inr),
+            "USD" => Ok(Self::Usd),
+            "EUR" => Ok(Self::Eur),
+            "GBP" => Ok(Self::Gbp),
+            "JPY" => Ok(Self::Jpy),
+            _ => Err(MoneyError::UnsupportedCurrency(code.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Represents an exact, non-negative amount of money in minor units (e.g. paise).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(try_from = "MoneyFields")]
+pub struct Money {
+    minor_units: i64,
+    currency: Currency,
+}
+
+/// Represents the fields of a deserialized Money, before `Money::new` validates them.
+#[derive(Deserialize)]
+struct MoneyFields {
+    minor_units: i64,
+    currency: Currency,
+}
+
+impl TryFrom<MoneyFields> for Money {
+    type Error = MoneyError;
+
+    fn try_from(fields: MoneyFields) -> Result<Self, Self::Error> {
+        Self::new(fields.minor_units, fields.currency)
+    }
+}
+
+impl Money {
+    /// Creates a new Money from minor units, rejecting negative amounts.
+    pub fn new(minor_units: i64, currency: Currency) -> Result<Self, MoneyError> {
+        if minor_units < 0 {
+            return Err(MoneyError::NegativeAmount);
+        }
+
+        Ok(Self {
+            minor_units,
+            currency,
+        })
+    }
+
+    /// Creates a zero amount in the given currency.
+    pub const fn zero(currency: Currency) -> Self {
+        Self {
+            minor_units: 0,
+            currency,
+        }
+    }
+
+    /// Parses a decimal string such as `"12.50"` in major units.
+    pub fn from_decimal_str(value: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let invalid = || MoneyError::InvalidFormat(value.to_string());
+
+        if value.starts_with('-') {
+            return Err(MoneyError::NegativeAmount);
+        }
+
+        let (major, minor) = value.split_once('.').unwrap_or((value, ""));
+        let exponent = currency.exponent() as usize;
+
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if major.is_empty() || !is_digits(major) || !is_digits(minor) || minor.len() > exponent {
+            return Err(invalid());
+        }
+        if value.contains('.') && minor.is_empty() {
+            return Err(invalid());
+        }
+
+        let major: i64 = major.parse().map_err(|_| MoneyError::Overflow)?;
+        let minor: i64 = format!("{minor:0<exponent$}").parse().unwrap_or(0);
+
+        let minor_units = major
+            .checked_mul(currency.minor_units_per_major())
+            .and_then(|units| units.checked_add(minor))
+            .ok_or(MoneyError::Overflow)?;
+
+        Self::new(minor_units, currency)
+    }
+
+    /// Returns the amount in minor units.
+    pub const fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    /// Returns the currency of the amount.
+    pub const fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// Returns true if the amount is zero.
+    pub const fn is_zero(&self) -> bool {
+        self.minor_units == 0
+    }
+
+    /// Adds two amounts, rejecting currency mismatches and overflow.
+    pub fn checked_add(self, other: Self) -> Result<Self, MoneyError> {
+        self.ensure_same_currency(other)?;
+        let minor_units = self
+            .minor_units
+            .checked_add(other.minor_units)
+            .ok_or(MoneyError::Overflow)?;
+
+        Self::new(minor_units, self.currency)
+    }
+
+    /// Subtracts `other` from the amount, rejecting currency mismatches and negative results.
+    pub fn checked_sub(self, other: Self) -> Result<Self, MoneyError> {
+        self.ensure_same_currency(other)?;
+        let minor_units = self
+            .minor_units
+            .checked_sub(other.minor_units)
+            .ok_or(MoneyError::Overflow)?;
+
+        Self::new(minor_units, self.currency)
+    }
+
+    /// Formats the amount as a decimal string in major units, e.g. `"12.50"`.
+    pub fn to_decimal_string(&self) -> String {
+        let per_major = self.currency.minor_units_per_major();
+        let exponent = self.currency.exponent() as usize;
+        if exponent == 0 {
+            return self.minor_units.to_string();
+        }
+
+        format!(
+            "{}.{:0exponent$}",
+            self.minor_units / per_major,
+            self.minor_units % per_major
+        )
+    }
+
+    fn ensure_same_currency(&self, other: Self) -> Result<(), MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch {
+                expected: self.currency.code(),
+                found: other.currency.code(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.to_decimal_string(), self.currency)
+    }
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, Month, PrimitiveDateTime, Time, Weekday};
+
+use crate::error::RecurrenceError;
+
+/// Number of days searched for the next match of a cron expression before giving up.
+const CRON_SEARCH_DAYS: u32 = 366 * 5;
+
+/// Represents how often a standing instruction repeats.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "frequency", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Recurrence {
+    /// Every day at the time of day of the start.
+    Daily,
+    /// Every week on the weekday and at the time of day of the start.
+    Weekly,
+    /// Every month on `day_of_month` at the time of day of the start. Months shorter than
+    /// `day_of_month` run on their last day.
+    Monthly { day_of_month: u8 },
+    /// Every minute matching a five field cron expression, evaluated in UTC.
+    Cron { expression: CronSchedule },
+}
+
+impl Recurrence {
+    /// Creates a monthly recurrence, rejecting days outside 1 to 31.
+    pub fn monthly(day_of_month: u8) -> Result<Self, RecurrenceError> {
+        if !(1..=31).contains(&day_of_month) {
+            return Err(RecurrenceError::InvalidDayOfMonth(day_of_month));
+        }
+
+        Ok(Self::Monthly { day_of_month })
+    }
+
+    /// Validates a recurrence built through deserialization.
+    pub fn validate(&self) -> Result<(), RecurrenceError> {
+        match self {
+            Self::Monthly { day_of_month } => Self::monthly(*day_of_month).map(|_| ()),
+            Self::Daily | Self::Weekly | Self::Cron { .. } => Ok(()),
+        }
+    }
+
+    /// Returns the first occurrence at or after `start`.
+    pub fn first_occurrence(&self, start: PrimitiveDateTime) -> Option<PrimitiveDateTime> {
+        match self {
+            Self::Daily | Self::Weekly => Some(start),
+            Self::Monthly { day_of_month } => {
+                let candidate =
+                    monthly_occurrence(start.year(), start.month(), *day_of_month, start.time())?;
+                if candidate >= start {
+                    Some(candidate)
+                } else {
+                    self.next_occurrence(candidate)
+                }
+            }
+            Self::Cron { expression } => expression.next_after(start - Duration::NANOSECOND),
+        }
+    }
+
+    /// Returns the first occurrence at or after `not_before` of the series that continues from
+    /// the occurrence `previous`, skipping the ones in between.
+    pub fn occurrence_not_before(
+        &self,
+        previous: PrimitiveDateTime,
+        not_before: PrimitiveDateTime,
+    ) -> Option<PrimitiveDateTime> {
+        if previous >= not_before {
+            return Some(previous);
+        }
+
+        match self {
+            Self::Daily | Self::Weekly => {
+                let period = if *self == Self::Daily {
+                    Duration::DAY
+                } else {
+                    Duration::WEEK
+                };
+                let gap = u64::try_from((not_before - previous).whole_seconds()).ok()?;
+                let periods = gap.div_ceil(u64::try_from(period.whole_seconds()).ok()?);
+                previous.checked_add(period.checked_mul(i32::try_from(periods).ok()?)?)
+            }
+            Self::Monthly { .. } => {
+                let mut occurrence = previous;
+                while occurrence < not_before {
+                    occurrence = self.next_occurrence(occurrence)?;
+                }
+                Some(occurrence)
+            }
+            Self::Cron { expression } => expression.next_after(not_before - Duration::NANOSECOND),
+        }
+    }
+
+    /// Returns the occurrence that follows `previous`, which must itself be an occurrence.
+    pub fn next_occurrence(&self, previous: PrimitiveDateTime) -> Option<PrimitiveDateTime> {
+        match self {
+            Self::Daily => previous.checked_add(Duration::DAY),
+            Self::Weekly => previous.checked_add(Duration::WEEK),
+            Self::Monthly { day_of_month } => {
+                let (year, month) = match previous.month() {
+                    Month::December => (previous.year().checked_add(1)?, Month::January),
+                    month => (previous.year(), month.next()),
+                };
+                monthly_occurrence(year, month, *day_of_month, previous.time())
+            }
+            Self::Cron { expression } => expression.next_after(previous),
+        }
+    }
+}
+
+/// Builds the occurrence of a monthly recurrence in a given month.
+fn monthly_occurrence(
+    year: i32,
+    month: Month,
+    day_of_month: u8,
+    time: Time,
+) -> Option<PrimitiveDateTime> {
+    let day = day_of_month.min(month.length(year));
+    let date = Date::from_calendar_date(year, month, day).ok()?;
+    Some(PrimitiveDateTime::new(date, time))
+}
+
+/// Represents a parsed five field cron expression: minute, hour, day of month, month and day
+/// of week. Fields accept `*`, single values, ranges, lists and `/` steps. Day of week runs from
+/// 0 (Sunday) to 6, with 7 also meaning Sunday.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day of month field was `*`. As in cron, when both day fields are
+    /// restricted a day matches if either of them does.
+    any_day_of_month: bool,
+    /// Whether the day of week field was `*`.
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    /// Returns the expression the schedule was parsed from.
+    pub fn as_str(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns the first matching minute strictly after `after`.
+    pub fn next_after(&self, after: PrimitiveDateTime) -> Option<PrimitiveDateTime> {
+        let truncated = after.replace_time(Time::from_hms(after.hour(), after.minute(), 0).ok()?);
+        let start = truncated.checked_add(Duration::MINUTE)?;
+
+        let mut date = start.date();
+        for _ in 0..CRON_SEARCH_DAYS {
+            if self.matches_date(date) {
+                let (first_hour, first_minute) = if date == start.date() {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+
+                for hour in first_hour..24 {
+                    if !has_bit(self.hours, hour) {
+                        continue;
+                    }
+                    let from_minute = if hour == first_hour { first_minute } else { 0 };
+                    for minute in from_minute..60 {
+                        if has_bit(self.minutes, minute) {
+                            let time = Time::from_hms(hour, minute, 0).ok()?;
+                            return Some(PrimitiveDateTime::new(date, time));
+                        }
+                    }
+                }
+            }
+            date = date.next_day()?;
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        if !has_bit(self.months, u8::from(date.month())) {
+            return false;
+        }
+
+        let day_of_month = has_bit(self.days_of_month, date.day());
+        let day_of_week = has_bit(self.days_of_week, date.weekday().number_days_from_sunday());
+
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (false, true) => day_of_month,
+            (true, false) => day_of_week,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+}
+
+impl std::str::FromStr for CronSchedule {
+    type Err = RecurrenceError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let invalid = || RecurrenceError::InvalidCronExpression(expression.to_string());
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(invalid());
+        };
+
+        let mut days_of_week_bits = parse_field(days_of_week, 0, 7).ok_or_else(invalid)?;
+        if has_bit(days_of_week_bits, 7) {
+            days_of_week_bits |= 1 << Weekday::Sunday.number_days_from_sunday();
+        }
+
+        let schedule = Self {
+            expression: fields.join(" "),
+            minutes: parse_field(minutes, 0, 59).ok_or_else(invalid)?,
+            hours: parse_field(hours, 0, 23).ok_or_else(invalid)?,
+            days_of_month: parse_field(days_of_month, 1, 31).ok_or_else(invalid)?,
+            months: parse_field(months, 1, 12).ok_or_else(invalid)?,
+            days_of_week: days_of_week_bits,
+            any_day_of_month: days_of_month == "*",
+            any_day_of_week: days_of_week == "*",
+        };
+
+        // Reject expressions such as `0 0 31 2 *` that can never fire. The search window starts
+        // on a leap year so that February 29 is reachable.
+        let leap_year = Date::from_calendar_date(2000, Month::January, 1).map_err(|_| invalid())?;
+        if schedule
+            .next_after(PrimitiveDateTime::new(leap_year, Time::MIDNIGHT))
+            .is_none()
+        {
+            return Err(invalid());
+        }
+
+        Ok(schedule)
+    }
+}
+
+impl std::fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl Serialize for CronSchedule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.expression)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronSchedule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let expression = String::deserialize(deserializer)?;
+        expression.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parses one cron field into a bitset of the values it matches.
+fn parse_field(field: &str, min: u8, max: u8) -> Option<u64> {
+    let mut bits = 0_u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u8>().ok().filter(|step| *step > 0)?),
+            None => (part, 1),
+        };
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                // A single value with a step, e.g. `5/15`, runs from the value to the maximum.
+                None if part.contains('/') => (range.parse().ok()?, max),
+                None => {
+                    let value = range.parse().ok()?;
+                    (value, value)
+                }
+            },
+        };
+
+        if start < min || end > max || start > end {
+            return None;
+        }
+
+        for value in (start..=end).step_by(usize::from(step)) {
+            bits |= 1 << value;
+        }
+    }
+
+    Some(bits)
+}
+
+fn has_bit(bits: u64, value: u8) -> bool {
+    bits & (1 << value) != 0
+}
@@ -64,4 +64,54 @@ pub mod datetime {
         let utc_date_time: OffsetDateTime = OffsetDateTime::now_utc();
         PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
     }
+
+    /// Converts an offset datetime into the UTC datetime stored in the database.
+    pub fn to_utc(date_time: OffsetDateTime) -> PrimitiveDateTime {
+        let utc_date_time = date_time.to_offset(time::UtcOffset::UTC);
+        PrimitiveDateTime::new(utc_date_time.date(), utc_date_time.time())
+    }
+
+    // Serializes dates as `YYYY-MM-DD`, e.g. in query strings. The macro cannot carry docs.
+    time::serde::format_description!(pub iso_date, Date, "[year]-[month]-[day]");
+
+    /// Formats a UTC datetime as RFC 3339 with microsecond precision, e.g.
+    /// `2025-06-10T12:00:00.000000Z`, so every timestamp has the same width.
+    pub fn to_rfc3339(date_time: PrimitiveDateTime) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+            date_time.year(),
+            u8::from(date_time.month()),
+            date_time.day(),
+            date_time.hour(),
+            date_time.minute(),
+            date_time.second(),
+            date_time.microsecond()
+        )
+    }
+}
+
+/// CSV utilities.
+pub mod csv {
+    /// Quotes a CSV field when needed. Fields that a spreadsheet would read as a formula are
+    /// prefixed with an apostrophe, since many fields are written by users.
+    pub fn field(value: &str) -> String {
+        let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+            format!("'{value}")
+        } else {
+            value.to_string()
+        };
+
+        if value.contains([',', '"', '\r', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    }
+
+    /// Joins fields into a CSV record, terminated by CRLF.
+    pub fn record<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+        let mut record = fields.into_iter().map(field).collect::<Vec<_>>().join(",");
+        record.push_str("\r\n");
+        record
+    }
 }
@@ -0,0 +1,389 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    app::AppState,
+    error::{PayoutError, TransactionDbError, container::ContainerError},
+    logger,
+    payout::{self, PayoutStatus},
+    storage::{
+        BankAccountInterface, PaymentRequestInterface, ReconciliationInterface,
+        ScheduledTransferInterface, StandingInstructionInterface, TransactionInterface,
+        WalletInterface, WithdrawalInterface,
+        enums::{ScheduledTransferStatus, StandingInstructionStatus},
+        types::Withdrawal,
+    },
+    utils::datetime,
+};
+
+/// Spawns the task that periodically marks lapsed authorizations as EXPIRED.
+///
+/// Lapsed holds stop counting against the available balance as soon as they expire; the sweep
+/// only keeps their status in step.
+pub fn spawn_authorization_expiry(app_state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    let period = Duration::from_secs(app_state.config.authorization.expiry_sweep_interval.max(1));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match app_state.db.expire_authorizations().await {
+                Ok(0) => {}
+                Ok(count) => logger::info!("Expired {count} lapsed authorizations"),
+                Err(error) => logger::error!(?error, "Failed to expire lapsed authorizations"),
+            }
+        }
+    })
+}
+
+/// Spawns the task that periodically marks lapsed payment requests as EXPIRED.
+///
+/// Lapsed requests can no longer be paid even before the sweep reaches them.
+pub fn spawn_payment_request_expiry(app_state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    let period = Duration::from_secs(
+        app_state
+            .config
+            .payment_request
+            .expiry_sweep_interval
+            .max(1),
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match app_state.db.expire_payment_requests().await {
+                Ok(0) => {}
+                Ok(count) => logger::info!("Expired {count} lapsed payment requests"),
+                Err(error) => logger::error!(?error, "Failed to expire lapsed payment requests"),
+            }
+        }
+    })
+}
+
+/// Spawns the task that executes scheduled transfers once they are due.
+///
+/// Every instance of the server runs this task; rows are claimed with `FOR UPDATE SKIP LOCKED`
+/// so each transfer is executed by exactly one of them.
+pub fn spawn_scheduled_transfer_executor(app_state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    let scheduler = app_state.config.scheduler.clone();
+    let period = Duration::from_secs(scheduler.poll_interval.max(1));
+    let retry_delay =
+        time::Duration::seconds(i64::try_from(scheduler.retry_delay).unwrap_or(i64::MAX));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            for _ in 0..scheduler.batch_size {
+                match app_state
+                    .db
+                    .execute_next_scheduled_transfer(retry_delay, scheduler.max_attempts)
+                    .await
+                {
+                    Ok(Some(scheduled_transfer))
+                        if scheduled_transfer.status == ScheduledTransferStatus::Scheduled =>
+                    {
+                        logger::warn!(
+                            "Scheduled transfer {} hit a database error on attempt {}, retrying \
+                             at {:?}",
+                            scheduled_transfer.scheduled_transfer_id,
+                            scheduled_transfer.attempts,
+                            scheduled_transfer.retry_at
+                        )
+                    }
+                    Ok(Some(scheduled_transfer)) => logger::info!(
+                        "Scheduled transfer {} finished as {}",
+                        scheduled_transfer.scheduled_transfer_id,
+                        scheduled_transfer.status
+                    ),
+                    Ok(None) => break,
+                    Err(error) => {
+                        logger::error!(?error, "Failed to execute scheduled transfer");
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Spawns the task that executes the runs of standing instructions once they are due.
+///
+/// Shares the poll interval, batch size and retry settings of the scheduled transfer executor.
+/// Runs missed while no instance was running are skipped or executed according to
+/// `scheduler.catch_up_policy`.
+pub fn spawn_standing_instruction_executor(
+    app_state: Arc<AppState>,
+) -> tokio::task::JoinHandle<()> {
+    let scheduler = app_state.config.scheduler.clone();
+    let period = Duration::from_secs(scheduler.poll_interval.max(1));
+    let grace =
+        time::Duration::seconds(i64::try_from(scheduler.missed_run_grace).unwrap_or(i64::MAX));
+    let retry_delay =
+        time::Duration::seconds(i64::try_from(scheduler.retry_delay).unwrap_or(i64::MAX));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            for _ in 0..scheduler.batch_size {
+                match app_state
+                    .db
+                    .execute_next_standing_instruction(
+                        scheduler.catch_up_policy,
+                        grace,
+                        retry_delay,
+                        scheduler.max_attempts,
+                    )
+                    .await
+                {
+                    Ok(Some(standing_instruction)) if standing_instruction.retry_at.is_some() => {
+                        logger::warn!(
+                            "Standing instruction {} hit a database error on attempt {}, \
+                             retrying at {:?}",
+                            standing_instruction.standing_instruction_id,
+                            standing_instruction.attempts,
+                            standing_instruction.retry_at
+                        )
+                    }
+                    Ok(Some(standing_instruction))
+                        if standing_instruction.status == StandingInstructionStatus::Failed =>
+                    {
+                        logger::error!(
+                            "Standing instruction {} failed: {:?}",
+                            standing_instruction.standing_instruction_id,
+                            standing_instruction.last_failure_reason
+                        )
+                    }
+                    Ok(Some(standing_instruction)) => logger::info!(
+                        "Standing instruction {} ran {} times, next run at {:?}",
+                        standing_instruction.standing_instruction_id,
+                        standing_instruction.occurrences_count,
+                        standing_instruction.next_run_at
+                    ),
+                    Ok(None) => break,
+                    Err(error) => {
+                        logger::error!(?error, "Failed to execute standing instruction");
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Spawns the task that periodically reconciles every wallet, unless `reconciliation.interval`
+/// is 0.
+///
+/// The first run happens one interval after startup, so restarts do not each scan the whole
+/// history. Every instance ticks, but a tick is skipped while another instance holds the
+/// reconciliation lock or when any run started within the last interval, less a tenth for the
+/// drift between the timers of the instances, so only one instance reconciles per interval.
+pub fn spawn_reconciliation(app_state: Arc<AppState>) -> Option<tokio::task::JoinHandle<()>> {
+    let interval_secs = app_state.config.reconciliation.interval;
+    if interval_secs == 0 {
+        return None;
+    }
+    let period = Duration::from_secs(interval_secs);
+    let min_interval = time::Duration::seconds((interval_secs - interval_secs / 10) as i64);
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match app_state.db.reconcile_if_due(min_interval).await {
+                Ok(None) => {
+                    logger::debug!("Reconciliation skipped, another instance ran it recently")
+                }
+                Ok(Some((run, findings))) if findings.is_empty() => logger::info!(
+                    "Reconciliation run {} checked {} wallets and found no discrepancies",
+                    run.run_id,
+                    run.wallets_checked
+                ),
+                Ok(Some((run, findings))) => logger::warn!(
+                    "Reconciliation run {} checked {} wallets and found {} discrepancies",
+                    run.run_id,
+                    run.wallets_checked,
+                    findings.len()
+                ),
+                Err(error) => logger::error!(?error, "Failed to reconcile balances"),
+            }
+        }
+    }))
+}
+
+/// Spawns the task that periodically snapshots the closing balance of every wallet for each day
+/// that is over, unless `balance_history.snapshot_interval` is 0.
+///
+/// The first pass happens right after startup, catching up on the days missed while no
+/// instance was running. Instances may snapshot the same day; the first to store it wins.
+pub fn spawn_balance_snapshots(app_state: Arc<AppState>) -> Option<tokio::task::JoinHandle<()>> {
+    let interval_secs = app_state.config.balance_history.snapshot_interval;
+    if interval_secs == 0 {
+        return None;
+    }
+    let period = Duration::from_secs(interval_secs);
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            let Some(yesterday) = datetime::now().date().previous_day() else {
+                continue;
+            };
+            match app_state.db.snapshot_balances(yesterday).await {
+                Ok(0) => {}
+                Ok(count) => logger::info!("Stored {count} balance snapshots through {yesterday}"),
+                Err(error) => logger::error!(?error, "Failed to snapshot balances"),
+            }
+        }
+    }))
+}
+
+/// Spawns the task that settles withdrawals left PENDING, unless `withdrawal.recovery_interval`
+/// is 0.
+///
+/// A withdrawal stays PENDING when its connector does not answer within `withdrawal.timeout`
+/// or the server stops before the payout gets an outcome. Once a withdrawal is older than the
+/// timeout, the task asks the connector what became of it, up to `scheduler.batch_size`
+/// withdrawals a pass. Each withdrawal is claimed for twice the timeout, enough for a status
+/// lookup and a dispatch, so no other instance checks or dispatches it meanwhile.
+pub fn spawn_withdrawal_recovery(app_state: Arc<AppState>) -> Option<tokio::task::JoinHandle<()>> {
+    let interval_secs = app_state.config.withdrawal.recovery_interval;
+    if interval_secs == 0 {
+        return None;
+    }
+    let period = Duration::from_secs(interval_secs);
+    let timeout = time::Duration::seconds(
+        i64::try_from(app_state.config.withdrawal.timeout).unwrap_or(i64::MAX),
+    );
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            for _ in 0..app_state.config.scheduler.batch_size {
+                let now = datetime::now();
+                let withdrawal = match app_state
+                    .db
+                    .claim_stale_withdrawal(now - timeout, now + timeout * 2)
+                    .await
+                {
+                    Ok(Some(withdrawal)) => withdrawal,
+                    Ok(None) => break,
+                    Err(error) => {
+                        logger::error!(?error, "Failed to claim a pending withdrawal");
+                        break;
+                    }
+                };
+
+                let withdrawal_id = withdrawal.withdrawal_id.clone();
+                match recover_withdrawal(&app_state, withdrawal).await {
+                    Ok(withdrawal) => logger::info!(
+                        "Pending withdrawal {} is now {}",
+                        withdrawal.withdrawal_id,
+                        withdrawal.status
+                    ),
+                    Err(error) => logger::error!(
+                        ?error,
+                        "Failed to recover pending withdrawal {withdrawal_id}"
+                    ),
+                }
+            }
+        }
+    }))
+}
+
+/// Settles a PENDING withdrawal by what its connector knows of the payout.
+///
+/// A paid payout completes the withdrawal and a rejected one fails it, returning the money. A
+/// payout the connector never received is dispatched again under the same withdrawal ID. The
+/// withdrawal stays PENDING while the payout is processing or the connector does not answer.
+///
+/// The connector is the one recorded on the withdrawal, not the one configured for new
+/// withdrawals. If it is no longer configured, the withdrawal stays PENDING until an operator
+/// settles it.
+///
+/// The withdrawal must be claimed through `claim_stale_withdrawal` first, so that no other
+/// instance dispatches it at the same time.
+pub async fn recover_withdrawal(
+    app_state: &AppState,
+    withdrawal: Withdrawal,
+) -> Result<Withdrawal, ContainerError<TransactionDbError>> {
+    let Some(connector) = app_state.payout_connector_named(&withdrawal.connector) else {
+        logger::error!(
+            "Payout connector {} of withdrawal_id: {} is not configured, leaving it pending",
+            withdrawal.connector,
+            withdrawal.withdrawal_id
+        );
+        return Ok(withdrawal);
+    };
+    let timeout = Duration::from_secs(app_state.config.withdrawal.timeout);
+
+    let outcome = match payout::status(connector, &withdrawal.withdrawal_id, timeout).await {
+        Ok(PayoutStatus::Paid(reference)) => Ok(reference),
+        Ok(PayoutStatus::Rejected(reason)) => Err(PayoutError::Rejected(reason)),
+        Ok(PayoutStatus::Processing) => return Ok(withdrawal),
+        Err(error) => {
+            logger::warn!(
+                "Payout status lookup failed for withdrawal_id: {}: {}",
+                withdrawal.withdrawal_id,
+                error
+            );
+            return Ok(withdrawal);
+        }
+        Ok(PayoutStatus::NotFound) => {
+            let bank_account = app_state
+                .db
+                .get_bank_account(&withdrawal.bank_account_id)
+                .await?;
+            let payout = payout::Payout {
+                withdrawal_id: withdrawal.withdrawal_id.clone(),
+                amount: withdrawal.amount().map_err(TransactionDbError::from)?,
+                account_holder_name: bank_account.account_holder_name,
+                account_number: bank_account.account_number,
+                ifsc: bank_account.ifsc,
+            };
+            payout::dispatch(connector, &payout, timeout).await
+        }
+    };
+
+    match outcome {
+        Ok(reference) => {
+            app_state
+                .db
+                .complete_withdrawal(&withdrawal.withdrawal_id, reference)
+                .await
+        }
+        Err(error @ PayoutError::Rejected(_)) => {
+            app_state
+                .db
+                .fail_withdrawal(&withdrawal.withdrawal_id, error.to_string())
+                .await
+        }
+        Err(PayoutError::TimedOut) => Ok(withdrawal),
+    }
+}
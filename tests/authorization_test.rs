@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        routes::api_models::{
+            AmountRequest, AuthorizeTransactionRequest, CaptureTransactionRequest,
+        },
+        storage::enums::TransactionStatus,
+    };
+
+    /// Tests the status transitions of an authorization.
+    #[test]
+    fn test_authorization_transitions() {
+        use TransactionStatus::*;
+
+        assert!(Pending.can_transition_to(Authorized));
+        assert!(Authorized.can_transition_to(Completed));
+        assert!(Authorized.can_transition_to(Voided));
+        assert!(Authorized.can_transition_to(Expired));
+
+        assert!(!Authorized.can_transition_to(Failed));
+        assert!(!Voided.can_transition_to(Completed));
+        assert!(!Expired.can_transition_to(Completed));
+        assert!(!Completed.can_transition_to(Voided));
+        assert!(!Authorized.is_refundable());
+    }
+
+    /// Tests that an authorize request reuses the transfer fields and bounds the hold expiry.
+    #[test]
+    fn test_authorize_request_validation() {
+        let request: AuthorizeTransactionRequest = serde_json::from_str(
+            r#"{"sender_id": "a", "receiver_id": "b", "amount": "10.00", "expires_in": 60}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request.transfer.amount,
+            AmountRequest::Decimal("10.00".into())
+        );
+        assert!(request.validate(3600).is_ok());
+        assert!(request.validate(30).is_err());
+
+        let no_expiry: AuthorizeTransactionRequest =
+            serde_json::from_str(r#"{"sender_id": "a", "receiver_id": "b", "amount": 1000}"#)
+                .unwrap();
+        assert!(no_expiry.validate(3600).is_ok());
+
+        let same_account: AuthorizeTransactionRequest =
+            serde_json::from_str(r#"{"sender_id": "a", "receiver_id": "a", "amount": 1000}"#)
+                .unwrap();
+        assert!(same_account.validate(3600).is_err());
+    }
+
+    /// Tests that a capture request may omit the amount but not ask for zero.
+    #[test]
+    fn test_capture_request_validation() {
+        let full: CaptureTransactionRequest = serde_json::from_str("{}").unwrap();
+        assert!(full.validate().is_ok());
+
+        let zero = CaptureTransactionRequest {
+            amount: Some(AmountRequest::MinorUnits(0)),
+        };
+        assert!(zero.validate().is_err());
+    }
+}
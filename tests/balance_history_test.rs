@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use dodopayments::{
+        balance_history::{self, BalanceBucket, BalanceInterval},
+        configs::Config,
+        routes::api_models::BalanceHistoryRequest,
+        types::Currency,
+    };
+    use time::{Date, macros::date};
+
+    fn spans(from: Date, to: Date, interval: BalanceInterval) -> Vec<(Date, Date)> {
+        balance_history::buckets(from, to, interval)
+            .map(|BalanceBucket { start, end }| (start, end))
+            .collect()
+    }
+
+    fn request(query: serde_json::Value) -> BalanceHistoryRequest {
+        serde_json::from_value(query).unwrap()
+    }
+
+    /// Tests that weeks run from Monday to Sunday and months to their last day, with the first
+    /// and last buckets cut short at the ends of the range.
+    #[test]
+    fn test_buckets() {
+        assert_eq!(
+            spans(date!(2025-06-10), date!(2025-06-12), BalanceInterval::Day),
+            [
+                (date!(2025-06-10), date!(2025-06-10)),
+                (date!(2025-06-11), date!(2025-06-11)),
+                (date!(2025-06-12), date!(2025-06-12)),
+            ]
+        );
+        assert_eq!(
+            spans(date!(2025-06-12), date!(2025-06-24), BalanceInterval::Week),
+            [
+                (date!(2025-06-12), date!(2025-06-15)),
+                (date!(2025-06-16), date!(2025-06-22)),
+                (date!(2025-06-23), date!(2025-06-24)),
+            ]
+        );
+        assert_eq!(
+            spans(date!(2024-01-15), date!(2024-03-10), BalanceInterval::Month),
+            [
+                (date!(2024-01-15), date!(2024-01-31)),
+                (date!(2024-02-01), date!(2024-02-29)),
+                (date!(2024-03-01), date!(2024-03-10)),
+            ]
+        );
+        assert!(spans(date!(2025-06-12), date!(2025-06-11), BalanceInterval::Day).is_empty());
+    }
+
+    /// Tests that closing balances walk back from the current balance, only through what
+    /// moved after each day.
+    #[test]
+    fn test_closing_balances() {
+        let flows = BTreeMap::from([
+            (date!(2025-06-10), 100_000),
+            (date!(2025-06-12), -10_000),
+            (date!(2025-06-15), -5_000),
+        ]);
+
+        let closing = balance_history::closing_balances(
+            85_000,
+            &flows,
+            [
+                date!(2025-06-16),
+                date!(2025-06-09),
+                date!(2025-06-12),
+                date!(2025-06-11),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            closing,
+            BTreeMap::from([
+                (date!(2025-06-09), 0),
+                (date!(2025-06-11), 100_000),
+                (date!(2025-06-12), 90_000),
+                (date!(2025-06-16), 85_000),
+            ])
+        );
+
+        let overflowing = BTreeMap::from([(date!(2025-06-10), -1)]);
+        assert_eq!(
+            balance_history::closing_balances(i64::MAX, &overflowing, [date!(2025-06-09)]),
+            None
+        );
+    }
+
+    /// Tests that the history covers the 30 days up to today in the INR wallet by default.
+    #[test]
+    fn test_request_defaults() {
+        let request = request(serde_json::json!({}));
+        assert_eq!(request.currency, Currency::Inr);
+        assert_eq!(request.interval, BalanceInterval::Day);
+
+        let buckets = request.buckets(date!(2025-06-30)).unwrap();
+        assert_eq!(buckets.len(), 30);
+        assert_eq!(buckets[0].start, date!(2025-06-01));
+        assert_eq!(buckets[29].end, date!(2025-06-30));
+    }
+
+    /// Tests that ranges in the future, backwards or with too many points are rejected.
+    #[test]
+    fn test_request_validation() {
+        let today = date!(2025-06-30);
+        let buckets = |query| request(query).buckets(today);
+
+        assert!(buckets(serde_json::json!({ "to": "2025-07-01" })).is_err());
+        assert!(buckets(serde_json::json!({ "from": "2025-06-20", "to": "2025-06-19" })).is_err());
+        assert!(buckets(serde_json::json!({ "from": "2020-01-01" })).is_err());
+        assert_eq!(
+            buckets(serde_json::json!({ "from": "2020-01-01", "interval": "month" }))
+                .unwrap()
+                .len(),
+            66
+        );
+
+        assert!(
+            serde_json::from_value::<BalanceHistoryRequest>(
+                serde_json::json!({ "from": "2025-6-1" })
+            )
+            .is_err()
+        );
+        assert!(
+            serde_json::from_value::<BalanceHistoryRequest>(
+                serde_json::json!({ "interval": "year" })
+            )
+            .is_err()
+        );
+    }
+
+    /// Tests that balances are snapshotted by default.
+    #[test]
+    fn test_balance_history_config() {
+        let config = Config::new().unwrap();
+        assert!(config.balance_history.snapshot_interval > 0);
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        routes::api_models::CreateBatchTransferRequest,
+        storage::{
+            enums::{TransactionStatus, TransactionType},
+            types::{NewTransaction, NewTransferBatch},
+        },
+        types::{Currency, Money},
+        utils::datetime,
+    };
+
+    fn request(legs: serde_json::Value) -> CreateBatchTransferRequest {
+        serde_json::from_value(serde_json::json!({
+            "sender_id": "a",
+            "legs": legs,
+            "description": "Payroll",
+        }))
+        .unwrap()
+    }
+
+    fn leg(receiver: &str, amount_minor_units: i64) -> NewTransaction {
+        let now = datetime::now();
+        NewTransaction {
+            transaction_id: format!("txn_{receiver}"),
+            sender_id: "a".into(),
+            recipient_id: receiver.into(),
+            amount_minor_units,
+            currency: "INR".into(),
+            description: None,
+            created_at: now,
+            status: TransactionStatus::Pending,
+            updated_at: now,
+            transaction_type: TransactionType::Transfer,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: Some("bat_test".into()),
+            metadata: None,
+        }
+    }
+
+    /// Tests that a batch needs between one and the maximum number of valid legs.
+    #[test]
+    fn test_create_request_validation() {
+        let valid = request(serde_json::json!([
+            { "receiver_id": "b", "amount": "10.00" },
+            { "receiver_id": "c", "amount": 250, "description": "Bonus" },
+        ]));
+        assert!(valid.validate(2).is_ok());
+        assert!(valid.validate(1).is_err());
+
+        assert!(request(serde_json::json!([])).validate(10).is_err());
+        for invalid_leg in [
+            serde_json::json!({ "receiver_id": "", "amount": "10.00" }),
+            serde_json::json!({ "receiver_id": "a", "amount": "10.00" }),
+            serde_json::json!({ "receiver_id": "b", "amount": "0.00" }),
+            serde_json::json!({ "receiver_id": "b", "amount": "-1.00" }),
+        ] {
+            assert!(
+                request(serde_json::json!([
+                    { "receiver_id": "c", "amount": "10.00" },
+                    invalid_leg,
+                ]))
+                .validate(10)
+                .is_err(),
+                "{invalid_leg} should be rejected"
+            );
+        }
+    }
+
+    /// Tests that the legs of a batch are read in the currency of the request, INR if not given.
+    #[test]
+    fn test_request_currency() {
+        let legs = serde_json::json!([{ "receiver_id": "b", "amount": "10.50" }]);
+        assert_eq!(request(legs.clone()).currency, Currency::Inr);
+        assert!(request(legs.clone()).validate(10).is_ok());
+
+        let mut yen = request(legs);
+        yen.currency = Currency::Jpy;
+        assert!(yen.validate(10).is_err());
+
+        let mut leg = leg("b", 1050);
+        leg.currency = "JPY".into();
+        let batch =
+            NewTransferBatch::new("bat_test".into(), "a".into(), Currency::Jpy, None, &[leg])
+                .unwrap();
+        assert_eq!(
+            batch.total().unwrap(),
+            Money::new(1050, Currency::Jpy).unwrap()
+        );
+    }
+
+    /// Tests that a batch totals the amounts of its legs.
+    #[test]
+    fn test_batch_total() {
+        let legs = [leg("b", 1000), leg("c", 250), leg("b", 5)];
+        let batch =
+            NewTransferBatch::new("bat_test".into(), "a".into(), Currency::Inr, None, &legs)
+                .unwrap();
+
+        assert_eq!(batch.total_minor_units, 1255);
+        assert_eq!(batch.leg_count, 3);
+        assert_eq!(batch.currency, "INR");
+    }
+
+    /// Tests that a batch whose total does not fit the supported range is rejected.
+    #[test]
+    fn test_batch_total_overflow() {
+        let legs = [leg("b", i64::MAX), leg("c", 1)];
+
+        assert!(
+            NewTransferBatch::new("bat_test".into(), "a".into(), Currency::Inr, None, &legs)
+                .is_err()
+        );
+    }
+}
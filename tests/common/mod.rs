@@ -0,0 +1,162 @@
+//! Helpers shared by the tests that run against the database of the development config.
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use dodopayments::{
+    app::AppState,
+    configs::Config,
+    error::{TransactionDbError, container::ContainerError},
+    storage::{
+        BankAccountInterface, DepositInterface, TransactionInterface, UserInterface,
+        WalletInterface,
+        enums::{DepositStatus, TransactionStatus, TransactionType, WithdrawalStatus},
+        types::{NewBankAccount, NewDeposit, NewTransaction, NewWithdrawal, Transaction, UserNew},
+    },
+    types::{Claims, Currency},
+    utils::{self, datetime, generate_jwt},
+};
+
+/// Builds the application state from the development config.
+pub async fn app_state() -> Arc<AppState> {
+    let config = Config::new().unwrap();
+    Arc::new(AppState::new(config).await.unwrap())
+}
+
+/// Issues a JWT for a user, valid for an hour.
+pub fn token(app_state: &AppState, user_id: &str) -> String {
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 3600;
+    let claims = Claims {
+        user_id: user_id.to_string(),
+        email: format!("{user_id}@example.com"),
+        name: user_id.to_string(),
+        exp,
+    };
+    generate_jwt(&claims, &app_state.config.secrets.jwt_secret).unwrap()
+}
+
+/// Signs up a user with an empty INR wallet, returning their ID.
+pub async fn user(app_state: &AppState) -> String {
+    let user_id = utils::generate_uuid();
+    app_state
+        .db
+        .create_user(UserNew {
+            user_id: user_id.clone(),
+            email: format!("{user_id}@example.com"),
+            name: "Test".to_string(),
+            password: "unused".to_string(),
+        })
+        .await
+        .unwrap();
+    user_id
+}
+
+/// Credits an INR wallet through a completed deposit.
+pub async fn fund(app_state: &AppState, user_id: &str, minor_units: i64) {
+    let now = datetime::now();
+    let deposit = app_state
+        .db
+        .create_deposit(NewDeposit {
+            deposit_id: format!("dep_{}", utils::generate_nano_id(20)),
+            user_id: user_id.to_string(),
+            amount_minor_units: minor_units,
+            currency: Currency::Inr.code().to_string(),
+            status: DepositStatus::Pending,
+            funding_source: "mock".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .unwrap();
+    app_state
+        .db
+        .complete_deposit(&deposit.deposit_id, "test".to_string())
+        .await
+        .unwrap();
+}
+
+/// Reads the balance of an INR wallet in minor units.
+pub async fn balance(app_state: &AppState, user_id: &str) -> i64 {
+    app_state
+        .db
+        .get_wallet(user_id, Currency::Inr)
+        .await
+        .unwrap()
+        .balance_minor_units
+}
+
+/// Builds a PENDING INR transfer.
+pub fn new_transfer(sender_id: &str, recipient_id: &str, minor_units: i64) -> NewTransaction {
+    let now = datetime::now();
+    NewTransaction {
+        transaction_id: format!("txn_{}", utils::generate_nano_id(20)),
+        sender_id: sender_id.to_string(),
+        recipient_id: recipient_id.to_string(),
+        amount_minor_units: minor_units,
+        currency: Currency::Inr.code().to_string(),
+        description: None,
+        created_at: now,
+        status: TransactionStatus::Pending,
+        updated_at: now,
+        transaction_type: TransactionType::Transfer,
+        parent_transaction_id: None,
+        authorized_amount_minor_units: None,
+        expires_at: None,
+        standing_instruction_id: None,
+        payment_request_id: None,
+        batch_id: None,
+        metadata: None,
+    }
+}
+
+/// Moves money between INR wallets, charging the configured fee.
+pub async fn transfer(
+    app_state: &AppState,
+    sender_id: &str,
+    recipient_id: &str,
+    minor_units: i64,
+) -> Result<Transaction, ContainerError<TransactionDbError>> {
+    app_state
+        .db
+        .create_transaction(new_transfer(sender_id, recipient_id, minor_units))
+        .await
+}
+
+/// Links a bank account to a user, returning its ID.
+pub async fn bank_account(app_state: &AppState, user_id: &str) -> String {
+    app_state
+        .db
+        .create_bank_account(NewBankAccount {
+            bank_account_id: format!("ba_{}", utils::generate_nano_id(20)),
+            user_id: user_id.to_string(),
+            account_holder_name: "Test".to_string(),
+            account_number: "123456789012".to_string(),
+            ifsc: "HDFC0001234".to_string(),
+            created_at: datetime::now(),
+        })
+        .await
+        .unwrap()
+        .bank_account_id
+}
+
+/// Builds a PENDING INR withdrawal paid out by the mock connector.
+pub fn new_withdrawal(user_id: &str, bank_account_id: &str, minor_units: i64) -> NewWithdrawal {
+    let now = datetime::now();
+    NewWithdrawal {
+        withdrawal_id: format!("wd_{}", utils::generate_nano_id(20)),
+        user_id: user_id.to_string(),
+        bank_account_id: bank_account_id.to_string(),
+        amount_minor_units: minor_units,
+        currency: Currency::Inr.code().to_string(),
+        status: WithdrawalStatus::Pending,
+        connector: "mock".to_string(),
+        transaction_id: format!("txn_{}", utils::generate_nano_id(20)),
+        created_at: now,
+        updated_at: now,
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        configs::{Config, Deposit, FundingSourceKind},
+        error::FundingError,
+        funding::{FundingCharge, FundingSource, MockFundingSource},
+        routes::api_models::{CreateDepositRequest, UpdateUserRequest},
+        storage::enums::DepositStatus,
+        types::{Currency, Money},
+    };
+
+    fn charge(payment_token: &str) -> FundingCharge {
+        FundingCharge {
+            deposit_id: "dep_1".into(),
+            user_id: "user".into(),
+            amount: Money::new(1_000, Currency::Inr).unwrap(),
+            payment_token: payment_token.into(),
+        }
+    }
+
+    /// Tests that the mock funding source charges every token except its test tokens.
+    #[tokio::test]
+    async fn test_mock_funding_source() {
+        let source = MockFundingSource;
+
+        assert_eq!(
+            source.charge(&charge("tok_visa")).await,
+            Ok("mock_dep_1".into())
+        );
+        assert!(matches!(
+            source
+                .charge(&charge(MockFundingSource::DECLINED_TOKEN))
+                .await,
+            Err(FundingError::Declined(_))
+        ));
+        assert_eq!(
+            source
+                .charge(&charge(MockFundingSource::UNAVAILABLE_TOKEN))
+                .await,
+            Err(FundingError::Unavailable)
+        );
+    }
+
+    /// Tests that deposits must be positive and within the configured maximum.
+    #[test]
+    fn test_create_request_validation() {
+        let request = |value: serde_json::Value| {
+            serde_json::from_value::<CreateDepositRequest>(value).unwrap()
+        };
+
+        assert!(
+            request(serde_json::json!({ "amount": 10_000 }))
+                .validate(10_000)
+                .is_ok()
+        );
+        assert!(
+            request(serde_json::json!({ "amount": "100.00" }))
+                .validate(10_000)
+                .is_ok()
+        );
+        for invalid in [
+            serde_json::json!({ "amount": 0 }),
+            serde_json::json!({ "amount": -1 }),
+            serde_json::json!({ "amount": 10_001 }),
+        ] {
+            assert!(
+                request(invalid.clone()).validate(10_000).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    /// Tests that the deposit configuration picks the funding source and bounds the amount.
+    #[test]
+    fn test_deposit_config() {
+        let config = Config::new().unwrap();
+        assert!(config.deposit.validate().is_ok());
+        assert_eq!(config.deposit.funding_source, FundingSourceKind::Mock);
+
+        let deposit = Deposit {
+            funding_source: FundingSourceKind::Mock,
+            max_amount_minor_units: 0,
+        };
+        assert!(deposit.validate().is_err());
+    }
+
+    /// Tests that the profile update no longer accepts a balance.
+    #[test]
+    fn test_update_user_rejects_amount() {
+        assert!(
+            serde_json::from_value::<UpdateUserRequest>(serde_json::json!({ "name": "Alice" }))
+                .is_ok()
+        );
+        assert!(
+            serde_json::from_value::<UpdateUserRequest>(
+                serde_json::json!({ "name": "Alice", "amount": "100.00" })
+            )
+            .is_err()
+        );
+    }
+
+    /// Tests that only pending deposits can complete or fail.
+    #[test]
+    fn test_deposit_transitions() {
+        use DepositStatus::*;
+
+        assert!(Pending.can_transition_to(Completed));
+        assert!(Pending.can_transition_to(Failed));
+
+        assert!(!Completed.can_transition_to(Failed));
+        assert!(!Failed.can_transition_to(Completed));
+        assert!(!Completed.can_transition_to(Pending));
+    }
+}
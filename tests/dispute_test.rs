@@ -0,0 +1,310 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        error::TransactionDbError,
+        routes::api_models::{CreateDisputeRequest, ResolveDisputeRequest},
+        storage::{
+            DisputeInterface, TransactionInterface,
+            enums::{DisputeReason, DisputeStatus, TransactionStatus, TransactionType},
+            types::{Dispute, NewDispute, NewRefund},
+        },
+        types::{Currency, Metadata, Money},
+        utils::generate_nano_id,
+    };
+    use time::macros::datetime;
+
+    use crate::common;
+
+    fn new_dispute(transaction_id: &str, opened_by: &str) -> NewDispute {
+        NewDispute {
+            dispute_id: format!("dsp_{}", generate_nano_id(20)),
+            transaction_id: transaction_id.to_string(),
+            opened_by: opened_by.to_string(),
+            reason: DisputeReason::SentInError,
+            evidence: "Meant to pay someone else".into(),
+        }
+    }
+
+    fn refund(transaction_id: &str, minor_units: i64) -> NewRefund {
+        NewRefund {
+            transaction_id: format!("txn_{}", generate_nano_id(20)),
+            parent_transaction_id: transaction_id.to_string(),
+            amount: Some(Money::new(minor_units, Currency::Inr).unwrap()),
+            description: None,
+        }
+    }
+
+    fn dispute() -> Dispute {
+        let created_at = datetime!(2025-06-13 09:00);
+        Dispute {
+            id: 1,
+            dispute_id: "dsp_1".into(),
+            transaction_id: "txn_1".into(),
+            opened_by: "alice".into(),
+            sender_id: "alice".into(),
+            recipient_id: "bob".into(),
+            amount_minor_units: 9_800,
+            currency: "INR".into(),
+            reason: DisputeReason::SentInError,
+            evidence: "Meant to pay carol".into(),
+            status: DisputeStatus::UnderReview,
+            resolution_note: None,
+            reversal_transaction_id: None,
+            resolved_at: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    /// Tests that disputes are resolved once, from OPEN or UNDER_REVIEW, and keep their amount
+    /// frozen until then.
+    #[test]
+    fn test_dispute_transitions() {
+        use DisputeStatus::*;
+
+        assert!(Open.can_transition_to(UnderReview));
+        assert!(Open.can_transition_to(Won));
+        assert!(Open.can_transition_to(Lost));
+        assert!(UnderReview.can_transition_to(Won));
+        assert!(UnderReview.can_transition_to(Lost));
+
+        assert!(!UnderReview.can_transition_to(Open));
+        assert!(!Won.can_transition_to(Lost));
+        assert!(!Lost.can_transition_to(Won));
+        assert!(!Lost.can_transition_to(Open));
+
+        assert!(Open.is_open() && UnderReview.is_open());
+        assert!(!Won.is_open() && !Lost.is_open());
+    }
+
+    /// Tests that completed and partially refunded transfers can be reversed, and nothing else.
+    #[test]
+    fn test_reversible_statuses() {
+        use TransactionStatus::*;
+
+        assert!(Completed.can_transition_to(Reversed));
+        assert!(PartiallyRefunded.can_transition_to(Reversed));
+
+        assert!(!Refunded.can_transition_to(Reversed));
+        assert!(!Reversed.can_transition_to(Reversed));
+        assert!(!Pending.can_transition_to(Reversed));
+    }
+
+    /// Tests that a won dispute returns its amount from the recipient to the sender, linked to
+    /// the disputed transfer.
+    #[test]
+    fn test_reversal_transaction() {
+        let dispute = dispute();
+        let metadata = Metadata([("order_id".to_string(), "42".to_string())].into());
+
+        let reversal = dispute.to_reversal_transaction(Some(metadata.clone()));
+
+        assert!(reversal.transaction_id.starts_with("txn_"));
+        assert_eq!(reversal.sender_id, "bob");
+        assert_eq!(reversal.recipient_id, "alice");
+        assert_eq!(reversal.amount_minor_units, 9_800);
+        assert_eq!(reversal.currency, "INR");
+        assert_eq!(reversal.status, TransactionStatus::Pending);
+        assert_eq!(reversal.transaction_type, TransactionType::Reversal);
+        assert_eq!(reversal.parent_transaction_id.as_deref(), Some("txn_1"));
+        assert_eq!(reversal.description.as_deref(), Some("Dispute dsp_1"));
+        assert_eq!(reversal.metadata, Some(metadata));
+    }
+
+    /// Tests that disputes need a transaction, a known reason and evidence of bounded length.
+    #[test]
+    fn test_create_request_validation() {
+        let request = |value: serde_json::Value| {
+            serde_json::from_value::<CreateDisputeRequest>(value).unwrap()
+        };
+
+        let valid = request(serde_json::json!({
+            "transaction_id": "txn_1",
+            "reason": "UNAUTHORIZED",
+            "evidence": "I never sent this",
+        }));
+        assert_eq!(valid.reason, DisputeReason::Unauthorized);
+        assert!(valid.validate().is_ok());
+
+        for invalid in [
+            serde_json::json!({ "transaction_id": "", "reason": "OTHER", "evidence": "x" }),
+            serde_json::json!({ "transaction_id": "txn_1", "reason": "OTHER", "evidence": " " }),
+            serde_json::json!({
+                "transaction_id": "txn_1",
+                "reason": "OTHER",
+                "evidence": "a".repeat(5_001),
+            }),
+        ] {
+            assert!(request(invalid.clone()).validate().is_err(), "{invalid}");
+        }
+
+        assert!(
+            serde_json::from_value::<CreateDisputeRequest>(serde_json::json!({
+                "transaction_id": "txn_1",
+                "reason": "CHANGED_MY_MIND",
+                "evidence": "x",
+            }))
+            .is_err()
+        );
+    }
+
+    /// Tests that a dispute can only be resolved as WON or LOST.
+    #[test]
+    fn test_resolve_request_validation() {
+        let request = |value: serde_json::Value| {
+            serde_json::from_value::<ResolveDisputeRequest>(value).unwrap()
+        };
+
+        assert!(
+            request(serde_json::json!({ "outcome": "WON", "note": "Confirmed with bank" }))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            request(serde_json::json!({ "outcome": "LOST" }))
+                .validate()
+                .is_ok()
+        );
+
+        for invalid in [
+            serde_json::json!({ "outcome": "OPEN" }),
+            serde_json::json!({ "outcome": "UNDER_REVIEW" }),
+            serde_json::json!({ "outcome": "WON", "note": "" }),
+        ] {
+            assert!(request(invalid.clone()).validate().is_err(), "{invalid}");
+        }
+    }
+
+    /// Tests that a WON dispute returns the frozen net amount to the sender through a REVERSAL,
+    /// leaving the fee with the platform, and that the frozen amount cannot be spent meanwhile.
+    #[tokio::test]
+    async fn test_won_dispute_reverses_transfer() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        let other = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 20_000).await;
+
+        let transfer = common::transfer(&app_state, &sender, &recipient, 10_000)
+            .await
+            .unwrap();
+        let dispute = app_state
+            .db
+            .create_dispute(new_dispute(&transfer.transaction_id, &sender))
+            .await
+            .unwrap();
+        assert_eq!(dispute.amount_minor_units, 9_900);
+
+        let err = common::transfer(&app_state, &recipient, &other, 1_000)
+            .await
+            .unwrap_err();
+        assert_eq!(*err.get_inner(), TransactionDbError::InsufficientBalance);
+
+        let resolved = app_state
+            .db
+            .resolve_dispute(&dispute.dispute_id, DisputeStatus::Won, None)
+            .await
+            .unwrap();
+        assert_eq!(resolved.status, DisputeStatus::Won);
+
+        let reversal = app_state
+            .db
+            .get_transaction_by_id(&resolved.reversal_transaction_id.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(reversal.transaction_type, TransactionType::Reversal);
+        assert_eq!(reversal.status, TransactionStatus::Completed);
+        assert_eq!(reversal.amount_minor_units, 9_900);
+        assert_eq!(reversal.fee_minor_units, 0);
+
+        let original = app_state
+            .db
+            .get_transaction_by_id(&transfer.transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(original.status, TransactionStatus::Reversed);
+        assert_eq!(common::balance(&app_state, &recipient).await, 0);
+        assert_eq!(common::balance(&app_state, &sender).await, 19_900);
+    }
+
+    /// Tests that a dispute of a partially refunded transfer freezes what was not refunded,
+    /// blocks further refunds, and when WON returns all of it and reverses the transfer.
+    #[tokio::test]
+    async fn test_won_dispute_of_partially_refunded_transfer() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 20_000).await;
+
+        let transfer = common::transfer(&app_state, &sender, &recipient, 10_000)
+            .await
+            .unwrap();
+        app_state
+            .db
+            .refund_transaction(refund(&transfer.transaction_id, 4_000))
+            .await
+            .unwrap();
+        let dispute = app_state
+            .db
+            .create_dispute(new_dispute(&transfer.transaction_id, &sender))
+            .await
+            .unwrap();
+        assert_eq!(dispute.amount_minor_units, 5_900);
+
+        let err = app_state
+            .db
+            .refund_transaction(refund(&transfer.transaction_id, 1_000))
+            .await
+            .unwrap_err();
+        assert_eq!(*err.get_inner(), TransactionDbError::TransactionDisputed);
+
+        let resolved = app_state
+            .db
+            .resolve_dispute(&dispute.dispute_id, DisputeStatus::Won, None)
+            .await
+            .unwrap();
+        let reversal = app_state
+            .db
+            .get_transaction_by_id(&resolved.reversal_transaction_id.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(reversal.amount_minor_units, 5_900);
+
+        let original = app_state
+            .db
+            .get_transaction_by_id(&transfer.transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(original.status, TransactionStatus::Reversed);
+        assert_eq!(common::balance(&app_state, &recipient).await, 0);
+        assert_eq!(common::balance(&app_state, &sender).await, 19_900);
+    }
+
+    /// Tests that a dispute is refused when the recipient already spent the money, instead of
+    /// freezing less than the disputed amount.
+    #[tokio::test]
+    async fn test_dispute_not_covered() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        let other = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 20_000).await;
+
+        let transfer = common::transfer(&app_state, &sender, &recipient, 10_000)
+            .await
+            .unwrap();
+        common::transfer(&app_state, &recipient, &other, 5_000)
+            .await
+            .unwrap();
+
+        let err = app_state
+            .db
+            .create_dispute(new_dispute(&transfer.transaction_id, &sender))
+            .await
+            .unwrap_err();
+        assert_eq!(*err.get_inner(), TransactionDbError::DisputeNotCovered);
+        assert_eq!(common::balance(&app_state, &recipient).await, 4_900);
+    }
+}
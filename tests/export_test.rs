@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        export::{EXPORT_COLUMNS, ExportFormat, ExportRow},
+        routes::api_models::ExportTransactionsRequest,
+        storage::{
+            enums::{TransactionStatus, TransactionType},
+            types::Transaction,
+        },
+        types::Metadata,
+        utils::datetime,
+    };
+    use time::macros::datetime;
+
+    fn transaction() -> Transaction {
+        Transaction {
+            id: 1,
+            transaction_id: "txn_1".into(),
+            sender_id: "alice".into(),
+            recipient_id: "bob".into(),
+            amount_minor_units: 3_000,
+            description: Some("Rent, \"June\"".into()),
+            created_at: datetime!(2025-06-10 12:00),
+            status: TransactionStatus::Completed,
+            updated_at: datetime!(2025-06-10 12:00:01.5),
+            currency: "INR".into(),
+            failure_reason: None,
+            transaction_type: TransactionType::Transfer,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: Some(Metadata(
+                [("order".to_string(), "42".to_string())]
+                    .into_iter()
+                    .collect(),
+            )),
+            fee_minor_units: 30,
+        }
+    }
+
+    /// Tests that timestamps are RFC 3339 in UTC with a fixed microsecond precision.
+    #[test]
+    fn test_timestamp_format() {
+        assert_eq!(
+            datetime::to_rfc3339(datetime!(2025-06-10 12:00)),
+            "2025-06-10T12:00:00.000000Z"
+        );
+        assert_eq!(
+            datetime::to_rfc3339(datetime!(2025-06-10 09:05:03.123_456_789)),
+            "2025-06-10T09:05:03.123456Z"
+        );
+    }
+
+    /// Tests that a CSV export starts with the documented columns and quotes its values.
+    #[test]
+    fn test_csv_export() {
+        assert_eq!(
+            ExportFormat::Csv.header(),
+            format!("{}\r\n", EXPORT_COLUMNS.join(","))
+        );
+        assert_eq!(
+            ExportFormat::Csv.render(&[transaction()]).unwrap(),
+            "txn_1,2025-06-10T12:00:00.000000Z,2025-06-10T12:00:01.500000Z,TRANSFER,COMPLETED,\
+             alice,bob,INR,30.00,0.30,29.70,\"Rent, \"\"June\"\"\",,,\"{\"\"order\"\":\"\"42\"\"}\"\r\n"
+        );
+    }
+
+    /// Tests that NDJSON exports one object per line with the same columns in the same order.
+    #[test]
+    fn test_ndjson_export() {
+        assert_eq!(ExportFormat::Ndjson.header(), "");
+
+        let rendered = ExportFormat::Ndjson
+            .render(&[transaction(), transaction()])
+            .unwrap();
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(rendered.ends_with('\n'));
+
+        let positions = EXPORT_COLUMNS
+            .iter()
+            .map(|column| lines[0].find(&format!("\"{column}\":")).unwrap())
+            .collect::<Vec<_>>();
+        assert!(positions.is_sorted());
+
+        let row = serde_json::from_str::<ExportRow>(lines[0]).unwrap();
+        assert_eq!(row, ExportRow::try_from(&transaction()).unwrap());
+        assert_eq!(row.net_amount, "29.70");
+    }
+
+    /// Tests that exports default to CSV and reject an empty range.
+    #[test]
+    fn test_export_request_validation() {
+        let request = |value: serde_json::Value| {
+            serde_json::from_value::<ExportTransactionsRequest>(value).unwrap()
+        };
+
+        let defaults = request(serde_json::json!({}));
+        assert_eq!(defaults.format, ExportFormat::Csv);
+        assert!(defaults.validate().is_ok());
+
+        assert!(
+            request(serde_json::json!({
+                "format": "ndjson",
+                "from": "2025-06-01T00:00:00Z",
+                "to": "2025-07-01T00:00:00+05:30",
+            }))
+            .validate()
+            .is_ok()
+        );
+        assert!(
+            request(serde_json::json!({
+                "from": "2025-07-01T00:00:00Z",
+                "to": "2025-07-01T00:00:00Z",
+            }))
+            .validate()
+            .is_err()
+        );
+        assert!(
+            serde_json::from_value::<ExportTransactionsRequest>(serde_json::json!({
+                "format": "xml"
+            }))
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        configs::{Config, Fees},
+        error::FeeError,
+        types::{Currency, FeeSchedule, Money},
+    };
+
+    fn schedule(value: serde_json::Value) -> FeeSchedule {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn fee(schedule: &FeeSchedule, minor_units: i64) -> i64 {
+        schedule
+            .fee_for(Money::new(minor_units, Currency::Inr).unwrap())
+            .unwrap()
+            .minor_units()
+    }
+
+    /// Tests the flat and percentage rules, with percentages rounded half up.
+    #[test]
+    fn test_flat_and_percentage_fees() {
+        assert_eq!(fee(&FeeSchedule::free(), 10_000), 0);
+
+        let flat = schedule(serde_json::json!({ "type": "flat", "amount_minor_units": 250 }));
+        assert_eq!(fee(&flat, 1), 250);
+        assert_eq!(fee(&flat, 1_000_000), 250);
+
+        let percentage = schedule(serde_json::json!({ "type": "percentage", "basis_points": 150 }));
+        assert_eq!(fee(&percentage, 10_000), 150);
+        assert_eq!(fee(&percentage, 33), 0);
+        assert_eq!(fee(&percentage, 34), 1);
+        assert_eq!(fee(&percentage, i64::MAX), 138_350_580_552_821_637);
+    }
+
+    /// Tests that the first tier covering the amount applies.
+    #[test]
+    fn test_tiered_fees() {
+        let tiered = schedule(serde_json::json!({
+            "type": "tiered",
+            "tiers": [
+                { "up_to_minor_units": 10_000, "flat_minor_units": 100 },
+                { "up_to_minor_units": 100_000, "flat_minor_units": 50, "basis_points": 100 },
+                { "basis_points": 50 },
+            ],
+        }));
+        assert!(tiered.validate().is_ok());
+
+        assert_eq!(fee(&tiered, 10_000), 100);
+        assert_eq!(fee(&tiered, 10_001), 150);
+        assert_eq!(fee(&tiered, 100_000), 1_050);
+        assert_eq!(fee(&tiered, 1_000_000), 5_000);
+    }
+
+    /// Tests that the minimum and maximum cap the calculated fee.
+    #[test]
+    fn test_fee_caps() {
+        let capped = schedule(serde_json::json!({
+            "type": "percentage",
+            "basis_points": 100,
+            "min_minor_units": 50,
+            "max_minor_units": 5_000,
+        }));
+
+        assert_eq!(fee(&capped, 100), 50);
+        assert_eq!(fee(&capped, 100_000), 1_000);
+        assert_eq!(fee(&capped, 10_000_000), 5_000);
+    }
+
+    /// Tests that schedules with invalid amounts, percentages or tiers are rejected.
+    #[test]
+    fn test_schedule_validation() {
+        for (value, error) in [
+            (
+                serde_json::json!({ "type": "flat", "amount_minor_units": -1 }),
+                FeeError::NegativeAmount,
+            ),
+            (
+                serde_json::json!({ "type": "percentage", "basis_points": 10_001 }),
+                FeeError::InvalidBasisPoints(10_001),
+            ),
+            (
+                serde_json::json!({ "type": "none", "min_minor_units": 10, "max_minor_units": 5 }),
+                FeeError::MinimumExceedsMaximum,
+            ),
+            (
+                serde_json::json!({ "type": "tiered", "tiers": [] }),
+                FeeError::InvalidTiers,
+            ),
+            (
+                serde_json::json!({ "type": "tiered", "tiers": [{ "up_to_minor_units": 100 }] }),
+                FeeError::InvalidTiers,
+            ),
+            (
+                serde_json::json!({ "type": "tiered", "tiers": [
+                    { "up_to_minor_units": 100 },
+                    { "up_to_minor_units": 100 },
+                    {},
+                ] }),
+                FeeError::InvalidTiers,
+            ),
+        ] {
+            assert_eq!(schedule(value.clone()).validate(), Err(error), "{value}");
+        }
+    }
+
+    /// Tests that a sender's override replaces the default schedule.
+    #[test]
+    fn test_schedule_overrides() {
+        let fees: Fees = serde_json::from_value(serde_json::json!({
+            "default": { "type": "percentage", "basis_points": 100 },
+            "overrides": { "merchant": { "type": "none" } },
+        }))
+        .unwrap();
+        assert!(fees.validate().is_ok());
+
+        assert_eq!(fee(fees.schedule_for("someone"), 10_000), 100);
+        assert_eq!(fees.schedule_for("merchant"), &FeeSchedule::free());
+
+        let config = Config::new().unwrap();
+        assert!(config.fees.validate().is_ok());
+    }
+}
@@ -0,0 +1,200 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        configs::{Config, Fx},
+        error::{ApiError, FxError, TransactionDbError},
+        routes::api_models::{CreateFxQuoteRequest, SetFxRateRequest},
+        storage::{
+            enums::FxQuoteStatus,
+            types::{FxRate, NewFxQuote},
+        },
+        types::{Currency, ExchangeRate, Money, fx},
+        utils::datetime,
+    };
+
+    fn rate(value: &str) -> ExchangeRate {
+        ExchangeRate::from_decimal_str(value).unwrap()
+    }
+
+    /// Tests that rates parse from decimal strings with up to eight decimal places and format
+    /// back without trailing zeros.
+    #[test]
+    fn test_rate_parsing() {
+        assert_eq!(rate("83.25").scaled(), 8_325_000_000);
+        assert_eq!(rate("0.0067").scaled(), 670_000);
+        assert_eq!(rate("1").to_string(), "1");
+        assert_eq!(rate("83.2500").to_string(), "83.25");
+        assert_eq!(rate("0.00000001").to_string(), "0.00000001");
+
+        for invalid in ["0", "0.000", "-1", "1.", ".5", "1.123456789", "1e3", ""] {
+            assert!(
+                matches!(
+                    ExchangeRate::from_decimal_str(invalid),
+                    Err(FxError::InvalidRate(_))
+                ),
+                "{invalid}"
+            );
+        }
+
+        assert_eq!(
+            serde_json::to_value(rate("83.25")).unwrap(),
+            serde_json::json!("83.25")
+        );
+        assert!(serde_json::from_value::<ExchangeRate>(serde_json::json!(83.25)).is_err());
+    }
+
+    /// Tests that conversions follow the minor units of both currencies and round down.
+    #[test]
+    fn test_convert() {
+        let money = |minor_units, currency| Money::new(minor_units, currency).unwrap();
+
+        assert_eq!(
+            rate("83.25").convert(money(10_000, Currency::Usd), Currency::Inr),
+            Ok(money(832_500, Currency::Inr))
+        );
+        assert_eq!(
+            rate("0.0067").convert(money(1_000, Currency::Jpy), Currency::Usd),
+            Ok(money(670, Currency::Usd))
+        );
+        assert_eq!(
+            rate("149.5").convert(money(101, Currency::Usd), Currency::Jpy),
+            Ok(money(150, Currency::Jpy))
+        );
+        assert_eq!(
+            rate("0.012").convert(money(1, Currency::Inr), Currency::Usd),
+            Ok(money(0, Currency::Usd))
+        );
+        assert!(
+            rate("99999999")
+                .convert(money(i64::MAX, Currency::Usd), Currency::Inr)
+                .is_err()
+        );
+    }
+
+    /// Tests that a quote keeps the spread of the converted amount and locks the rate.
+    #[test]
+    fn test_quote_pricing() {
+        let now = datetime::now();
+        let fx_rate = FxRate {
+            id: 1,
+            base_currency: "USD".into(),
+            quote_currency: "INR".into(),
+            rate_scaled: rate("83.25").scaled(),
+            created_at: now,
+            updated_at: now,
+        };
+        let source = Money::new(10_000, Currency::Usd).unwrap();
+
+        let quote =
+            NewFxQuote::new("alice".into(), source, Currency::Inr, &fx_rate, 50, 60).unwrap();
+        assert_eq!(quote.source_amount_minor_units, 10_000);
+        assert_eq!(quote.spread_minor_units, 4_163);
+        assert_eq!(quote.target_amount_minor_units, 828_337);
+        assert_eq!(quote.status, FxQuoteStatus::Open);
+        assert_eq!(
+            quote.expires_at,
+            quote.created_at + time::Duration::seconds(60)
+        );
+
+        assert_eq!(
+            fx::spread_of(Money::new(832_500, Currency::Inr).unwrap(), 0),
+            Ok(Money::zero(Currency::Inr))
+        );
+        assert_eq!(rate("83.25").less_basis_points(50), Ok(rate("82.83375")));
+
+        assert!(!fx_rate.is_stale(now, 60));
+        assert!(fx_rate.is_stale(now + time::Duration::seconds(60), 60));
+    }
+
+    /// Tests that the FX configuration leaves the customer something and lets quotes live.
+    #[test]
+    fn test_fx_config() {
+        let config = Config::new().unwrap();
+        assert!(config.fx.validate().is_ok());
+
+        let fx = |spread_basis_points, quote_ttl, max_rate_age| Fx {
+            spread_basis_points,
+            quote_ttl,
+            max_rate_age,
+        };
+        assert!(fx(0, 60, 86_400).validate().is_ok());
+        assert!(fx(10_000, 60, 86_400).validate().is_err());
+        assert!(fx(50, 0, 86_400).validate().is_err());
+        assert!(fx(50, 60, 0).validate().is_err());
+    }
+
+    /// Tests that a quote can only be executed once.
+    #[test]
+    fn test_quote_transitions() {
+        use FxQuoteStatus::*;
+
+        assert!(Open.can_transition_to(Executed));
+        assert!(!Executed.can_transition_to(Open));
+        assert!(!Executed.can_transition_to(Executed));
+    }
+
+    /// Tests that quotes and rates need two different currencies and a positive amount.
+    #[test]
+    fn test_request_validation() {
+        let quote = |value: serde_json::Value| {
+            serde_json::from_value::<CreateFxQuoteRequest>(value)
+                .unwrap()
+                .validate()
+        };
+
+        assert!(
+            quote(serde_json::json!({
+                "source_currency": "USD",
+                "target_currency": "INR",
+                "amount": "100.00",
+            }))
+            .is_ok()
+        );
+        for invalid in [
+            serde_json::json!({ "source_currency": "USD", "target_currency": "USD", "amount": 1 }),
+            serde_json::json!({ "source_currency": "USD", "target_currency": "INR", "amount": 0 }),
+            serde_json::json!({ "source_currency": "JPY", "target_currency": "INR", "amount": "1.5" }),
+        ] {
+            assert!(quote(invalid.clone()).is_err(), "{invalid}");
+        }
+
+        let set_rate = |value: serde_json::Value| serde_json::from_value::<SetFxRateRequest>(value);
+        assert!(
+            set_rate(serde_json::json!({
+                "base_currency": "USD",
+                "quote_currency": "INR",
+                "rate": "83.25",
+            }))
+            .unwrap()
+            .validate()
+            .is_ok()
+        );
+        assert!(
+            set_rate(serde_json::json!({
+                "base_currency": "USD",
+                "quote_currency": "USD",
+                "rate": "1",
+            }))
+            .unwrap()
+            .validate()
+            .is_err()
+        );
+        assert!(
+            set_rate(serde_json::json!({
+                "base_currency": "USD",
+                "quote_currency": "INR",
+                "rate": "0",
+            }))
+            .is_err()
+        );
+    }
+
+    /// Tests that executing a lapsed quote maps to its API error.
+    #[test]
+    fn test_expired_quote_error() {
+        assert!(matches!(
+            ApiError::from(&TransactionDbError::FxQuoteExpired),
+            ApiError::FxQuoteExpired
+        ));
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        error::ApiError,
+        routes::{
+            api_models::{AmountRequest, CreateTransactionRequest},
+            idempotency::fingerprint,
+        },
+        types::Currency,
+    };
+
+    fn request(amount: i64) -> CreateTransactionRequest {
+        CreateTransactionRequest {
+            sender_id: "sender".to_string(),
+            receiver_id: "receiver".to_string(),
+            amount: AmountRequest::MinorUnits(amount),
+            currency: Currency::Inr,
+            description: None,
+            metadata: None,
+            fx_quote_id: None,
+        }
+    }
+
+    /// Tests that identical payloads share a fingerprint and different payloads do not.
+    #[test]
+    fn test_fingerprint() {
+        let first = fingerprint("POST /transaction", &request(100)).unwrap();
+
+        assert_eq!(
+            first,
+            fingerprint("POST /transaction", &request(100)).unwrap()
+        );
+        assert_ne!(
+            first,
+            fingerprint("POST /transaction", &request(200)).unwrap()
+        );
+        assert_eq!(first.len(), 64);
+    }
+
+    /// Tests that the same payload sent to another endpoint or resource does not match.
+    #[test]
+    fn test_fingerprint_scope() {
+        let refund = serde_json::json!({});
+        let first = fingerprint("POST /transaction/txn_1/refund", &refund).unwrap();
+
+        assert_ne!(
+            first,
+            fingerprint("POST /transaction/txn_2/refund", &refund).unwrap()
+        );
+        assert_ne!(
+            first,
+            fingerprint("POST /transaction/txn_1/capture", &refund).unwrap()
+        );
+    }
+
+    /// Tests that database errors release the key for a retry while business errors are kept.
+    #[test]
+    fn test_retryable_errors() {
+        assert!(ApiError::DatabaseError.is_retryable());
+        assert!(ApiError::TransactionDatabaseError.is_retryable());
+        assert!(ApiError::FundingSourceUnavailable.is_retryable());
+
+        assert!(!ApiError::InsufficientBalance.is_retryable());
+        assert!(!ApiError::TransferBlocked.is_retryable());
+        assert!(
+            !ApiError::UnknownError("The deposit was charged but could not be recorded")
+                .is_retryable()
+        );
+    }
+}
@@ -0,0 +1,108 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use diesel_async::RunQueryDsl;
+    use dodopayments::{
+        consts,
+        routes::admin,
+        storage::{enums::LedgerDirection, schema::ledger_entries, types::NewLedgerEntry},
+        types::{Currency, Money},
+        utils::datetime,
+    };
+    use tower::ServiceExt;
+
+    use crate::common;
+
+    /// Tests that a transfer journal nets to zero across its postings.
+    #[test]
+    fn test_transfer_postings_balance() {
+        let amount = Money::new(1050, Currency::Inr).unwrap();
+        let postings = [
+            NewLedgerEntry::debit("txn_1", "sender", amount),
+            NewLedgerEntry::credit("txn_1", "recipient", amount),
+        ];
+
+        assert_eq!(postings[0].signed_minor_units(), -1050);
+        assert_eq!(postings[1].signed_minor_units(), 1050);
+        assert_eq!(
+            postings
+                .iter()
+                .map(NewLedgerEntry::signed_minor_units)
+                .sum::<i64>(),
+            0
+        );
+    }
+
+    /// Tests that ledger directions round trip through their stored value.
+    #[test]
+    fn test_ledger_direction_round_trip() {
+        for direction in [LedgerDirection::Debit, LedgerDirection::Credit] {
+            assert_eq!(direction.as_str().parse(), Ok(direction));
+        }
+        assert!("debit".parse::<LedgerDirection>().is_err());
+    }
+
+    /// Tests that the balance of an account that held money before the ledger was introduced
+    /// is not rebuilt for an instant before its opening balance.
+    #[tokio::test]
+    async fn test_ledger_rejects_as_of_before_opening() {
+        let app_state = common::app_state().await;
+        let app = admin::serve(app_state.clone()).with_state(app_state.clone());
+        let user = common::user(&app_state).await;
+
+        // Book an opening balance like the ledger backfill does, wallet included.
+        let opened_at = datetime::now();
+        let amount = Money::new(5_000, Currency::Inr).unwrap();
+        let journal_id = format!("opening_{user}");
+        let opening = [
+            NewLedgerEntry::credit(&journal_id, &user, amount),
+            NewLedgerEntry::debit(&journal_id, consts::SYSTEM_ADJUSTMENT_ACCOUNT, amount),
+        ]
+        .map(|entry| NewLedgerEntry {
+            created_at: opened_at,
+            ..entry
+        });
+        let mut conn = app_state.db.get_conn().await.unwrap();
+        diesel::insert_into(ledger_entries::table)
+            .values(&opening[..])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::sql_query(format!(
+            "UPDATE wallets SET balance_minor_units = balance_minor_units + 5000 \
+             WHERE user_id = '{user}' AND currency = 'INR'"
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        let ledger = |as_of: time::PrimitiveDateTime| {
+            let as_of = as_of.assume_utc().unix_timestamp();
+            let as_of = time::OffsetDateTime::from_unix_timestamp(as_of).unwrap();
+            let as_of = as_of
+                .format(&time::format_description::well_known::Rfc3339)
+                .unwrap();
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!("/ledger/{user}?as_of={as_of}"))
+                    .header(
+                        consts::ADMIN_API_KEY_HEADER,
+                        &app_state.config.secrets.admin_api_key,
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let before = ledger(opened_at - time::Duration::hours(1)).await.unwrap();
+        assert_eq!(before.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let after = ledger(opened_at + time::Duration::hours(1)).await.unwrap();
+        assert_eq!(after.status(), StatusCode::OK);
+    }
+}
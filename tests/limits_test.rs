@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        routes::api_models::UpdateUserLimitsRequest,
+        storage::types::UserLimits,
+        types::{
+            TransferLimits,
+            limits::{LimitKind, LimitUsage, LimitWindows},
+        },
+        utils::datetime,
+    };
+    use time::{Date, Month, PrimitiveDateTime, Time};
+
+    const LIMITS: TransferLimits = TransferLimits {
+        max_transfer_minor_units: 10_000,
+        daily_outgoing_minor_units: 20_000,
+        monthly_outgoing_minor_units: 50_000,
+        transfers_per_hour: 3,
+    };
+
+    fn at(year: i32, month: Month, day: u8, hour: u8) -> PrimitiveDateTime {
+        PrimitiveDateTime::new(
+            Date::from_calendar_date(year, month, day).unwrap(),
+            Time::from_hms(hour, 30, 0).unwrap(),
+        )
+    }
+
+    /// Tests that each limit rejects the transfer that would exceed it.
+    #[test]
+    fn test_limit_check() {
+        let usage = LimitUsage {
+            daily_outgoing_minor_units: 15_000,
+            monthly_outgoing_minor_units: 40_000,
+            transfers_last_hour: 2,
+        };
+
+        assert_eq!(LIMITS.check(&usage, &[5_000]), Ok(()));
+        assert_eq!(
+            LIMITS.check(&LimitUsage::default(), &[10_001]),
+            Err(LimitKind::MaxTransfer)
+        );
+        assert_eq!(
+            LIMITS.check(&usage, &[5_001]),
+            Err(LimitKind::DailyOutgoing)
+        );
+        assert_eq!(
+            LIMITS.check(
+                &LimitUsage {
+                    monthly_outgoing_minor_units: 45_001,
+                    ..usage
+                },
+                &[5_000]
+            ),
+            Err(LimitKind::MonthlyOutgoing)
+        );
+        assert_eq!(
+            LIMITS.check(
+                &LimitUsage {
+                    transfers_last_hour: 3,
+                    ..usage
+                },
+                &[1]
+            ),
+            Err(LimitKind::TransfersPerHour)
+        );
+    }
+
+    /// Tests that every transfer of a batch counts towards the totals and the hourly count.
+    #[test]
+    fn test_batch_limit_check() {
+        let usage = LimitUsage::default();
+
+        assert_eq!(LIMITS.check(&usage, &[10_000, 10_000]), Ok(()));
+        assert_eq!(
+            LIMITS.check(&usage, &[10_000, 10_000, 1]),
+            Err(LimitKind::DailyOutgoing)
+        );
+        assert_eq!(
+            LIMITS.check(&usage, &[1, 1, 1, 1]),
+            Err(LimitKind::TransfersPerHour)
+        );
+        assert_eq!(
+            LIMITS.check(&usage, &[i64::MAX, i64::MAX]),
+            Err(LimitKind::MaxTransfer)
+        );
+    }
+
+    /// Tests that overrides replace only the limits they set.
+    #[test]
+    fn test_user_overrides() {
+        let now = datetime::now();
+        let overrides = UserLimits {
+            id: 1,
+            user_id: "user".into(),
+            max_transfer_minor_units: Some(500),
+            daily_outgoing_minor_units: None,
+            monthly_outgoing_minor_units: None,
+            transfers_per_hour: Some(10),
+            created_at: now,
+            updated_at: now,
+        };
+
+        assert_eq!(
+            overrides.apply_to(LIMITS),
+            TransferLimits {
+                max_transfer_minor_units: 500,
+                transfers_per_hour: 10,
+                ..LIMITS
+            }
+        );
+    }
+
+    /// Tests the daily and monthly windows, which follow the UTC calendar.
+    #[test]
+    fn test_limit_windows() {
+        let windows = LimitWindows::at(at(2025, Month::December, 31, 23));
+
+        assert_eq!(
+            windows.day_start,
+            Date::from_calendar_date(2025, Month::December, 31)
+                .unwrap()
+                .midnight()
+        );
+        assert_eq!(
+            windows.month_start.date(),
+            Date::from_calendar_date(2025, Month::December, 1).unwrap()
+        );
+        assert_eq!(windows.hour_start, at(2025, Month::December, 31, 22));
+        assert_eq!(
+            windows.day_end(),
+            Date::from_calendar_date(2026, Month::January, 1)
+                .unwrap()
+                .midnight()
+        );
+        assert_eq!(windows.month_end(), windows.day_end());
+
+        let windows = LimitWindows::at(at(2024, Month::February, 10, 8));
+        assert_eq!(
+            windows.month_end(),
+            Date::from_calendar_date(2024, Month::March, 1)
+                .unwrap()
+                .midnight()
+        );
+    }
+
+    /// Tests that limit overrides must be positive.
+    #[test]
+    fn test_update_request_validation() {
+        let request = |value: serde_json::Value| {
+            serde_json::from_value::<UpdateUserLimitsRequest>(value).unwrap()
+        };
+
+        assert!(request(serde_json::json!({})).validate().is_ok());
+        assert!(
+            request(serde_json::json!({ "max_transfer": "500.00", "transfers_per_hour": 5 }))
+                .validate()
+                .is_ok()
+        );
+        for invalid in [
+            serde_json::json!({ "daily_outgoing": "0.00" }),
+            serde_json::json!({ "monthly_outgoing": -1 }),
+            serde_json::json!({ "transfers_per_hour": 0 }),
+        ] {
+            assert!(request(invalid.clone()).validate().is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        routes::api_models::{
+            CreateStandingInstructionRequest, CreateTransactionRequest, ListTransactionsRequest,
+        },
+        types::{
+            Metadata,
+            metadata::{MAX_METADATA_KEY_LENGTH, MAX_METADATA_KEYS, MAX_METADATA_VALUE_LENGTH},
+        },
+    };
+
+    fn metadata(pairs: impl IntoIterator<Item = (String, String)>) -> Metadata {
+        Metadata(pairs.into_iter().collect())
+    }
+
+    fn transfer(extra: serde_json::Value) -> CreateTransactionRequest {
+        let mut body = serde_json::json!({
+            "sender_id": "a",
+            "receiver_id": "b",
+            "amount": "10.00",
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(body).unwrap()
+    }
+
+    /// Tests the bounds on the number of keys and the length of keys and values.
+    #[test]
+    fn test_metadata_validation() {
+        let at_limits = metadata((0..MAX_METADATA_KEYS).map(|i| {
+            (
+                format!("{i:0>width$}", width = MAX_METADATA_KEY_LENGTH),
+                "v".repeat(MAX_METADATA_VALUE_LENGTH),
+            )
+        }));
+        assert!(at_limits.validate().is_ok());
+
+        let too_many_keys =
+            metadata((0..=MAX_METADATA_KEYS).map(|i| (i.to_string(), String::new())));
+        assert!(too_many_keys.validate().is_err());
+
+        for (key, value) in [
+            (String::new(), "v".to_string()),
+            ("k".repeat(MAX_METADATA_KEY_LENGTH + 1), "v".to_string()),
+            ("k".to_string(), "v".repeat(MAX_METADATA_VALUE_LENGTH + 1)),
+        ] {
+            assert!(metadata([(key, value)]).validate().is_err());
+        }
+    }
+
+    /// Tests that metadata is a flat object of strings.
+    #[test]
+    fn test_metadata_format() {
+        let request = transfer(serde_json::json!({
+            "description": "Order 42",
+            "metadata": { "order_id": "42", "channel": "web" },
+        }));
+        assert_eq!(request.description.as_deref(), Some("Order 42"));
+        assert_eq!(
+            request.metadata.as_ref().unwrap().get("order_id"),
+            Some("42")
+        );
+        assert!(request.validate().is_ok());
+
+        for invalid in [
+            serde_json::json!({ "metadata": { "order_id": 42 } }),
+            serde_json::json!({ "metadata": { "nested": { "a": "b" } } }),
+            serde_json::json!({ "metadata": ["order_id"] }),
+        ] {
+            let mut body = serde_json::json!({
+                "sender_id": "a",
+                "receiver_id": "b",
+                "amount": "10.00",
+            });
+            body.as_object_mut()
+                .unwrap()
+                .extend(invalid.as_object().unwrap().clone());
+            assert!(serde_json::from_value::<CreateTransactionRequest>(body).is_err());
+        }
+    }
+
+    /// Tests that an overlong description is rejected.
+    #[test]
+    fn test_description_validation() {
+        assert!(
+            transfer(serde_json::json!({ "description": "d".repeat(500) }))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            transfer(serde_json::json!({ "description": "d".repeat(501) }))
+                .validate()
+                .is_err()
+        );
+    }
+
+    /// Tests that requests built on a transfer keep its description and metadata.
+    #[test]
+    fn test_flattened_transfer_fields() {
+        let request: CreateStandingInstructionRequest = serde_json::from_value(serde_json::json!({
+            "sender_id": "a",
+            "receiver_id": "b",
+            "amount": "10.00",
+            "recurrence": { "frequency": "DAILY" },
+            "description": "Rent",
+            "metadata": { "lease": "L-7" },
+        }))
+        .unwrap();
+
+        assert_eq!(request.transfer.description.as_deref(), Some("Rent"));
+        assert_eq!(request.transfer.metadata.unwrap().get("lease"), Some("L-7"));
+    }
+
+    /// Tests that the list filter needs both a metadata key and a value.
+    #[test]
+    fn test_list_metadata_filter() {
+        let params = |query: serde_json::Value| {
+            serde_json::from_value::<ListTransactionsRequest>(query)
+                .unwrap()
+                .metadata_filter()
+        };
+
+        assert_eq!(params(serde_json::json!({})).unwrap(), None);
+        assert_eq!(
+            params(serde_json::json!({ "metadata_key": "order_id", "metadata_value": "42" }))
+                .unwrap(),
+            Some(metadata([("order_id".to_string(), "42".to_string())]))
+        );
+        assert!(params(serde_json::json!({ "metadata_key": "order_id" })).is_err());
+        assert!(params(serde_json::json!({ "metadata_value": "42" })).is_err());
+        assert!(params(serde_json::json!({ "metadata_key": "", "metadata_value": "42" })).is_err());
+    }
+}
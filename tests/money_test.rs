@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        error::MoneyError,
+        routes::api_models::AmountRequest,
+        types::{Currency, Money},
+    };
+
+    /// Tests parsing decimal strings into minor units.
+    #[test]
+    fn test_from_decimal_str() {
+        let parse = |value| Money::from_decimal_str(value, Currency::Inr).map(|m| m.minor_units());
+
+        assert_eq!(parse("10"), Ok(1000));
+        assert_eq!(parse("10.5"), Ok(1050));
+        assert_eq!(parse("0.01"), Ok(1));
+        assert_eq!(parse("-1.00"), Err(MoneyError::NegativeAmount));
+        assert!(matches!(parse("1.001"), Err(MoneyError::InvalidFormat(_))));
+        assert!(matches!(parse("1."), Err(MoneyError::InvalidFormat(_))));
+        assert!(matches!(parse(".5"), Err(MoneyError::InvalidFormat(_))));
+        assert!(matches!(parse("1e3"), Err(MoneyError::InvalidFormat(_))));
+        assert_eq!(parse("92233720368547758.08"), Err(MoneyError::Overflow));
+    }
+
+    /// Tests formatting minor units as a decimal string.
+    #[test]
+    fn test_to_decimal_string() {
+        let money = Money::new(1005, Currency::Inr).unwrap();
+        assert_eq!(money.to_decimal_string(), "10.05");
+        assert_eq!(Money::zero(Currency::Inr).to_decimal_string(), "0.00");
+    }
+
+    /// Tests that arithmetic stays exact and rejects negative or overflowing results.
+    #[test]
+    fn test_checked_arithmetic() {
+        let ten_paise = Money::new(10, Currency::Inr).unwrap();
+        let twenty_paise = Money::new(20, Currency::Inr).unwrap();
+
+        let total = (0..10).try_fold(Money::zero(Currency::Inr), |acc, _| {
+            acc.checked_add(ten_paise)
+        });
+        assert_eq!(total.map(|m| m.minor_units()), Ok(100));
+
+        assert_eq!(
+            ten_paise.checked_sub(twenty_paise),
+            Err(MoneyError::NegativeAmount)
+        );
+
+        let max = Money::new(i64::MAX, Currency::Inr).unwrap();
+        assert_eq!(max.checked_add(ten_paise), Err(MoneyError::Overflow));
+        assert_eq!(
+            Money::new(-1, Currency::Inr),
+            Err(MoneyError::NegativeAmount)
+        );
+    }
+
+    /// Tests that request amounts accept minor units or decimal strings but not floats.
+    #[test]
+    fn test_amount_request_deserialization() {
+        let minor: AmountRequest = serde_json::from_str("1050").unwrap();
+        let decimal: AmountRequest = serde_json::from_str("\"10.50\"").unwrap();
+
+        assert_eq!(minor.to_money(Currency::Inr).unwrap().minor_units(), 1050);
+        assert_eq!(decimal.to_money(Currency::Inr).unwrap().minor_units(), 1050);
+        assert!(serde_json::from_str::<AmountRequest>("10.5").is_err());
+    }
+
+    /// Tests that deserialized money is validated like money built with `Money::new`.
+    #[test]
+    fn test_money_deserialization() {
+        let money: Money =
+            serde_json::from_str(r#"{"minor_units": 1050, "currency": "INR"}"#).unwrap();
+        assert_eq!(money, Money::new(1050, Currency::Inr).unwrap());
+
+        let negative = serde_json::from_str::<Money>(r#"{"minor_units": -1, "currency": "INR"}"#);
+        assert!(negative.is_err());
+    }
+}
@@ -0,0 +1,199 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use diesel::{ExpressionMethods, QueryDsl};
+    use diesel_async::RunQueryDsl;
+    use dodopayments::{
+        error::TransactionDbError,
+        routes::api_models::{CreatePaymentRequestRequest, ListPaymentRequestsRequest},
+        storage::{
+            PaymentRequestInterface, TransactionInterface,
+            enums::{PaymentRequestStatus, TransactionStatus, TransactionType},
+            schema::transactions,
+            types::{NewPaymentRequest, PaymentRequest, PaymentRequestDirection},
+        },
+        types::Currency,
+        utils::{datetime, generate_nano_id},
+    };
+
+    use crate::common;
+
+    fn request(extra: serde_json::Value) -> CreatePaymentRequestRequest {
+        let mut body = serde_json::json!({
+            "payer_id": "payer",
+            "amount": "25.00",
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn payment_request() -> PaymentRequest {
+        let now = datetime::now();
+        PaymentRequest {
+            id: 1,
+            payment_request_id: "pr_test".into(),
+            requester_id: "requester".into(),
+            payer_id: "payer".into(),
+            amount_minor_units: 2500,
+            currency: "INR".into(),
+            note: Some("dinner".into()),
+            status: PaymentRequestStatus::Pending,
+            transaction_id: None,
+            expires_at: now + time::Duration::hours(1),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Tests that a payment request needs a payer, a positive amount and a bounded expiry.
+    #[test]
+    fn test_create_request_validation() {
+        assert!(request(serde_json::json!({})).validate(3600).is_ok());
+        assert!(
+            request(serde_json::json!({ "expires_in": 3600 }))
+                .validate(3600)
+                .is_ok()
+        );
+
+        assert!(
+            request(serde_json::json!({ "payer_id": "" }))
+                .validate(3600)
+                .is_err()
+        );
+        assert!(
+            request(serde_json::json!({ "amount": "0.00" }))
+                .validate(3600)
+                .is_err()
+        );
+        assert!(
+            request(serde_json::json!({ "expires_in": 0 }))
+                .validate(3600)
+                .is_err()
+        );
+        assert!(
+            request(serde_json::json!({ "expires_in": 3601 }))
+                .validate(3600)
+                .is_err()
+        );
+    }
+
+    /// Tests that only pending payment requests can be answered.
+    #[test]
+    fn test_status_transitions() {
+        use PaymentRequestStatus::*;
+
+        for next in [Paid, Declined, Expired, Cancelled] {
+            assert!(Pending.can_transition_to(next));
+        }
+
+        for current in [Paid, Declined, Expired, Cancelled] {
+            for next in [Pending, Paid, Declined, Expired, Cancelled] {
+                assert!(!current.can_transition_to(next), "{current} -> {next}");
+            }
+        }
+    }
+
+    /// Tests that paying a request moves its amount from the payer to the requester.
+    #[test]
+    fn test_paying_transaction() {
+        let payment_request = payment_request();
+        let transaction = payment_request.to_new_transaction();
+
+        assert_eq!(transaction.sender_id, "payer");
+        assert_eq!(transaction.recipient_id, "requester");
+        assert_eq!(transaction.amount_minor_units, 2500);
+        assert_eq!(transaction.description.as_deref(), Some("dinner"));
+        assert_eq!(transaction.transaction_type, TransactionType::Transfer);
+        assert_eq!(transaction.payment_request_id.as_deref(), Some("pr_test"));
+    }
+
+    /// Tests that a request is expired from its expiry time onwards.
+    #[test]
+    fn test_is_expired() {
+        let payment_request = payment_request();
+
+        assert!(!payment_request.is_expired(payment_request.created_at));
+        assert!(payment_request.is_expired(payment_request.expires_at));
+    }
+
+    /// Tests the query format of the list filters.
+    #[test]
+    fn test_list_filters() {
+        let params: ListPaymentRequestsRequest = serde_json::from_value(serde_json::json!({
+            "direction": "INCOMING",
+            "status": "PENDING",
+        }))
+        .unwrap();
+
+        assert_eq!(params.direction, Some(PaymentRequestDirection::Incoming));
+        assert_eq!(params.status, Some(PaymentRequestStatus::Pending));
+        assert!(
+            serde_json::from_value::<ListPaymentRequestsRequest>(
+                serde_json::json!({ "direction": "incoming" })
+            )
+            .is_err()
+        );
+    }
+
+    /// Tests that paying a request the payer cannot cover records the failed transfer and
+    /// leaves the request PENDING, and that paying it after a top-up marks it PAID with the
+    /// transfer that paid it.
+    #[tokio::test]
+    async fn test_pay_payment_request() {
+        let app_state = common::app_state().await;
+        let requester = common::user(&app_state).await;
+        let payer = common::user(&app_state).await;
+        let now = datetime::now();
+        let payment_request = app_state
+            .db
+            .create_payment_request(NewPaymentRequest {
+                payment_request_id: format!("pr_{}", generate_nano_id(20)),
+                requester_id: requester.clone(),
+                payer_id: payer.clone(),
+                amount_minor_units: 2_500,
+                currency: Currency::Inr.code().to_string(),
+                note: None,
+                status: PaymentRequestStatus::Pending,
+                expires_at: now + time::Duration::hours(1),
+                created_at: now,
+                updated_at: now,
+            })
+            .await
+            .unwrap();
+        let id = &payment_request.payment_request_id;
+
+        let err = app_state.db.pay_payment_request(id).await.unwrap_err();
+        assert_eq!(*err.get_inner(), TransactionDbError::InsufficientBalance);
+        let pending = app_state.db.get_payment_request(id).await.unwrap();
+        assert_eq!(pending.status, PaymentRequestStatus::Pending);
+        let mut conn = app_state.db.get_conn().await.unwrap();
+        let failed: Vec<TransactionStatus> = transactions::table
+            .filter(transactions::payment_request_id.eq(id))
+            .select(transactions::status)
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(failed, [TransactionStatus::Failed]);
+
+        common::fund(&app_state, &payer, 10_000).await;
+        let paid = app_state.db.pay_payment_request(id).await.unwrap();
+        assert_eq!(paid.status, PaymentRequestStatus::Paid);
+        let transfer = app_state
+            .db
+            .get_transaction_by_id(&paid.transaction_id.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(transfer.status, TransactionStatus::Completed);
+        assert_eq!(transfer.payment_request_id.as_deref(), Some(id.as_str()));
+
+        let err = app_state.db.pay_payment_request(id).await.unwrap_err();
+        assert_eq!(
+            *err.get_inner(),
+            TransactionDbError::InvalidStatusTransition
+        );
+        assert_eq!(common::balance(&app_state, &requester).await, 2_475);
+    }
+}
@@ -0,0 +1,197 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use diesel_async::RunQueryDsl;
+    use dodopayments::{
+        configs::Config,
+        consts,
+        reconciliation::{BalanceKey, BalanceSnapshot, Discrepancy},
+        storage::{ReconciliationInterface, enums::ReconciliationFindingKind},
+    };
+
+    use crate::common;
+
+    fn balances(entries: &[(&str, &str, i64)]) -> HashMap<BalanceKey, i64> {
+        entries
+            .iter()
+            .map(|(account, currency, balance)| {
+                ((account.to_string(), currency.to_string()), *balance)
+            })
+            .collect()
+    }
+
+    /// A consistent system: alice deposited 100.00 and paid bob 30.00 with a 0.30 fee.
+    fn consistent() -> BalanceSnapshot {
+        BalanceSnapshot {
+            wallets: balances(&[("alice", "INR", 7_000), ("bob", "INR", 2_970)]),
+            history: balances(&[
+                ("alice", "INR", 7_000),
+                ("bob", "INR", 2_970),
+                ("sys_funding", "INR", -10_000),
+            ]),
+            ledger: balances(&[
+                ("alice", "INR", 7_000),
+                ("bob", "INR", 2_970),
+                ("sys_funding", "INR", -10_000),
+                ("sys_revenue", "INR", 30),
+            ]),
+        }
+    }
+
+    /// Tests that balances matching their history and ledger yield no discrepancies.
+    #[test]
+    fn test_consistent_snapshot() {
+        assert!(consistent().discrepancies().is_empty());
+        assert!(BalanceSnapshot::default().discrepancies().is_empty());
+    }
+
+    /// Tests that a drifted wallet is reported against its history, its ledger and the money
+    /// the system accounts paid in.
+    #[test]
+    fn test_drifted_wallet() {
+        let mut snapshot = consistent();
+        snapshot
+            .wallets
+            .insert(("bob".to_string(), "INR".to_string()), 3_470);
+
+        assert_eq!(
+            snapshot.discrepancies(),
+            vec![
+                Discrepancy {
+                    kind: ReconciliationFindingKind::BalanceMismatch,
+                    account_id: Some("bob".into()),
+                    currency: "INR".into(),
+                    expected_minor_units: 2_970,
+                    actual_minor_units: 3_470,
+                },
+                Discrepancy {
+                    kind: ReconciliationFindingKind::LedgerMismatch,
+                    account_id: Some("bob".into()),
+                    currency: "INR".into(),
+                    expected_minor_units: 2_970,
+                    actual_minor_units: 3_470,
+                },
+                Discrepancy {
+                    kind: ReconciliationFindingKind::MoneyNotConserved,
+                    account_id: None,
+                    currency: "INR".into(),
+                    expected_minor_units: 9_970,
+                    actual_minor_units: 10_470,
+                },
+            ]
+        );
+    }
+
+    /// Tests that a transfer missing from the ledger is reported as a ledger mismatch of both
+    /// wallets, and that its unbooked fee leaves the money held short of the money paid in.
+    #[test]
+    fn test_unbooked_transfer() {
+        let mut snapshot = consistent();
+        snapshot.ledger = balances(&[("alice", "INR", 10_000), ("sys_funding", "INR", -10_000)]);
+
+        let kinds = snapshot
+            .discrepancies()
+            .into_iter()
+            .map(|discrepancy| discrepancy.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ReconciliationFindingKind::LedgerMismatch,
+                ReconciliationFindingKind::LedgerMismatch,
+                ReconciliationFindingKind::MoneyNotConserved,
+            ]
+        );
+    }
+
+    /// Tests that money is checked per currency, and that a wallet with no history is expected
+    /// to be empty.
+    #[test]
+    fn test_currencies_reconciled_separately() {
+        let mut snapshot = consistent();
+        snapshot
+            .wallets
+            .insert(("alice".to_string(), "USD".to_string()), 0);
+        assert!(snapshot.discrepancies().is_empty());
+
+        snapshot
+            .wallets
+            .insert(("alice".to_string(), "USD".to_string()), 100);
+        let discrepancies = snapshot.discrepancies();
+        assert_eq!(discrepancies.len(), 3);
+        assert!(
+            discrepancies
+                .iter()
+                .all(|discrepancy| discrepancy.currency == "USD")
+        );
+    }
+
+    /// Tests that finding kinds round-trip through their stored names.
+    #[test]
+    fn test_finding_kinds() {
+        for (kind, name) in [
+            (
+                ReconciliationFindingKind::BalanceMismatch,
+                "BALANCE_MISMATCH",
+            ),
+            (ReconciliationFindingKind::LedgerMismatch, "LEDGER_MISMATCH"),
+            (
+                ReconciliationFindingKind::MoneyNotConserved,
+                "MONEY_NOT_CONSERVED",
+            ),
+        ] {
+            assert_eq!(kind.to_string(), name);
+            assert_eq!(serde_json::to_value(kind).unwrap(), serde_json::json!(name));
+        }
+    }
+
+    /// Tests that reconciliation is scheduled by default.
+    #[test]
+    fn test_reconciliation_config() {
+        let config = Config::new().unwrap();
+        assert!(config.reconciliation.interval > 0);
+    }
+
+    /// Tests that a scheduled run is skipped while another connection holds the reconciliation
+    /// lock or when a run started within the interval.
+    #[tokio::test]
+    async fn test_reconcile_if_due() {
+        let app_state = common::app_state().await;
+        let lock = format!(
+            "SELECT pg_advisory_lock({})",
+            consts::RECONCILIATION_LOCK_ID
+        );
+        let unlock = format!(
+            "SELECT pg_advisory_unlock({})",
+            consts::RECONCILIATION_LOCK_ID
+        );
+
+        let mut conn = app_state.db.get_conn().await.unwrap();
+        diesel::sql_query(&lock).execute(&mut conn).await.unwrap();
+        let skipped = app_state
+            .db
+            .reconcile_if_due(time::Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(skipped.is_none());
+        diesel::sql_query(&unlock).execute(&mut conn).await.unwrap();
+
+        let (run, _) = app_state
+            .db
+            .reconcile_if_due(time::Duration::ZERO)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(run.run_id.starts_with("rcn_"));
+
+        let skipped = app_state
+            .db
+            .reconcile_if_due(time::Duration::hours(1))
+            .await
+            .unwrap();
+        assert!(skipped.is_none());
+    }
+}
@@ -0,0 +1,124 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        error::TransactionDbError,
+        routes::api_models::{AmountRequest, RefundTransactionRequest},
+        storage::{
+            TransactionInterface,
+            enums::{TransactionStatus, TransactionType},
+            types::NewRefund,
+        },
+        types::{Currency, Money},
+        utils::generate_nano_id,
+    };
+
+    use crate::common;
+
+    fn refund(transaction_id: &str, minor_units: Option<i64>) -> NewRefund {
+        NewRefund {
+            transaction_id: format!("txn_{}", generate_nano_id(20)),
+            parent_transaction_id: transaction_id.to_string(),
+            amount: minor_units.map(|units| Money::new(units, Currency::Inr).unwrap()),
+            description: None,
+        }
+    }
+
+    /// Tests the status transitions a refund may make on the original transaction.
+    #[test]
+    fn test_refund_transitions() {
+        use TransactionStatus::*;
+
+        assert!(Completed.can_transition_to(PartiallyRefunded));
+        assert!(Completed.can_transition_to(Refunded));
+        assert!(PartiallyRefunded.can_transition_to(PartiallyRefunded));
+        assert!(PartiallyRefunded.can_transition_to(Refunded));
+
+        assert!(!Pending.can_transition_to(Refunded));
+        assert!(!Failed.can_transition_to(PartiallyRefunded));
+        assert!(!Refunded.can_transition_to(PartiallyRefunded));
+        assert!(!Refunded.can_transition_to(Completed));
+    }
+
+    /// Tests that only completed or partially refunded transactions can be refunded.
+    #[test]
+    fn test_is_refundable() {
+        assert!(TransactionStatus::Completed.is_refundable());
+        assert!(TransactionStatus::PartiallyRefunded.is_refundable());
+        assert!(!TransactionStatus::Pending.is_refundable());
+        assert!(!TransactionStatus::Failed.is_refundable());
+        assert!(!TransactionStatus::Reversed.is_refundable());
+        assert!(!TransactionStatus::Refunded.is_refundable());
+
+        assert_eq!("REFUND".parse(), Ok(TransactionType::Refund));
+        assert_eq!(
+            serde_json::to_string(&TransactionStatus::PartiallyRefunded).unwrap(),
+            "\"PARTIALLY_REFUNDED\""
+        );
+    }
+
+    /// Tests that a refund request may omit the amount but not ask for zero.
+    #[test]
+    fn test_refund_request_validation() {
+        let full: RefundTransactionRequest = serde_json::from_str("{}").unwrap();
+        assert!(full.validate().is_ok());
+
+        let partial = RefundTransactionRequest {
+            amount: Some(AmountRequest::Decimal("10.50".into())),
+            description: None,
+        };
+        assert!(partial.validate().is_ok());
+
+        let zero = RefundTransactionRequest {
+            amount: Some(AmountRequest::MinorUnits(0)),
+            description: None,
+        };
+        assert!(zero.validate().is_err());
+    }
+
+    /// Tests that a full refund of a transfer that was charged a fee takes back what the
+    /// recipient was credited, and no more.
+    #[tokio::test]
+    async fn test_full_refund_of_transfer_with_fee() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 20_000).await;
+
+        // The development config charges 1% on transfers.
+        let transfer = common::transfer(&app_state, &sender, &recipient, 10_000)
+            .await
+            .unwrap();
+        assert_eq!(transfer.fee_minor_units, 100);
+        assert_eq!(common::balance(&app_state, &recipient).await, 9_900);
+
+        let err = app_state
+            .db
+            .refund_transaction(refund(&transfer.transaction_id, Some(10_000)))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.get_inner(),
+            TransactionDbError::RefundExceedsAmount
+        ));
+
+        let full = app_state
+            .db
+            .refund_transaction(refund(&transfer.transaction_id, None))
+            .await
+            .unwrap();
+        assert_eq!(full.amount_minor_units, 9_900);
+        assert_eq!(full.fee_minor_units, 0);
+        assert_eq!(full.status, TransactionStatus::Completed);
+        assert_eq!(common::balance(&app_state, &recipient).await, 0);
+        assert_eq!(common::balance(&app_state, &sender).await, 19_900);
+
+        let original = app_state
+            .db
+            .get_transaction_by_id(&transfer.transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(original.status, TransactionStatus::Refunded);
+    }
+}
@@ -0,0 +1,616 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use dodopayments::{
+        app::AppState,
+        configs::{Config, Risk},
+        error::TransactionDbError,
+        risk::{
+            FanOutRule, NewAccountRule, NewCounterpartyRule, RecentTransfer, RiskEngine,
+            RiskHistory, RiskInput, RiskOutcome, RiskRule, RoundTripRule, RuleAction,
+        },
+        storage::{
+            PaymentRequestInterface, RiskInterface, ScheduledTransferInterface,
+            TransactionInterface, TransferBatchInterface,
+            enums::{
+                PaymentRequestStatus, RiskDecision, ScheduledTransferStatus, TransactionStatus,
+            },
+            types::{
+                NewPaymentRequest, NewScheduledTransfer, NewTransaction, NewTransferBatch,
+                PaymentRequest,
+            },
+        },
+        types::{Currency, Money},
+        utils::{datetime, generate_nano_id},
+    };
+
+    use crate::common;
+
+    const REVIEW: RuleAction = RuleAction {
+        action: RiskDecision::Review,
+        score: 10,
+    };
+    const BLOCK: RuleAction = RuleAction {
+        action: RiskDecision::Block,
+        score: 50,
+    };
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    fn input(amount: i64, history: RiskHistory) -> RiskInput {
+        RiskInput {
+            sender_id: "alice".into(),
+            recipient_id: "bob".into(),
+            amount: Money::new(amount, Currency::Inr).unwrap(),
+            now: datetime::now(),
+            history,
+        }
+    }
+
+    fn history(recent_transfers: Vec<RecentTransfer>) -> RiskHistory {
+        RiskHistory {
+            sender_created_at: datetime::now() - DAY * 365,
+            transfers_to_recipient: 1,
+            recent_transfers,
+        }
+    }
+
+    fn transfer(sender_id: &str, recipient_id: &str, age: Duration) -> RecentTransfer {
+        RecentTransfer {
+            sender_id: sender_id.into(),
+            recipient_id: recipient_id.into(),
+            amount_minor_units: 1_000,
+            created_at: datetime::now() - age,
+        }
+    }
+
+    /// Tests that only large transfers from recently created accounts are flagged.
+    #[test]
+    fn test_new_account_rule() {
+        let rule = NewAccountRule {
+            max_account_age: 7 * 86_400,
+            min_amount_minor_units: 10_000,
+            action: REVIEW,
+        };
+        let new_account = RiskHistory {
+            sender_created_at: datetime::now() - DAY,
+            ..history(Vec::new())
+        };
+
+        assert_eq!(
+            rule.evaluate(&input(10_000, new_account.clone())).decision,
+            RiskDecision::Review
+        );
+        assert_eq!(
+            rule.evaluate(&input(9_999, new_account)),
+            RiskOutcome::allow()
+        );
+        assert_eq!(
+            rule.evaluate(&input(10_000, history(Vec::new()))),
+            RiskOutcome::allow()
+        );
+    }
+
+    /// Tests that only large first transfers to a recipient are flagged.
+    #[test]
+    fn test_new_counterparty_rule() {
+        let rule = NewCounterpartyRule {
+            min_amount_minor_units: 10_000,
+            action: REVIEW,
+        };
+        let first_transfer = RiskHistory {
+            transfers_to_recipient: 0,
+            ..history(Vec::new())
+        };
+
+        let outcome = rule.evaluate(&input(10_000, first_transfer.clone()));
+        assert_eq!(outcome.decision, RiskDecision::Review);
+        assert_eq!(outcome.score, 10);
+        assert_eq!(
+            rule.evaluate(&input(9_999, first_transfer)),
+            RiskOutcome::allow()
+        );
+        assert_eq!(
+            rule.evaluate(&input(10_000, history(Vec::new()))),
+            RiskOutcome::allow()
+        );
+    }
+
+    /// Tests that distinct recipients within the window are counted, including the new one.
+    #[test]
+    fn test_fan_out_rule() {
+        let rule = FanOutRule {
+            window: 3_600,
+            max_recipients: 3,
+            action: REVIEW,
+        };
+        let minute = Duration::from_secs(60);
+
+        let within_limit = history(vec![
+            transfer("alice", "carol", minute),
+            transfer("alice", "carol", minute),
+            transfer("alice", "dave", minute),
+            transfer("alice", "erin", DAY),
+            transfer("frank", "alice", minute),
+        ]);
+        assert_eq!(
+            rule.evaluate(&input(100, within_limit)),
+            RiskOutcome::allow()
+        );
+
+        let fanned_out = history(vec![
+            transfer("alice", "carol", minute),
+            transfer("alice", "dave", minute),
+            transfer("alice", "erin", minute),
+        ]);
+        assert_eq!(
+            rule.evaluate(&input(100, fanned_out)).decision,
+            RiskDecision::Review
+        );
+    }
+
+    /// Tests that sending money back to an account that recently paid the sender is flagged.
+    #[test]
+    fn test_round_trip_rule() {
+        let rule = RoundTripRule {
+            window: 86_400,
+            min_amount_minor_units: 500,
+            action: BLOCK,
+        };
+        let hour = Duration::from_secs(3_600);
+
+        let returned = history(vec![transfer("bob", "alice", hour)]);
+        assert_eq!(
+            rule.evaluate(&input(500, returned.clone())).decision,
+            RiskDecision::Block
+        );
+        assert_eq!(rule.evaluate(&input(499, returned)), RiskOutcome::allow());
+
+        for unrelated in [
+            transfer("bob", "alice", DAY * 2),
+            transfer("alice", "bob", hour),
+            transfer("bob", "carol", hour),
+        ] {
+            assert_eq!(
+                rule.evaluate(&input(500, history(vec![unrelated]))),
+                RiskOutcome::allow()
+            );
+        }
+    }
+
+    struct FixedRule(RiskDecision, u32);
+
+    impl RiskRule for FixedRule {
+        fn name(&self) -> &'static str {
+            "FIXED"
+        }
+
+        fn lookback(&self) -> Duration {
+            Duration::from_secs(u64::from(self.1))
+        }
+
+        fn evaluate(&self, _input: &RiskInput) -> RiskOutcome {
+            RiskOutcome {
+                decision: self.0,
+                score: self.1,
+                reason: "fixed".into(),
+            }
+        }
+    }
+
+    /// Tests that the engine keeps the most severe decision, sums the scores and records the
+    /// rules that did not allow the transfer.
+    #[test]
+    fn test_engine_combines_rules() {
+        let engine = RiskEngine::new(vec![
+            Box::new(FixedRule(RiskDecision::Review, 10)),
+            Box::new(FixedRule(RiskDecision::Allow, 5)),
+            Box::new(FixedRule(RiskDecision::Block, 30)),
+            Box::new(FixedRule(RiskDecision::Review, 20)),
+        ]);
+
+        let assessment = engine.evaluate(&input(100, history(Vec::new())));
+        assert_eq!(assessment.decision, RiskDecision::Block);
+        assert_eq!(assessment.score, 65);
+        assert_eq!(assessment.hits.len(), 3);
+        assert_eq!(engine.lookback(), Duration::from_secs(30));
+
+        let empty = RiskEngine::new(Vec::new());
+        let assessment = empty.evaluate(&input(100, history(Vec::new())));
+        assert_eq!(assessment.decision, RiskDecision::Allow);
+        assert!(assessment.hits.is_empty());
+    }
+
+    /// Tests that rules must flag transfers for review or block them.
+    #[test]
+    fn test_risk_config_validation() {
+        let config = Config::new().unwrap();
+        assert!(config.risk.validate().is_ok());
+        assert!(RiskEngine::from_config(&config.risk).lookback() >= DAY);
+
+        let risk = |value: serde_json::Value| serde_json::from_value::<Risk>(value).unwrap();
+        assert!(risk(serde_json::json!({})).validate().is_ok());
+        assert!(
+            risk(serde_json::json!({
+                "new_counterparty": { "min_amount_minor_units": 1, "action": "ALLOW", "score": 1 },
+            }))
+            .validate()
+            .is_err()
+        );
+        assert!(
+            risk(serde_json::json!({
+                "fan_out": { "window": 60, "max_recipients": 0, "action": "REVIEW", "score": 1 },
+            }))
+            .validate()
+            .is_err()
+        );
+    }
+
+    /// Tests that transfers under review can only be approved or rejected.
+    #[test]
+    fn test_under_review_transitions() {
+        use TransactionStatus::*;
+
+        assert!(Pending.can_transition_to(UnderReview));
+        assert!(UnderReview.can_transition_to(Completed));
+        assert!(UnderReview.can_transition_to(Failed));
+        assert!(UnderReview.can_transition_to(Authorized));
+
+        assert!(!UnderReview.can_transition_to(Voided));
+        assert!(!UnderReview.can_transition_to(Refunded));
+        assert!(!Completed.can_transition_to(UnderReview));
+    }
+
+    /// Tests that scheduled transfers pass the risk rules, so a scheduled round trip is blocked
+    /// and recorded as FAILED.
+    #[tokio::test]
+    async fn test_scheduled_transfer_is_screened() {
+        let app_state = common::app_state().await;
+        let alice = common::user(&app_state).await;
+        let bob = common::user(&app_state).await;
+        common::fund(&app_state, &alice, 2_000_000).await;
+        common::fund(&app_state, &bob, 2_000_000).await;
+        common::transfer(&app_state, &alice, &bob, 1_000_000)
+            .await
+            .unwrap();
+
+        let now = datetime::now();
+        let scheduled = app_state
+            .db
+            .create_scheduled_transfer(NewScheduledTransfer {
+                scheduled_transfer_id: format!("st_{}", generate_nano_id(20)),
+                transaction_id: format!("txn_{}", generate_nano_id(20)),
+                sender_id: bob.clone(),
+                recipient_id: alice.clone(),
+                amount_minor_units: 1_000_000,
+                currency: Currency::Inr.code().to_string(),
+                description: None,
+                execute_at: now - time::Duration::minutes(1),
+                status: ScheduledTransferStatus::Scheduled,
+                created_at: now,
+                updated_at: now,
+                metadata: None,
+            })
+            .await
+            .unwrap();
+
+        // Other transfers may be due as well; run the queue until nothing is left.
+        while app_state
+            .db
+            .execute_next_scheduled_transfer(time::Duration::minutes(1), 2)
+            .await
+            .unwrap()
+            .is_some()
+        {}
+
+        let scheduled = app_state
+            .db
+            .get_scheduled_transfer(&scheduled.scheduled_transfer_id)
+            .await
+            .unwrap();
+        assert_eq!(scheduled.status, ScheduledTransferStatus::Failed);
+        assert_eq!(
+            scheduled.failure_reason.as_deref(),
+            Some("Transfer was blocked by risk checks")
+        );
+        let transaction = app_state
+            .db
+            .get_transaction_by_id(&scheduled.transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(transaction.status, TransactionStatus::Failed);
+        assert_eq!(common::balance(&app_state, &bob).await, 2_995_000);
+    }
+
+    /// Tests that every leg of a batch passes the risk rules after the legs before it, so the
+    /// leg paying one recipient too many is held for review while the others complete.
+    #[tokio::test]
+    async fn test_batch_legs_trip_fan_out() {
+        let app_state = common::app_state().await;
+        let max_recipients = app_state
+            .config
+            .risk
+            .fan_out
+            .as_ref()
+            .unwrap()
+            .max_recipients;
+        let sender = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 100_000).await;
+
+        let batch_id = format!("batch_{}", generate_nano_id(20));
+        let mut legs = Vec::new();
+        for _ in 0..=max_recipients {
+            let recipient = common::user(&app_state).await;
+            let mut leg = common::new_transfer(&sender, &recipient, 1_000);
+            leg.batch_id = Some(batch_id.clone());
+            legs.push(leg);
+        }
+        let batch =
+            NewTransferBatch::new(batch_id, sender.clone(), Currency::Inr, None, &legs).unwrap();
+
+        let (_, transactions) = app_state
+            .db
+            .create_transfer_batch(batch, legs)
+            .await
+            .unwrap();
+
+        let (last, rest) = transactions.split_last().unwrap();
+        assert!(
+            rest.iter()
+                .all(|leg| leg.status == TransactionStatus::Completed)
+        );
+        assert_eq!(last.status, TransactionStatus::UnderReview);
+        let reviews = app_state.db.list_risk_reviews().await.unwrap();
+        let (_, hits) = reviews
+            .iter()
+            .find(|(held, _)| held.transaction_id == last.transaction_id)
+            .unwrap();
+        assert_eq!(hits[0].rule, "RAPID_FAN_OUT");
+
+        let sent = i64::try_from(max_recipients).unwrap() * 1_000;
+        assert_eq!(common::balance(&app_state, &sender).await, 100_000 - sent);
+    }
+
+    /// Asks a new payer for an amount the new counterparty rule holds for review.
+    async fn reviewed_payment_request(app_state: &AppState) -> (String, PaymentRequest) {
+        let requester = common::user(app_state).await;
+        let payer = common::user(app_state).await;
+        common::fund(app_state, &payer, 3_000_000).await;
+
+        let now = datetime::now();
+        let request = app_state
+            .db
+            .create_payment_request(NewPaymentRequest {
+                payment_request_id: format!("pr_{}", generate_nano_id(20)),
+                requester_id: requester.clone(),
+                payer_id: payer,
+                amount_minor_units: 2_500_000,
+                currency: Currency::Inr.code().to_string(),
+                note: None,
+                status: PaymentRequestStatus::Pending,
+                expires_at: now + time::Duration::days(1),
+                created_at: now,
+                updated_at: now,
+            })
+            .await
+            .unwrap();
+
+        (requester, request)
+    }
+
+    /// Tests that paying a payment request passes the risk rules, and a request paid by a
+    /// transfer held for review stays PENDING until an admin approves the transfer.
+    #[tokio::test]
+    async fn test_payment_request_held_for_review() {
+        let app_state = common::app_state().await;
+        let (requester, request) = reviewed_payment_request(&app_state).await;
+
+        let pending = app_state
+            .db
+            .pay_payment_request(&request.payment_request_id)
+            .await
+            .unwrap();
+        assert_eq!(pending.status, PaymentRequestStatus::Pending);
+        assert_eq!(common::balance(&app_state, &requester).await, 0);
+
+        // Paying again while the transfer is held does not make a second transfer.
+        app_state
+            .db
+            .pay_payment_request(&request.payment_request_id)
+            .await
+            .unwrap();
+        let reviews = app_state.db.list_risk_reviews().await.unwrap();
+        let held: Vec<_> = reviews
+            .iter()
+            .filter(|(held, _)| {
+                held.payment_request_id.as_deref() == Some(&request.payment_request_id)
+            })
+            .collect();
+        assert_eq!(held.len(), 1);
+
+        let approved = app_state
+            .db
+            .approve_risk_review(&held[0].0.transaction_id)
+            .await
+            .unwrap();
+        let paid = app_state
+            .db
+            .get_payment_request(&request.payment_request_id)
+            .await
+            .unwrap();
+        assert_eq!(paid.status, PaymentRequestStatus::Paid);
+        assert_eq!(paid.transaction_id, Some(approved.transaction_id));
+        assert_eq!(common::balance(&app_state, &requester).await, 2_495_000);
+    }
+
+    /// Tests that a payment request paid by a transfer under review can be neither declined
+    /// nor cancelled, so approving the transfer never pays a request that was withdrawn.
+    #[tokio::test]
+    async fn test_held_payment_request_cannot_be_withdrawn() {
+        let app_state = common::app_state().await;
+        let (requester, request) = reviewed_payment_request(&app_state).await;
+        app_state
+            .db
+            .pay_payment_request(&request.payment_request_id)
+            .await
+            .unwrap();
+
+        for new_status in [
+            PaymentRequestStatus::Declined,
+            PaymentRequestStatus::Cancelled,
+        ] {
+            let err = app_state
+                .db
+                .update_payment_request_status(&request.payment_request_id, new_status)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                *err.get_inner(),
+                TransactionDbError::PaymentRequestUnderReview
+            );
+        }
+
+        // Once the review rejects the transfer, the request can be declined.
+        let reviews = app_state.db.list_risk_reviews().await.unwrap();
+        let (held, _) = reviews
+            .iter()
+            .find(|(held, _)| {
+                held.payment_request_id.as_deref() == Some(&request.payment_request_id)
+            })
+            .unwrap();
+        app_state
+            .db
+            .reject_risk_review(&held.transaction_id)
+            .await
+            .unwrap();
+        let declined = app_state
+            .db
+            .update_payment_request_status(
+                &request.payment_request_id,
+                PaymentRequestStatus::Declined,
+            )
+            .await
+            .unwrap();
+        assert_eq!(declined.status, PaymentRequestStatus::Declined);
+        assert_eq!(common::balance(&app_state, &requester).await, 0);
+    }
+
+    /// Tests that a blocked transfer is recorded as FAILED and rejected.
+    #[tokio::test]
+    async fn test_submit_blocked_transfer() {
+        let app_state = common::app_state().await;
+        let alice = common::user(&app_state).await;
+        let bob = common::user(&app_state).await;
+        common::fund(&app_state, &alice, 2_000_000).await;
+        common::fund(&app_state, &bob, 2_000_000).await;
+        common::transfer(&app_state, &alice, &bob, 1_000_000)
+            .await
+            .unwrap();
+
+        let round_trip = common::new_transfer(&bob, &alice, 1_000_000);
+        let transaction_id = round_trip.transaction_id.clone();
+        let err = app_state.db.submit_transfer(round_trip).await.unwrap_err();
+        assert_eq!(*err.get_inner(), TransactionDbError::TransferBlocked);
+
+        let blocked = app_state
+            .db
+            .get_transaction_by_id(&transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(blocked.status, TransactionStatus::Failed);
+    }
+
+    /// Builds a PENDING INR authorization holding its amount for an hour.
+    fn new_authorization(sender_id: &str, recipient_id: &str, minor_units: i64) -> NewTransaction {
+        let mut authorization = common::new_transfer(sender_id, recipient_id, minor_units);
+        authorization.authorized_amount_minor_units = Some(minor_units);
+        authorization.expires_at = Some(datetime::now() + time::Duration::hours(1));
+        authorization
+    }
+
+    /// Tests that authorizations pass the risk rules, so a round trip cannot be sent by
+    /// authorizing and capturing it instead of transferring it.
+    #[tokio::test]
+    async fn test_authorize_round_trip_is_blocked() {
+        let app_state = common::app_state().await;
+        let alice = common::user(&app_state).await;
+        let bob = common::user(&app_state).await;
+        common::fund(&app_state, &alice, 2_000_000).await;
+        common::fund(&app_state, &bob, 2_000_000).await;
+        common::transfer(&app_state, &alice, &bob, 1_000_000)
+            .await
+            .unwrap();
+
+        let round_trip = new_authorization(&bob, &alice, 1_000_000);
+        let transaction_id = round_trip.transaction_id.clone();
+        let err = app_state
+            .db
+            .authorize_transaction(round_trip)
+            .await
+            .unwrap_err();
+        assert_eq!(*err.get_inner(), TransactionDbError::TransferBlocked);
+
+        let blocked = app_state
+            .db
+            .get_transaction_by_id(&transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(blocked.status, TransactionStatus::Failed);
+
+        let err = app_state
+            .db
+            .capture_transaction(&transaction_id, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            *err.get_inner(),
+            TransactionDbError::InvalidStatusTransition
+        );
+        assert_eq!(common::balance(&app_state, &bob).await, 2_995_000);
+    }
+
+    /// Tests that an authorization flagged by the risk rules cannot be captured until an admin
+    /// approves it, and that approving it leaves it AUTHORIZED without moving any money.
+    #[tokio::test]
+    async fn test_authorization_held_for_review() {
+        let app_state = common::app_state().await;
+        let alice = common::user(&app_state).await;
+        let bob = common::user(&app_state).await;
+        common::fund(&app_state, &alice, 3_000_000).await;
+
+        let held = app_state
+            .db
+            .authorize_transaction(new_authorization(&alice, &bob, 2_500_000))
+            .await
+            .unwrap();
+        assert_eq!(held.status, TransactionStatus::UnderReview);
+
+        let err = app_state
+            .db
+            .capture_transaction(&held.transaction_id, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            *err.get_inner(),
+            TransactionDbError::InvalidStatusTransition
+        );
+
+        let approved = app_state
+            .db
+            .approve_risk_review(&held.transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(approved.status, TransactionStatus::Authorized);
+        assert_eq!(common::balance(&app_state, &bob).await, 0);
+
+        let captured = app_state
+            .db
+            .capture_transaction(&held.transaction_id, None)
+            .await
+            .unwrap();
+        assert_eq!(captured.status, TransactionStatus::Completed);
+        assert_eq!(common::balance(&app_state, &bob).await, 2_495_000);
+    }
+}
@@ -0,0 +1,286 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use diesel_async::RunQueryDsl;
+    use dodopayments::{
+        app::AppState,
+        configs::Pagination,
+        routes::api_models::{CreateScheduledTransferRequest, page_offset, validate_page_size},
+        storage::{
+            ScheduledTransferInterface, TransactionInterface, TransferLimitInterface,
+            enums::{ScheduledTransferStatus, TransactionStatus},
+            types::{NewScheduledTransfer, NewUserLimits, ScheduledTransfer},
+        },
+        types::{Currency, Metadata},
+        utils::{datetime, generate_nano_id},
+    };
+
+    use crate::common;
+
+    fn request(execute_at: &str) -> CreateScheduledTransferRequest {
+        serde_json::from_value(serde_json::json!({
+            "sender_id": "a",
+            "receiver_id": "b",
+            "amount": "10.00",
+            "execute_at": execute_at,
+        }))
+        .unwrap()
+    }
+
+    /// Schedules a transfer that has been due for `due_ago` minutes.
+    async fn schedule(
+        app_state: &AppState,
+        sender: &str,
+        recipient: &str,
+        amount: i64,
+        due_ago: i64,
+    ) -> ScheduledTransfer {
+        let now = datetime::now();
+        app_state
+            .db
+            .create_scheduled_transfer(NewScheduledTransfer {
+                scheduled_transfer_id: format!("st_{}", generate_nano_id(20)),
+                transaction_id: format!("txn_{}", generate_nano_id(20)),
+                sender_id: sender.to_string(),
+                recipient_id: recipient.to_string(),
+                amount_minor_units: amount,
+                currency: Currency::Inr.code().to_string(),
+                description: None,
+                execute_at: now - time::Duration::minutes(due_ago),
+                status: ScheduledTransferStatus::Scheduled,
+                created_at: now,
+                updated_at: now,
+                metadata: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    /// Executes due transfers until none is left. Other transfers may be due as well.
+    async fn run_queue(app_state: &AppState) {
+        while app_state
+            .db
+            .execute_next_scheduled_transfer(time::Duration::minutes(1), 2)
+            .await
+            .unwrap()
+            .is_some()
+        {}
+    }
+
+    /// Tests that a scheduled transfer must execute in the future.
+    #[test]
+    fn test_create_request_validation() {
+        let tomorrow = time::OffsetDateTime::now_utc() + time::Duration::days(1);
+        let tomorrow = tomorrow
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap();
+
+        assert!(request(&tomorrow).validate().is_ok());
+        assert!(request("2020-01-01T00:00:00Z").validate().is_err());
+        assert!(
+            serde_json::from_value::<CreateScheduledTransferRequest>(serde_json::json!({
+                "sender_id": "a",
+                "receiver_id": "b",
+                "amount": "10.00",
+                "execute_at": "tomorrow",
+            }))
+            .is_err()
+        );
+    }
+
+    /// Tests that the executed transfer reuses the transaction ID reserved on the schedule.
+    #[test]
+    fn test_to_new_transaction() {
+        let now = datetime::now();
+        let scheduled_transfer = ScheduledTransfer {
+            id: 1,
+            scheduled_transfer_id: "st_1".into(),
+            transaction_id: "txn_1".into(),
+            sender_id: "a".into(),
+            recipient_id: "b".into(),
+            amount_minor_units: 1000,
+            currency: "INR".into(),
+            description: Some("rent".into()),
+            execute_at: now,
+            status: ScheduledTransferStatus::Scheduled,
+            failure_reason: None,
+            executed_at: None,
+            created_at: now,
+            updated_at: now,
+            metadata: Some(Metadata(
+                [("invoice".to_string(), "INV-1".to_string())].into(),
+            )),
+            attempts: 0,
+            retry_at: None,
+        };
+
+        let transaction = scheduled_transfer.to_new_transaction();
+        assert_eq!(transaction.transaction_id, "txn_1");
+        assert_eq!(transaction.amount().unwrap().minor_units(), 1000);
+        assert_eq!(transaction.status, TransactionStatus::Pending);
+        assert_eq!(transaction.description.as_deref(), Some("rent"));
+        assert_eq!(transaction.metadata, scheduled_transfer.metadata);
+    }
+
+    /// Tests that scheduled transfer statuses use the stored values.
+    #[test]
+    fn test_status_serialization() {
+        assert_eq!(
+            serde_json::to_string(&ScheduledTransferStatus::Cancelled).unwrap(),
+            "\"CANCELLED\""
+        );
+        assert_eq!("EXECUTED".parse(), Ok(ScheduledTransferStatus::Executed));
+    }
+
+    /// Tests that list page sizes are bounded by the configuration and that offsets too large
+    /// to query are rejected.
+    #[test]
+    fn test_list_page_size() {
+        let pagination = Pagination {
+            default_page_size: 10,
+            max_page_size: 100,
+        };
+
+        assert_eq!(validate_page_size(None, &pagination).unwrap(), 10);
+        assert_eq!(validate_page_size(Some(100), &pagination).unwrap(), 100);
+        assert!(validate_page_size(Some(101), &pagination).is_err());
+        assert!(validate_page_size(Some(0), &pagination).is_err());
+
+        assert_eq!(page_offset(0, 10).unwrap(), 0);
+        assert_eq!(page_offset(1, 10).unwrap(), 0);
+        assert_eq!(page_offset(3, 10).unwrap(), 20);
+        assert!(page_offset(u64::MAX, 100).is_err());
+        assert!(page_offset(u64::MAX / 100, 100).is_err());
+    }
+
+    /// Tests that a due transfer the sender's limits reject is failed rather than retried, so
+    /// the transfers due after it still run.
+    #[tokio::test]
+    async fn test_limit_exceeded_does_not_block_the_queue() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 50_000).await;
+        app_state
+            .db
+            .set_user_limits(NewUserLimits {
+                user_id: sender.clone(),
+                max_transfer_minor_units: Some(20_000),
+                daily_outgoing_minor_units: None,
+                monthly_outgoing_minor_units: None,
+                transfers_per_hour: None,
+                updated_at: datetime::now(),
+            })
+            .await
+            .unwrap();
+
+        let scheduled = [
+            schedule(&app_state, &sender, &recipient, 30_000, 2).await,
+            schedule(&app_state, &sender, &recipient, 10_000, 1).await,
+        ];
+        run_queue(&app_state).await;
+
+        let over_limit = app_state
+            .db
+            .get_scheduled_transfer(&scheduled[0].scheduled_transfer_id)
+            .await
+            .unwrap();
+        assert_eq!(over_limit.status, ScheduledTransferStatus::Failed);
+        assert!(over_limit.failure_reason.unwrap().contains("limit"));
+
+        let next = app_state
+            .db
+            .get_scheduled_transfer(&scheduled[1].scheduled_transfer_id)
+            .await
+            .unwrap();
+        assert_eq!(next.status, ScheduledTransferStatus::Executed);
+        assert_eq!(common::balance(&app_state, &recipient).await, 9_900);
+    }
+
+    /// Tests that a due transfer that hits a database error backs off and is retried while the
+    /// transfers due after it run, and fails once it runs out of attempts.
+    #[tokio::test]
+    async fn test_database_error_backs_off() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 50_000).await;
+
+        let failing = schedule(&app_state, &sender, &recipient, 10_000, 2).await;
+        let next = schedule(&app_state, &sender, &recipient, 10_000, 1).await;
+
+        // Make inserting the transfer of the first schedule fail inside the database.
+        let trigger = format!("fail_{}", failing.transaction_id.to_lowercase());
+        let mut conn = app_state.db.get_conn().await.unwrap();
+        diesel::sql_query(format!(
+            "CREATE FUNCTION {trigger}() RETURNS trigger AS $$ \
+             BEGIN RAISE EXCEPTION 'injected failure'; END $$ LANGUAGE plpgsql"
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        diesel::sql_query(format!(
+            "CREATE TRIGGER {trigger} BEFORE INSERT ON transactions FOR EACH ROW \
+             WHEN (NEW.transaction_id = '{}') EXECUTE FUNCTION {trigger}()",
+            failing.transaction_id
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        run_queue(&app_state).await;
+
+        let retried = app_state
+            .db
+            .get_scheduled_transfer(&failing.scheduled_transfer_id)
+            .await
+            .unwrap();
+        assert_eq!(retried.status, ScheduledTransferStatus::Scheduled);
+        assert_eq!(retried.attempts, 1);
+        assert!(retried.retry_at.is_some_and(|at| at > datetime::now()));
+
+        let next = app_state
+            .db
+            .get_scheduled_transfer(&next.scheduled_transfer_id)
+            .await
+            .unwrap();
+        assert_eq!(next.status, ScheduledTransferStatus::Executed);
+        assert_eq!(common::balance(&app_state, &recipient).await, 9_900);
+
+        diesel::sql_query(format!(
+            "UPDATE scheduled_transfers SET retry_at = execute_at \
+             WHERE scheduled_transfer_id = '{}'",
+            failing.scheduled_transfer_id
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        run_queue(&app_state).await;
+
+        diesel::sql_query(format!("DROP TRIGGER {trigger} ON transactions"))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::sql_query(format!("DROP FUNCTION {trigger}()"))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let failed = app_state
+            .db
+            .get_scheduled_transfer(&failing.scheduled_transfer_id)
+            .await
+            .unwrap();
+        assert_eq!(failed.status, ScheduledTransferStatus::Failed);
+        assert!(failed.failure_reason.is_some());
+        assert!(
+            app_state
+                .db
+                .get_transaction_by_id(&failing.transaction_id)
+                .await
+                .is_err()
+        );
+        assert_eq!(common::balance(&app_state, &recipient).await, 9_900);
+    }
+}
@@ -0,0 +1,406 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use diesel_async::RunQueryDsl;
+    use dodopayments::{
+        app::AppState,
+        configs::CatchUpPolicy,
+        error::RecurrenceError,
+        routes::api_models::CreateStandingInstructionRequest,
+        storage::{
+            StandingInstructionInterface,
+            enums::{RecurrenceFrequency, StandingInstructionStatus},
+            types::{NewStandingInstruction, StandingInstruction},
+        },
+        types::{CronSchedule, Currency, Recurrence},
+        utils::{datetime, generate_nano_id},
+    };
+    use time::{Date, Month, PrimitiveDateTime, Time};
+
+    use crate::common;
+
+    fn at(year: i32, month: u8, day: u8, hour: u8, minute: u8) -> PrimitiveDateTime {
+        let date = Date::from_calendar_date(year, Month::try_from(month).unwrap(), day).unwrap();
+        PrimitiveDateTime::new(date, Time::from_hms(hour, minute, 0).unwrap())
+    }
+
+    fn cron(expression: &str) -> Recurrence {
+        Recurrence::Cron {
+            expression: expression.parse().unwrap(),
+        }
+    }
+
+    fn request(extra: serde_json::Value) -> serde_json::Result<CreateStandingInstructionRequest> {
+        let mut body = serde_json::json!({
+            "sender_id": "a",
+            "receiver_id": "b",
+            "amount": "10.00",
+            "recurrence": { "frequency": "DAILY" },
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(body)
+    }
+
+    /// Tests that daily and weekly recurrences keep the time of day of the start.
+    #[test]
+    fn test_daily_and_weekly_occurrences() {
+        let start = at(2025, 1, 30, 9, 15);
+
+        assert_eq!(Recurrence::Daily.first_occurrence(start), Some(start));
+        assert_eq!(
+            Recurrence::Daily.next_occurrence(start),
+            Some(at(2025, 1, 31, 9, 15))
+        );
+        assert_eq!(
+            Recurrence::Weekly.next_occurrence(start),
+            Some(at(2025, 2, 6, 9, 15))
+        );
+    }
+
+    /// Tests that monthly recurrences run on the last day of months shorter than the day.
+    #[test]
+    fn test_monthly_occurrences() {
+        let monthly = Recurrence::monthly(31).unwrap();
+
+        let first = monthly.first_occurrence(at(2024, 1, 31, 10, 0)).unwrap();
+        assert_eq!(first, at(2024, 1, 31, 10, 0));
+        let second = monthly.next_occurrence(first).unwrap();
+        assert_eq!(second, at(2024, 2, 29, 10, 0));
+        assert_eq!(
+            monthly.next_occurrence(second),
+            Some(at(2024, 3, 31, 10, 0))
+        );
+
+        // A start after the day of month begins in the following month.
+        let monthly = Recurrence::monthly(15).unwrap();
+        assert_eq!(
+            monthly.first_occurrence(at(2024, 12, 20, 8, 0)),
+            Some(at(2025, 1, 15, 8, 0))
+        );
+        assert_eq!(
+            Recurrence::monthly(0),
+            Err(RecurrenceError::InvalidDayOfMonth(0))
+        );
+    }
+
+    /// Tests cron expressions with ranges, steps, lists and day of week.
+    #[test]
+    fn test_cron_occurrences() {
+        // Every 15 minutes during office hours on weekdays.
+        let office_hours = cron("*/15 9-17 * * 1-5");
+        assert_eq!(
+            office_hours.first_occurrence(at(2025, 5, 30, 17, 50)),
+            // Friday evening, so the next run is on Monday morning.
+            Some(at(2025, 6, 2, 9, 0))
+        );
+        assert_eq!(
+            office_hours.next_occurrence(at(2025, 6, 2, 9, 0)),
+            Some(at(2025, 6, 2, 9, 15))
+        );
+
+        // A start on a matching minute is itself the first occurrence.
+        let payday = cron("0 10 1,15 * *");
+        assert_eq!(
+            payday.first_occurrence(at(2025, 6, 15, 10, 0)),
+            Some(at(2025, 6, 15, 10, 0))
+        );
+        assert_eq!(
+            payday.next_occurrence(at(2025, 6, 15, 10, 0)),
+            Some(at(2025, 7, 1, 10, 0))
+        );
+
+        // Sundays may be written as 0 or 7.
+        for expression in ["0 0 * * 0", "0 0 * * 7"] {
+            assert_eq!(
+                cron(expression).first_occurrence(at(2025, 6, 2, 0, 0)),
+                Some(at(2025, 6, 8, 0, 0))
+            );
+        }
+    }
+
+    /// Tests that invalid or never matching cron expressions are rejected.
+    #[test]
+    fn test_invalid_cron_expressions() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 31 2 *",
+            "every day",
+        ] {
+            assert!(
+                matches!(
+                    expression.parse::<CronSchedule>(),
+                    Err(RecurrenceError::InvalidCronExpression(_))
+                ),
+                "{expression} should be rejected"
+            );
+        }
+
+        assert!("0 0 29 2 *".parse::<CronSchedule>().is_ok());
+    }
+
+    /// Tests that skipping missed runs keeps the series anchored to its original schedule.
+    #[test]
+    fn test_occurrence_not_before() {
+        let previous = at(2025, 1, 1, 9, 0);
+
+        assert_eq!(
+            Recurrence::Daily.occurrence_not_before(previous, at(2025, 1, 10, 12, 0)),
+            Some(at(2025, 1, 11, 9, 0))
+        );
+        assert_eq!(
+            Recurrence::Weekly.occurrence_not_before(previous, at(2025, 1, 15, 9, 0)),
+            Some(at(2025, 1, 15, 9, 0))
+        );
+        assert_eq!(
+            Recurrence::monthly(1)
+                .unwrap()
+                .occurrence_not_before(previous, at(2025, 3, 2, 0, 0)),
+            Some(at(2025, 4, 1, 9, 0))
+        );
+        assert_eq!(
+            Recurrence::Daily.occurrence_not_before(previous, at(2024, 12, 1, 0, 0)),
+            Some(previous)
+        );
+    }
+
+    /// Tests the request format of each recurrence and the validation of the bounds.
+    #[test]
+    fn test_create_request_validation() {
+        let monthly = request(serde_json::json!({
+            "recurrence": { "frequency": "MONTHLY", "day_of_month": 31 },
+            "start_at": "2030-01-01T00:00:00Z",
+            "end_at": "2030-12-31T00:00:00Z",
+            "max_occurrences": 12,
+        }))
+        .unwrap();
+        assert_eq!(monthly.recurrence, Recurrence::Monthly { day_of_month: 31 });
+        assert!(monthly.validate().is_ok());
+
+        let weekly_cron = request(serde_json::json!({
+            "recurrence": { "frequency": "CRON", "expression": "30 8 * * 1" },
+        }))
+        .unwrap();
+        assert_eq!(weekly_cron.recurrence, cron("30 8 * * 1"));
+
+        assert!(
+            request(serde_json::json!({
+                "recurrence": { "frequency": "CRON", "expression": "30 8 * *" },
+            }))
+            .is_err()
+        );
+        assert!(
+            request(serde_json::json!({
+                "recurrence": { "frequency": "MONTHLY", "day_of_month": 32 },
+            }))
+            .unwrap()
+            .validate()
+            .is_err()
+        );
+        assert!(
+            request(serde_json::json!({
+                "start_at": "2030-01-01T00:00:00Z",
+                "end_at": "2029-01-01T00:00:00Z",
+            }))
+            .unwrap()
+            .validate()
+            .is_err()
+        );
+        assert!(
+            request(serde_json::json!({ "max_occurrences": 0 }))
+                .unwrap()
+                .validate()
+                .is_err()
+        );
+    }
+
+    /// Creates a standing instruction first due `due_ago` minutes ago, stored as given so that
+    /// invalid recurrences can be tested.
+    async fn instruction(
+        app_state: &AppState,
+        sender: &str,
+        recipient: &str,
+        due_ago: i64,
+        frequency: RecurrenceFrequency,
+        cron_expression: Option<&str>,
+    ) -> StandingInstruction {
+        let now = datetime::now();
+        let start_at = now - time::Duration::minutes(due_ago);
+        app_state
+            .db
+            .create_standing_instruction(NewStandingInstruction {
+                standing_instruction_id: format!("si_{}", generate_nano_id(20)),
+                sender_id: sender.to_string(),
+                recipient_id: recipient.to_string(),
+                amount_minor_units: 1_000,
+                currency: Currency::Inr.code().to_string(),
+                description: None,
+                frequency,
+                day_of_month: None,
+                cron_expression: cron_expression.map(str::to_string),
+                start_at,
+                end_at: None,
+                max_occurrences: None,
+                next_run_at: Some(start_at),
+                status: StandingInstructionStatus::Active,
+                created_at: now,
+                updated_at: now,
+                metadata: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    /// Executes due runs until none is left. Other instructions may be due as well.
+    async fn run_queue(app_state: &AppState) {
+        while app_state
+            .db
+            .execute_next_standing_instruction(
+                CatchUpPolicy::Execute,
+                time::Duration::hours(1),
+                time::Duration::minutes(1),
+                2,
+            )
+            .await
+            .unwrap()
+            .is_some()
+        {}
+    }
+
+    async fn reload(
+        app_state: &AppState,
+        instruction: &StandingInstruction,
+    ) -> StandingInstruction {
+        app_state
+            .db
+            .get_standing_instruction(&instruction.standing_instruction_id)
+            .await
+            .unwrap()
+    }
+
+    /// Tests that an instruction whose recurrence cannot be read is failed instead of holding
+    /// up the instructions due after it.
+    #[tokio::test]
+    async fn test_unreadable_recurrence_fails_instruction() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 10_000).await;
+
+        let broken = instruction(
+            &app_state,
+            &sender,
+            &recipient,
+            2,
+            RecurrenceFrequency::Cron,
+            Some("not a cron"),
+        )
+        .await;
+        let next = instruction(
+            &app_state,
+            &sender,
+            &recipient,
+            1,
+            RecurrenceFrequency::Daily,
+            None,
+        )
+        .await;
+        run_queue(&app_state).await;
+
+        let broken = reload(&app_state, &broken).await;
+        assert_eq!(broken.status, StandingInstructionStatus::Failed);
+        assert!(broken.last_failure_reason.is_some());
+        assert_eq!(broken.occurrences_count, 0);
+        assert_eq!(reload(&app_state, &next).await.occurrences_count, 1);
+    }
+
+    /// Tests that a run that hits a database error backs off while the instructions due after
+    /// it run, and is recorded as failed once it runs out of attempts.
+    #[tokio::test]
+    async fn test_database_error_backs_off() {
+        let app_state = common::app_state().await;
+        let sender = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 10_000).await;
+
+        let failing = instruction(
+            &app_state,
+            &sender,
+            &recipient,
+            2,
+            RecurrenceFrequency::Daily,
+            None,
+        )
+        .await;
+        let next = instruction(
+            &app_state,
+            &sender,
+            &recipient,
+            1,
+            RecurrenceFrequency::Daily,
+            None,
+        )
+        .await;
+
+        // Make inserting the transfer of the first run fail inside the database.
+        let transaction_id = failing.to_new_transaction(1).transaction_id;
+        let trigger = format!("fail_{}", failing.standing_instruction_id.to_lowercase());
+        let mut conn = app_state.db.get_conn().await.unwrap();
+        diesel::sql_query(format!(
+            "CREATE FUNCTION {trigger}() RETURNS trigger AS $$ \
+             BEGIN RAISE EXCEPTION 'injected failure'; END $$ LANGUAGE plpgsql"
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        diesel::sql_query(format!(
+            "CREATE TRIGGER {trigger} BEFORE INSERT ON transactions FOR EACH ROW \
+             WHEN (NEW.transaction_id = '{transaction_id}') EXECUTE FUNCTION {trigger}()"
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        run_queue(&app_state).await;
+
+        let retried = reload(&app_state, &failing).await;
+        assert_eq!(retried.status, StandingInstructionStatus::Active);
+        assert_eq!(retried.occurrences_count, 0);
+        assert_eq!(retried.attempts, 1);
+        assert!(retried.retry_at.is_some_and(|at| at > datetime::now()));
+        assert_eq!(reload(&app_state, &next).await.occurrences_count, 1);
+
+        diesel::sql_query(format!(
+            "UPDATE standing_instructions SET retry_at = start_at \
+             WHERE standing_instruction_id = '{}'",
+            failing.standing_instruction_id
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        run_queue(&app_state).await;
+
+        diesel::sql_query(format!("DROP TRIGGER {trigger} ON transactions"))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::sql_query(format!("DROP FUNCTION {trigger}()"))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let failed_run = reload(&app_state, &failing).await;
+        assert_eq!(failed_run.status, StandingInstructionStatus::Active);
+        assert_eq!(failed_run.occurrences_count, 1);
+        assert_eq!(failed_run.attempts, 0);
+        assert_eq!(failed_run.retry_at, None);
+        assert!(failed_run.last_failure_reason.is_some());
+        assert_eq!(common::balance(&app_state, &recipient).await, 990);
+    }
+}
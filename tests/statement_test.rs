@@ -0,0 +1,282 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        routes::api_models::{StatementFormat, StatementRequest},
+        statement::{Statement, StatementPeriod},
+        storage::{
+            enums::{LedgerDirection, TransactionStatus, TransactionType},
+            types::{LedgerEntry, Transaction},
+        },
+        types::{Currency, Money},
+    };
+    use time::macros::datetime;
+
+    fn transaction(
+        transaction_id: &str,
+        sender_id: &str,
+        recipient_id: &str,
+        amount_minor_units: i64,
+        fee_minor_units: i64,
+    ) -> Transaction {
+        let created_at = datetime!(2025-06-10 12:00);
+        Transaction {
+            id: 1,
+            transaction_id: transaction_id.into(),
+            sender_id: sender_id.into(),
+            recipient_id: recipient_id.into(),
+            amount_minor_units,
+            description: None,
+            created_at,
+            status: TransactionStatus::Completed,
+            updated_at: created_at,
+            currency: "INR".into(),
+            failure_reason: None,
+            transaction_type: TransactionType::Transfer,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: None,
+            fee_minor_units,
+        }
+    }
+
+    fn statement(transactions: Vec<Transaction>) -> Statement {
+        let period = StatementPeriod::new(2025, 6).unwrap();
+        Statement::new(
+            "alice".into(),
+            Currency::Inr,
+            period,
+            1_000,
+            transactions,
+            Vec::new(),
+        )
+        .unwrap()
+    }
+
+    fn posting(
+        journal_id: &str,
+        account_id: &str,
+        direction: LedgerDirection,
+        amount_minor_units: i64,
+        created_at: time::PrimitiveDateTime,
+    ) -> LedgerEntry {
+        LedgerEntry {
+            id: 1,
+            entry_id: format!("le_{journal_id}_{account_id}"),
+            journal_id: journal_id.into(),
+            account_id: account_id.into(),
+            direction,
+            amount_minor_units,
+            currency: "INR".into(),
+            created_at,
+        }
+    }
+
+    fn inr(minor_units: i64) -> Money {
+        Money::new(minor_units, Currency::Inr).unwrap()
+    }
+
+    /// Tests that a period covers its calendar month, rolling over into the next year.
+    #[test]
+    fn test_period_bounds() {
+        let june = StatementPeriod::new(2025, 6).unwrap();
+        assert_eq!(june.start(), datetime!(2025-06-01 0:00));
+        assert_eq!(june.end(), datetime!(2025-07-01 0:00));
+        assert_eq!(june.to_string(), "2025-06");
+
+        let december = StatementPeriod::new(2025, 12).unwrap();
+        assert_eq!(december.end(), datetime!(2026-01-01 0:00));
+
+        assert!(StatementPeriod::new(2025, 0).is_err());
+        assert!(StatementPeriod::new(2025, 13).is_err());
+        assert!(StatementPeriod::new(9999, 12).is_err());
+    }
+
+    /// Tests that credits are net of fees, debits are the full amount, and the running balance
+    /// ends at the closing balance.
+    #[test]
+    fn test_running_balance() {
+        let statement = statement(vec![
+            transaction("txn_1", "bob", "alice", 3_000, 30),
+            transaction("txn_2", "alice", "bob", 500, 5),
+        ]);
+
+        assert_eq!(statement.opening_balance, inr(1_000));
+        assert_eq!(statement.total_credits, inr(2_970));
+        assert_eq!(statement.total_debits, inr(500));
+        assert_eq!(statement.closing_balance, inr(3_470));
+
+        let lines = statement
+            .lines
+            .iter()
+            .map(|line| (line.direction, line.amount, line.balance))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                (LedgerDirection::Credit, inr(2_970), inr(3_970)),
+                (LedgerDirection::Debit, inr(500), inr(3_470)),
+            ]
+        );
+        assert_eq!(statement.lines[0].counterparty_id, "bob");
+    }
+
+    /// Tests that transactions in other currencies or between other accounts are left out.
+    #[test]
+    fn test_unrelated_transactions_skipped() {
+        let mut usd = transaction("txn_1", "bob", "alice", 3_000, 0);
+        usd.currency = "USD".into();
+
+        let statement = statement(vec![usd, transaction("txn_2", "bob", "carol", 100, 0)]);
+        assert!(statement.lines.is_empty());
+        assert_eq!(statement.closing_balance, statement.opening_balance);
+    }
+
+    /// Tests that adjustments booked during the period are listed in order with the
+    /// transactions, against the other side of their journal, so the running balance adds up.
+    #[test]
+    fn test_adjustments_listed() {
+        let period = StatementPeriod::new(2025, 6).unwrap();
+        let adjustments = vec![
+            posting(
+                "opening_alice",
+                "alice",
+                LedgerDirection::Credit,
+                2_000,
+                datetime!(2025-06-10 12:00),
+            ),
+            posting(
+                "opening_alice",
+                "sys_adjustments",
+                LedgerDirection::Debit,
+                2_000,
+                datetime!(2025-06-10 12:00),
+            ),
+        ];
+        let mut earlier = transaction("txn_1", "bob", "alice", 300, 0);
+        earlier.created_at = datetime!(2025-06-02 9:00);
+
+        let statement = Statement::new(
+            "alice".into(),
+            Currency::Inr,
+            period,
+            1_000,
+            vec![earlier, transaction("txn_2", "alice", "bob", 2_500, 0)],
+            adjustments,
+        )
+        .unwrap();
+
+        let lines = statement
+            .lines
+            .iter()
+            .map(|line| {
+                (
+                    line.transaction_id.as_str(),
+                    line.transaction_type,
+                    line.balance,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                ("txn_1", Some(TransactionType::Transfer), inr(1_300)),
+                ("opening_alice", None, inr(3_300)),
+                ("txn_2", Some(TransactionType::Transfer), inr(800)),
+            ]
+        );
+        assert_eq!(statement.lines[1].counterparty_id, "sys_adjustments");
+        assert_eq!(statement.total_credits, inr(2_300));
+        assert_eq!(statement.closing_balance, inr(800));
+        assert!(
+            statement
+                .to_csv()
+                .contains(",opening_alice,ADJUSTMENT,sys_adjustments,")
+        );
+    }
+
+    /// Tests that a statement cannot show a negative balance.
+    #[test]
+    fn test_negative_balance_rejected() {
+        let period = StatementPeriod::new(2025, 6).unwrap();
+        assert!(
+            Statement::new(
+                "alice".into(),
+                Currency::Inr,
+                period,
+                100,
+                vec![transaction("txn_1", "alice", "bob", 500, 0)],
+                Vec::new(),
+            )
+            .is_err()
+        );
+    }
+
+    /// Tests that CSV fields are quoted and that formulas in descriptions are neutralised.
+    #[test]
+    fn test_csv_rendering() {
+        let mut paid = transaction("txn_1", "alice", "bob", 500, 0);
+        paid.description = Some("Rent, \"June\"".into());
+        let mut formula = transaction("txn_2", "bob", "alice", 100, 0);
+        formula.description = Some("=HYPERLINK(\"x\")".into());
+
+        let csv = statement(vec![paid, formula]).to_csv();
+        let rows = csv.split("\r\n").collect::<Vec<_>>();
+
+        assert_eq!(
+            rows[0],
+            "date,transaction_id,type,counterparty_id,description,credit,debit,balance,currency"
+        );
+        assert_eq!(
+            rows[1],
+            "2025-06-01 0:00:00.0,,,,Opening balance,,,10.00,INR"
+        );
+        assert_eq!(
+            rows[2],
+            "2025-06-10 12:00:00.0,txn_1,TRANSFER,bob,\"Rent, \"\"June\"\"\",,5.00,5.00,INR"
+        );
+        assert_eq!(
+            rows[3],
+            "2025-06-10 12:00:00.0,txn_2,TRANSFER,bob,\"'=HYPERLINK(\"\"x\"\")\",1.00,,6.00,INR"
+        );
+        assert_eq!(rows[4], ",,,,Totals,1.00,5.00,,INR");
+        assert_eq!(
+            rows[5],
+            "2025-07-01 0:00:00.0,,,,Closing balance,,,6.00,INR"
+        );
+        assert_eq!(rows[6], "");
+    }
+
+    /// Tests that text written by users is escaped in the HTML rendering.
+    #[test]
+    fn test_html_rendering() {
+        let mut paid = transaction("txn_1", "alice", "bob", 500, 0);
+        paid.description = Some("<script>alert('x')</script>".into());
+
+        let html = statement(vec![paid]).to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<td class=\"amount\">5.00</td>"));
+
+        assert!(statement(Vec::new()).to_html().contains("No transactions"));
+    }
+
+    /// Tests that statements default to the INR wallet rendered as JSON.
+    #[test]
+    fn test_statement_request_defaults() {
+        let request = serde_json::from_value::<StatementRequest>(serde_json::json!({})).unwrap();
+        assert_eq!(request.currency, Currency::Inr);
+        assert_eq!(request.format, StatementFormat::Json);
+
+        let request = serde_json::from_value::<StatementRequest>(
+            serde_json::json!({ "currency": "USD", "format": "csv" }),
+        )
+        .unwrap();
+        assert_eq!(request.currency, Currency::Usd);
+        assert_eq!(request.format, StatementFormat::Csv);
+    }
+}
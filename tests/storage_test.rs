@@ -8,7 +8,13 @@ mod tests {
         // Load the config.
         let config = Config::new().unwrap();
         // Create a new storage instance.
-        let storage = Storage::new(&config.database).await;
+        let storage = Storage::new(
+            &config.database,
+            &config.fees,
+            &config.transfer_limits,
+            &config.risk,
+        )
+        .await;
         // Assert that the storage connection is successful.
         assert!(storage.is_ok());
     }
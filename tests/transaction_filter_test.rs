@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        routes::api_models::ListTransactionsRequest,
+        storage::{
+            enums::TransactionStatus,
+            types::{TransactionDirection, TransactionFilter, TransactionSort},
+        },
+        types::{Currency, Metadata},
+    };
+    use time::macros::datetime;
+
+    fn request(query: serde_json::Value) -> ListTransactionsRequest {
+        serde_json::from_value(query).unwrap()
+    }
+
+    fn filter(query: serde_json::Value) -> TransactionFilter {
+        request(query).to_filter("alice".into()).unwrap()
+    }
+
+    /// Tests that a request without parameters lists every transaction of the user, newest
+    /// first.
+    #[test]
+    fn test_default_filter() {
+        assert_eq!(
+            filter(serde_json::json!({})),
+            TransactionFilter {
+                account_id: "alice".into(),
+                sort: TransactionSort::CreatedAtDesc,
+                ..Default::default()
+            }
+        );
+    }
+
+    /// Tests that every parameter ends up in the filter, with timestamps in UTC and amounts in
+    /// minor units.
+    #[test]
+    fn test_full_filter() {
+        let filter = filter(serde_json::json!({
+            "direction": "SENT",
+            "counterparty_id": "bob",
+            "status": "COMPLETED",
+            "currency": "USD",
+            "from": "2025-06-01T05:30:00+05:30",
+            "to": "2025-07-01T00:00:00Z",
+            "min_amount": "1.50",
+            "max_amount": "20",
+            "search": "rent",
+            "metadata_key": "order_id",
+            "metadata_value": "42",
+            "sort": "amount_asc",
+        }));
+
+        assert_eq!(
+            filter,
+            TransactionFilter {
+                account_id: "alice".into(),
+                direction: Some(TransactionDirection::Sent),
+                counterparty_id: Some("bob".into()),
+                status: Some(TransactionStatus::Completed),
+                currency: Some(Currency::Usd),
+                created_from: Some(datetime!(2025-06-01 0:00)),
+                created_to: Some(datetime!(2025-07-01 0:00)),
+                min_amount_minor_units: Some(150),
+                max_amount_minor_units: Some(2_000),
+                search: Some("rent".into()),
+                metadata: Some(Metadata(
+                    [("order_id".to_string(), "42".to_string())].into()
+                )),
+                sort: TransactionSort::AmountAsc,
+            }
+        );
+    }
+
+    /// Tests that amount bounds without a currency are read as INR and only match INR
+    /// transactions.
+    #[test]
+    fn test_amount_bounds_default_to_inr() {
+        let filter = filter(serde_json::json!({ "min_amount": "10.5" }));
+        assert_eq!(filter.currency, Some(Currency::Inr));
+        assert_eq!(filter.min_amount_minor_units, Some(1_050));
+        assert_eq!(filter.max_amount_minor_units, None);
+    }
+
+    /// Tests that inconsistent or malformed parameters are rejected.
+    #[test]
+    fn test_invalid_filters() {
+        for query in [
+            serde_json::json!({ "min_amount": "20", "max_amount": "10" }),
+            serde_json::json!({ "min_amount": "-1" }),
+            serde_json::json!({ "max_amount": "1.234" }),
+            serde_json::json!({ "max_amount": "ten" }),
+            serde_json::json!({
+                "from": "2025-07-01T00:00:00Z",
+                "to": "2025-07-01T00:00:00Z",
+            }),
+            serde_json::json!({ "counterparty_id": "" }),
+            serde_json::json!({ "search": "" }),
+            serde_json::json!({ "search": "a".repeat(501) }),
+        ] {
+            assert!(
+                request(query.clone()).to_filter("alice".into()).is_err(),
+                "{query} should be rejected"
+            );
+        }
+
+        assert!(
+            serde_json::from_value::<ListTransactionsRequest>(
+                serde_json::json!({ "sort": "description_asc" })
+            )
+            .is_err()
+        );
+        assert!(
+            serde_json::from_value::<ListTransactionsRequest>(
+                serde_json::json!({ "direction": "BOTH" })
+            )
+            .is_err()
+        );
+    }
+
+    /// Tests that LIKE wildcards in the search text match literally.
+    #[test]
+    fn test_search_pattern_escaping() {
+        let pattern = |search: &str| {
+            TransactionFilter {
+                search: Some(search.into()),
+                ..Default::default()
+            }
+            .search_pattern()
+        };
+
+        assert_eq!(pattern("rent"), Some("%rent%".into()));
+        assert_eq!(pattern("50%_off\\"), Some("%50\\%\\_off\\\\%".into()));
+        assert_eq!(TransactionFilter::default().search_pattern(), None);
+    }
+}
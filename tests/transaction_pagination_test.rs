@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        configs::{Config, Pagination},
+        routes::api_models::ListTransactionsRequest,
+        storage::{
+            enums::{TransactionStatus, TransactionType},
+            types::{
+                CursorDirection, Transaction, TransactionCursor, TransactionPage, TransactionSort,
+            },
+        },
+    };
+    use time::{Duration, macros::datetime};
+
+    /// The `n`th transaction, created `n` seconds after the first.
+    fn transaction(id: i32, amount_minor_units: i64) -> Transaction {
+        let created_at = datetime!(2025-06-10 12:00:00.123456) + Duration::seconds(id.into());
+        Transaction {
+            id,
+            transaction_id: format!("txn_{id}"),
+            sender_id: "alice".into(),
+            recipient_id: "bob".into(),
+            amount_minor_units,
+            description: None,
+            created_at,
+            status: TransactionStatus::Completed,
+            updated_at: created_at,
+            currency: "INR".into(),
+            failure_reason: None,
+            transaction_type: TransactionType::Transfer,
+            parent_transaction_id: None,
+            authorized_amount_minor_units: None,
+            expires_at: None,
+            standing_instruction_id: None,
+            payment_request_id: None,
+            batch_id: None,
+            metadata: None,
+            fee_minor_units: 0,
+        }
+    }
+
+    fn ids(page: &TransactionPage) -> Vec<i32> {
+        page.transactions
+            .iter()
+            .map(|transaction| transaction.id)
+            .collect()
+    }
+
+    fn request(query: serde_json::Value) -> ListTransactionsRequest {
+        serde_json::from_value(query).unwrap()
+    }
+
+    /// Tests that cursors survive encoding with microsecond timestamps, and that anything else
+    /// is rejected.
+    #[test]
+    fn test_cursor_encoding() {
+        let cursor = TransactionCursor::new(
+            TransactionSort::AmountAsc,
+            CursorDirection::Before,
+            &transaction(7, 1_050),
+        );
+        let encoded = cursor.encode();
+
+        assert!(
+            encoded
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+        assert_eq!(TransactionCursor::decode(&encoded).unwrap(), cursor);
+        assert!(TransactionCursor::decode("garbage").is_err());
+        assert!(TransactionCursor::decode("").is_err());
+    }
+
+    /// Tests that sorts order by their key and break ties by creation order.
+    #[test]
+    fn test_sort_order() {
+        let mut rows = vec![
+            transaction(1, 500),
+            transaction(2, 100),
+            transaction(3, 500),
+        ];
+        let sorted = |sort: TransactionSort, rows: &mut Vec<Transaction>| {
+            rows.sort_by(|a, b| sort.compare(a, b));
+            rows.iter().map(|row| row.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(TransactionSort::CreatedAtDesc, &mut rows), [3, 2, 1]);
+        assert_eq!(sorted(TransactionSort::CreatedAtAsc, &mut rows), [1, 2, 3]);
+        assert_eq!(sorted(TransactionSort::AmountDesc, &mut rows), [3, 1, 2]);
+        assert_eq!(sorted(TransactionSort::AmountAsc, &mut rows), [2, 1, 3]);
+    }
+
+    /// Tests the cursors of pages read forwards: the first page has no previous page and the
+    /// last page has no next page.
+    #[test]
+    fn test_forward_pages() {
+        let sort = TransactionSort::CreatedAtDesc;
+
+        let first = TransactionPage::new(
+            sort,
+            None,
+            vec![transaction(5, 0), transaction(4, 0), transaction(3, 0)],
+            2,
+            Some(5),
+        );
+        assert_eq!(ids(&first), [5, 4]);
+        assert_eq!(first.prev_cursor, None);
+        let next = first.next_cursor.unwrap();
+        assert_eq!((next.direction, next.id), (CursorDirection::After, 4));
+        assert_eq!(first.total_count, Some(5));
+
+        let last = TransactionPage::new(sort, Some(&next), vec![transaction(3, 0)], 2, None);
+        assert_eq!(ids(&last), [3]);
+        assert_eq!(last.next_cursor, None);
+        let prev = last.prev_cursor.unwrap();
+        assert_eq!((prev.direction, prev.id), (CursorDirection::Before, 3));
+    }
+
+    /// Tests that pages read backwards come out in the order of the sort, and that reaching
+    /// the start leaves no previous page.
+    #[test]
+    fn test_backward_pages() {
+        let sort = TransactionSort::CreatedAtDesc;
+        let cursor = TransactionCursor::new(sort, CursorDirection::Before, &transaction(3, 0));
+        assert!(cursor.scans_ascending());
+
+        let middle = TransactionPage::new(
+            sort,
+            Some(&cursor),
+            vec![transaction(4, 0), transaction(5, 0), transaction(6, 0)],
+            2,
+            None,
+        );
+        assert_eq!(ids(&middle), [5, 4]);
+        assert_eq!(middle.prev_cursor.unwrap().id, 5);
+        assert_eq!(middle.next_cursor.unwrap().id, 4);
+
+        let first = TransactionPage::new(sort, Some(&cursor), vec![transaction(4, 0)], 2, None);
+        assert_eq!(ids(&first), [4]);
+        assert_eq!(first.prev_cursor, None);
+        assert_eq!(first.next_cursor.unwrap().id, 4);
+    }
+
+    /// Tests that the configured default page size must be within the maximum.
+    #[test]
+    fn test_pagination_config() {
+        let pagination = Pagination {
+            default_page_size: 10,
+            max_page_size: 100,
+        };
+
+        assert!(pagination.validate().is_ok());
+        assert!(
+            Pagination {
+                default_page_size: 200,
+                max_page_size: 100,
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(Config::new().unwrap().pagination.validate().is_ok());
+    }
+
+    /// Tests that a cursor only applies to the sort it was issued for.
+    #[test]
+    fn test_cursor_bound_to_sort() {
+        let cursor = TransactionCursor::new(
+            TransactionSort::AmountDesc,
+            CursorDirection::After,
+            &transaction(1, 100),
+        )
+        .encode();
+
+        assert_eq!(request(serde_json::json!({})).cursor().unwrap(), None);
+        assert!(
+            request(serde_json::json!({ "cursor": cursor, "sort": "amount_desc" }))
+                .cursor()
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            request(serde_json::json!({ "cursor": cursor }))
+                .cursor()
+                .is_err()
+        );
+        assert!(
+            request(serde_json::json!({ "cursor": "not a cursor" }))
+                .cursor()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::storage::enums::TransactionStatus;
+
+    /// Tests the legal transitions of the transaction status state machine.
+    #[test]
+    fn test_status_transitions() {
+        use TransactionStatus::*;
+
+        assert!(Pending.can_transition_to(Completed));
+        assert!(Pending.can_transition_to(Failed));
+        assert!(Completed.can_transition_to(Reversed));
+
+        assert!(!Completed.can_transition_to(Pending));
+        assert!(!Completed.can_transition_to(Failed));
+        assert!(!Failed.can_transition_to(Completed));
+        assert!(!Reversed.can_transition_to(Completed));
+        assert!(!Pending.can_transition_to(Pending));
+    }
+
+    /// Tests that statuses serialize to the values stored in the database.
+    #[test]
+    fn test_status_serialization() {
+        assert_eq!(
+            serde_json::to_string(&TransactionStatus::Completed).unwrap(),
+            "\"COMPLETED\""
+        );
+        assert_eq!("FAILED".parse(), Ok(TransactionStatus::Failed));
+        assert!("pending".parse::<TransactionStatus>().is_err());
+    }
+}
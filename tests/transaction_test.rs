@@ -0,0 +1,380 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::{Request, StatusCode, header},
+    };
+    use dodopayments::{
+        app::AppState,
+        consts,
+        routes::{
+            api_models::{CreateTransactionRequest, GetTransactionResponse},
+            idempotency::fingerprint,
+            transaction,
+        },
+        storage::{
+            FxInterface, IdempotencyInterface, TransferLimitInterface, WalletInterface,
+            enums::FxQuoteStatus,
+            types::{
+                FxQuote, IdempotencyClaim, NewFxQuote, NewFxRate, NewIdempotencyKey, NewUserLimits,
+                NewWallet,
+            },
+        },
+        types::{Currency, ExchangeRate, Money},
+        utils::datetime,
+    };
+    use tower::ServiceExt;
+
+    use crate::common;
+
+    /// Tests that a user cannot move money out of another user's account.
+    #[tokio::test]
+    async fn test_create_transaction_requires_sender() {
+        let app_state = common::app_state().await;
+        let app = transaction::serve(app_state.clone()).with_state(app_state.clone());
+
+        let body = serde_json::json!({
+            "sender_id": "victim",
+            "receiver_id": "mallory",
+            "amount": 10_000,
+        });
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(
+                        header::AUTHORIZATION,
+                        format!("Bearer {}", common::token(&app_state, "mallory")),
+                    )
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    /// Tests that only the sender and the recipient of a transaction can see it.
+    #[tokio::test]
+    async fn test_get_transaction_requires_party() {
+        let app_state = common::app_state().await;
+        let app = transaction::serve(app_state.clone()).with_state(app_state.clone());
+        let sender = common::user(&app_state).await;
+        let receiver = common::user(&app_state).await;
+        let outsider = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 10_000).await;
+
+        let body = serde_json::json!({
+            "sender_id": sender,
+            "receiver_id": receiver,
+            "amount": 1_000,
+        });
+        let response = post(app.clone(), &common::token(&app_state, &sender), body).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: GetTransactionResponse = serde_json::from_slice(&bytes).unwrap();
+
+        for (user_id, status) in [
+            (&sender, StatusCode::OK),
+            (&receiver, StatusCode::OK),
+            (&outsider, StatusCode::NOT_FOUND),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/{}", created.transaction_id))
+                        .header(
+                            header::AUTHORIZATION,
+                            format!("Bearer {}", common::token(&app_state, user_id)),
+                        )
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    /// Tests that a failed transfer retried with the same idempotency key replays the error
+    /// instead of being attempted again.
+    #[tokio::test]
+    async fn test_idempotent_error_replayed() {
+        let app_state = common::app_state().await;
+        let app = transaction::serve(app_state.clone()).with_state(app_state.clone());
+        let sender = common::user(&app_state).await;
+        let receiver = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 1_000).await;
+
+        let body = serde_json::json!({
+            "sender_id": sender,
+            "receiver_id": receiver,
+            "amount": 5_000,
+        });
+        let send = || {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(
+                        header::AUTHORIZATION,
+                        format!("Bearer {}", common::token(&app_state, &sender)),
+                    )
+                    .header(consts::IDEMPOTENCY_KEY_HEADER, format!("key_{sender}"))
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let failed = send().await.unwrap();
+        assert_eq!(failed.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let failed_body = to_bytes(failed.into_body(), usize::MAX).await.unwrap();
+
+        // The retry would go through now, but the key already holds the error.
+        common::fund(&app_state, &sender, 10_000).await;
+        let replayed = send().await.unwrap();
+        assert_eq!(replayed.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            replayed.headers()[consts::IDEMPOTENT_REPLAYED_HEADER],
+            "true"
+        );
+        let replayed_body = to_bytes(replayed.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&replayed_body).unwrap(),
+            serde_json::from_slice::<serde_json::Value>(&failed_body).unwrap()
+        );
+        assert_eq!(common::balance(&app_state, &receiver).await, 0);
+    }
+
+    /// Tests that a key claimed by a request that died without a response is taken over by a
+    /// retry once its lease lapsed, and not before.
+    #[tokio::test]
+    async fn test_lapsed_idempotency_claim_taken_over() {
+        let app_state = common::app_state().await;
+        let app = transaction::serve(app_state.clone()).with_state(app_state.clone());
+        let sender = common::user(&app_state).await;
+        let receiver = common::user(&app_state).await;
+        common::fund(&app_state, &sender, 10_000).await;
+
+        let body = serde_json::json!({
+            "sender_id": sender,
+            "receiver_id": receiver,
+            "amount": 5_000,
+        });
+        let payload: CreateTransactionRequest = serde_json::from_value(body.clone()).unwrap();
+        let send = |key: String| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header(
+                        header::AUTHORIZATION,
+                        format!("Bearer {}", common::token(&app_state, &sender)),
+                    )
+                    .header(consts::IDEMPOTENCY_KEY_HEADER, key)
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        // Claims left behind by requests that died, one still leased and one lapsed.
+        let now = datetime::now();
+        for (key, locked_until) in [
+            (format!("live_{sender}"), now + time::Duration::minutes(5)),
+            (format!("lapsed_{sender}"), now - time::Duration::minutes(5)),
+        ] {
+            let claim = app_state
+                .db
+                .claim_idempotency_key(NewIdempotencyKey {
+                    user_id: sender.clone(),
+                    idempotency_key: key,
+                    request_fingerprint: fingerprint("POST /", &payload).unwrap(),
+                    created_at: now - time::Duration::minutes(10),
+                    expires_at: now + time::Duration::days(1),
+                    locked_until: Some(locked_until),
+                })
+                .await
+                .unwrap();
+            assert!(matches!(claim, IdempotencyClaim::Claimed));
+        }
+
+        let in_progress = send(format!("live_{sender}")).await.unwrap();
+        assert_eq!(in_progress.status(), StatusCode::CONFLICT);
+        assert_eq!(common::balance(&app_state, &receiver).await, 0);
+
+        let taken_over = send(format!("lapsed_{sender}")).await.unwrap();
+        assert_eq!(taken_over.status(), StatusCode::CREATED);
+        assert!(
+            !taken_over
+                .headers()
+                .contains_key(consts::IDEMPOTENT_REPLAYED_HEADER)
+        );
+
+        let replayed = send(format!("lapsed_{sender}")).await.unwrap();
+        assert_eq!(replayed.status(), StatusCode::CREATED);
+        assert_eq!(
+            replayed.headers()[consts::IDEMPOTENT_REPLAYED_HEADER],
+            "true"
+        );
+        assert_eq!(common::balance(&app_state, &receiver).await, 4_950);
+    }
+
+    /// Posts a create transaction request as a user.
+    async fn post(app: Router, token: &str, body: serde_json::Value) -> axum::response::Response {
+        app.oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Funds `sender` with 10,000 INR, opens USD wallets for both users and quotes converting
+    /// 1,000 INR of the sender's to USD.
+    async fn fx_quote(app_state: &AppState, sender: &str, receiver: &str) -> FxQuote {
+        common::fund(app_state, sender, 1_000_000).await;
+        for user_id in [sender, receiver] {
+            app_state
+                .db
+                .create_wallet(NewWallet::new(user_id.to_string(), Currency::Usd))
+                .await
+                .unwrap();
+        }
+
+        let rate = app_state
+            .db
+            .set_fx_rate(NewFxRate::new(
+                Currency::Inr,
+                Currency::Usd,
+                ExchangeRate::from_decimal_str("0.012").unwrap(),
+            ))
+            .await
+            .unwrap();
+        app_state
+            .db
+            .create_fx_quote(
+                NewFxQuote::new(
+                    sender.to_string(),
+                    Money::new(100_000, Currency::Inr).unwrap(),
+                    Currency::Usd,
+                    &rate,
+                    app_state.config.fx.spread_basis_points,
+                    app_state.config.fx.quote_ttl,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    /// Tests that a transfer with an FX quote converts the sender's money first and sends the
+    /// converted amount, and that nothing is converted when the amount does not match the quote.
+    #[tokio::test]
+    async fn test_create_transaction_with_fx_quote() {
+        let app_state = common::app_state().await;
+        let app = transaction::serve(app_state.clone()).with_state(app_state.clone());
+        let sender = common::user(&app_state).await;
+        let receiver = common::user(&app_state).await;
+        let quote = fx_quote(&app_state, &sender, &receiver).await;
+        let token = common::token(&app_state, &sender);
+
+        let mismatched = serde_json::json!({
+            "sender_id": sender,
+            "receiver_id": receiver,
+            "amount": 50_000,
+            "fx_quote_id": quote.quote_id,
+        });
+        let response = post(app.clone(), &token, mismatched).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let open = app_state.db.get_fx_quote(&quote.quote_id).await.unwrap();
+        assert_eq!(open.status, FxQuoteStatus::Open);
+
+        let body = serde_json::json!({
+            "sender_id": sender,
+            "receiver_id": receiver,
+            "amount": 100_000,
+            "fx_quote_id": quote.quote_id,
+        });
+        let response = post(app, &token, body).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: GetTransactionResponse = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(created.amount.minor_units, quote.target_amount_minor_units);
+
+        let executed = app_state.db.get_fx_quote(&quote.quote_id).await.unwrap();
+        assert_eq!(executed.status, FxQuoteStatus::Executed);
+        assert_eq!(common::balance(&app_state, &sender).await, 900_000);
+        let sender_usd = app_state
+            .db
+            .get_wallet(&sender, Currency::Usd)
+            .await
+            .unwrap();
+        assert_eq!(sender_usd.balance_minor_units, 0);
+        let receiver_usd = app_state
+            .db
+            .get_wallet(&receiver, Currency::Usd)
+            .await
+            .unwrap();
+        assert_eq!(
+            receiver_usd.balance_minor_units,
+            created.net_amount.minor_units
+        );
+    }
+
+    /// Tests that a transfer rejected after its quote is executed rolls the conversion back,
+    /// leaving the quote open and the sender's wallets untouched.
+    #[tokio::test]
+    async fn test_rejected_fx_transfer_converts_nothing() {
+        let app_state = common::app_state().await;
+        let app = transaction::serve(app_state.clone()).with_state(app_state.clone());
+        let sender = common::user(&app_state).await;
+        let receiver = common::user(&app_state).await;
+        let quote = fx_quote(&app_state, &sender, &receiver).await;
+        app_state
+            .db
+            .set_user_limits(NewUserLimits {
+                user_id: sender.clone(),
+                max_transfer_minor_units: Some(100),
+                daily_outgoing_minor_units: None,
+                monthly_outgoing_minor_units: None,
+                transfers_per_hour: None,
+                updated_at: datetime::now(),
+            })
+            .await
+            .unwrap();
+        let token = common::token(&app_state, &sender);
+
+        let body = serde_json::json!({
+            "sender_id": sender,
+            "receiver_id": receiver,
+            "amount": 100_000,
+            "fx_quote_id": quote.quote_id,
+        });
+        let response = post(app, &token, body).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let open = app_state.db.get_fx_quote(&quote.quote_id).await.unwrap();
+        assert_eq!(open.status, FxQuoteStatus::Open);
+        assert_eq!(common::balance(&app_state, &sender).await, 1_000_000);
+        let sender_usd = app_state
+            .db
+            .get_wallet(&sender, Currency::Usd)
+            .await
+            .unwrap();
+        assert_eq!(sender_usd.balance_minor_units, 0);
+    }
+}
@@ -1,8 +1,7 @@
 #[cfg(test)]
 mod tests {
     use dodopayments::routes::user::password::{generate_password_hash, is_correct_password};
-    use dodopayments::utils::{generate_jwt};
-    use serde::Serialize;
+    use dodopayments::utils::generate_jwt;
 
     /// Tests password hash generation.
     #[tokio::test]
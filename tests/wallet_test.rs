@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use dodopayments::{
+        error::{ApiError, MoneyError, TransactionDbError},
+        routes::api_models::{CreateDepositRequest, CreateTransactionRequest, CreateWalletRequest},
+        types::{Currency, Money},
+    };
+
+    /// Tests that every supported ISO 4217 code parses and round-trips through its code.
+    #[test]
+    fn test_currency_codes() {
+        for currency in [
+            Currency::Inr,
+            Currency::Usd,
+            Currency::Eur,
+            Currency::Gbp,
+            Currency::Jpy,
+        ] {
+            assert_eq!(currency.code().parse::<Currency>(), Ok(currency));
+            assert_eq!(
+                serde_json::to_value(currency).unwrap(),
+                serde_json::json!(currency.code())
+            );
+        }
+
+        assert_eq!(
+            "usd".parse::<Currency>(),
+            Err(MoneyError::UnsupportedCurrency("usd".into()))
+        );
+        assert!(
+            serde_json::from_value::<CreateWalletRequest>(serde_json::json!({ "currency": "XYZ" }))
+                .is_err()
+        );
+    }
+
+    /// Tests that amounts follow the minor unit of their currency, so yen have no decimals.
+    #[test]
+    fn test_currency_exponents() {
+        let parse =
+            |value, currency| Money::from_decimal_str(value, currency).map(|m| m.minor_units());
+
+        assert_eq!(parse("12.50", Currency::Usd), Ok(1250));
+        assert_eq!(parse("12.50", Currency::Gbp), Ok(1250));
+        assert_eq!(parse("1250", Currency::Jpy), Ok(1250));
+        assert!(matches!(
+            parse("12.5", Currency::Jpy),
+            Err(MoneyError::InvalidFormat(_))
+        ));
+
+        assert_eq!(
+            Money::new(1250, Currency::Jpy).unwrap().to_string(),
+            "1250 JPY"
+        );
+        assert_eq!(
+            Money::new(1250, Currency::Eur).unwrap().to_string(),
+            "12.50 EUR"
+        );
+    }
+
+    /// Tests that amounts in different currencies cannot be added together.
+    #[test]
+    fn test_currency_mismatch() {
+        let rupees = Money::new(100, Currency::Inr).unwrap();
+        let dollars = Money::new(100, Currency::Usd).unwrap();
+
+        assert_eq!(
+            rupees.checked_add(dollars),
+            Err(MoneyError::CurrencyMismatch {
+                expected: "INR",
+                found: "USD",
+            })
+        );
+    }
+
+    /// Tests that transfers and deposits default to INR and validate amounts in their currency.
+    #[test]
+    fn test_request_currency() {
+        let transfer = |value: serde_json::Value| {
+            serde_json::from_value::<CreateTransactionRequest>(value).unwrap()
+        };
+
+        let default = transfer(serde_json::json!({
+            "sender_id": "alice",
+            "receiver_id": "bob",
+            "amount": "10.00",
+        }));
+        assert_eq!(default.currency, Currency::Inr);
+        assert!(default.validate().is_ok());
+
+        let yen = transfer(serde_json::json!({
+            "sender_id": "alice",
+            "receiver_id": "bob",
+            "amount": "10.50",
+            "currency": "JPY",
+        }));
+        assert_eq!(yen.currency, Currency::Jpy);
+        assert!(yen.validate().is_err());
+
+        let deposit = serde_json::from_value::<CreateDepositRequest>(
+            serde_json::json!({ "amount": 100, "currency": "EUR" }),
+        )
+        .unwrap();
+        assert_eq!(deposit.currency, Currency::Eur);
+        assert!(deposit.validate(10_000).is_ok());
+    }
+
+    /// Tests that a missing wallet or a second wallet in the same currency maps to its API error.
+    #[test]
+    fn test_wallet_errors() {
+        assert!(matches!(
+            ApiError::from(&TransactionDbError::CurrencyNotHeld(Currency::Usd)),
+            ApiError::CurrencyNotHeld(Currency::Usd)
+        ));
+        assert!(matches!(
+            ApiError::from(&TransactionDbError::DuplicateWallet(Currency::Gbp)),
+            ApiError::WalletAlreadyExists(Currency::Gbp)
+        ));
+    }
+}
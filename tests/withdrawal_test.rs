@@ -0,0 +1,373 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use dodopayments::{
+        app::AppState,
+        configs::{Config, PayoutConnectorKind},
+        error::{PayoutError, TransactionDbError},
+        payout::{
+            self, MockPayoutConnector, MockPayoutOutcome, Payout, PayoutConnector, PayoutStatus,
+        },
+        routes::api_models::CreateWithdrawalRequest,
+        storage::{
+            TransferLimitInterface, WithdrawalInterface,
+            enums::WithdrawalStatus,
+            types::{NewUserLimits, NewWithdrawal},
+        },
+        types::{AccountNumber, Currency, Ifsc, Money, limits::LimitKind},
+        utils::datetime,
+        workers,
+    };
+
+    use crate::common;
+
+    /// Payout connector that never received any payout, and pays out what is dispatched to it.
+    #[derive(Default)]
+    struct LostPayoutConnector {
+        dispatches: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl PayoutConnector for LostPayoutConnector {
+        fn name(&self) -> &'static str {
+            "lost"
+        }
+
+        async fn dispatch(&self, payout: &Payout) -> Result<String, PayoutError> {
+            self.dispatches.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("lost_{}", payout.withdrawal_id))
+        }
+
+        async fn status(&self, _withdrawal_id: &str) -> Result<PayoutStatus, PayoutError> {
+            Ok(PayoutStatus::NotFound)
+        }
+    }
+
+    fn payout() -> Payout {
+        Payout {
+            withdrawal_id: "wd_1".into(),
+            amount: Money::new(1_000, Currency::Inr).unwrap(),
+            account_holder_name: "Alice".into(),
+            account_number: "123456789012".into(),
+            ifsc: "HDFC0001234".into(),
+        }
+    }
+
+    /// Tests that account numbers must have 9 to 18 digits and are masked to their last four.
+    #[test]
+    fn test_account_number_validation() {
+        for valid in ["123456789", "123456789012345678"] {
+            assert!(AccountNumber(valid.into()).validate().is_ok(), "{valid}");
+        }
+        for invalid in ["12345678", "1234567890123456789", "12345678901A", ""] {
+            assert!(
+                AccountNumber(invalid.into()).validate().is_err(),
+                "{invalid}"
+            );
+        }
+
+        assert_eq!(
+            AccountNumber("123456789012".into()).masked(),
+            "XXXXXXXX9012"
+        );
+    }
+
+    /// Tests that an IFSC is four letters, a zero and six letters or digits.
+    #[test]
+    fn test_ifsc_validation() {
+        for valid in ["HDFC0001234", "SBIN0ABC123"] {
+            assert!(Ifsc(valid.into()).validate().is_ok(), "{valid}");
+        }
+        for invalid in [
+            "hdfc0001234",
+            "HDFC1001234",
+            "HDF00001234",
+            "HDFC000123",
+            "HDFC00012345",
+        ] {
+            assert!(Ifsc(invalid.into()).validate().is_err(), "{invalid}");
+        }
+    }
+
+    /// Tests each outcome of the mock connector, including a payout that never gets an answer.
+    #[tokio::test(start_paused = true)]
+    async fn test_mock_payout_connector() {
+        let timeout = Duration::from_secs(10);
+        let connector = |outcome| MockPayoutConnector { outcome };
+
+        assert_eq!(
+            payout::dispatch(&connector(MockPayoutOutcome::Succeed), &payout(), timeout).await,
+            Ok("mock_wd_1".into())
+        );
+        assert!(matches!(
+            payout::dispatch(&connector(MockPayoutOutcome::Fail), &payout(), timeout).await,
+            Err(PayoutError::Rejected(_))
+        ));
+        assert_eq!(
+            payout::dispatch(&connector(MockPayoutOutcome::Timeout), &payout(), timeout).await,
+            Err(PayoutError::TimedOut)
+        );
+
+        assert_eq!(
+            payout::status(&connector(MockPayoutOutcome::Succeed), "wd_1", timeout).await,
+            Ok(PayoutStatus::Paid("mock_wd_1".into()))
+        );
+        assert!(matches!(
+            payout::status(&connector(MockPayoutOutcome::Fail), "wd_1", timeout).await,
+            Ok(PayoutStatus::Rejected(_))
+        ));
+        assert_eq!(
+            payout::status(&connector(MockPayoutOutcome::Timeout), "wd_1", timeout).await,
+            Ok(PayoutStatus::Processing)
+        );
+    }
+
+    /// Tests that withdrawals must be positive and within the configured maximum.
+    #[test]
+    fn test_create_request_validation() {
+        let request = |value: serde_json::Value| {
+            serde_json::from_value::<CreateWithdrawalRequest>(value).unwrap()
+        };
+
+        assert!(
+            request(serde_json::json!({ "bank_account_id": "ba_1", "amount": "100.00" }))
+                .validate(10_000)
+                .is_ok()
+        );
+        for invalid in [
+            serde_json::json!({ "bank_account_id": "", "amount": 100 }),
+            serde_json::json!({ "bank_account_id": "ba_1", "amount": 0 }),
+            serde_json::json!({ "bank_account_id": "ba_1", "amount": 10_001 }),
+        ] {
+            assert!(
+                request(invalid.clone()).validate(10_000).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    /// Tests that the withdrawal configuration picks the connector and must allow payouts.
+    #[test]
+    fn test_withdrawal_config() {
+        let config = Config::new().unwrap();
+        assert!(config.withdrawal.validate().is_ok());
+        assert_eq!(
+            config.withdrawal.connector,
+            PayoutConnectorKind::Mock {
+                outcome: MockPayoutOutcome::Succeed
+            }
+        );
+
+        let mut withdrawal = config.withdrawal.clone();
+        withdrawal.timeout = 0;
+        assert!(withdrawal.validate().is_err());
+    }
+
+    /// Tests that only pending withdrawals can complete or fail.
+    #[test]
+    fn test_withdrawal_transitions() {
+        use WithdrawalStatus::*;
+
+        assert!(Pending.can_transition_to(Completed));
+        assert!(Pending.can_transition_to(Failed));
+
+        assert!(!Completed.can_transition_to(Failed));
+        assert!(!Failed.can_transition_to(Completed));
+        assert!(!Failed.can_transition_to(Pending));
+    }
+
+    /// Tests that withdrawals are checked against the daily limit and count toward it, next to
+    /// transfers.
+    #[tokio::test]
+    async fn test_withdrawals_count_toward_limits() {
+        let app_state = common::app_state().await;
+        let user = common::user(&app_state).await;
+        let recipient = common::user(&app_state).await;
+        let bank_account = common::bank_account(&app_state, &user).await;
+        common::fund(&app_state, &user, 50_000).await;
+        app_state
+            .db
+            .set_user_limits(NewUserLimits {
+                user_id: user.clone(),
+                max_transfer_minor_units: None,
+                daily_outgoing_minor_units: Some(20_000),
+                monthly_outgoing_minor_units: None,
+                transfers_per_hour: None,
+                updated_at: datetime::now(),
+            })
+            .await
+            .unwrap();
+
+        common::transfer(&app_state, &user, &recipient, 15_000)
+            .await
+            .unwrap();
+
+        let err = app_state
+            .db
+            .create_withdrawal(common::new_withdrawal(&user, &bank_account, 6_000))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.get_inner(),
+            TransactionDbError::LimitExceeded(LimitKind::DailyOutgoing)
+        ));
+
+        let withdrawal = app_state
+            .db
+            .create_withdrawal(common::new_withdrawal(&user, &bank_account, 4_000))
+            .await
+            .unwrap();
+        assert_eq!(withdrawal.status, WithdrawalStatus::Pending);
+
+        // The pending withdrawal leaves only 1_000 of the day's limit.
+        let err = common::transfer(&app_state, &user, &recipient, 2_000)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.get_inner(),
+            TransactionDbError::LimitExceeded(LimitKind::DailyOutgoing)
+        ));
+        assert_eq!(common::balance(&app_state, &user).await, 31_000);
+    }
+
+    /// Tests that the recovery worker settles a pending withdrawal by the status of its payout:
+    /// paid payouts complete, rejected ones fail and return the money, and ones still
+    /// processing stay pending.
+    #[tokio::test]
+    async fn test_recover_pending_withdrawal() {
+        let mut config = Config::new().unwrap();
+        let cases = [
+            (MockPayoutOutcome::Timeout, WithdrawalStatus::Pending, 6_000),
+            (
+                MockPayoutOutcome::Succeed,
+                WithdrawalStatus::Completed,
+                6_000,
+            ),
+            (MockPayoutOutcome::Fail, WithdrawalStatus::Failed, 10_000),
+        ];
+
+        for (outcome, expected_status, expected_balance) in cases {
+            config.withdrawal.connector = PayoutConnectorKind::Mock { outcome };
+            let app_state = AppState::new(config.clone()).await.unwrap();
+            let user = common::user(&app_state).await;
+            let bank_account = common::bank_account(&app_state, &user).await;
+            common::fund(&app_state, &user, 10_000).await;
+
+            let withdrawal = app_state
+                .db
+                .create_withdrawal(common::new_withdrawal(&user, &bank_account, 4_000))
+                .await
+                .unwrap();
+
+            let withdrawal = workers::recover_withdrawal(&app_state, withdrawal)
+                .await
+                .unwrap();
+            assert_eq!(withdrawal.status, expected_status, "{outcome:?}");
+            assert_eq!(
+                common::balance(&app_state, &user).await,
+                expected_balance,
+                "{outcome:?}"
+            );
+        }
+    }
+
+    /// Tests that the recovery worker dispatches a payout the connector never received again,
+    /// and that a claimed withdrawal is not claimed by another worker until its claim runs out.
+    #[tokio::test]
+    async fn test_recover_lost_payout() {
+        let mut app_state = AppState::new(Config::new().unwrap()).await.unwrap();
+        let connector = LostPayoutConnector::default();
+        let dispatches = connector.dispatches.clone();
+        app_state.payout_connector = Box::new(connector);
+        let user = common::user(&app_state).await;
+        let bank_account = common::bank_account(&app_state, &user).await;
+        common::fund(&app_state, &user, 10_000).await;
+
+        let withdrawal = app_state
+            .db
+            .create_withdrawal(NewWithdrawal {
+                connector: "lost".to_string(),
+                ..common::new_withdrawal(&user, &bank_account, 4_000)
+            })
+            .await
+            .unwrap();
+
+        // Claim every stale withdrawal, as this worker would over its passes.
+        let now = datetime::now();
+        let claim_until = now + time::Duration::hours(1);
+        let mut claimed = None;
+        while let Some(stale) = app_state
+            .db
+            .claim_stale_withdrawal(now + time::Duration::seconds(1), claim_until)
+            .await
+            .unwrap()
+        {
+            if stale.withdrawal_id == withdrawal.withdrawal_id {
+                claimed = Some(stale);
+            }
+        }
+        let claimed = claimed.unwrap();
+        assert!(
+            claimed
+                .recovery_claimed_until
+                .is_some_and(|until| until > now)
+        );
+
+        // Another worker finds nothing left to claim.
+        assert!(
+            app_state
+                .db
+                .claim_stale_withdrawal(now + time::Duration::seconds(1), claim_until)
+                .await
+                .unwrap()
+                .is_none_or(|stale| stale.withdrawal_id != withdrawal.withdrawal_id)
+        );
+
+        let recovered = workers::recover_withdrawal(&app_state, claimed)
+            .await
+            .unwrap();
+        assert_eq!(recovered.status, WithdrawalStatus::Completed);
+        assert_eq!(
+            recovered.connector_reference,
+            Some(format!("lost_{}", withdrawal.withdrawal_id))
+        );
+        assert_eq!(dispatches.load(Ordering::SeqCst), 1);
+        assert_eq!(common::balance(&app_state, &user).await, 6_000);
+    }
+
+    /// Tests that the recovery worker asks the connector a withdrawal was paid out through, and
+    /// leaves the withdrawal pending when that connector is no longer configured.
+    #[tokio::test]
+    async fn test_recover_with_removed_connector() {
+        let mut app_state = AppState::new(Config::new().unwrap()).await.unwrap();
+        let connector = LostPayoutConnector::default();
+        let dispatches = connector.dispatches.clone();
+        app_state.payout_connector = Box::new(connector);
+        let user = common::user(&app_state).await;
+        let bank_account = common::bank_account(&app_state, &user).await;
+        common::fund(&app_state, &user, 10_000).await;
+
+        // Paid out through the mock connector, which was replaced since.
+        let withdrawal = app_state
+            .db
+            .create_withdrawal(common::new_withdrawal(&user, &bank_account, 4_000))
+            .await
+            .unwrap();
+
+        let recovered = workers::recover_withdrawal(&app_state, withdrawal)
+            .await
+            .unwrap();
+        assert_eq!(recovered.status, WithdrawalStatus::Pending);
+        assert_eq!(dispatches.load(Ordering::SeqCst), 0);
+        assert_eq!(common::balance(&app_state, &user).await, 6_000);
+    }
+}